@@ -0,0 +1,148 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Data, DeriveInput, Fields};
+
+pub fn derive_custom_enum_impl(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "CustomEnum can only be derived for enums",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut all_field_names: Vec<String> = Vec::new();
+    let mut constructors = Vec::new();
+    let mut variant_names = Vec::new();
+    let mut field_names = Vec::new();
+    let mut field_counts = Vec::new();
+    let mut fields = Vec::new();
+    let mut builders = Vec::new();
+
+    for variant in data.variants.iter() {
+        let var_name = &variant.ident;
+        let var_str = var_name.to_string();
+
+        let vars: Vec<_> = match variant.fields {
+            Fields::Named(ref f) => f.named.iter().map(|f| f.ident.clone().unwrap()).collect(),
+            Fields::Unnamed(ref f) => (0..f.unnamed.len())
+                .map(|i| format_ident!("_{}", i))
+                .collect(),
+            Fields::Unit => Vec::new(),
+        };
+        let types: Vec<_> = variant.fields.iter().map(|f| &f.ty).collect();
+        let indices: Vec<_> = (0..vars.len()).collect();
+        let count = vars.len();
+
+        let (pattern, ignore, build) = match variant.fields {
+            Fields::Named(_) => (
+                quote! { { #(#vars),* } },
+                quote! { { .. } },
+                quote! { { #(#vars: fields.next()?.try_cast::<#types>()?),* } },
+            ),
+            Fields::Unnamed(_) => (
+                quote! { ( #(#vars),* ) },
+                quote! { (..) },
+                quote! { ( #(fields.next()?.try_cast::<#types>()?),* ) },
+            ),
+            Fields::Unit => (quote! {}, quote! {}, quote! {}),
+        };
+
+        constructors.push(if let Fields::Unit = variant.fields {
+            quote! {
+                module.set_var(#var_str, Self::#var_name);
+                module.set_native_fn(#var_str, || {
+                    Ok::<_, Box<rhai::EvalAltResult>>(Self::#var_name)
+                });
+            }
+        } else {
+            quote! {
+                module.set_native_fn(#var_str, |#(#vars: #types),*| {
+                    Ok::<_, Box<rhai::EvalAltResult>>(Self::#var_name #pattern)
+                });
+            }
+        });
+
+        variant_names.push(quote! { Self::#var_name #ignore => #var_str });
+        field_counts.push(quote! { Self::#var_name #ignore => #count });
+
+        if let Fields::Named(_) = variant.fields {
+            let names: Vec<_> = vars.iter().map(|v| v.to_string()).collect();
+            field_names.push(quote! { Self::#var_name #ignore => &[#(#names),*] });
+
+            names.into_iter().for_each(|name| {
+                if !all_field_names.contains(&name) {
+                    all_field_names.push(name);
+                }
+            });
+        } else {
+            field_names.push(quote! { Self::#var_name #ignore => &[] });
+        }
+
+        builders.push(quote! {
+            #var_str => Self::#var_name #build
+        });
+
+        fields.push(if count > 0 {
+            quote! {
+                Self::#var_name #pattern => match index {
+                    #(#indices => Some(rhai::Dynamic::from(#vars.clone())),)*
+                    _ => None,
+                }
+            }
+        } else {
+            quote! { Self::#var_name #ignore => None }
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics rhai::CustomEnum for #name #ty_generics #where_clause {
+            fn enum_name() -> &'static str {
+                #name_str
+            }
+            fn all_field_names() -> &'static [&'static str] {
+                &[#(#all_field_names),*]
+            }
+            fn register_variants(module: &mut rhai::Module) {
+                #(#constructors)*
+            }
+            fn variant_name(&self) -> &'static str {
+                match self {
+                    #(#variant_names,)*
+                }
+            }
+            fn field_names(&self) -> &'static [&'static str] {
+                match self {
+                    #(#field_names,)*
+                }
+            }
+            fn field_count(&self) -> usize {
+                match self {
+                    #(#field_counts,)*
+                }
+            }
+            fn field(&self, index: usize) -> Option<rhai::Dynamic> {
+                match self {
+                    #(#fields,)*
+                }
+            }
+            fn from_fields(variant: &str, fields: Vec<rhai::Dynamic>) -> Option<Self> {
+                let mut fields = fields.into_iter();
+                let value = match variant {
+                    #(#builders,)*
+                    _ => return None,
+                };
+                match fields.next() {
+                    Some(_) => None,
+                    None => Some(value),
+                }
+            }
+        }
+    })
+}
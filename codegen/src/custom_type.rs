@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned, Attribute, Data, DeriveInput, Fields, Lit, Meta, NestedMeta};
+
+/// Options specified via `#[rhai(...)]` attributes.
+#[derive(Default)]
+struct RhaiAttrs {
+    name: Option<String>,
+    skip: bool,
+    readonly: bool,
+    eq: bool,
+    display: bool,
+}
+
+fn parse_rhai_attrs(attrs: &[Attribute]) -> syn::Result<RhaiAttrs> {
+    let mut result = RhaiAttrs::default();
+
+    for attr in attrs.iter().filter(|a| a.path.is_ident("rhai")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new(meta.span(), "expecting #[rhai(...)]")),
+        };
+
+        for item in list.nested.iter() {
+            match item {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => result.skip = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("readonly") => {
+                    result.readonly = true
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("eq") => result.eq = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("display") => {
+                    result.display = true
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => match nv.lit {
+                    Lit::Str(ref s) => result.name = Some(s.value()),
+                    ref lit => return Err(syn::Error::new(lit.span(), "expecting string")),
+                },
+                item => return Err(syn::Error::new(item.span(), "unknown rhai attribute")),
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn derive_custom_type_impl(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(ref fields) => {
+                return Err(syn::Error::new(
+                    fields.span(),
+                    "CustomType cannot be derived for tuple structs",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "CustomType can only be derived for structs",
+            ))
+        }
+    };
+
+    let type_attrs = parse_rhai_attrs(&input.attrs)?;
+
+    if type_attrs.skip || type_attrs.readonly {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "skip and readonly can only be used on fields",
+        ));
+    }
+
+    let name = &input.ident;
+    let type_name = type_attrs.name.unwrap_or_else(|| name.to_string());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut accessors = Vec::new();
+
+    for field in fields {
+        let attrs = parse_rhai_attrs(&field.attrs)?;
+
+        if attrs.eq || attrs.display {
+            return Err(syn::Error::new(
+                field.span(),
+                "eq and display can only be used on types",
+            ));
+        }
+        if attrs.skip {
+            continue;
+        }
+
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let prop = attrs.name.unwrap_or_else(|| ident.to_string());
+
+        accessors.push(if attrs.readonly {
+            quote! {
+                builder.with_get(#prop, |obj: &mut Self| obj.#ident.clone());
+            }
+        } else {
+            quote! {
+                builder.with_get_set(
+                    #prop,
+                    |obj: &mut Self| obj.#ident.clone(),
+                    |obj: &mut Self, value: #ty| obj.#ident = value,
+                );
+            }
+        });
+    }
+
+    let eq = if type_attrs.eq {
+        quote! {
+            builder
+                .with_fn("==", |x: &mut Self, y: Self| *x == y)
+                .with_fn("!=", |x: &mut Self, y: Self| *x != y);
+        }
+    } else {
+        quote! {}
+    };
+
+    let display = if type_attrs.display {
+        quote! {
+            builder
+                .with_fn("print", |obj: &mut Self| format!("{}", obj))
+                .with_fn("to_string", |obj: &mut Self| format!("{}", obj));
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #impl_generics rhai::CustomType for #name #ty_generics #where_clause {
+            fn build(mut builder: rhai::TypeBuilder<Self>) {
+                builder.with_name(#type_name);
+                #(#accessors)*
+                builder
+                    .with_fn("debug", |obj: &mut Self| format!("{:?}", obj))
+                    .with_fn("to_debug", |obj: &mut Self| format!("{:?}", obj));
+                #display
+                #eq
+            }
+        }
+    })
+}
@@ -0,0 +1,85 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Expr, LitStr, Token,
+};
+
+/// Arguments of `embed_scripts!`: an engine expression and a directory path.
+pub struct EmbedScriptsArgs {
+    engine: Expr,
+    dir: LitStr,
+}
+
+impl Parse for EmbedScriptsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let engine = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let dir = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { engine, dir })
+    }
+}
+
+pub fn embed_scripts_impl(args: EmbedScriptsArgs) -> syn::Result<TokenStream> {
+    let EmbedScriptsArgs { engine, dir } = args;
+
+    // Paths are relative to the root of the crate using the macro
+    let root = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let base = root.join(dir.value());
+
+    let mut scripts = Vec::new();
+    collect_scripts(&base, "", &mut scripts).map_err(|err| {
+        syn::Error::new(
+            dir.span(),
+            format!("cannot read '{}': {}", base.display(), err),
+        )
+    })?;
+
+    // Embed the scripts via `include_str!` so that the crate is rebuilt when they change
+    let entries = scripts.into_iter().map(|(name, path)| {
+        let path = path.to_string_lossy().into_owned();
+        quote! { (#name, include_str!(#path)) }
+    });
+
+    Ok(quote! {
+        {
+            let mut resolver = rhai::module_resolvers::StaticModuleResolver::new();
+            resolver
+                .insert_scripts(&#engine, &[#(#entries),*])
+                .map(|_| resolver)
+        }
+    })
+}
+
+/// Collect all `.rhai` files under a directory, named by their paths relative to the directory
+/// without extension.
+fn collect_scripts(
+    dir: &Path,
+    prefix: &str,
+    scripts: &mut Vec<(String, PathBuf)>,
+) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            let name = path.file_name().unwrap().to_string_lossy();
+            collect_scripts(&path, &format!("{}{}/", prefix, name), scripts)?;
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("rhai") {
+            let name = format!("{}{}", prefix, path.file_stem().unwrap().to_string_lossy());
+            scripts.push((name, path));
+        }
+    }
+
+    Ok(())
+}
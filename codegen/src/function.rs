@@ -731,7 +731,7 @@ impl ExportedFn {
                         syn::Type::Reference(syn::TypeReference { ref elem, .. }) => elem.as_ref(),
                         p => p,
                     };
-                    let downcast_span = quote_spanned!(arg_type.span() =>
+                    let downcast_span = quote!(
                         &mut args[0usize].write_lock::<#arg_type>().unwrap()
                     );
                     unpack_statements.push(
@@ -792,7 +792,7 @@ impl ExportedFn {
                             syn::Type::Path(ref p) if p.path == str_type_path => {
                                 is_string = true;
                                 is_ref = true;
-                                quote_spanned!(arg_type.span() =>
+                                quote!(
                                     mem::take(args[#i]).take_immutable_string().unwrap()
                                 )
                             }
@@ -801,14 +801,14 @@ impl ExportedFn {
                         syn::Type::Path(ref p) if p.path == string_type_path => {
                             is_string = true;
                             is_ref = false;
-                            quote_spanned!(arg_type.span() =>
+                            quote!(
                                 mem::take(args[#i]).take_string().unwrap()
                             )
                         }
                         _ => {
                             is_string = false;
                             is_ref = false;
-                            quote_spanned!(arg_type.span() =>
+                            quote!(
                                 mem::take(args[#i]).cast::<#arg_type>()
                             )
                         }
@@ -91,6 +91,9 @@ use quote::quote;
 use syn::parse_macro_input;
 
 mod attrs;
+mod custom_enum;
+mod custom_type;
+mod embed;
 mod function;
 mod module;
 mod register;
@@ -388,3 +391,142 @@ pub fn set_exported_global_fn(args: proc_macro::TokenStream) -> proc_macro::Toke
     };
     proc_macro::TokenStream::from(tokens)
 }
+
+/// Derive macro implementing `CustomEnum` for a Rust `enum`, so that it can be registered via
+/// `Engine::register_enum`.
+///
+/// All fields of all variants must implement `Clone`.
+///
+/// # Usage
+///
+/// ```
+/// # use rhai::{Engine, EvalAltResult, INT};
+/// use rhai::CustomEnum;
+///
+/// #[derive(Debug, Clone, PartialEq, CustomEnum)]
+/// enum Token {
+///     Eof,
+///     Number(INT),
+///     Ident { name: String },
+/// }
+///
+/// # fn main() -> Result<(), Box<EvalAltResult>> {
+/// let mut engine = Engine::new();
+///
+/// engine.register_enum::<Token>();
+///
+/// assert_eq!(engine.eval::<Token>("Token::Number(42)")?, Token::Number(42));
+/// assert_eq!(engine.eval::<String>("Token::Ident(\"x\").variant")?, "Ident");
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(CustomEnum)]
+pub fn derive_custom_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    match crate::custom_enum::derive_custom_enum_impl(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive macro implementing `CustomType` for a Rust struct, so that it can be registered via
+/// `Engine::build_type`.
+///
+/// The type name, a property getter and setter for each field, and `debug` and `to_debug`
+/// (via [`Debug`][std::fmt::Debug]) are registered.  Fields must implement `Clone`.
+///
+/// Attributes:
+///
+/// * `#[rhai(name = "...")]` on the struct sets the type name for `type_of`;
+///   on a field, it renames the property.
+///
+/// * `#[rhai(eq)]` on the struct registers `==` and `!=` (via `PartialEq`).
+///
+/// * `#[rhai(display)]` on the struct registers `print` and `to_string` (via
+///   [`Display`][std::fmt::Display]).  Without it, they are not registered and the default
+///   implementations, which print the name of the type, are used.
+///
+/// * `#[rhai(skip)]` on a field skips it.
+///
+/// * `#[rhai(readonly)]` on a field registers only a getter.
+///
+/// # Usage
+///
+/// ```
+/// # use rhai::{Engine, EvalAltResult, INT};
+/// use rhai::CustomType;
+///
+/// #[derive(Debug, Clone, CustomType)]
+/// #[rhai(name = "Point")]
+/// struct MyPoint {
+///     #[rhai(name = "x")]
+///     pos_x: INT,
+///     #[rhai(readonly)]
+///     y: INT,
+/// }
+///
+/// # fn main() -> Result<(), Box<EvalAltResult>> {
+/// let mut engine = Engine::new();
+///
+/// engine
+///     .build_type::<MyPoint>()
+///     .register_fn("new_point", |x: INT, y: INT| MyPoint { pos_x: x, y });
+///
+/// assert_eq!(engine.eval::<INT>("let p = new_point(1, 2); p.x = 40; p.x + p.y")?, 42);
+/// assert_eq!(engine.eval::<String>("type_of(new_point(1, 2))")?, "Point");
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(CustomType, attributes(rhai))]
+pub fn derive_custom_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+
+    match crate::custom_type::derive_custom_type_impl(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Macro to embed a directory of Rhai script files into the binary at build time, producing a
+/// `StaticModuleResolver` with a [module][rhai::Module] for each script, so that `import` works
+/// without any file access (e.g. under `no_std`).
+///
+/// The directory is relative to the root of the crate using the macro, and is searched
+/// recursively for `.rhai` files.  Each script is keyed by its path relative to the directory,
+/// using `/` as separator and without the extension (e.g. `utils/math`).  Changes to the scripts
+/// rebuild the crate using the macro, but adding or removing scripts does not.
+///
+/// The scripts are compiled and evaluated with the given [`Engine`][rhai::Engine] (via
+/// `StaticModuleResolver::insert_scripts`) when the macro is evaluated, which returns
+/// `Result<StaticModuleResolver, Box<EvalAltResult>>`.  A script with a syntax error fails with
+/// `EvalAltResult::ErrorInModule` naming the script; as the scripts are parsed by the
+/// [`Engine`][rhai::Engine] itself, any custom syntax or custom operators registered on it can
+/// be used.
+///
+/// # Usage
+///
+/// ```ignore
+/// use rhai::{embed_scripts, Engine, EvalAltResult, INT};
+///
+/// # fn main() -> Result<(), Box<EvalAltResult>> {
+/// let mut engine = Engine::new();
+///
+/// // Embed 'scripts/utils/math.rhai' (plus any other scripts) as module 'utils/math'
+/// let resolver = embed_scripts!(engine, "scripts")?;
+///
+/// engine.set_module_resolver(resolver);
+///
+/// engine.eval::<INT>(r#"import "utils/math" as math; math::add(40, 2)"#)?;
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro]
+pub fn embed_scripts(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as crate::embed::EmbedScriptsArgs);
+
+    match crate::embed::embed_scripts_impl(args) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
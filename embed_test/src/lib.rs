@@ -0,0 +1 @@
+//! Test crate for embedding the scripts under `scripts` via `rhai::embed_scripts!`.
@@ -0,0 +1,26 @@
+use rhai::{Engine, EvalAltResult, INT};
+
+#[test]
+fn test_embedded_scripts() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let resolver = rhai::embed_scripts!(engine, "scripts")?;
+
+    assert_eq!(
+        resolver.paths().collect::<Vec<_>>(),
+        ["main", "utils/calc", "utils/math"]
+    );
+
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "main" as m; m::answer()"#)?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "utils/calc" as c; c::twice(21)"#)?,
+        42
+    );
+
+    Ok(())
+}
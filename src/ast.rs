@@ -17,7 +17,8 @@ use crate::stdlib::{
 };
 use crate::token::Token;
 use crate::{
-    Dynamic, FnNamespace, FnPtr, ImmutableString, Module, Position, Shared, StaticVec, INT,
+    Dynamic, FnNamespace, FnPtr, ImmutableString, Module, Permissions, Position, Shared, StaticVec,
+    INT,
 };
 
 #[cfg(not(feature = "no_float"))]
@@ -64,6 +65,8 @@ pub struct ScriptFnDef {
     pub externals: StaticVec<ImmutableString>,
     /// Function doc-comments (if any).
     pub comments: StaticVec<String>,
+    /// Is this a generator function (i.e. does its body contain `yield` statements)?
+    pub is_generator: bool,
 }
 
 impl fmt::Display for ScriptFnDef {
@@ -155,6 +158,8 @@ pub struct AST {
     /// Embedded module resolver, if any.
     #[cfg(not(feature = "no_module"))]
     resolver: Option<Shared<crate::module::resolvers::StaticModuleResolver>>,
+    /// Permissions restricting evaluations of the [`AST`], if any.
+    permissions: Option<Shared<Permissions>>,
 }
 
 impl Default for AST {
@@ -166,6 +171,7 @@ impl Default for AST {
             functions: Default::default(),
             #[cfg(not(feature = "no_module"))]
             resolver: None,
+            permissions: None,
         }
     }
 }
@@ -186,6 +192,7 @@ impl AST {
             functions: functions.into(),
             #[cfg(not(feature = "no_module"))]
             resolver: None,
+            permissions: None,
         }
     }
     /// Create a new [`AST`] with a source name.
@@ -204,6 +211,7 @@ impl AST {
             functions: functions.into(),
             #[cfg(not(feature = "no_module"))]
             resolver: None,
+            permissions: None,
         }
     }
     /// Get the source, if any.
@@ -233,6 +241,31 @@ impl AST {
         self.source = None;
         self
     }
+    /// Get the [`Permissions`] restricting evaluations of the [`AST`], if any.
+    #[inline(always)]
+    pub fn permissions(&self) -> Option<&Permissions> {
+        self.permissions.as_ref().map(|p| p.as_ref())
+    }
+    /// Clone the [`Permissions`] restricting evaluations of the [`AST`], if any.
+    #[inline(always)]
+    pub(crate) fn clone_permissions(&self) -> Option<Shared<Permissions>> {
+        self.permissions.clone()
+    }
+    /// Set the [`Permissions`] restricting evaluations of the [`AST`].
+    ///
+    /// When [`AST`]'s are merged or combined, the [`Permissions`] of the first [`AST`] are kept,
+    /// or those of the second if the first has none.
+    #[inline(always)]
+    pub fn set_permissions(&mut self, permissions: impl Into<Shared<Permissions>>) -> &mut Self {
+        self.permissions = Some(permissions.into());
+        self
+    }
+    /// Remove the [`Permissions`] restricting evaluations of the [`AST`].
+    #[inline(always)]
+    pub fn clear_permissions(&mut self) -> &mut Self {
+        self.permissions = None;
+        self
+    }
     /// Get the statements.
     #[cfg(not(feature = "internals"))]
     #[inline(always)]
@@ -343,6 +376,7 @@ impl AST {
             functions: functions.into(),
             #[cfg(not(feature = "no_module"))]
             resolver: self.resolver.clone(),
+            permissions: self.permissions.clone(),
         }
     }
     /// Clone the [`AST`]'s script statements into a new [`AST`].
@@ -355,6 +389,7 @@ impl AST {
             functions: Default::default(),
             #[cfg(not(feature = "no_module"))]
             resolver: self.resolver.clone(),
+            permissions: self.permissions.clone(),
         }
     }
     /// Merge two [`AST`] into one.  Both [`AST`]'s are untouched and a new, merged, version
@@ -541,11 +576,16 @@ impl AST {
         let mut functions = functions.as_ref().clone();
         functions.merge_filtered(&other.functions, &filter);
 
-        if let Some(source) = source {
+        let mut ast = if let Some(source) = source {
             Self::new_with_source(merged.statements, functions, source)
         } else {
             Self::new(merged.statements, functions)
-        }
+        };
+        ast.permissions = self
+            .permissions
+            .clone()
+            .or_else(|| other.permissions.clone());
+        ast
     }
     /// Combine one [`AST`] with another.  The second [`AST`] is consumed.
     ///
@@ -611,6 +651,9 @@ impl AST {
         if !other.functions.is_empty() {
             shared_make_mut(&mut self.functions).merge_filtered(&other.functions, &filter);
         }
+        if self.permissions.is_none() {
+            self.permissions = other.permissions;
+        }
         self
     }
     /// Filter out the functions, retaining only some based on a filter predicate.
@@ -780,7 +823,7 @@ impl fmt::Debug for Ident {
     }
 }
 
-/// _(INTERNALS)_ A type encapsulating the mode of a `return`/`throw` statement.
+/// _(INTERNALS)_ A type encapsulating the mode of a `return`/`throw`/`yield` statement.
 /// Exported under the `internals` feature only.
 ///
 /// # Volatile Data Structure
@@ -792,6 +835,10 @@ pub enum ReturnType {
     Return,
     /// `throw` statement.
     Exception,
+    /// `yield` statement.
+    ///
+    /// Never appears under the `no_function` feature.
+    Yield,
 }
 
 /// _(INTERNALS)_ An [`AST`] node, consisting of either an [`Expr`] or a [`Stmt`].
@@ -853,6 +900,51 @@ impl fmt::Debug for StmtBlock {
     }
 }
 
+/// _(INTERNALS)_ The names bound by an `import` statement.
+/// Exported under the `internals` feature only.
+///
+/// # Volatile Data Structure
+///
+/// This type is volatile and may change.
+#[cfg(not(feature = "no_module"))]
+#[derive(Debug, Clone, Hash)]
+pub enum ImportNames {
+    /// `import` expr `as` var
+    Module(Ident),
+    /// `import` `{` var `as` var `,` ... `}` `from` expr
+    Selected(StaticVec<(Ident, Option<Ident>)>),
+}
+
+/// _(INTERNALS)_ A module declared inline via a `module` block.
+/// Exported under the `internals` feature only.
+///
+/// # Volatile Data Structure
+///
+/// This type is volatile and may change.
+#[cfg(not(feature = "no_module"))]
+#[derive(Debug, Clone)]
+pub struct ModuleDef {
+    /// Module name.
+    pub name: Ident,
+    /// Module body, evaluated into a [`Module`] when the declaration is run.
+    pub ast: AST,
+}
+
+#[cfg(not(feature = "no_module"))]
+impl Hash for ModuleDef {
+    fn hash<H: crate::stdlib::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.ast.body.hash(state);
+
+        #[cfg(not(feature = "no_function"))]
+        self.ast.iter_fn_def().for_each(|f| {
+            f.name.hash(state);
+            f.params.hash(state);
+            f.body.hash(state);
+        });
+    }
+}
+
 /// _(INTERNALS)_ A statement.
 /// Exported under the `internals` feature only.
 ///
@@ -895,12 +987,15 @@ pub enum Stmt {
     Break(Position),
     /// `return`/`throw`
     Return(ReturnType, Option<Expr>, Position),
-    /// `import` expr `as` var
+    /// `import` expr `as` var, or `import` `{` var `as` var `,` ... `}` `from` expr
     #[cfg(not(feature = "no_module"))]
-    Import(Expr, Option<Ident>, Position),
+    Import(Expr, Option<Box<ImportNames>>, Position),
     /// `export` var `as` var `,` ...
     #[cfg(not(feature = "no_module"))]
     Export(Vec<(Ident, Option<Ident>)>, Position),
+    /// `module` id `{` stmt`;` ... `}`
+    #[cfg(not(feature = "no_module"))]
+    Module(Box<ModuleDef>, Position),
     /// Convert a variable to shared.
     #[cfg(not(feature = "no_closure"))]
     Share(Ident),
@@ -967,6 +1062,8 @@ impl Stmt {
             Self::Import(_, _, pos) => *pos,
             #[cfg(not(feature = "no_module"))]
             Self::Export(_, pos) => *pos,
+            #[cfg(not(feature = "no_module"))]
+            Self::Module(_, pos) => *pos,
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(x) => x.pos,
@@ -998,6 +1095,8 @@ impl Stmt {
             Self::Import(_, _, pos) => *pos = new_pos,
             #[cfg(not(feature = "no_module"))]
             Self::Export(_, pos) => *pos = new_pos,
+            #[cfg(not(feature = "no_module"))]
+            Self::Module(_, pos) => *pos = new_pos,
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(x) => x.pos = new_pos,
@@ -1024,7 +1123,7 @@ impl Stmt {
             | Self::Return(_, _, _) => false,
 
             #[cfg(not(feature = "no_module"))]
-            Self::Import(_, _, _) | Self::Export(_, _) => false,
+            Self::Import(_, _, _) | Self::Export(_, _) | Self::Module(_, _) => false,
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(_) => unreachable!("Stmt::Share should not be parsed"),
@@ -1054,6 +1153,8 @@ impl Stmt {
 
             #[cfg(not(feature = "no_module"))]
             Self::Import(_, _, _) | Self::Export(_, _) => false,
+            #[cfg(not(feature = "no_module"))]
+            Self::Module(_, _) => true,
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(_) => unreachable!("Stmt::Share should not be parsed"),
@@ -1096,6 +1197,8 @@ impl Stmt {
             Self::Import(_, _, _) => false,
             #[cfg(not(feature = "no_module"))]
             Self::Export(_, _) => false,
+            #[cfg(not(feature = "no_module"))]
+            Self::Module(_, _) => false,
 
             #[cfg(not(feature = "no_closure"))]
             Self::Share(_) => false,
@@ -1128,6 +1231,7 @@ impl Stmt {
     #[inline(always)]
     pub fn is_control_flow_break(&self) -> bool {
         match self {
+            Self::Return(ReturnType::Yield, _, _) => false,
             Self::Return(_, _, _) | Self::Break(_) | Self::Continue(_) => true,
             _ => false,
         }
@@ -1239,6 +1343,20 @@ impl Stmt {
                     return false;
                 }
             }
+            #[cfg(not(feature = "no_module"))]
+            Self::Module(x, _) => {
+                for s in &x.ast.body.statements {
+                    if !s.walk(path, on_node) {
+                        return false;
+                    }
+                }
+                #[cfg(not(feature = "no_function"))]
+                for s in x.ast.iter_fn_def().flat_map(|f| f.body.statements.iter()) {
+                    if !s.walk(path, on_node) {
+                        return false;
+                    }
+                }
+            }
             _ => (),
         }
 
@@ -1534,8 +1652,12 @@ pub enum Expr {
     FnPointer(ImmutableString, Position),
     /// [ expr, ... ]
     Array(Box<StaticVec<Expr>>, Position),
+    /// #[ expr, ... ]
+    Set(Box<StaticVec<Expr>>, Position),
     /// #{ name:expr, ... }
     Map(Box<StaticVec<(Ident, Expr)>>, Position),
+    /// Record construction - (type name, [(field, expr), ...])
+    Record(Box<(Ident, StaticVec<(Ident, Expr)>)>, Position),
     /// ()
     Unit(Position),
     /// Variable access - (optional index, optional (hash, modules), variable name)
@@ -1592,6 +1714,15 @@ impl Expr {
                 Dynamic(Union::Array(Box::new(arr), AccessMode::ReadOnly))
             }
 
+            #[cfg(not(feature = "no_index"))]
+            Self::Set(x, _) if self.is_constant() => {
+                let mut set = crate::Set::new();
+                x.iter().for_each(|v| {
+                    set.insert(v.get_constant_value().unwrap()).unwrap();
+                });
+                Dynamic(Union::Set(Box::new(set), AccessMode::ReadOnly))
+            }
+
             #[cfg(not(feature = "no_object"))]
             Self::Map(x, _) if self.is_constant() => {
                 let mut map = Map::new();
@@ -1626,8 +1757,8 @@ impl Expr {
             Self::CharConstant(_, pos) => *pos,
             Self::StringConstant(_, pos) => *pos,
             Self::FnPointer(_, pos) => *pos,
-            Self::Array(_, pos) => *pos,
-            Self::Map(_, pos) => *pos,
+            Self::Array(_, pos) | Self::Set(_, pos) => *pos,
+            Self::Map(_, pos) | Self::Record(_, pos) => *pos,
             Self::Property(x) => (x.2).pos,
             Self::Stmt(x) => x.pos,
             Self::Variable(x) => (x.2).pos,
@@ -1655,8 +1786,8 @@ impl Expr {
             Self::CharConstant(_, pos) => *pos = new_pos,
             Self::StringConstant(_, pos) => *pos = new_pos,
             Self::FnPointer(_, pos) => *pos = new_pos,
-            Self::Array(_, pos) => *pos = new_pos,
-            Self::Map(_, pos) => *pos = new_pos,
+            Self::Array(_, pos) | Self::Set(_, pos) => *pos = new_pos,
+            Self::Map(_, pos) | Self::Record(_, pos) => *pos = new_pos,
             Self::Variable(x) => (x.2).pos = new_pos,
             Self::Property(x) => (x.2).pos = new_pos,
             Self::Stmt(x) => x.pos = new_pos,
@@ -1675,10 +1806,12 @@ impl Expr {
     #[inline]
     pub fn is_pure(&self) -> bool {
         match self {
-            Self::Array(x, _) => x.iter().all(Self::is_pure),
+            Self::Array(x, _) | Self::Set(x, _) => x.iter().all(Self::is_pure),
 
             Self::Map(x, _) => x.iter().map(|(_, v)| v).all(Self::is_pure),
 
+            Self::Record(x, _) => x.1.iter().map(|(_, v)| v).all(Self::is_pure),
+
             Self::Index(x, _) | Self::And(x, _) | Self::Or(x, _) => {
                 x.lhs.is_pure() && x.rhs.is_pure()
             }
@@ -1716,6 +1849,14 @@ impl Expr {
             // An array literal is constant if all items are constant
             Self::Array(x, _) => x.iter().all(Self::is_constant),
 
+            // A set literal is constant if all items are constant and hashable
+            Self::Set(x, _) => x.iter().all(|expr| {
+                expr.is_constant()
+                    && expr
+                        .get_constant_value()
+                        .map_or(false, |value| value.is_hashable())
+            }),
+
             // An map literal is constant if all items are constant
             Self::Map(x, _) => x.iter().map(|(_, expr)| expr).all(Self::is_constant),
 
@@ -1750,7 +1891,9 @@ impl Expr {
             | Self::Dot(_, _)
             | Self::Index(_, _)
             | Self::Array(_, _)
-            | Self::Map(_, _) => match token {
+            | Self::Set(_, _)
+            | Self::Map(_, _)
+            | Self::Record(_, _) => match token {
                 #[cfg(not(feature = "no_index"))]
                 Token::LeftBracket => true,
                 _ => false,
@@ -1796,7 +1939,7 @@ impl Expr {
                     }
                 }
             }
-            Self::Array(x, _) => {
+            Self::Array(x, _) | Self::Set(x, _) => {
                 for e in x.as_ref() {
                     if !e.walk(path, on_node) {
                         return false;
@@ -1810,6 +1953,13 @@ impl Expr {
                     }
                 }
             }
+            Self::Record(x, _) => {
+                for (_, e) in &x.1 {
+                    if !e.walk(path, on_node) {
+                        return false;
+                    }
+                }
+            }
             Self::Index(x, _) | Self::Dot(x, _) | Expr::And(x, _) | Expr::Or(x, _) => {
                 if !x.lhs.walk(path, on_node) {
                     return false;
@@ -0,0 +1,99 @@
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+use rhai::module_resolvers::Bundle;
+
+use rhai::Engine;
+
+use std::{env, process::exit};
+
+fn print_usage() {
+    eprintln!("Usage: rhai-bundle [options] <directory> <output>");
+    eprintln!();
+    eprintln!("Packs all the script files under <directory> into the bundle file <output>.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --check            compile every script, failing on any syntax error");
+    eprintln!("  --ext <extension>  extension of script files (default: rhai)");
+    eprintln!("  --set <key=value>  add an entry to the bundle manifest");
+}
+
+fn main() {
+    let mut check = false;
+    let mut extension = "rhai".to_string();
+    let mut manifest = Vec::new();
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--ext" => match args.next() {
+                Some(ext) => extension = ext,
+                None => {
+                    print_usage();
+                    exit(1);
+                }
+            },
+            "--set" => match args.next().as_ref().and_then(|s| {
+                let mut parts = s.splitn(2, '=');
+                Some((parts.next()?, parts.next()?))
+            }) {
+                Some((key, value)) => manifest.push((key.to_string(), value.to_string())),
+                None => {
+                    print_usage();
+                    exit(1);
+                }
+            },
+            "-h" | "--help" => {
+                print_usage();
+                exit(0);
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.len() != 2 {
+        print_usage();
+        exit(1);
+    }
+
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(not(feature = "no_std"))]
+    {
+        let (dir, output) = (&paths[0], &paths[1]);
+
+        let mut bundle = match Bundle::from_dir(dir, &extension) {
+            Ok(bundle) => bundle,
+            Err(err) => {
+                eprintln!("{}", err);
+                exit(1);
+            }
+        };
+
+        for (key, value) in manifest {
+            bundle.set_manifest_entry(key, value);
+        }
+
+        if check {
+            if let Err(err) = bundle.check_syntax(&Engine::new()) {
+                eprintln!("{}", err);
+                exit(1);
+            }
+        }
+
+        if let Err(err) = bundle.save(output) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+
+        println!("{} script(s) bundled into '{}'.", bundle.len(), output);
+    }
+
+    #[cfg(any(feature = "no_module", feature = "no_std"))]
+    {
+        let _ = (check, extension, manifest, Engine::new());
+        eprintln!("Script bundles are not available under `no_module` or `no_std`.");
+        exit(1);
+    }
+}
@@ -0,0 +1,119 @@
+//! Module defining the trait and builder for registering custom types.
+
+use crate::dynamic::Variant;
+use crate::fn_native::SendSync;
+use crate::fn_register::RegisterNativeFunction;
+use crate::stdlib::{any::type_name, marker::PhantomData};
+use crate::Engine;
+
+/// Trait for a custom type that can be registered with an [`Engine`] in one go via
+/// [`build_type`][Engine::build_type].
+///
+/// This trait is normally implemented via `#[derive(CustomType)]`.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{CustomType, Engine, TypeBuilder, INT};
+///
+/// #[derive(Debug, Clone)]
+/// struct Counter {
+///     count: INT,
+/// }
+///
+/// impl CustomType for Counter {
+///     fn build(mut builder: TypeBuilder<Self>) {
+///         builder
+///             .with_name("Counter")
+///             .with_fn("new_counter", || Counter { count: 0 })
+///             .with_fn("inc", |c: &mut Counter| c.count += 1)
+///             .with_get("count", |c: &mut Counter| c.count);
+///     }
+/// }
+///
+/// let mut engine = Engine::new();
+///
+/// engine.build_type::<Counter>();
+///
+/// assert_eq!(engine.eval::<INT>("let c = new_counter(); c.inc(); c.inc(); c.count")?, 2);
+/// assert_eq!(engine.eval::<String>("type_of(new_counter())")?, "Counter");
+/// # Ok(())
+/// # }
+/// ```
+pub trait CustomType: Variant + Clone {
+    /// Register the API of this type via a [`TypeBuilder`].
+    fn build(builder: TypeBuilder<Self>);
+}
+
+/// Builder for registering the API of a custom type with an [`Engine`].
+///
+/// Property getters and setters are ignored under `no_object`.
+pub struct TypeBuilder<'a, T: Variant + Clone> {
+    engine: &'a mut Engine,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
+    /// Create a [`TypeBuilder`] for a custom type.
+    #[inline(always)]
+    pub(crate) fn new(engine: &'a mut Engine) -> Self {
+        Self {
+            engine,
+            _marker: PhantomData,
+        }
+    }
+    /// Set a pretty-print name for the `type_of` function.
+    #[inline(always)]
+    pub fn with_name(&mut self, name: &str) -> &mut Self {
+        self.engine
+            .type_names
+            .insert(type_name::<T>().into(), name.into());
+        self
+    }
+    /// Register a function (or method, if its first parameter is `&mut T`).
+    #[inline(always)]
+    pub fn with_fn<A, F>(&mut self, name: &str, func: F) -> &mut Self
+    where
+        F: RegisterNativeFunction<A, ()>,
+    {
+        self.engine.register_fn(name, func);
+        self
+    }
+    /// Register a property getter.
+    #[inline(always)]
+    pub fn with_get<U: Variant + Clone>(
+        &mut self,
+        name: &str,
+        get_fn: impl Fn(&mut T) -> U + SendSync + 'static,
+    ) -> &mut Self {
+        #[cfg(not(feature = "no_object"))]
+        self.engine.register_get(name, get_fn);
+        #[cfg(feature = "no_object")]
+        let _ = (name, get_fn);
+        self
+    }
+    /// Register a property setter.
+    #[inline(always)]
+    pub fn with_set<U: Variant + Clone>(
+        &mut self,
+        name: &str,
+        set_fn: impl Fn(&mut T, U) + SendSync + 'static,
+    ) -> &mut Self {
+        #[cfg(not(feature = "no_object"))]
+        self.engine.register_set(name, set_fn);
+        #[cfg(feature = "no_object")]
+        let _ = (name, set_fn);
+        self
+    }
+    /// Register a property getter and setter.
+    #[inline(always)]
+    pub fn with_get_set<U: Variant + Clone>(
+        &mut self,
+        name: &str,
+        get_fn: impl Fn(&mut T) -> U + SendSync + 'static,
+        set_fn: impl Fn(&mut T, U) + SendSync + 'static,
+    ) -> &mut Self {
+        self.with_get(name, get_fn).with_set(name, set_fn)
+    }
+}
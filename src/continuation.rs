@@ -0,0 +1,587 @@
+//! Module defining the continuation of a suspended script evaluation.
+
+use crate::fn_native::{shared_try_take, AsyncCall, Locked, TypeIterator};
+use crate::stdlib::{
+    boxed::Box,
+    fmt, format, mem,
+    ops::DerefMut,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::{Dynamic, EvalAltResult, Position, RhaiResult, Scope, Shared};
+
+#[cfg(feature = "serde")]
+use crate::generator::GeneratorStep;
+
+#[cfg(feature = "serde")]
+use serde::{de::Deserializer, ser::Error, ser::Serializer, Deserialize, Serialize};
+
+/// The branch of a compound statement that leads to the point of suspension.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) enum Branch {
+    /// Statements block.
+    Block,
+    /// `if` branch of an `if` statement.
+    Then,
+    /// `else` branch of an `if` statement.
+    Else,
+    /// Matching case of a `switch` statement.  Wrapped value is the hash of the case.
+    Case(u64),
+    /// Default case of a `switch` statement.
+    Default,
+    /// Body of a `while`, `loop` or `do` statement.
+    Body,
+    /// Condition of a `do` statement.
+    Condition,
+    /// Body of a `for` statement, with the values still to be iterated.
+    For(ForLoop),
+    /// `try` block of a `try` ... `catch` statement.
+    Try,
+    /// `catch` block of a `try` ... `catch` statement.
+    Catch,
+}
+
+/// Lock the values iterated by a suspended `for` loop.
+#[inline(always)]
+fn lock(iter: &Locked<Box<TypeIterator>>) -> impl DerefMut<Target = Box<TypeIterator>> + '_ {
+    #[cfg(not(feature = "sync"))]
+    return iter.borrow_mut();
+    #[cfg(feature = "sync")]
+    return iter.write().unwrap();
+}
+
+/// The values still to be iterated by a `for` loop suspended within its body.
+///
+/// Clones share the same values, so a [`Continuation`] resumed more than once continues the loop
+/// from where the previous resumption left it.
+#[derive(Clone)]
+pub(crate) struct ForLoop(Shared<Locked<Box<TypeIterator>>>);
+
+impl fmt::Debug for ForLoop {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ForLoop")
+    }
+}
+
+impl ForLoop {
+    /// Create a new [`ForLoop`] with the values still to be iterated.
+    #[inline(always)]
+    pub(crate) fn new(iter: Box<TypeIterator>) -> Self {
+        Self(Shared::new(Locked::new(iter)))
+    }
+    /// Take the values still to be iterated.
+    pub(crate) fn take_iter(self) -> Box<TypeIterator> {
+        match shared_try_take(self.0) {
+            #[cfg(not(feature = "sync"))]
+            Ok(iter) => iter.into_inner(),
+            #[cfg(feature = "sync")]
+            Ok(iter) => iter.into_inner().unwrap(),
+            // Still shared by a clone
+            Err(shared) => Box::new(SharedIter(shared)),
+        }
+    }
+}
+
+/// Iterator over the values of a [`ForLoop`] shared by a clone.
+struct SharedIter(Shared<Locked<Box<TypeIterator>>>);
+
+impl Iterator for SharedIter {
+    type Item = Dynamic;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        lock(&self.0).next()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ForLoop {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        let mut iter = lock(&self.0);
+        let mut values = Vec::new();
+
+        // Collect the values still to be iterated, up to the first step of a generator
+        let result = loop {
+            match iter.next() {
+                Some(value) if value.is::<GeneratorStep>() => {
+                    let error = S::Error::custom("cannot serialize a for loop over a generator");
+                    break Err((value, error));
+                }
+                Some(value) => values.push(value),
+                None => break Ok(()),
+            }
+        };
+
+        // Keep iterating the collected values
+        match result {
+            Ok(()) => {
+                *iter = Box::new(values.clone().into_iter());
+                values.serialize(ser)
+            }
+            Err((step, error)) => {
+                let rest = mem::replace(&mut *iter, Box::new(crate::stdlib::iter::empty()));
+                *iter = Box::new(values.into_iter().chain(Some(step)).chain(rest));
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ForLoop {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let values: Vec<Dynamic> = Deserialize::deserialize(de)?;
+        Ok(Self::new(Box::new(values.into_iter())))
+    }
+}
+
+/// A step on the path to the point of suspension.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) enum Step {
+    /// Statement at `index` of a statements block entered with `scope_len` variables in scope.
+    Stmt { index: usize, scope_len: usize },
+    /// Branch of a compound statement.
+    Branch(Branch),
+    /// Values of the sub-expressions of an expression evaluated before the suspension.
+    Expr(Vec<Dynamic>),
+    /// Method call within a dot/index chain, with the number of levels of the chain after it
+    /// and the values of their indices and arguments.
+    #[cfg(not(feature = "no_object"))]
+    Method(usize, Vec<Dynamic>),
+}
+
+/// A call frame of a suspended script evaluation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Frame {
+    /// Name and number of parameters of the script-defined function.
+    /// `None` for the global level.
+    pub function: Option<(String, usize)>,
+    /// Position of the suspended call.
+    pub call_pos: Position,
+    /// Path of steps leading to the suspended call, inner-most first.
+    pub path: Vec<Step>,
+    /// Variables in scope: name, is constant, and value.
+    pub scope: Vec<(String, bool, Dynamic)>,
+}
+
+impl Frame {
+    /// Create a new [`Frame`] for a call at the specified position.
+    #[inline(always)]
+    fn new(call_pos: Position) -> Self {
+        Self {
+            function: None,
+            call_pos,
+            path: Default::default(),
+            scope: Default::default(),
+        }
+    }
+    /// Take the variables saved in this [`Frame`] as a new [`Scope`].
+    pub fn take_scope(&mut self) -> Scope<'static> {
+        let mut scope = Scope::new();
+        self.scope.drain(..).for_each(|(name, is_constant, value)| {
+            if is_constant {
+                scope.push_constant_dynamic(name, value);
+            } else {
+                scope.push_dynamic(name, value);
+            }
+        });
+        scope
+    }
+}
+
+/// The continuation of a suspended script evaluation.
+///
+/// A native Rust function requests suspension by returning [`Continuation::suspend`].
+/// The evaluation then stops with an
+/// [`EvalAltResult::ErrorSuspended`][crate::EvalAltResult::ErrorSuspended] error that wraps
+/// the [`Continuation`], which records the position of the suspended call, the variables in
+/// scope and all the script-defined function calls leading to it.
+///
+/// The [`Continuation`] can be kept (or, under the `serde` feature, serialized) and the
+/// evaluation resumed later via [`Engine::resume_with_scope`][crate::Engine::resume_with_scope]
+/// with the same [`AST`][crate::AST], passing in the value that the suspended call returns.
+///
+/// # Limitations
+///
+/// * `import` statements leading to the suspended call are evaluated again when resumed.
+///
+/// * Suspension is not supported within property getters, setters and indexers, custom syntax,
+///   method calls to script-defined functions, functions defined in modules, `eval` and
+///   callbacks from native Rust functions.
+///
+/// * Clones of a [`Continuation`] suspended within a `for` loop share the values still to be
+///   iterated.  Under the `serde` feature, these values are saved when serialized, which is not
+///   possible for a `for` loop over a generator.
+///
+/// * Shared values are not shared anymore after being serialized and deserialized.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Continuation, Engine, EvalAltResult, Scope, INT};
+///
+/// let mut engine = Engine::new();
+///
+/// engine.register_result_fn("ask", |question: &str| Continuation::suspend::<INT>(question.into()));
+///
+/// let ast = engine.compile("let x = 40; let y = ask(\"how much?\"); x + y")?;
+///
+/// let continuation = match *engine.eval_ast::<INT>(&ast).expect_err("should suspend") {
+///     EvalAltResult::ErrorSuspended(continuation, _) => continuation,
+///     err => panic!("{}", err),
+/// };
+///
+/// assert_eq!(continuation.value().clone().cast::<String>(), "how much?");
+///
+/// let mut scope = Scope::new();
+///
+/// // ... some time later
+/// let result = engine.resume_with_scope::<INT>(&mut scope, &ast, *continuation, (2 as INT).into())?;
+///
+/// assert_eq!(result, 42);
+/// assert_eq!(scope.get_value::<INT>("y").expect("variable y should exist"), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Continuation {
+    /// Value passed by the native Rust function requesting suspension.
+    pub(crate) value: Dynamic,
+    /// Call frames, inner-most first.
+    pub(crate) frames: Vec<Frame>,
+    /// Is this suspension caused by a `yield` statement within a generator function?
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) is_yield: bool,
+    /// Pending call to an `async` native Rust function, if any.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) async_call: Option<AsyncCall>,
+}
+
+impl Continuation {
+    /// Create a new [`Continuation`] with a value that is made available to the host.
+    #[inline(always)]
+    pub fn new(value: impl Into<Dynamic>) -> Self {
+        Self {
+            value: value.into(),
+            frames: Default::default(),
+            is_yield: false,
+            async_call: None,
+        }
+    }
+    /// Create a new [`Continuation`] for a `yield` statement within a generator function.
+    #[inline(always)]
+    pub(crate) fn new_yield(value: Dynamic) -> Self {
+        Self {
+            value,
+            frames: Default::default(),
+            is_yield: true,
+            async_call: None,
+        }
+    }
+    /// Create a new [`Continuation`] for a call to an `async` native Rust function.
+    #[inline(always)]
+    pub(crate) fn new_async(call: AsyncCall) -> Self {
+        Self {
+            value: Dynamic::UNIT,
+            frames: Default::default(),
+            is_yield: false,
+            async_call: Some(call),
+        }
+    }
+    /// Request suspension of the script evaluation from within a native Rust function,
+    /// passing a value to the host.
+    ///
+    /// The suspended function call returns the value passed to
+    /// [`Engine::resume_with_scope`][crate::Engine::resume_with_scope] when resumed.
+    #[inline(always)]
+    pub fn suspend<T>(value: Dynamic) -> Result<T, Box<EvalAltResult>> {
+        EvalAltResult::ErrorSuspended(Self::new(value).into(), Position::NONE).into()
+    }
+    /// Get the value passed by the native Rust function requesting suspension.
+    #[inline(always)]
+    pub fn value(&self) -> &Dynamic {
+        &self.value
+    }
+    /// Get the [position][Position] of the suspended function call.
+    #[inline(always)]
+    pub fn position(&self) -> Position {
+        self.frames
+            .first()
+            .map(|f| f.call_pos)
+            .unwrap_or(Position::NONE)
+    }
+    /// Has the suspension not yet passed through any statement?
+    #[inline(always)]
+    pub(crate) fn is_at_source(&self) -> bool {
+        self.frames.is_empty()
+    }
+    /// Record a step on the path to the point of suspension in the current frame.
+    #[inline(always)]
+    fn record(&mut self, step: Step, pos: Position) {
+        if self.frames.is_empty() {
+            self.frames.push(Frame::new(pos));
+        }
+        self.frames.last_mut().unwrap().path.push(step);
+    }
+    /// Record the statement at `index` of the current statements block.
+    ///
+    /// The variables in scope are saved if this is the inner-most statement leading to the
+    /// suspended call.
+    pub(crate) fn record_stmt(
+        &mut self,
+        scope: &Scope,
+        index: usize,
+        block_scope_len: usize,
+        stmt_scope_len: usize,
+        pos: Position,
+    ) {
+        if self.frames.is_empty() {
+            self.frames.push(Frame::new(pos));
+        }
+
+        let frame = self.frames.last_mut().unwrap();
+
+        if !frame
+            .path
+            .iter()
+            .any(|step| matches!(step, Step::Stmt { .. }))
+        {
+            frame.scope = scope
+                .iter_raw()
+                .take(stmt_scope_len)
+                .map(|(name, is_constant, value)| (name.into(), is_constant, value.clone()))
+                .collect();
+        }
+
+        frame.path.push(Step::Stmt {
+            index,
+            scope_len: block_scope_len,
+        });
+    }
+    /// Is the inner-most step recorded in the current frame a method call within a dot/index
+    /// chain?
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub(crate) fn is_in_method(&self) -> bool {
+        matches!(
+            self.frames.last().and_then(|frame| frame.path.last()),
+            Some(Step::Method(_, _))
+        )
+    }
+    /// Turn the current frame into the frame of a script-defined function and open a new frame
+    /// for the function call at `pos`.
+    ///
+    /// Variables below `scope_base` belong to the caller and are not saved in the function frame.
+    pub(crate) fn enter_function(
+        &mut self,
+        name: &str,
+        num_params: usize,
+        scope_base: usize,
+        pos: Position,
+    ) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.function = Some((name.into(), num_params));
+            frame.scope.drain(..scope_base.min(frame.scope.len()));
+            frame.path.iter_mut().for_each(|step| {
+                if let Step::Stmt { scope_len, .. } = step {
+                    *scope_len = scope_len.saturating_sub(scope_base);
+                }
+            });
+        }
+        self.frames.push(Frame::new(pos));
+    }
+}
+
+/// Record the branch of a compound statement if the error is a suspension.
+#[inline(always)]
+pub(crate) fn suspended_in(mut err: Box<EvalAltResult>, branch: Branch) -> Box<EvalAltResult> {
+    if let EvalAltResult::ErrorSuspended(ref mut cont, pos) = *err {
+        cont.record(Step::Branch(branch), pos);
+    }
+    err
+}
+
+/// Record the values of the sub-expressions evaluated before the suspension if the error is a
+/// suspension.
+#[inline(always)]
+pub(crate) fn suspended_with(
+    mut err: Box<EvalAltResult>,
+    values: impl IntoIterator<Item = Dynamic>,
+) -> Box<EvalAltResult> {
+    if let EvalAltResult::ErrorSuspended(ref mut cont, pos) = *err {
+        cont.record(Step::Expr(values.into_iter().collect()), pos);
+    }
+    err
+}
+
+/// Record a method call within a dot/index chain if the error is a suspension, with the number
+/// of levels of the chain after it and the values of their indices and arguments.
+#[cfg(not(feature = "no_object"))]
+#[inline(always)]
+pub(crate) fn suspended_in_method(
+    mut err: Box<EvalAltResult>,
+    levels: usize,
+    values: impl IntoIterator<Item = Dynamic>,
+) -> Box<EvalAltResult> {
+    if let EvalAltResult::ErrorSuspended(ref mut cont, pos) = *err {
+        cont.record(Step::Method(levels, values.into_iter().collect()), pos);
+    }
+    err
+}
+
+/// Make an error for a suspension that cannot be resumed later.
+#[inline(always)]
+pub(crate) fn make_unsupported_err(context: &str) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorRuntime(format!("Cannot suspend {}", context).into(), Position::NONE).into()
+}
+
+/// Reject a suspension passing through a native Rust function if it originates from a script
+/// callback.
+#[inline(always)]
+pub(crate) fn check_native_suspension(err: Box<EvalAltResult>) -> Box<EvalAltResult> {
+    match *err {
+        EvalAltResult::ErrorSuspended(ref cont, _) if !cont.is_at_source() => {
+            make_unsupported_err("within a callback from a native function")
+        }
+        _ => err,
+    }
+}
+
+/// A suspended call to be returned when resuming.
+#[derive(Debug)]
+pub(crate) enum ResumedCall {
+    /// Result of the call.
+    Value(RhaiResult),
+    /// Call to a script-defined function, which is itself resumed from its call frame (the last
+    /// one) with the frames of the calls within it, inner-most first, and the result of the
+    /// inner-most suspended call.
+    #[cfg(not(feature = "no_function"))]
+    Function(Vec<Frame>, RhaiResult),
+}
+
+/// Clone the result of a suspended call.
+///
+/// Errors cannot be cloned, so they are turned into runtime errors with the same message.
+fn clone_result(result: &RhaiResult) -> RhaiResult {
+    match result {
+        Ok(value) => Ok(value.clone()),
+        Err(err) => Err(EvalAltResult::ErrorRuntime(err.to_string().into(), err.position()).into()),
+    }
+}
+
+impl Clone for ResumedCall {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Value(result) => Self::Value(clone_result(result)),
+            #[cfg(not(feature = "no_function"))]
+            Self::Function(frames, result) => Self::Function(frames.clone(), clone_result(result)),
+        }
+    }
+}
+
+/// State of a script evaluation being resumed from a [`Continuation`].
+#[derive(Debug, Clone)]
+pub(crate) struct Resumption {
+    /// Steps still to be taken, last one first.
+    steps: Vec<Step>,
+    /// The suspended call, returned once all the steps are taken.
+    call: Option<(Position, ResumedCall)>,
+}
+
+impl Resumption {
+    /// Create a new [`Resumption`] for a [`Frame`], with the result of its suspended call.
+    #[inline(always)]
+    pub(crate) fn new(frame: &mut Frame, result: RhaiResult) -> Self {
+        Self::with_call(frame, ResumedCall::Value(result))
+    }
+    /// Create a new [`Resumption`] for a [`Frame`] whose suspended call is to a script-defined
+    /// function, with the frames of the calls within it (inner-most first) and the result of the
+    /// inner-most suspended call.
+    ///
+    /// If there are no inner frames, the suspended call returns `result` directly.
+    #[inline(always)]
+    pub(crate) fn with_frames(frame: &mut Frame, inner: Vec<Frame>, result: RhaiResult) -> Self {
+        #[cfg(not(feature = "no_function"))]
+        if !inner.is_empty() {
+            return Self::with_call(frame, ResumedCall::Function(inner, result));
+        }
+        #[cfg(feature = "no_function")]
+        let _ = inner;
+
+        Self::new(frame, result)
+    }
+    /// Create a new [`Resumption`] for a [`Frame`], with its suspended call.
+    #[inline(always)]
+    fn with_call(frame: &mut Frame, call: ResumedCall) -> Self {
+        Self {
+            steps: mem::take(&mut frame.path),
+            call: Some((frame.call_pos, call)),
+        }
+    }
+    /// Take the next step if it matches.
+    #[inline(always)]
+    fn take_step(&mut self, matches: impl Fn(&Step) -> bool) -> Option<Step> {
+        match self.steps.last() {
+            Some(step) if matches(step) => self.steps.pop(),
+            _ => None,
+        }
+    }
+    /// Take the next statement to skip to, returning its index and the number of variables in
+    /// scope when its statements block was entered.
+    #[inline(always)]
+    pub(crate) fn next_stmt(&mut self) -> Option<(usize, usize)> {
+        match self.take_step(|step| matches!(step, Step::Stmt { .. })) {
+            Some(Step::Stmt { index, scope_len }) => Some((index, scope_len)),
+            _ => None,
+        }
+    }
+    /// Number of variables in scope when the next statements block was entered.
+    #[inline(always)]
+    pub(crate) fn next_scope_len(&self) -> Option<usize> {
+        match self.steps.last() {
+            Some(Step::Stmt { scope_len, .. }) => Some(*scope_len),
+            _ => None,
+        }
+    }
+    /// Take the branch of the compound statement being entered.
+    #[inline(always)]
+    pub(crate) fn take_branch(&mut self) -> Option<Branch> {
+        match self.take_step(|step| matches!(step, Step::Branch(_))) {
+            Some(Step::Branch(branch)) => Some(branch),
+            _ => None,
+        }
+    }
+    /// Take the values of the sub-expressions evaluated before the suspension.
+    #[inline(always)]
+    pub(crate) fn take_values(&mut self) -> Option<Vec<Dynamic>> {
+        match self.take_step(|step| matches!(step, Step::Expr(_))) {
+            Some(Step::Expr(values)) => Some(values),
+            _ => None,
+        }
+    }
+    /// Take the method call within a dot/index chain.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub(crate) fn take_method(&mut self) -> Option<(usize, Vec<Dynamic>)> {
+        match self.take_step(|step| matches!(step, Step::Method(_, _))) {
+            Some(Step::Method(levels, values)) => Some((levels, values)),
+            _ => None,
+        }
+    }
+    /// Take the suspended call if all the steps are taken and it is at the specified position.
+    #[inline(always)]
+    pub(crate) fn take_call(&mut self, pos: Position) -> Option<ResumedCall> {
+        match self.call {
+            Some((call_pos, _)) if self.steps.is_empty() && call_pos == pos => {
+                self.call.take().map(|(_, call)| call)
+            }
+            _ => None,
+        }
+    }
+}
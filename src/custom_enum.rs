@@ -0,0 +1,88 @@
+//! Module defining the trait for registering Rust `enum` types.
+
+use crate::dynamic::Variant;
+use crate::stdlib::{boxed::Box, format, vec::Vec};
+use crate::{Dynamic, EvalAltResult, Module, Position};
+
+/// Trait for a Rust `enum` type that can be registered with an [`Engine`][crate::Engine] via
+/// [`register_enum`][crate::Engine::register_enum].
+///
+/// This trait is normally implemented via `#[derive(CustomEnum)]`, which requires all fields
+/// of all variants to implement [`Clone`].
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{CustomEnum, Engine, INT};
+///
+/// #[derive(Debug, Clone, PartialEq, CustomEnum)]
+/// enum Shape {
+///     Empty,
+///     Square(INT),
+///     Rect { w: INT, h: INT },
+/// }
+///
+/// let shape = Shape::Rect { w: 2, h: 3 };
+///
+/// assert_eq!(Shape::enum_name(), "Shape");
+/// assert_eq!(shape.variant_name(), "Rect");
+/// assert_eq!(shape.field_names(), &["w", "h"]);
+/// assert_eq!(shape.field_by_name("h").unwrap().as_int().unwrap(), 3);
+///
+/// let mut engine = Engine::new();
+///
+/// engine.register_enum::<Shape>();
+///
+/// assert_eq!(engine.eval::<Shape>("Shape::Square(42)")?, Shape::Square(42));
+/// # Ok(())
+/// # }
+/// ```
+pub trait CustomEnum: Variant + Clone {
+    /// Name of the `enum` type.
+    fn enum_name() -> &'static str;
+    /// Names of all the named fields in all the variants, without duplicates.
+    fn all_field_names() -> &'static [&'static str];
+    /// Register a constructor for each variant into a [`Module`].
+    ///
+    /// Each constructor is a function with the same name as the variant, taking the fields of the
+    /// variant (in order) as parameters.  Unit variants are also registered as constants.
+    fn register_variants(module: &mut Module);
+    /// Name of this variant.
+    fn variant_name(&self) -> &'static str;
+    /// Names of the fields of this variant.
+    ///
+    /// Empty for unit variants and tuple variants.
+    fn field_names(&self) -> &'static [&'static str];
+    /// Number of fields in this variant.
+    fn field_count(&self) -> usize;
+    /// Get the value of a field of this variant by position.
+    fn field(&self, index: usize) -> Option<Dynamic>;
+    /// Create a value of a variant from the values of its fields (in order).
+    ///
+    /// Returns [`None`] if there is no variant with that name, or if the values do not match
+    /// the fields of the variant.
+    fn from_fields(variant: &str, fields: Vec<Dynamic>) -> Option<Self>;
+    /// Get the value of a named field of this variant.
+    #[inline]
+    fn field_by_name(&self, name: &str) -> Option<Dynamic> {
+        self.field_names()
+            .iter()
+            .position(|&field| field == name)
+            .and_then(|index| self.field(index))
+    }
+}
+
+/// Make an error for reading a named field that the variant of a registered `enum` does not have.
+pub(crate) fn make_field_err<T: CustomEnum>(value: &T, field: &str) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorDotExpr(
+        format!(
+            "Variant '{}' of '{}' has no field '{}'",
+            value.variant_name(),
+            T::enum_name(),
+            field
+        ),
+        Position::NONE,
+    )
+    .into()
+}
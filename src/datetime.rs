@@ -0,0 +1,805 @@
+//! Module defining calendar date/time and duration types.
+
+use crate::stdlib::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use crate::stdlib::string::String;
+
+#[cfg(feature = "serde")]
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Number of nanoseconds in a second.
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Number of seconds in a day.
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Earliest year that can be represented by a [`DateTime`].
+const MIN_YEAR: i64 = 0;
+
+/// Latest year that can be represented by a [`DateTime`].
+const MAX_YEAR: i64 = 9999;
+
+/// Number of seconds since the Unix epoch at the start of [`MIN_YEAR`] in UTC.
+const MIN_SECS: i64 = -62_167_219_200;
+
+/// Number of seconds since the Unix epoch at the end of [`MAX_YEAR`] in UTC.
+const MAX_SECS: i64 = 253_402_300_799;
+
+/// A signed span of time, with nanosecond precision.
+///
+/// Formatted and parsed as an ISO-8601 duration (e.g. `PT1H30M`, `-P2DT0.5S`).
+/// Only weeks, days, hours, minutes and seconds are supported, because years and months
+/// do not have a fixed length.
+///
+/// # Example
+///
+/// ```
+/// use rhai::Duration;
+///
+/// let d: Duration = "P1DT2H3M4.5S".parse().unwrap();
+///
+/// assert_eq!(d.secs(), 93784);
+/// assert_eq!(d.subsec_nanos(), 500_000_000);
+/// assert_eq!((-d).to_string(), "-P1DT2H3M4.5S");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration {
+    /// Whole seconds, rounded towards negative infinity.
+    secs: i64,
+    /// Nanoseconds added to the whole seconds, always less than one second.
+    nanos: u32,
+}
+
+impl Duration {
+    /// A [`Duration`] of zero length.
+    pub const ZERO: Self = Self { secs: 0, nanos: 0 };
+
+    /// Create a new [`Duration`] from whole seconds plus additional nanoseconds.
+    #[inline(always)]
+    pub fn new(secs: i64, nanos: u32) -> Self {
+        Self {
+            secs: secs + (nanos / NANOS_PER_SEC) as i64,
+            nanos: nanos % NANOS_PER_SEC,
+        }
+    }
+    /// Create a new [`Duration`] from whole seconds.
+    #[inline(always)]
+    pub fn from_secs(secs: i64) -> Self {
+        Self { secs, nanos: 0 }
+    }
+    /// Create a new [`Duration`] from milliseconds.
+    #[inline(always)]
+    pub fn from_millis(millis: i64) -> Self {
+        Self {
+            secs: millis.div_euclid(1000),
+            nanos: millis.rem_euclid(1000) as u32 * 1_000_000,
+        }
+    }
+    /// Create a new [`Duration`] from nanoseconds.
+    /// Returns [`None`] if the number of seconds overflows.
+    #[inline]
+    pub fn from_nanos(nanos: i128) -> Option<Self> {
+        let secs = nanos.div_euclid(NANOS_PER_SEC as i128);
+
+        if secs < i64::MIN as i128 || secs > i64::MAX as i128 {
+            None
+        } else {
+            Some(Self {
+                secs: secs as i64,
+                nanos: nanos.rem_euclid(NANOS_PER_SEC as i128) as u32,
+            })
+        }
+    }
+    /// Get the number of whole seconds in this [`Duration`], rounded towards negative infinity.
+    #[inline(always)]
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+    /// Get the nanoseconds to add to [`secs`][Duration::secs] for the full [`Duration`].
+    /// This is always less than one second.
+    #[inline(always)]
+    pub fn subsec_nanos(&self) -> u32 {
+        self.nanos
+    }
+    /// Get the total number of nanoseconds in this [`Duration`].
+    #[inline(always)]
+    pub fn as_nanos(&self) -> i128 {
+        self.secs as i128 * NANOS_PER_SEC as i128 + self.nanos as i128
+    }
+    /// Get the total number of milliseconds in this [`Duration`], rounded towards negative infinity.
+    #[inline(always)]
+    pub fn as_millis(&self) -> i128 {
+        self.as_nanos().div_euclid(1_000_000)
+    }
+    /// Is this [`Duration`] negative?
+    #[inline(always)]
+    pub fn is_negative(&self) -> bool {
+        self.secs < 0
+    }
+    /// Get the absolute value of this [`Duration`].
+    /// Returns [`None`] on overflow.
+    #[inline(always)]
+    pub fn checked_abs(self) -> Option<Self> {
+        if self.is_negative() {
+            self.checked_neg()
+        } else {
+            Some(self)
+        }
+    }
+    /// Negate this [`Duration`].
+    /// Returns [`None`] on overflow.
+    #[inline]
+    pub fn checked_neg(self) -> Option<Self> {
+        if self.nanos == 0 {
+            Some(Self::from_secs(self.secs.checked_neg()?))
+        } else {
+            Some(Self {
+                secs: self.secs.checked_neg()?.checked_sub(1)?,
+                nanos: NANOS_PER_SEC - self.nanos,
+            })
+        }
+    }
+    /// Add two [`Duration`]'s.
+    /// Returns [`None`] on overflow.
+    #[inline]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut secs = self.secs.checked_add(rhs.secs)?;
+        let mut nanos = self.nanos + rhs.nanos;
+
+        if nanos >= NANOS_PER_SEC {
+            secs = secs.checked_add(1)?;
+            nanos -= NANOS_PER_SEC;
+        }
+
+        Some(Self { secs, nanos })
+    }
+    /// Subtract a [`Duration`] from this [`Duration`].
+    /// Returns [`None`] on overflow.
+    #[inline(always)]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+    /// Multiply this [`Duration`] by an integer.
+    /// Returns [`None`] on overflow.
+    #[inline(always)]
+    pub fn checked_mul(self, rhs: i64) -> Option<Self> {
+        Self::from_nanos(self.as_nanos().checked_mul(rhs as i128)?)
+    }
+    /// Divide this [`Duration`] by an integer, rounding towards negative infinity.
+    /// Returns [`None`] if dividing by zero or on overflow.
+    #[inline(always)]
+    pub fn checked_div(self, rhs: i64) -> Option<Self> {
+        if rhs == 0 {
+            None
+        } else {
+            Self::from_nanos(self.as_nanos().div_euclid(rhs as i128))
+        }
+    }
+}
+
+impl crate::stdlib::ops::Neg for Duration {
+    type Output = Self;
+
+    /// # Panics
+    ///
+    /// Panics on overflow.
+    #[inline(always)]
+    fn neg(self) -> Self {
+        self.checked_neg().expect("duration overflow")
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_negative() {
+            f.write_str("-")?;
+        }
+
+        // The absolute value is calculated with `u64` because negating `i64::MIN` overflows.
+        let (secs, nanos) = match (self.is_negative(), self.nanos) {
+            (false, nanos) => (self.secs as u64, nanos),
+            (true, 0) => (self.secs.unsigned_abs(), 0),
+            (true, nanos) => (self.secs.unsigned_abs() - 1, NANOS_PER_SEC - nanos),
+        };
+
+        let days = secs / SECS_PER_DAY as u64;
+        let hours = secs % SECS_PER_DAY as u64 / 3600;
+        let minutes = secs % 3600 / 60;
+        let seconds = secs % 60;
+
+        f.write_str("P")?;
+
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+        if hours > 0 || minutes > 0 || seconds > 0 || nanos > 0 || days == 0 {
+            f.write_str("T")?;
+
+            if hours > 0 {
+                write!(f, "{}H", hours)?;
+            }
+            if minutes > 0 {
+                write!(f, "{}M", minutes)?;
+            }
+            if seconds > 0 || nanos > 0 || (hours == 0 && minutes == 0) {
+                write!(f, "{}", seconds)?;
+                write_fraction(f, nanos)?;
+                f.write_str("S")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Duration {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERR: &str = "invalid ISO-8601 duration";
+
+        let s = s.trim();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let mut s = s
+            .strip_prefix('P')
+            .or_else(|| s.strip_prefix('p'))
+            .ok_or(ERR)?;
+
+        let mut total = Duration::ZERO;
+        let mut in_time = false;
+        let mut has_parts = false;
+
+        while !s.is_empty() {
+            if !in_time && (s.starts_with('T') || s.starts_with('t')) {
+                in_time = true;
+                s = &s[1..];
+                continue;
+            }
+
+            let digits = s.find(|c: char| !c.is_ascii_digit()).ok_or(ERR)?;
+            let whole: i64 = s[..digits].parse().map_err(|_| ERR)?;
+            s = &s[digits..];
+
+            let mut nanos = 0;
+
+            if s.starts_with('.') || s.starts_with(',') {
+                let (n, rest) = parse_fraction(&s[1..]).ok_or(ERR)?;
+                nanos = n;
+                s = rest;
+            }
+
+            let unit = s.chars().next().ok_or(ERR)?;
+            s = &s[unit.len_utf8()..];
+            let unit = unit.to_ascii_uppercase();
+
+            let multiplier = match (in_time, unit) {
+                (false, 'W') => 7 * SECS_PER_DAY,
+                (false, 'D') => SECS_PER_DAY,
+                (true, 'H') => 3600,
+                (true, 'M') => 60,
+                (true, 'S') => 1,
+                (false, 'Y') | (false, 'M') => {
+                    return Err("years and months are not supported in a duration")
+                }
+                _ => return Err(ERR),
+            };
+
+            // Only seconds can have a fraction
+            if nanos > 0 && unit != 'S' {
+                return Err(ERR);
+            }
+
+            let part = Duration::new(whole.checked_mul(multiplier).ok_or(ERR)?, nanos);
+            total = total.checked_add(part).ok_or(ERR)?;
+            has_parts = true;
+        }
+
+        if !has_parts {
+            return Err(ERR);
+        }
+
+        if negative {
+            total.checked_neg().ok_or(ERR)
+        } else {
+            Ok(total)
+        }
+    }
+}
+
+/// A calendar date and time (from year 0 to year 9999), with nanosecond precision,
+/// at a fixed offset from UTC.
+///
+/// Formatted and parsed in the ISO-8601 (RFC 3339) format, e.g. `2021-03-04T05:06:07Z` or
+/// `2021-03-04T10:36:07.5+05:30`.
+///
+/// Two [`DateTime`]'s are equal if they represent the same instant in time,
+/// even if they are at different offsets.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{DateTime, Duration};
+///
+/// let dt: DateTime = "2021-03-04T10:36:07+05:30".parse().unwrap();
+///
+/// assert_eq!(dt.year(), 2021);
+/// assert_eq!(dt.hour(), 10);
+/// assert_eq!(dt.to_utc().to_string(), "2021-03-04T05:06:07Z");
+///
+/// let later = dt.checked_add(Duration::from_secs(86400)).unwrap();
+/// assert_eq!(later.to_string(), "2021-03-05T10:36:07+05:30");
+/// assert_eq!(later.duration_since(&dt), Some(Duration::from_secs(86400)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DateTime {
+    /// Seconds since the Unix epoch (1970-01-01T00:00:00Z).
+    secs: i64,
+    /// Nanoseconds added to the seconds, always less than one second.
+    nanos: u32,
+    /// Offset from UTC in seconds.
+    offset: i32,
+}
+
+impl DateTime {
+    /// The Unix epoch (1970-01-01T00:00:00Z).
+    pub const UNIX_EPOCH: Self = Self {
+        secs: 0,
+        nanos: 0,
+        offset: 0,
+    };
+
+    /// Create a new [`DateTime`] in UTC from the number of seconds (and additional
+    /// nanoseconds) since the Unix epoch.
+    /// Returns [`None`] if out of range.
+    #[inline]
+    pub fn from_unix(secs: i64, nanos: u32) -> Option<Self> {
+        Self::UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+    }
+    /// Create a new [`DateTime`] in UTC from calendar date and time components.
+    /// Returns [`None`] if any of the components is out of range.
+    pub fn from_ymd_hms(
+        year: i64,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Option<Self> {
+        if !(MIN_YEAR..=MAX_YEAR).contains(&year)
+            || !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+
+        Some(Self {
+            secs: days_from_civil(year, month, day) * SECS_PER_DAY
+                + (hour * 3600 + minute * 60 + second) as i64,
+            nanos: 0,
+            offset: 0,
+        })
+    }
+    /// Get the current date and time in UTC from the system clock.
+    ///
+    /// Not available under `no_std`.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn now_utc() -> Self {
+        let now = match crate::stdlib::time::SystemTime::now()
+            .duration_since(crate::stdlib::time::UNIX_EPOCH)
+        {
+            Ok(d) => Duration::new(d.as_secs() as i64, d.subsec_nanos()),
+            Err(err) => -Duration::new(
+                err.duration().as_secs() as i64,
+                err.duration().subsec_nanos(),
+            ),
+        };
+
+        Self::from_unix(now.secs(), now.subsec_nanos()).expect("system clock out of range")
+    }
+    /// Create a new [`DateTime`] for the same instant in time at a different offset from UTC
+    /// (in seconds, east of UTC).
+    /// Returns [`None`] if the offset is not within a day.
+    #[inline]
+    pub fn with_offset(self, offset: i32) -> Option<Self> {
+        if (offset as i64).abs() >= SECS_PER_DAY {
+            None
+        } else {
+            Some(Self { offset, ..self })
+        }
+    }
+    /// Create a new [`DateTime`] for the same instant in time in UTC.
+    #[inline(always)]
+    pub fn to_utc(self) -> Self {
+        Self { offset: 0, ..self }
+    }
+    /// Get the number of seconds since the Unix epoch.
+    #[inline(always)]
+    pub fn unix_timestamp(&self) -> i64 {
+        self.secs
+    }
+    /// Get the offset from UTC, in seconds east of UTC.
+    #[inline(always)]
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+    /// Get the number of days since the Unix epoch and the seconds into the day,
+    /// at the offset of this [`DateTime`].
+    #[inline(always)]
+    fn local_days_secs(&self) -> (i64, i64) {
+        let local = self.secs + self.offset as i64;
+        (
+            local.div_euclid(SECS_PER_DAY),
+            local.rem_euclid(SECS_PER_DAY),
+        )
+    }
+    /// Get the year.
+    #[inline(always)]
+    pub fn year(&self) -> i64 {
+        civil_from_days(self.local_days_secs().0).0
+    }
+    /// Get the month, from 1 to 12.
+    #[inline(always)]
+    pub fn month(&self) -> u32 {
+        civil_from_days(self.local_days_secs().0).1
+    }
+    /// Get the day of the month, from 1 to 31.
+    #[inline(always)]
+    pub fn day(&self) -> u32 {
+        civil_from_days(self.local_days_secs().0).2
+    }
+    /// Get the hour, from 0 to 23.
+    #[inline(always)]
+    pub fn hour(&self) -> u32 {
+        (self.local_days_secs().1 / 3600) as u32
+    }
+    /// Get the minute, from 0 to 59.
+    #[inline(always)]
+    pub fn minute(&self) -> u32 {
+        (self.local_days_secs().1 % 3600 / 60) as u32
+    }
+    /// Get the second, from 0 to 59.
+    #[inline(always)]
+    pub fn second(&self) -> u32 {
+        (self.local_days_secs().1 % 60) as u32
+    }
+    /// Get the nanoseconds into the second.
+    #[inline(always)]
+    pub fn nanosecond(&self) -> u32 {
+        self.nanos
+    }
+    /// Get the ISO-8601 day of the week, from 1 (Monday) to 7 (Sunday).
+    #[inline(always)]
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 was a Thursday
+        ((self.local_days_secs().0 + 3).rem_euclid(7) + 1) as u32
+    }
+    /// Get the day of the year, from 1 to 366.
+    #[inline]
+    pub fn ordinal(&self) -> u32 {
+        let days = self.local_days_secs().0;
+        (days - days_from_civil(civil_from_days(days).0, 1, 1) + 1) as u32
+    }
+    /// Add a [`Duration`] to this [`DateTime`].
+    /// Returns [`None`] if out of range.
+    pub fn checked_add(self, rhs: Duration) -> Option<Self> {
+        let mut secs = self.secs.checked_add(rhs.secs)?;
+        let mut nanos = self.nanos + rhs.nanos;
+
+        if nanos >= NANOS_PER_SEC {
+            secs = secs.checked_add(1)?;
+            nanos -= NANOS_PER_SEC;
+        }
+
+        // The offset is within a day, so bound the seconds first to keep the local time
+        // from overflowing
+        if !(MIN_SECS - SECS_PER_DAY..=MAX_SECS + SECS_PER_DAY).contains(&secs) {
+            return None;
+        }
+
+        let result = Self {
+            secs,
+            nanos,
+            ..self
+        };
+        if !(MIN_YEAR..=MAX_YEAR).contains(&result.year()) {
+            None
+        } else {
+            Some(result)
+        }
+    }
+    /// Subtract a [`Duration`] from this [`DateTime`].
+    /// Returns [`None`] if out of range.
+    #[inline(always)]
+    pub fn checked_sub(self, rhs: Duration) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+    /// Get the [`Duration`] from an earlier [`DateTime`] to this [`DateTime`]
+    /// (negative if the other [`DateTime`] is later).
+    /// Returns [`None`] on overflow.
+    #[inline(always)]
+    pub fn duration_since(&self, earlier: &Self) -> Option<Duration> {
+        Duration::new(self.secs, self.nanos).checked_sub(Duration::new(earlier.secs, earlier.nanos))
+    }
+}
+
+impl PartialEq for DateTime {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.secs == other.secs && self.nanos == other.nanos
+    }
+}
+
+impl Eq for DateTime {}
+
+impl PartialOrd for DateTime {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.secs, self.nanos).cmp(&(other.secs, other.nanos))
+    }
+}
+
+impl Hash for DateTime {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.secs.hash(state);
+        self.nanos.hash(state);
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second()
+        )?;
+
+        write_fraction(f, self.nanos)?;
+
+        if self.offset == 0 {
+            f.write_str("Z")
+        } else {
+            let sign = if self.offset < 0 { '-' } else { '+' };
+            let offset = self.offset.abs();
+            write!(f, "{}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)?;
+
+            if offset % 60 != 0 {
+                write!(f, ":{:02}", offset % 60)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = &'static str;
+
+    /// Parse an ISO-8601 date (e.g. `2021-03-04`, which is taken as midnight UTC) or
+    /// date and time (e.g. `2021-03-04T05:06:07.123+08:00`).
+    ///
+    /// The offset may be omitted, in which case the time is taken as UTC.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERR: &str = "invalid ISO-8601 date/time";
+
+        let s = s.trim();
+
+        let year = parse_digits(s.get(0..4).ok_or(ERR)?).ok_or(ERR)? as i64;
+        let month = parse_digits(s.get(5..7).ok_or(ERR)?).ok_or(ERR)?;
+        let day = parse_digits(s.get(8..10).ok_or(ERR)?).ok_or(ERR)?;
+
+        if s.get(4..5) != Some("-") || s.get(7..8) != Some("-") {
+            return Err(ERR);
+        }
+
+        let s = &s[10..];
+
+        if s.is_empty() {
+            return Self::from_ymd_hms(year, month, day, 0, 0, 0).ok_or("invalid date");
+        }
+
+        match s.chars().next() {
+            Some('T') | Some('t') | Some(' ') => (),
+            _ => return Err(ERR),
+        }
+
+        let hour = parse_digits(s.get(1..3).ok_or(ERR)?).ok_or(ERR)?;
+        let minute = parse_digits(s.get(4..6).ok_or(ERR)?).ok_or(ERR)?;
+
+        if s.get(3..4) != Some(":") {
+            return Err(ERR);
+        }
+
+        let mut s = &s[6..];
+        let mut second = 0;
+        let mut nanos = 0;
+
+        if let Some(rest) = s.strip_prefix(':') {
+            second = parse_digits(rest.get(0..2).ok_or(ERR)?).ok_or(ERR)?;
+            s = &rest[2..];
+
+            if s.starts_with('.') || s.starts_with(',') {
+                let (n, rest) = parse_fraction(&s[1..]).ok_or(ERR)?;
+                nanos = n;
+                s = rest;
+            }
+        }
+
+        let offset = match s {
+            "" | "Z" | "z" => 0,
+            _ => {
+                let sign = match s.chars().next() {
+                    Some('+') => 1,
+                    Some('-') => -1,
+                    _ => return Err(ERR),
+                };
+                let s = s[1..].replace(':', "");
+
+                if s.len() != 4 {
+                    return Err(ERR);
+                }
+
+                let hours = parse_digits(s.get(0..2).ok_or(ERR)?).ok_or(ERR)?;
+                let minutes = parse_digits(s.get(2..4).ok_or(ERR)?).ok_or(ERR)?;
+
+                if minutes > 59 {
+                    return Err("invalid offset");
+                }
+
+                sign * (hours * 3600 + minutes * 60) as i32
+            }
+        };
+
+        let local = Self::from_ymd_hms(year, month, day, hour, minute, second)
+            .ok_or("invalid date/time")?;
+
+        Self::from_unix(local.secs - offset as i64, nanos)
+            .and_then(|dt| dt.with_offset(offset))
+            .ok_or("date/time out of range")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let s: String = Deserialize::deserialize(de)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let s: String = Deserialize::deserialize(de)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Write the fraction of a second, without trailing zeros.
+fn write_fraction(f: &mut fmt::Formatter<'_>, nanos: u32) -> fmt::Result {
+    if nanos == 0 {
+        return Ok(());
+    }
+
+    let mut digits = 9;
+    let mut nanos = nanos;
+
+    while nanos % 10 == 0 {
+        nanos /= 10;
+        digits -= 1;
+    }
+
+    write!(f, ".{:0width$}", nanos, width = digits)
+}
+
+/// Parse a fixed number of ASCII digits.
+fn parse_digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        None
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Parse the digits of a fraction of a second into nanoseconds,
+/// returning the rest of the string.
+fn parse_fraction(s: &str) -> Option<(u32, &str)> {
+    let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+    if len == 0 {
+        return None;
+    }
+
+    // Digits beyond nanosecond precision are truncated
+    let nanos = s[..len]
+        .bytes()
+        .chain(crate::stdlib::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |n, b| n * 10 + (b - b'0') as u32);
+
+    Some((nanos, &s[len..]))
+}
+
+/// Is a year a leap year?
+#[inline(always)]
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Get the number of days in a month.
+#[inline]
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Get the number of days since the Unix epoch for a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Get the date in the proleptic Gregorian calendar for a number of days since the Unix epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
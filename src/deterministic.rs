@@ -0,0 +1,480 @@
+//! Module defining the deterministic execution mode and replay logs.
+
+use crate::fn_native::{ClockCallback, Locked, SendSync};
+use crate::stdlib::{boxed::Box, fmt, format, string::ToString, time::Duration, vec::Vec};
+use crate::{Dynamic, Engine, EvalAltResult, ImmutableString, Position, RhaiResult, Shared};
+
+#[cfg(not(feature = "no_std"))]
+use crate::{calc_fn_hash, stdlib::iter::empty};
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use crate::{stdlib::time::Instant, DateTime};
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(any(target_arch = "wasm32", target_arch = "wasm64"))]
+use instant::Instant;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How a nondeterministic native Rust function is handled when the [`Engine`] is in
+/// [deterministic mode][Engine::set_deterministic].
+///
+/// Register a nondeterministic function via [`Engine::register_nondeterministic_fn`].
+#[derive(Debug, Clone)]
+pub enum Nondeterministic {
+    /// Reject calls to the function with an error.
+    Reject,
+    /// Do not call the function, but return the wrapped value instead.
+    Stub(Dynamic),
+}
+
+/// A host-supplied virtual clock used as the time source in deterministic mode.
+pub(crate) struct VirtualClock {
+    /// Clock returning the virtual time elapsed since the start.
+    #[allow(dead_code)]
+    clock: ClockCallback,
+    /// Real time-stamp corresponding to the start of the virtual clock.
+    #[cfg(not(feature = "no_std"))]
+    base: Instant,
+}
+
+impl VirtualClock {
+    /// Create a new [`VirtualClock`].
+    #[inline(always)]
+    pub(crate) fn new(clock: impl Fn() -> Duration + SendSync + 'static) -> Self {
+        Self {
+            clock: Box::new(clock),
+            #[cfg(not(feature = "no_std"))]
+            base: Instant::now(),
+        }
+    }
+    /// Get the current virtual time-stamp.
+    #[cfg(not(feature = "no_std"))]
+    #[inline(always)]
+    fn now(&self) -> Instant {
+        self.base + (self.clock)()
+    }
+    /// Get the current virtual date/time, taking the virtual time elapsed as the time since
+    /// the Unix epoch.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    #[inline(always)]
+    fn now_utc(&self) -> Option<DateTime> {
+        let elapsed = (self.clock)();
+        DateTime::from_unix(elapsed.as_secs() as i64, elapsed.subsec_nanos())
+    }
+}
+
+/// Recorded result of a nondeterministic native call.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Recorded {
+    /// Value returned.
+    Value(Dynamic),
+    /// Error returned, replayed as [`ErrorRuntime`][EvalAltResult::ErrorRuntime].
+    Error(Dynamic),
+    /// Time-stamp returned, as the time elapsed since the base of the log.
+    #[cfg(not(feature = "no_std"))]
+    TimeStamp(Duration),
+    /// Date/time returned.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    DateTime(DateTime),
+}
+
+impl Recorded {
+    /// Record a result.
+    fn new(result: &RhaiResult, #[cfg(not(feature = "no_std"))] base: Instant) -> Self {
+        match result {
+            #[cfg(not(feature = "no_std"))]
+            Ok(value) if value.is::<Instant>() => {
+                let timestamp = *value.downcast_ref::<Instant>().unwrap();
+                Self::TimeStamp(timestamp.saturating_duration_since(base))
+            }
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+            Ok(value) if value.is::<DateTime>() => {
+                Self::DateTime(*value.downcast_ref::<DateTime>().unwrap())
+            }
+            Ok(value) => Self::Value(value.flatten_clone()),
+            Err(err) => Self::Error(match **err {
+                EvalAltResult::ErrorRuntime(ref value, _) => value.flatten_clone(),
+                ref err => err.to_string().into(),
+            }),
+        }
+    }
+    /// Replay the recorded result.
+    fn replay(&self, #[cfg(not(feature = "no_std"))] base: Instant, pos: Position) -> RhaiResult {
+        match self {
+            Self::Value(value) => Ok(value.clone()),
+            Self::Error(value) => Err(EvalAltResult::ErrorRuntime(value.clone(), pos).into()),
+            #[cfg(not(feature = "no_std"))]
+            Self::TimeStamp(elapsed) => Ok(Dynamic::from(base + *elapsed)),
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+            Self::DateTime(dt) => Ok(Dynamic::from(*dt)),
+        }
+    }
+}
+
+/// A recorded nondeterministic native call.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct ReplayEntry {
+    /// Name of the function.
+    name: ImmutableString,
+    /// Hash of the function.
+    hash: u64,
+    /// Result of the call.
+    result: Recorded,
+}
+
+/// Recorded results of nondeterministic native calls.
+struct ReplayEntries {
+    /// Recorded calls.
+    entries: Vec<ReplayEntry>,
+    /// Index of the next entry to replay, if replaying.
+    next: Option<usize>,
+    /// Real time-stamp that recorded time-stamps are relative to.
+    #[cfg(not(feature = "no_std"))]
+    base: Instant,
+}
+
+impl Default for ReplayEntries {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+            next: None,
+            #[cfg(not(feature = "no_std"))]
+            base: Instant::now(),
+        }
+    }
+}
+
+/// A log of the results of nondeterministic native calls, used to reproduce a run exactly.
+///
+/// Set onto an [`Engine`] via [`Engine::set_replay_log`].
+///
+/// A log created via [`ReplayLog::new`] records, in order, the results of all calls to
+/// [nondeterministic functions][Engine::register_nondeterministic_fn] and all reads of the clock
+/// (e.g. `timestamp()`) made by evaluations on that [`Engine`].
+///
+/// [`ReplayLog::replay`] creates a new log that, instead of making those calls, returns the
+/// recorded results in the same order. Evaluating the same scripts with it reproduces the
+/// recorded run. A call that does not match the next recorded entry, i.e. a different function
+/// or a different overload of the same function, fails with an error.
+///
+/// Clones of a [`ReplayLog`] all refer to the same underlying log.
+///
+/// Errors returned by recorded calls are replayed as
+/// [`ErrorRuntime`][EvalAltResult::ErrorRuntime] with the same message.
+///
+/// # Serialization
+///
+/// Under the `serde` feature, a [`ReplayLog`] can be serialized in order to replay a run in
+/// another process.  A deserialized log is recording; call [`ReplayLog::replay`] on it to replay.
+///
+/// Functions are identified by hashes of their names and parameter types, so a log can only be
+/// replayed by the same build of the program that recorded it.  Recorded values are serialized
+/// as [`Dynamic`], so values of custom types are not preserved.  Recorded time-stamps are
+/// replayed relative to the time the log was deserialized.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use std::sync::atomic::{AtomicI64, Ordering};
+/// use rhai::{Engine, Nondeterministic, ReplayLog};
+///
+/// static COUNTER: AtomicI64 = AtomicI64::new(0);
+///
+/// let mut engine = Engine::new();
+/// engine.register_nondeterministic_fn(
+///     "next_id",
+///     || COUNTER.fetch_add(1, Ordering::SeqCst),
+///     Nondeterministic::Reject,
+/// );
+///
+/// let log = ReplayLog::new();
+/// engine.set_replay_log(log.clone());
+///
+/// let ast = engine.compile("next_id() * 10 + next_id()")?;
+///
+/// // Record a run
+/// assert_eq!(engine.eval_ast::<i64>(&ast)?, 1);
+/// assert_eq!(log.len(), 2);
+///
+/// // Without replaying, the results differ
+/// engine.set_replay_log(ReplayLog::new());
+/// assert_eq!(engine.eval_ast::<i64>(&ast)?, 23);
+///
+/// // Replay the recorded run
+/// engine.set_replay_log(log.replay());
+/// assert_eq!(engine.eval_ast::<i64>(&ast)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ReplayLog(Shared<Locked<ReplayEntries>>);
+
+impl fmt::Debug for ReplayLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReplayLog")
+            .field("len", &self.len())
+            .field("is_replaying", &self.is_replaying())
+            .finish()
+    }
+}
+
+impl ReplayLog {
+    /// Create a new, empty [`ReplayLog`] for recording.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Run a closure on the underlying log.
+    #[inline(always)]
+    fn with<T>(&self, f: impl FnOnce(&mut ReplayEntries) -> T) -> T {
+        #[cfg(not(feature = "sync"))]
+        return f(&mut *self.0.borrow_mut());
+        #[cfg(feature = "sync")]
+        return f(&mut *self.0.write().unwrap());
+    }
+    /// Number of entries in the [`ReplayLog`].
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.with(|log| log.entries.len())
+    }
+    /// Is the [`ReplayLog`] empty?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Is the [`ReplayLog`] replaying recorded results?
+    #[inline(always)]
+    pub fn is_replaying(&self) -> bool {
+        self.with(|log| log.next.is_some())
+    }
+    /// Create a new [`ReplayLog`] that replays the results recorded in this log.
+    pub fn replay(&self) -> Self {
+        self.with(|log| {
+            Self(Shared::new(Locked::new(ReplayEntries {
+                entries: log.entries.clone(),
+                next: Some(0),
+                #[cfg(not(feature = "no_std"))]
+                base: log.base,
+            })))
+        })
+    }
+    /// Take the next recorded result, if replaying.
+    ///
+    /// The next entry must be for the specified function name and hash.
+    fn take_next(&self, name: &str, hash: u64, pos: Position) -> Option<RhaiResult> {
+        self.with(|log| {
+            let index = log.next?;
+
+            let result = match log.entries.get(index) {
+                // Leave the entry in case the log is replayed again
+                Some(entry) if entry.hash == hash && entry.name.as_str() == name => {
+                    entry.result.replay(
+                        #[cfg(not(feature = "no_std"))]
+                        log.base,
+                        pos,
+                    )
+                }
+                Some(entry) if entry.name.as_str() == name => {
+                    return Some(Err(EvalAltResult::ErrorRuntime(
+                        format!(
+                            "Replay diverged: expecting a call to a different overload of '{}'",
+                            name
+                        )
+                        .into(),
+                        pos,
+                    )
+                    .into()))
+                }
+                Some(entry) => {
+                    return Some(Err(EvalAltResult::ErrorRuntime(
+                        format!(
+                            "Replay diverged: expecting a call to '{}' but found '{}'",
+                            entry.name, name
+                        )
+                        .into(),
+                        pos,
+                    )
+                    .into()))
+                }
+                None => {
+                    return Some(Err(EvalAltResult::ErrorRuntime(
+                        format!("Replay diverged: no more recorded calls for '{}'", name).into(),
+                        pos,
+                    )
+                    .into()))
+                }
+            };
+
+            log.next = Some(index + 1);
+            Some(result)
+        })
+    }
+    /// Record the result of a call, if recording.
+    fn record(&self, name: &str, hash: u64, result: &RhaiResult) {
+        self.with(|log| {
+            if log.next.is_some() {
+                return;
+            }
+
+            let result = Recorded::new(
+                result,
+                #[cfg(not(feature = "no_std"))]
+                log.base,
+            );
+
+            log.entries.push(ReplayEntry {
+                name: name.into(),
+                hash,
+                result,
+            });
+        });
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ReplayLog {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.with(|log| log.entries.serialize(ser))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ReplayLog {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let entries: Vec<ReplayEntry> = Deserialize::deserialize(de)?;
+
+        Ok(Self(Shared::new(Locked::new(ReplayEntries {
+            entries,
+            ..Default::default()
+        }))))
+    }
+}
+
+impl Engine {
+    /// Make a call to a nondeterministic source of values, honoring the deterministic mode
+    /// and [`ReplayLog`] of the [`Engine`].
+    ///
+    /// `deterministic` is called instead of `call` in deterministic mode.
+    pub(crate) fn call_nondeterministic(
+        &self,
+        name: &str,
+        hash: u64,
+        pos: Position,
+        deterministic: impl FnOnce() -> Option<RhaiResult>,
+        call: impl FnOnce() -> RhaiResult,
+    ) -> RhaiResult {
+        if let Some(ref log) = self.replay_log {
+            if let Some(result) = log.take_next(name, hash, pos) {
+                return result;
+            }
+        }
+
+        let result = if self.clock.is_some() {
+            deterministic().unwrap_or_else(call)
+        } else {
+            call()
+        };
+
+        if let Some(ref log) = self.replay_log {
+            log.record(name, hash, &result);
+        }
+
+        result
+    }
+    /// Call a native Rust function registered as nondeterministic.
+    pub(crate) fn call_nondeterministic_fn(
+        &self,
+        name: &str,
+        hash: u64,
+        handling: &Nondeterministic,
+        pos: Position,
+        call: impl FnOnce() -> RhaiResult,
+    ) -> RhaiResult {
+        self.call_nondeterministic(
+            name,
+            hash,
+            pos,
+            || {
+                Some(match handling {
+                    Nondeterministic::Reject => Err(EvalAltResult::ErrorRuntime(
+                        format!(
+                            "Nondeterministic function '{}' cannot be called in deterministic mode",
+                            name
+                        )
+                        .into(),
+                        pos,
+                    )
+                    .into()),
+                    Nondeterministic::Stub(value) => Ok(value.clone()),
+                })
+            },
+            call,
+        )
+    }
+    /// Get the current time-stamp, from the virtual clock in deterministic mode.
+    ///
+    /// The read is recorded in, or replayed from, the [`ReplayLog`] of the [`Engine`]
+    /// under the specified name.
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) fn now(&self, name: &str) -> Result<Instant, Box<EvalAltResult>> {
+        self.call_nondeterministic(
+            name,
+            calc_fn_hash(empty(), name, 0),
+            Position::NONE,
+            || self.clock.as_ref().map(|c| Ok(Dynamic::from(c.now()))),
+            || Ok(Dynamic::from(Instant::now())),
+        )?
+        .try_cast::<Instant>()
+        .ok_or_else(|| {
+            EvalAltResult::ErrorRuntime(
+                format!("Replay diverged: expecting a time-stamp for '{}'", name).into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Get the current date/time in UTC, from the virtual clock in deterministic mode.
+    ///
+    /// The read is recorded in, or replayed from, the [`ReplayLog`] of the [`Engine`]
+    /// under the specified name.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub(crate) fn now_utc(&self, name: &str) -> Result<DateTime, Box<EvalAltResult>> {
+        self.call_nondeterministic(
+            name,
+            calc_fn_hash(empty(), name, 0),
+            Position::NONE,
+            || {
+                self.clock.as_ref().map(|c| {
+                    c.now_utc().map(Dynamic::from).ok_or_else(|| {
+                        EvalAltResult::ErrorRuntime(
+                            "Virtual clock out of range for a date/time".into(),
+                            Position::NONE,
+                        )
+                        .into()
+                    })
+                })
+            },
+            || Ok(Dynamic::from(DateTime::now_utc())),
+        )?
+        .try_cast::<DateTime>()
+        .ok_or_else(|| {
+            EvalAltResult::ErrorRuntime(
+                format!("Replay diverged: expecting a date/time for '{}'", name).into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+}
@@ -10,7 +10,7 @@ use crate::stdlib::{
     ops::{Deref, DerefMut},
     string::String,
 };
-use crate::{FnPtr, ImmutableString, INT};
+use crate::{DateTime, Duration, FnPtr, Generator, ImmutableString, INT};
 
 #[cfg(not(feature = "no_float"))]
 use crate::{ast::FloatWrapper, FLOAT};
@@ -18,11 +18,18 @@ use crate::{ast::FloatWrapper, FLOAT};
 #[cfg(feature = "decimal")]
 use rust_decimal::Decimal;
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+
+#[cfg(not(feature = "no_index"))]
+use crate::{Array, Blob, IntArray, Set};
+
 #[cfg(not(feature = "no_index"))]
-use crate::Array;
+#[cfg(not(feature = "no_float"))]
+use crate::FloatArray;
 
 #[cfg(not(feature = "no_object"))]
-use crate::Map;
+use crate::{Map, Record};
 
 #[cfg(not(feature = "no_std"))]
 #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
@@ -163,9 +170,18 @@ pub enum Union {
     /// A fixed-precision decimal value.
     #[cfg(feature = "decimal")]
     Decimal(Box<Decimal>, AccessMode),
+    /// An arbitrary-precision integer value.
+    #[cfg(feature = "bigint")]
+    BigInt(Box<BigInt>, AccessMode),
     /// An array value.
     #[cfg(not(feature = "no_index"))]
     Array(Box<Array>, AccessMode),
+    /// A blob (byte buffer) value.
+    #[cfg(not(feature = "no_index"))]
+    Blob(Box<Blob>, AccessMode),
+    /// A set value.
+    #[cfg(not(feature = "no_index"))]
+    Set(Box<Set>, AccessMode),
     /// An object map value.
     #[cfg(not(feature = "no_object"))]
     Map(Box<Map>, AccessMode),
@@ -323,8 +339,14 @@ impl Dynamic {
             Union::Float(_, _) => TypeId::of::<FLOAT>(),
             #[cfg(feature = "decimal")]
             Union::Decimal(_, _) => TypeId::of::<Decimal>(),
+            #[cfg(feature = "bigint")]
+            Union::BigInt(_, _) => TypeId::of::<BigInt>(),
             #[cfg(not(feature = "no_index"))]
             Union::Array(_, _) => TypeId::of::<Array>(),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, _) => TypeId::of::<Blob>(),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, _) => TypeId::of::<Set>(),
             #[cfg(not(feature = "no_object"))]
             Union::Map(_, _) => TypeId::of::<Map>(),
             Union::FnPtr(_, _) => TypeId::of::<FnPtr>(),
@@ -361,8 +383,14 @@ impl Dynamic {
             Union::Float(_, _) => type_name::<FLOAT>(),
             #[cfg(feature = "decimal")]
             Union::Decimal(_, _) => "decimal",
+            #[cfg(feature = "bigint")]
+            Union::BigInt(_, _) => "bigint",
             #[cfg(not(feature = "no_index"))]
             Union::Array(_, _) => "array",
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, _) => "blob",
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, _) => "set",
             #[cfg(not(feature = "no_object"))]
             Union::Map(_, _) => "map",
             Union::FnPtr(_, _) => "Fn",
@@ -394,8 +422,14 @@ impl Hash for Dynamic {
             Union::Int(i, _) => i.hash(state),
             #[cfg(not(feature = "no_float"))]
             Union::Float(f, _) => f.hash(state),
+            #[cfg(feature = "bigint")]
+            Union::BigInt(n, _) => n.hash(state),
             #[cfg(not(feature = "no_index"))]
             Union::Array(a, _) => (**a).hash(state),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(a, _) => (**a).hash(state),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(a, _) => (**a).hash(state),
             #[cfg(not(feature = "no_object"))]
             Union::Map(m, _) => {
                 let mut buf: crate::StaticVec<_> = m.iter().collect();
@@ -433,23 +467,56 @@ pub(crate) fn map_std_type_name(name: &str) -> &str {
         "string"
     } else if name == type_name::<FnPtr>() {
         "Fn"
+    } else if name == type_name::<Generator>() {
+        "generator"
     } else {
         #[cfg(feature = "decimal")]
         if name == type_name::<Decimal>() {
             return "decimal";
         }
+        #[cfg(feature = "bigint")]
+        if name == type_name::<BigInt>() {
+            return "bigint";
+        }
         #[cfg(not(feature = "no_index"))]
         if name == type_name::<Array>() {
             return "array";
         }
+        #[cfg(not(feature = "no_index"))]
+        if name == type_name::<Blob>() {
+            return "blob";
+        }
+        #[cfg(not(feature = "no_index"))]
+        if name == type_name::<Set>() {
+            return "set";
+        }
+        #[cfg(not(feature = "no_index"))]
+        if name == type_name::<IntArray>() {
+            return "int_array";
+        }
+        #[cfg(not(feature = "no_index"))]
+        #[cfg(not(feature = "no_float"))]
+        if name == type_name::<FloatArray>() {
+            return "float_array";
+        }
         #[cfg(not(feature = "no_object"))]
         if name == type_name::<Map>() {
             return "map";
         }
+        #[cfg(not(feature = "no_object"))]
+        if name == type_name::<Record>() {
+            return "record";
+        }
         #[cfg(not(feature = "no_std"))]
         if name == type_name::<Instant>() {
             return "timestamp";
         }
+        if name == type_name::<DateTime>() {
+            return "datetime";
+        }
+        if name == type_name::<Duration>() {
+            return "duration";
+        }
 
         name
     }
@@ -467,8 +534,14 @@ impl fmt::Display for Dynamic {
             Union::Float(value, _) => fmt::Display::fmt(value, f),
             #[cfg(feature = "decimal")]
             Union::Decimal(value, _) => fmt::Display::fmt(value, f),
+            #[cfg(feature = "bigint")]
+            Union::BigInt(value, _) => fmt::Display::fmt(value, f),
             #[cfg(not(feature = "no_index"))]
             Union::Array(value, _) => fmt::Debug::fmt(value, f),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(value, _) => fmt::Debug::fmt(value, f),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(value, _) => fmt::Debug::fmt(value, f),
             #[cfg(not(feature = "no_object"))]
             Union::Map(value, _) => {
                 f.write_str("#")?;
@@ -481,6 +554,12 @@ impl fmt::Display for Dynamic {
             Union::Variant(value, _) => {
                 let _type_id = (***value).type_id();
 
+                #[cfg(not(feature = "no_object"))]
+                if _type_id == TypeId::of::<Record>() {
+                    let record = (**value).as_any().downcast_ref::<Record>().unwrap();
+                    return fmt::Display::fmt(record, f);
+                }
+
                 #[cfg(not(feature = "only_i32"))]
                 #[cfg(not(feature = "only_i64"))]
                 if _type_id == TypeId::of::<u8>() {
@@ -546,8 +625,14 @@ impl fmt::Debug for Dynamic {
             Union::Float(value, _) => fmt::Debug::fmt(value, f),
             #[cfg(feature = "decimal")]
             Union::Decimal(value, _) => fmt::Debug::fmt(value, f),
+            #[cfg(feature = "bigint")]
+            Union::BigInt(value, _) => fmt::Debug::fmt(value, f),
             #[cfg(not(feature = "no_index"))]
             Union::Array(value, _) => fmt::Debug::fmt(value, f),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(value, _) => fmt::Debug::fmt(value, f),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(value, _) => fmt::Debug::fmt(value, f),
             #[cfg(not(feature = "no_object"))]
             Union::Map(value, _) => {
                 f.write_str("#")?;
@@ -560,6 +645,12 @@ impl fmt::Debug for Dynamic {
             Union::Variant(value, _) => {
                 let _type_id = (***value).type_id();
 
+                #[cfg(not(feature = "no_object"))]
+                if _type_id == TypeId::of::<Record>() {
+                    let record = (**value).as_any().downcast_ref::<Record>().unwrap();
+                    return fmt::Debug::fmt(record, f);
+                }
+
                 #[cfg(not(feature = "only_i32"))]
                 #[cfg(not(feature = "only_i64"))]
                 if _type_id == TypeId::of::<u8>() {
@@ -640,8 +731,16 @@ impl Clone for Dynamic {
             Union::Decimal(ref value, _) => {
                 Self(Union::Decimal(value.clone(), AccessMode::ReadWrite))
             }
+            #[cfg(feature = "bigint")]
+            Union::BigInt(ref value, _) => {
+                Self(Union::BigInt(value.clone(), AccessMode::ReadWrite))
+            }
             #[cfg(not(feature = "no_index"))]
             Union::Array(ref value, _) => Self(Union::Array(value.clone(), AccessMode::ReadWrite)),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(ref value, _) => Self(Union::Blob(value.clone(), AccessMode::ReadWrite)),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(ref value, _) => Self(Union::Set(value.clone(), AccessMode::ReadWrite)),
             #[cfg(not(feature = "no_object"))]
             Union::Map(ref value, _) => Self(Union::Map(value.clone(), AccessMode::ReadWrite)),
             Union::FnPtr(ref value, _) => Self(Union::FnPtr(value.clone(), AccessMode::ReadWrite)),
@@ -706,8 +805,14 @@ impl Dynamic {
             Union::Float(_, access) => access,
             #[cfg(feature = "decimal")]
             Union::Decimal(_, access) => access,
+            #[cfg(feature = "bigint")]
+            Union::BigInt(_, access) => access,
             #[cfg(not(feature = "no_index"))]
             Union::Array(_, access) => access,
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, access) => access,
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, access) => access,
             #[cfg(not(feature = "no_object"))]
             Union::Map(_, access) => access,
             #[cfg(not(feature = "no_std"))]
@@ -731,8 +836,14 @@ impl Dynamic {
             Union::Float(_, access) => *access = typ,
             #[cfg(feature = "decimal")]
             Union::Decimal(_, access) => *access = typ,
+            #[cfg(feature = "bigint")]
+            Union::BigInt(_, access) => *access = typ,
             #[cfg(not(feature = "no_index"))]
             Union::Array(_, access) => *access = typ,
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, access) => *access = typ,
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, access) => *access = typ,
             #[cfg(not(feature = "no_object"))]
             Union::Map(_, access) => *access = typ,
             #[cfg(not(feature = "no_std"))]
@@ -782,10 +893,16 @@ impl Dynamic {
 
             #[cfg(not(feature = "no_float"))]
             Union::Float(_, _) => true,
+            #[cfg(feature = "bigint")]
+            Union::BigInt(_, _) => true,
             #[cfg(not(feature = "no_index"))]
-            Union::Array(_, _) => true,
+            Union::Array(a, _) => a.iter().all(Self::is_hashable),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, _) => true,
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, _) => true,
             #[cfg(not(feature = "no_object"))]
-            Union::Map(_, _) => true,
+            Union::Map(m, _) => m.values().all(Self::is_hashable),
 
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(cell, _) => {
@@ -860,6 +977,13 @@ impl Dynamic {
                 .clone()
                 .into();
         }
+        #[cfg(feature = "bigint")]
+        if TypeId::of::<T>() == TypeId::of::<BigInt>() {
+            return <dyn Any>::downcast_ref::<BigInt>(&value)
+                .unwrap()
+                .clone()
+                .into();
+        }
         if TypeId::of::<T>() == TypeId::of::<bool>() {
             return <dyn Any>::downcast_ref::<bool>(&value)
                 .unwrap()
@@ -898,6 +1022,14 @@ impl Dynamic {
                 Ok(array) => return (array).into(),
                 Err(val) => val,
             };
+            value = match unsafe_try_cast::<_, Blob>(value) {
+                Ok(blob) => return Self::from_blob(blob),
+                Err(val) => val,
+            };
+            value = match unsafe_try_cast::<_, Set>(value) {
+                Ok(set) => return set.into(),
+                Err(val) => val,
+            };
         }
 
         #[cfg(not(feature = "no_object"))]
@@ -1013,6 +1145,14 @@ impl Dynamic {
             };
         }
 
+        #[cfg(feature = "bigint")]
+        if TypeId::of::<T>() == TypeId::of::<BigInt>() {
+            return match self.0 {
+                Union::BigInt(value, _) => unsafe_try_cast(*value).ok(),
+                _ => None,
+            };
+        }
+
         if TypeId::of::<T>() == TypeId::of::<bool>() {
             return match self.0 {
                 Union::Bool(value, _) => unsafe_try_cast(value).ok(),
@@ -1049,6 +1189,22 @@ impl Dynamic {
             };
         }
 
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Blob>() {
+            return match self.0 {
+                Union::Blob(value, _) => unsafe_cast_box::<_, T>(value).ok().map(|v| *v),
+                _ => None,
+            };
+        }
+
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Set>() {
+            return match self.0 {
+                Union::Set(value, _) => unsafe_cast_box::<_, T>(value).ok().map(|v| *v),
+                _ => None,
+            };
+        }
+
         #[cfg(not(feature = "no_object"))]
         if TypeId::of::<T>() == TypeId::of::<Map>() {
             return match self.0 {
@@ -1335,6 +1491,13 @@ impl Dynamic {
                 _ => None,
             };
         }
+        #[cfg(feature = "bigint")]
+        if TypeId::of::<T>() == TypeId::of::<BigInt>() {
+            return match &self.0 {
+                Union::BigInt(value, _) => <dyn Any>::downcast_ref::<T>(value.as_ref()),
+                _ => None,
+            };
+        }
         if TypeId::of::<T>() == TypeId::of::<bool>() {
             return match &self.0 {
                 Union::Bool(value, _) => <dyn Any>::downcast_ref::<T>(value),
@@ -1366,6 +1529,20 @@ impl Dynamic {
                 _ => None,
             };
         }
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Blob>() {
+            return match &self.0 {
+                Union::Blob(value, _) => <dyn Any>::downcast_ref::<T>(value.as_ref()),
+                _ => None,
+            };
+        }
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Set>() {
+            return match &self.0 {
+                Union::Set(value, _) => <dyn Any>::downcast_ref::<T>(value.as_ref()),
+                _ => None,
+            };
+        }
         #[cfg(not(feature = "no_object"))]
         if TypeId::of::<T>() == TypeId::of::<Map>() {
             return match &self.0 {
@@ -1431,6 +1608,13 @@ impl Dynamic {
                 _ => None,
             };
         }
+        #[cfg(feature = "bigint")]
+        if TypeId::of::<T>() == TypeId::of::<BigInt>() {
+            return match &mut self.0 {
+                Union::BigInt(value, _) => <dyn Any>::downcast_mut::<T>(value.as_mut()),
+                _ => None,
+            };
+        }
         if TypeId::of::<T>() == TypeId::of::<bool>() {
             return match &mut self.0 {
                 Union::Bool(value, _) => <dyn Any>::downcast_mut::<T>(value),
@@ -1456,6 +1640,20 @@ impl Dynamic {
                 _ => None,
             };
         }
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Blob>() {
+            return match &mut self.0 {
+                Union::Blob(value, _) => <dyn Any>::downcast_mut::<T>(value.as_mut()),
+                _ => None,
+            };
+        }
+        #[cfg(not(feature = "no_index"))]
+        if TypeId::of::<T>() == TypeId::of::<Set>() {
+            return match &mut self.0 {
+                Union::Set(value, _) => <dyn Any>::downcast_mut::<T>(value.as_mut()),
+                _ => None,
+            };
+        }
         #[cfg(not(feature = "no_object"))]
         if TypeId::of::<T>() == TypeId::of::<Map>() {
             return match &mut self.0 {
@@ -1543,6 +1741,23 @@ impl Dynamic {
             _ => Err(self.type_name()),
         }
     }
+    /// _(BIGINT)_ Cast the [`Dynamic`] as a [`BigInt`] and return a clone of it.
+    /// Returns the name of the actual type if the cast fails.
+    ///
+    /// Exported under the `bigint` feature only.
+    #[cfg(feature = "bigint")]
+    #[inline(always)]
+    pub fn as_bigint(&self) -> Result<BigInt, &'static str> {
+        match &self.0 {
+            Union::BigInt(n, _) => Ok((**n).clone()),
+            #[cfg(not(feature = "no_closure"))]
+            Union::Shared(_, _) => self
+                .read_lock::<BigInt>()
+                .map(|v| v.clone())
+                .ok_or_else(|| self.type_name()),
+            _ => Err(self.type_name()),
+        }
+    }
     /// Cast the [`Dynamic`] as a [`bool`] and return it.
     /// Returns the name of the actual type if the cast fails.
     #[inline(always)]
@@ -1609,6 +1824,14 @@ impl Dynamic {
             _ => Err(self.type_name()),
         }
     }
+    /// Create a [`Dynamic`] from a [`Blob`].
+    ///
+    /// A [`Blob`] is a [`Vec<u8>`][Vec], which otherwise converts into an [`Array`] via [`From`].
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn from_blob(blob: Blob) -> Self {
+        Self(Union::Blob(Box::new(blob), AccessMode::ReadWrite))
+    }
 }
 
 impl From<()> for Dynamic {
@@ -1653,6 +1876,13 @@ impl From<Decimal> for Dynamic {
         ))
     }
 }
+#[cfg(feature = "bigint")]
+impl From<BigInt> for Dynamic {
+    #[inline(always)]
+    fn from(value: BigInt) -> Self {
+        Self(Union::BigInt(Box::new(value), AccessMode::ReadWrite))
+    }
+}
 impl From<char> for Dynamic {
     #[inline(always)]
     fn from(value: char) -> Self {
@@ -1676,6 +1906,13 @@ impl<T: Variant + Clone> From<crate::stdlib::vec::Vec<T>> for Dynamic {
     }
 }
 #[cfg(not(feature = "no_index"))]
+impl From<Set> for Dynamic {
+    #[inline(always)]
+    fn from(value: Set) -> Self {
+        Self(Union::Set(Box::new(value), AccessMode::ReadWrite))
+    }
+}
+#[cfg(not(feature = "no_index"))]
 impl<T: Variant + Clone> From<&[T]> for Dynamic {
     #[inline(always)]
     fn from(value: &[T]) -> Self {
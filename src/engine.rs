@@ -1,6 +1,11 @@
 //! Main module defining the script evaluation [`Engine`].
 
 use crate::ast::{Expr, FnCallExpr, FnCallHash, Ident, OpAssignment, ReturnType, Stmt, StmtBlock};
+use crate::continuation::{
+    make_unsupported_err, suspended_in, suspended_with, Branch, Continuation, ForLoop, ResumedCall,
+    Resumption,
+};
+use crate::deterministic::VirtualClock;
 use crate::dynamic::{map_std_type_name, AccessMode, Union, Variant};
 use crate::fn_native::{
     CallableFunction, IteratorFn, OnDebugCallback, OnPrintCallback, OnProgressCallback,
@@ -17,28 +22,41 @@ use crate::stdlib::{
     collections::{BTreeMap, BTreeSet},
     fmt, format,
     hash::{Hash, Hasher},
+    iter::{empty, once},
+    mem,
     num::{NonZeroU8, NonZeroUsize},
     ops::DerefMut,
     string::{String, ToString},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     vec::Vec,
 };
 use crate::syntax::CustomSyntax;
+use crate::token::Token;
 use crate::utils::get_hasher;
 use crate::{
-    Dynamic, EvalAltResult, FnPtr, ImmutableString, Module, Position, RhaiResult, Scope, Shared,
-    StaticVec,
+    Capability, Dynamic, EvalAltResult, FnPtr, ImmutableString, Module, Nondeterministic,
+    Permissions, Position, ReplayLog, RhaiResult, Scope, Shared, StaticVec,
 };
 
 #[cfg(not(feature = "no_index"))]
-use crate::{calc_fn_hash, stdlib::iter::empty, Array};
+use crate::calc_fn_hash;
 
 #[cfg(not(feature = "no_object"))]
-use crate::Map;
+use crate::{Map, Record};
+
+#[cfg(not(feature = "no_module"))]
+use crate::ast::{ImportNames, ModuleDef};
 
 pub type Precedence = NonZeroU8;
 
-/// _(INTERNALS)_ A stack of imported [modules][Module].
-/// Exported under the `internals` feature only.
+/// A stack of imported [modules][Module].
+///
+/// Pass it to [`Engine::eval_ast_with_imports`] to keep the modules imported by a script
+/// available to later evaluations, and to [`Snapshot::with_imports`][crate::Snapshot::with_imports]
+/// to record them in a [`Snapshot`][crate::Snapshot].
 ///
 /// # Volatile Data Structure
 ///
@@ -49,8 +67,59 @@ pub type Precedence = NonZeroU8;
 // We cannot use Cow<str> here because `eval` may load a [module][Module] and
 // the module name will live beyond the AST of the eval script text.
 // The best we can do is a shared reference.
+//
+// The third list holds where each module comes from: the path it was resolved from, together
+// with the source of the importing script (which relative paths depend on), so that the imports
+// can be recorded in a [`Snapshot`][crate::Snapshot] and resolved again later.
+//
+// The fourth list marks lazy imports that are not yet resolved; until then, the module
+// held in the second list is an empty placeholder.
+//
+// The fifth list holds the functions selected by a selective import, which are kept in an
+// unnamed module, so that they can be selected again when the imports are restored.
+//
+// The sixth list is not part of the stack: it holds the chain of `import` statements whose
+// modules are being resolved, leading to the script evaluated with this stack, and is used to
+// detect cyclic imports.
 #[derive(Debug, Clone, Default)]
-pub struct Imports(StaticVec<ImmutableString>, StaticVec<Shared<Module>>);
+pub struct Imports(
+    StaticVec<ImmutableString>,
+    StaticVec<Shared<Module>>,
+    StaticVec<ImportOrigin>,
+    StaticVec<Option<Box<LazyImport>>>,
+    StaticVec<Option<Box<SelectedFns>>>,
+    #[cfg(not(feature = "no_module"))] StaticVec<ImportLink>,
+);
+
+/// Where an imported [module][Module] comes from.
+#[derive(Debug, Clone)]
+pub(crate) enum ImportOrigin {
+    /// Pushed directly onto the stack.
+    Direct,
+    /// Declared inline via a `module` statement.
+    #[allow(dead_code)]
+    Inline,
+    /// Resolved from a path: the path and the source of the script containing the `import`
+    /// statement.
+    Path(ImmutableString, Option<ImmutableString>),
+}
+
+/// Names and aliases of the functions selected by a selective `import` statement.
+pub(crate) type SelectedFns = StaticVec<(ImmutableString, ImmutableString)>;
+
+/// An `import` statement whose [module][Module] is resolved when one of its members is first
+/// accessed.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub(crate) struct LazyImport {
+    /// Source of the script containing the `import` statement.
+    source: Option<ImmutableString>,
+    /// Embedded module resolver of the script containing the `import` statement.
+    #[cfg(not(feature = "no_module"))]
+    resolver: Option<Shared<crate::module::resolvers::StaticModuleResolver>>,
+    /// Position of the path in the `import` statement.
+    pos: Position,
+}
 
 impl Imports {
     /// Get the length of this stack of imported [modules][Module].
@@ -82,12 +151,154 @@ impl Imports {
     pub fn push(&mut self, name: impl Into<ImmutableString>, module: impl Into<Shared<Module>>) {
         self.0.push(name.into());
         self.1.push(module.into());
+        self.2.push(ImportOrigin::Direct);
+        self.3.push(None);
+        self.4.push(None);
+    }
+    /// Push an imported [modules][Module], resolved from a path, onto the stack.
+    #[allow(dead_code)]
+    #[inline(always)]
+    pub(crate) fn push_with_path(
+        &mut self,
+        name: impl Into<ImmutableString>,
+        module: impl Into<Shared<Module>>,
+        path: impl Into<ImmutableString>,
+        source: Option<ImmutableString>,
+    ) {
+        self.0.push(name.into());
+        self.1.push(module.into());
+        self.2.push(ImportOrigin::Path(path.into(), source));
+        self.3.push(None);
+        self.4.push(None);
+    }
+    /// Push a [module][Module] declared inline via a `module` statement onto the stack.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn push_inline(
+        &mut self,
+        name: impl Into<ImmutableString>,
+        module: impl Into<Shared<Module>>,
+    ) {
+        self.0.push(name.into());
+        self.1.push(module.into());
+        self.2.push(ImportOrigin::Inline);
+        self.3.push(None);
+        self.4.push(None);
+    }
+    /// Push the functions selected from an imported [module][Module], resolved from a path,
+    /// onto the stack.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn push_selected(
+        &mut self,
+        module: impl Into<Shared<Module>>,
+        path: impl Into<ImmutableString>,
+        source: Option<ImmutableString>,
+        fns: SelectedFns,
+    ) {
+        self.0.push(Default::default());
+        self.1.push(module.into());
+        self.2.push(ImportOrigin::Path(path.into(), source));
+        self.3.push(None);
+        self.4.push(Some(fns.into()));
+    }
+    /// Push a lazily-imported [module][Module], to be resolved from a path when first accessed,
+    /// onto the stack.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn push_lazy(
+        &mut self,
+        name: impl Into<ImmutableString>,
+        path: impl Into<ImmutableString>,
+        lazy: LazyImport,
+    ) {
+        self.0.push(name.into());
+        self.1.push(Module::new().into());
+        self.2
+            .push(ImportOrigin::Path(path.into(), lazy.source.clone()));
+        self.3.push(Some(lazy.into()));
+        self.4.push(None);
+    }
+    /// Get the path and `import` statement of the imported [module][Module] at a particular
+    /// index, if it is imported lazily and not yet resolved.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn get_pending(&self, index: usize) -> Option<(ImmutableString, LazyImport)> {
+        match (self.2.get(index), self.3.get(index)) {
+            (Some(ImportOrigin::Path(path, _)), Some(Some(lazy))) => {
+                Some((path.clone(), lazy.as_ref().clone()))
+            }
+            _ => None,
+        }
+    }
+    /// Are there lazily-imported [modules][Module] not yet resolved?
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn has_pending(&self) -> bool {
+        self.3.iter().any(Option::is_some)
+    }
+    /// Create an empty stack of imported [modules][Module] for evaluating the script of a
+    /// [module][Module] imported by a script (with a particular source) evaluated with this stack.
+    ///
+    /// The `import` statement is added to the chain of `import` statements being resolved.
+    /// Returns [`ErrorCyclicImport`][EvalAltResult::ErrorCyclicImport] if the [module][Module]
+    /// is already being resolved.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn for_import(
+        &self,
+        source: Option<&str>,
+        path: &ImmutableString,
+        pos: Position,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        let key = import_key(source, path);
+
+        if let Some(index) = self.5.iter().position(|link| link.key == key) {
+            let mut cycle: Vec<_> = self.5[index..]
+                .iter()
+                .map(|link| (link.path.to_string(), link.pos))
+                .collect();
+            cycle.push((path.to_string(), pos));
+            return EvalAltResult::ErrorCyclicImport(cycle, pos).into();
+        }
+
+        let mut mods = self.nested();
+        mods.5.push(ImportLink {
+            key,
+            path: path.clone(),
+            pos,
+        });
+        Ok(mods)
+    }
+    /// Create an empty stack of imported [modules][Module] for evaluating a script within the
+    /// script evaluated with this stack (e.g. an inline `module` declaration), continuing the
+    /// chain of `import` statements being resolved.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn nested(&self) -> Self {
+        Self(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            self.5.clone(),
+        )
+    }
+    /// Set the resolved [module][Module] of a lazy import at a particular index.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub(crate) fn set_resolved(&mut self, index: usize, module: Shared<Module>) {
+        self.1[index] = module;
+        self.3[index] = None;
     }
     /// Truncate the stack of imported [modules][Module] to a particular length.
     #[inline(always)]
     pub fn truncate(&mut self, size: usize) {
         self.0.truncate(size);
         self.1.truncate(size);
+        self.2.truncate(size);
+        self.3.truncate(size);
+        self.4.truncate(size);
     }
     /// Get an iterator to this stack of imported [modules][Module] in reverse order.
     #[allow(dead_code)]
@@ -111,6 +322,19 @@ impl Imports {
     pub(crate) fn scan_raw(&self) -> impl Iterator<Item = (&ImmutableString, &Shared<Module>)> {
         self.0.iter().zip(self.1.iter())
     }
+    /// Get an iterator to the names of this stack of imported [modules][Module], where they
+    /// come from and the functions selected by selective imports, in forward order.
+    #[allow(dead_code)]
+    #[inline(always)]
+    pub(crate) fn scan_origins(
+        &self,
+    ) -> impl Iterator<Item = (&ImmutableString, &ImportOrigin, Option<&SelectedFns>)> {
+        self.0
+            .iter()
+            .zip(self.2.iter())
+            .zip(self.4.iter().map(|fns| fns.as_deref()))
+            .map(|((name, origin), fns)| (name, origin, fns))
+    }
     /// Get a consuming iterator to this stack of imported [modules][Module] in reverse order.
     #[inline(always)]
     pub fn into_iter(self) -> impl Iterator<Item = (ImmutableString, Shared<Module>)> {
@@ -125,10 +349,16 @@ impl Imports {
     /// Get specified function via its hash key.
     #[inline(always)]
     pub fn get_fn(&self, hash: u64) -> Option<(&CallableFunction, Option<&ImmutableString>)> {
+        self.get_fn_with_module(hash).map(|(f, m)| (f, m.id_raw()))
+    }
+    /// Get the specified function via its hash key from this stack of imported [modules][Module],
+    /// together with the [module][Module] containing it.
+    #[inline(always)]
+    pub(crate) fn get_fn_with_module(&self, hash: u64) -> Option<(&CallableFunction, &Module)> {
         self.1
             .iter()
             .rev()
-            .find_map(|m| m.get_qualified_fn(hash).map(|f| (f, m.id_raw())))
+            .find_map(|m| m.get_qualified_fn(hash).map(|f| (f, &**m)))
     }
     /// Does the specified [`TypeId`][std::any::TypeId] iterator exist in this stack of
     /// imported [modules][Module]?
@@ -142,6 +372,19 @@ impl Imports {
     pub fn get_iter(&self, id: TypeId) -> Option<IteratorFn> {
         self.1.iter().rev().find_map(|m| m.get_qualified_iter(id))
     }
+    /// Find the name of a custom type set in the imported [modules][Module], qualified by the
+    /// name of the [module][Module].
+    pub(crate) fn find_custom_type(&self, type_name: &str) -> Option<String> {
+        self.0
+            .iter()
+            .zip(self.1.iter())
+            .rev()
+            .find_map(|(name, m)| match m.find_custom_type(type_name) {
+                Some(typ) if name.is_empty() => Some(typ),
+                Some(typ) => Some(format!("{}{}{}", name, Token::DoubleColon.syntax(), typ)),
+                None => None,
+            })
+    }
 }
 
 #[cfg(not(feature = "unchecked"))]
@@ -276,6 +519,88 @@ impl From<(Dynamic, Position)> for ChainArgument {
     }
 }
 
+/// Collect the expressions of the index values and arguments of a dot/index chain, in
+/// evaluation order.
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+fn collect_chain_exprs<'e>(
+    expr: &'e Expr,
+    parent_chain_type: ChainType,
+    exprs: &mut StaticVec<&'e Expr>,
+) {
+    match expr {
+        Expr::FnCall(x, _) if parent_chain_type == ChainType::Dot && x.namespace.is_none() => {
+            exprs.extend(x.args.iter())
+        }
+        Expr::Property(_) if parent_chain_type == ChainType::Dot => (),
+
+        Expr::Index(x, _) | Expr::Dot(x, _) => {
+            match &x.lhs {
+                Expr::Property(_) if parent_chain_type == ChainType::Dot => (),
+                Expr::FnCall(f, _)
+                    if parent_chain_type == ChainType::Dot && f.namespace.is_none() =>
+                {
+                    exprs.extend(f.args.iter())
+                }
+                lhs => exprs.push(lhs),
+            }
+
+            let chain_type = match expr {
+                Expr::Index(_, _) => ChainType::Index,
+                _ => ChainType::Dot,
+            };
+            collect_chain_exprs(&x.rhs, chain_type, exprs);
+        }
+
+        _ => exprs.push(expr),
+    }
+}
+
+/// Number of levels of a dot/index chain.
+#[cfg(not(feature = "no_object"))]
+fn chain_levels(expr: &Expr) -> usize {
+    match expr {
+        Expr::Index(x, _) | Expr::Dot(x, _) => 1 + chain_levels(&x.rhs),
+        _ => 1,
+    }
+}
+
+/// Record a method call within a dot/index chain if the error is a suspension, with the index
+/// values and arguments of the levels of the chain after it.
+#[cfg(not(feature = "no_object"))]
+fn suspended_in_chain(
+    err: Box<EvalAltResult>,
+    idx_values: &mut StaticVec<ChainArgument>,
+) -> Box<EvalAltResult> {
+    if !matches!(*err, EvalAltResult::ErrorSuspended(_, _)) {
+        return err;
+    }
+
+    let levels = idx_values.len();
+    let mut values = StaticVec::new();
+
+    // Index values are kept in reverse order
+    idx_values.drain(..).rev().for_each(|arg| match arg {
+        ChainArgument::Property(_) => (),
+        ChainArgument::FnCallArgs(args, _) => values.extend(args),
+        ChainArgument::IndexValue(value, _) => values.push(value),
+    });
+
+    crate::continuation::suspended_in_method(err, levels, values)
+}
+
+/// Reject a suspension within a dot/index chain unless it is within a method call.
+#[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+fn check_chain_suspension(err: Box<EvalAltResult>) -> Box<EvalAltResult> {
+    match *err {
+        #[cfg(not(feature = "no_object"))]
+        EvalAltResult::ErrorSuspended(ref cont, _) if cont.is_in_method() => err,
+        EvalAltResult::ErrorSuspended(_, _) => {
+            make_unsupported_err("within a property getter or indexer")
+        }
+        _ => err,
+    }
+}
+
 /// A type that encapsulates a mutation target for an expression with side effects.
 #[derive(Debug)]
 pub enum Target<'a> {
@@ -292,6 +617,10 @@ pub enum Target<'a> {
     /// This is necessary because directly pointing to a char inside a String is impossible.
     #[cfg(not(feature = "no_index"))]
     StringChar(&'a mut Dynamic, usize, Dynamic),
+    /// The target is a byte inside a [`Blob`][crate::Blob].
+    /// This is necessary because a byte cannot be pointed to as a `Dynamic` value.
+    #[cfg(not(feature = "no_index"))]
+    BlobByte(&'a mut Dynamic, usize, Dynamic),
 }
 
 impl<'a> Target<'a> {
@@ -307,6 +636,8 @@ impl<'a> Target<'a> {
             Self::Value(_) => false,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, _) => false,
         }
     }
     /// Is the `Target` an owned value?
@@ -321,6 +652,8 @@ impl<'a> Target<'a> {
             Self::Value(_) => true,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, _) => false,
         }
     }
     /// Is the `Target` a shared value?
@@ -335,6 +668,8 @@ impl<'a> Target<'a> {
             Self::Value(r) => r.is_shared(),
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, _) => false,
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, _) => false,
         }
     }
     /// Is the `Target` a specific type?
@@ -349,6 +684,8 @@ impl<'a> Target<'a> {
             Target::Value(r) => r.is::<T>(),
             #[cfg(not(feature = "no_index"))]
             Target::StringChar(_, _, _) => TypeId::of::<T>() == TypeId::of::<char>(),
+            #[cfg(not(feature = "no_index"))]
+            Target::BlobByte(_, _, _) => TypeId::of::<T>() == TypeId::of::<crate::INT>(),
         }
     }
     /// Get the value of the `Target` as a `Dynamic`, cloning a referenced value if necessary.
@@ -362,6 +699,8 @@ impl<'a> Target<'a> {
             Self::Value(v) => v,       // Owned value is simply taken
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ch) => ch, // Character is taken
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, b) => b, // Byte is taken
         }
     }
     /// Take a `&mut Dynamic` reference from the `Target`.
@@ -391,6 +730,11 @@ impl<'a> Target<'a> {
                 let char_value = ch.clone();
                 self.set_value(char_value, Position::NONE).unwrap();
             }
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, b) => {
+                let byte_value = b.clone();
+                self.set_value(byte_value, Position::NONE).unwrap();
+            }
         }
     }
     /// Update the value of the `Target`.
@@ -427,6 +771,21 @@ impl<'a> Target<'a> {
                     .map(|(i, ch)| if i == index { new_ch } else { ch })
                     .collect();
             }
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(b, index, _) => {
+                let b = &mut *b.write_lock::<crate::Blob>().unwrap();
+
+                // Only the lowest 8 bits of the integer are kept
+                let new_byte = new_val.as_int().map_err(|err| {
+                    Box::new(EvalAltResult::ErrorMismatchDataType(
+                        type_name::<crate::INT>().to_string(),
+                        err.to_string(),
+                        _pos,
+                    ))
+                })?;
+
+                b[*index] = (new_byte & 0xff) as u8;
+            }
         }
 
         Ok(())
@@ -459,6 +818,8 @@ impl AsRef<Dynamic> for Target<'_> {
             Self::Value(ref r) => r,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ref r) => r,
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, ref r) => r,
         }
     }
 }
@@ -474,6 +835,8 @@ impl AsMut<Dynamic> for Target<'_> {
             Self::Value(ref mut r) => r,
             #[cfg(not(feature = "no_index"))]
             Self::StringChar(_, _, ref mut r) => r,
+            #[cfg(not(feature = "no_index"))]
+            Self::BlobByte(_, _, ref mut r) => r,
         }
     }
 }
@@ -492,6 +855,12 @@ pub struct FnResolutionCacheEntry {
     pub func: CallableFunction,
     /// Optional source.
     pub source: Option<ImmutableString>,
+    /// Hash of the function, if registered directly with the [`Engine`].
+    pub registered_hash: Option<u64>,
+    /// Is the function restricted by the function allowlist of [`Permissions`][crate::Permissions]?
+    ///
+    /// Native Rust functions are restricted unless they are in a package.
+    pub restricted: bool,
 }
 
 /// A function resolution cache.
@@ -521,8 +890,12 @@ pub struct State {
     /// Embedded module resolver.
     #[cfg(not(feature = "no_module"))]
     pub resolver: Option<Shared<crate::module::resolvers::StaticModuleResolver>>,
+    /// Permissions restricting the evaluation, if any.
+    pub permissions: Option<Shared<Permissions>>,
     /// Function resolution cache and free list.
     fn_resolution_caches: (StaticVec<FnResolutionCache>, Vec<FnResolutionCache>),
+    /// Script evaluation being resumed from a [`Continuation`][crate::Continuation], if any.
+    pub(crate) resume: Option<Box<Resumption>>,
 }
 
 impl State {
@@ -545,6 +918,14 @@ impl State {
             .0
             .push(self.fn_resolution_caches.1.pop().unwrap_or_default());
     }
+    /// Clear all the function resolution caches in the stack.
+    #[cfg(not(feature = "no_module"))]
+    pub fn clear_fn_resolution_caches(&mut self) {
+        self.fn_resolution_caches
+            .0
+            .iter_mut()
+            .for_each(|cache| cache.clear());
+    }
     /// Remove the current function resolution cache from the stack and make the last one current.
     ///
     /// # Panics
@@ -555,6 +936,65 @@ impl State {
         cache.clear();
         self.fn_resolution_caches.1.push(cache);
     }
+    /// Take the suspended function call at the specified position when resuming.
+    ///
+    /// The evaluation is no longer resuming once the suspended call is taken.
+    #[inline(always)]
+    pub(crate) fn take_resumed_call(&mut self, pos: Position) -> Option<ResumedCall> {
+        let call = self.resume.as_mut()?.take_call(pos)?;
+        self.resume = None;
+        Some(call)
+    }
+    /// Take the branch of the compound statement to enter when resuming.
+    #[inline(always)]
+    pub(crate) fn take_resumed_branch(&mut self) -> Option<Branch> {
+        self.resume.as_mut().and_then(|r| r.take_branch())
+    }
+    /// Take the values of the sub-expressions evaluated before the suspension when resuming.
+    #[inline(always)]
+    pub(crate) fn take_resumed_values(&mut self) -> Vec<Dynamic> {
+        self.resume
+            .as_mut()
+            .and_then(|r| r.take_values())
+            .unwrap_or_default()
+    }
+    /// Take the method call within a dot/index chain to resume at.
+    #[cfg(not(feature = "no_object"))]
+    #[inline(always)]
+    pub(crate) fn take_resumed_method(&mut self) -> Option<(usize, Vec<Dynamic>)> {
+        self.resume.as_mut().and_then(|r| r.take_method())
+    }
+}
+
+/// An `import` statement whose [module][Module] is being resolved.
+#[cfg(not(feature = "no_module"))]
+#[derive(Debug, Clone)]
+pub(crate) struct ImportLink {
+    /// Key identifying the [module][Module].
+    /// Relative paths are prefixed with the directory of the importing script's source.
+    key: String,
+    /// Path of the [module][Module] as written in the `import` statement.
+    path: ImmutableString,
+    /// Position of the `import` statement.
+    pos: Position,
+}
+
+/// Make a key identifying the [module][Module] imported via a path from a script with a
+/// particular source.
+///
+/// Relative paths (starting with `./` or `../`) are qualified by the directory of the source, so
+/// that the same relative path imported from different directories gives different keys.
+#[cfg(not(feature = "no_module"))]
+pub(crate) fn import_key(source: Option<&str>, path: &str) -> String {
+    match source {
+        Some(source) if path.starts_with("./") || path.starts_with("../") => {
+            match source.rfind(|c| c == '/' || c == '\\') {
+                Some(index) => format!("{}{}", &source[..=index], path),
+                None => path.to_string(),
+            }
+        }
+        _ => path.to_string(),
+    }
 }
 
 /// _(INTERNALS)_ A type containing all the limits imposed by the [`Engine`].
@@ -591,16 +1031,78 @@ pub struct Limits {
     pub max_modules: usize,
     /// Maximum length of a [string][ImmutableString].
     pub max_string_size: Option<NonZeroUsize>,
-    /// Maximum length of an [array][Array].
+    /// Maximum length of an [array][crate::Array].
     ///
     /// Not available under `no_index`.
     #[cfg(not(feature = "no_index"))]
     pub max_array_size: Option<NonZeroUsize>,
+    /// Maximum number of members in a [set][crate::Set].
+    ///
+    /// Not available under `no_index`.
+    #[cfg(not(feature = "no_index"))]
+    pub max_set_size: Option<NonZeroUsize>,
     /// Maximum number of properties in an [object map][Map].
     ///
     /// Not available under `no_object`.
     #[cfg(not(feature = "no_object"))]
     pub max_map_size: Option<NonZeroUsize>,
+    /// Maximum number of bits in a [`BigInt`][num_bigint::BigInt].
+    ///
+    /// Exported under the `bigint` feature only.
+    #[cfg(feature = "bigint")]
+    pub max_bigint_bits: Option<NonZeroUsize>,
+}
+
+/// A thread-safe handle for terminating running script evaluations.
+///
+/// Clones of a [`CancellationToken`] all refer to the same underlying flag, so a clone can be
+/// moved to another thread and [cancelled][CancellationToken::cancel] from there.
+///
+/// Once cancelled, any evaluation running on an [`Engine`] holding the token stops at the next
+/// operation check with [`ErrorTerminated`][EvalAltResult::ErrorTerminated].
+///
+/// # Example
+///
+/// ```
+/// use rhai::{CancellationToken, Engine, EvalAltResult};
+///
+/// let token = CancellationToken::new();
+///
+/// let mut engine = Engine::new();
+/// engine.set_cancellation_token(token.clone());
+///
+/// // Cancel from anywhere, e.g. another thread
+/// token.cancel();
+///
+/// assert!(matches!(
+///     *engine.eval::<()>("let x = 0; loop { x += 1; }").expect_err("should error"),
+///     EvalAltResult::ErrorTerminated(_, _)
+/// ));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new, non-cancelled [`CancellationToken`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Request termination of all evaluations watching this [`CancellationToken`].
+    #[inline(always)]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+    /// Has termination been requested?
+    #[inline(always)]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+    /// Clear a previous cancellation so that the [`CancellationToken`] can be reused.
+    #[inline(always)]
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
 }
 
 /// Context of a script evaluation process.
@@ -708,9 +1210,17 @@ pub struct Engine {
     /// A module resolution service.
     #[cfg(not(feature = "no_module"))]
     pub(crate) module_resolver: Box<dyn crate::ModuleResolver>,
+    /// Resolve imported modules only when first accessed?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) lazy_import: bool,
 
     /// A map mapping type names to pretty-print names.
     pub(crate) type_names: BTreeMap<String, String>,
+    /// A map mapping registered `enum` types to functions returning the variant names of values.
+    pub(crate) enum_variants: BTreeMap<TypeId, fn(&Dynamic) -> Option<&'static str>>,
+    /// Registered `enum` types, for conversions to and from Rust values via [`serde`].
+    #[cfg(feature = "serde")]
+    pub(crate) custom_enums: crate::serde::enums::EnumRegistry,
 
     /// A set of symbols to disable.
     pub(crate) disabled_symbols: BTreeSet<String>,
@@ -727,6 +1237,15 @@ pub struct Engine {
     pub(crate) debug: OnDebugCallback,
     /// Callback closure for progress reporting.
     pub(crate) progress: Option<OnProgressCallback>,
+    /// Token for terminating running evaluations.
+    pub(crate) cancel_token: Option<CancellationToken>,
+    /// Virtual clock, if in deterministic mode.
+    pub(crate) clock: Option<VirtualClock>,
+    /// Handling of nondeterministic native Rust functions in deterministic mode,
+    /// keyed by function hash.
+    pub(crate) nondeterministic: BTreeMap<u64, Nondeterministic>,
+    /// Log recording or replaying the results of nondeterministic calls.
+    pub(crate) replay_log: Option<ReplayLog>,
 
     /// Optimize the AST after compilation.
     pub(crate) optimization_level: OptimizationLevel,
@@ -767,6 +1286,21 @@ pub fn make_setter(id: &str) -> String {
     format!("{}{}", FN_SET, id)
 }
 
+/// Make the internal name of a method defined on a script-defined record type.
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+#[inline(always)]
+pub fn make_record_method(type_name: &str, fn_name: &str) -> String {
+    format!("{}::{}", type_name, fn_name)
+}
+
+/// Is this value an object map or a script-defined record?
+#[cfg(not(feature = "no_object"))]
+#[inline(always)]
+fn is_map_like(value: &Dynamic) -> bool {
+    value.is::<Map>() || value.is::<Record>()
+}
+
 /// Is this function an anonymous function?
 #[cfg(not(feature = "no_function"))]
 #[inline(always)]
@@ -813,6 +1347,9 @@ impl Engine {
             module_resolver: Box::new(crate::module::resolvers::DummyModuleResolver::new()),
 
             type_names: Default::default(),
+            enum_variants: Default::default(),
+            #[cfg(feature = "serde")]
+            custom_enums: Default::default(),
             disabled_symbols: Default::default(),
             custom_keywords: Default::default(),
             custom_syntax: Default::default(),
@@ -826,6 +1363,10 @@ impl Engine {
 
             // progress callback
             progress: None,
+            cancel_token: None,
+            clock: None,
+            nondeterministic: Default::default(),
+            replay_log: None,
 
             // optimization level
             optimization_level: if cfg!(feature = "no_optimize") {
@@ -847,11 +1388,18 @@ impl Engine {
                 max_string_size: None,
                 #[cfg(not(feature = "no_index"))]
                 max_array_size: None,
+                #[cfg(not(feature = "no_index"))]
+                max_set_size: None,
                 #[cfg(not(feature = "no_object"))]
                 max_map_size: None,
+                #[cfg(feature = "bigint")]
+                max_bigint_bits: None,
             },
 
             disable_doc_comments: false,
+
+            #[cfg(not(feature = "no_module"))]
+            lazy_import: false,
         };
 
         engine.register_global_module(StandardPackage::new().as_shared_module());
@@ -873,6 +1421,9 @@ impl Engine {
             module_resolver: Box::new(crate::module::resolvers::DummyModuleResolver::new()),
 
             type_names: Default::default(),
+            enum_variants: Default::default(),
+            #[cfg(feature = "serde")]
+            custom_enums: Default::default(),
             disabled_symbols: Default::default(),
             custom_keywords: Default::default(),
             custom_syntax: Default::default(),
@@ -882,6 +1433,10 @@ impl Engine {
             print: Box::new(|_| {}),
             debug: Box::new(|_, _, _| {}),
             progress: None,
+            cancel_token: None,
+            clock: None,
+            nondeterministic: Default::default(),
+            replay_log: None,
 
             optimization_level: if cfg!(feature = "no_optimize") {
                 OptimizationLevel::None
@@ -902,11 +1457,18 @@ impl Engine {
                 max_string_size: None,
                 #[cfg(not(feature = "no_index"))]
                 max_array_size: None,
+                #[cfg(not(feature = "no_index"))]
+                max_set_size: None,
                 #[cfg(not(feature = "no_object"))]
                 max_map_size: None,
+                #[cfg(feature = "bigint")]
+                max_bigint_bits: None,
             },
 
             disable_doc_comments: false,
+
+            #[cfg(not(feature = "no_module"))]
+            lazy_import: false,
         }
     }
 
@@ -914,11 +1476,13 @@ impl Engine {
     #[inline]
     pub(crate) fn search_imports(
         &self,
-        mods: &Imports,
+        mods: &mut Imports,
         state: &mut State,
         namespace: &NamespaceRef,
-    ) -> Option<Shared<Module>> {
-        let root = &namespace[0].name;
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let Ident {
+            name: root, pos, ..
+        } = &namespace[0];
 
         // Qualified - check if the root module is directly indexed
         let index = if state.always_search {
@@ -929,12 +1493,127 @@ impl Engine {
 
         if let Some(index) = index {
             let offset = mods.len() - index.get();
-            Some(mods.get(offset).expect("invalid index in Imports"))
-        } else {
-            mods.find(root)
-                .map(|n| mods.get(n).expect("invalid index in Imports"))
-                .or_else(|| self.global_sub_modules.get(root).cloned())
+            return self.get_import(mods, state, offset);
+        }
+
+        if let Some(n) = mods.find(root) {
+            return self.get_import(mods, state, n);
+        }
+
+        let module = self
+            .global_sub_modules
+            .get(root)
+            .cloned()
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(root.to_string(), *pos))?;
+
+        if let Some(ref permissions) = state.permissions {
+            permissions.check(Capability::Module, root, *pos)?;
+        }
+
+        Ok(module)
+    }
+
+    /// Get an imported [module][Module] within an imports stack,
+    /// resolving it first if it is imported lazily.
+    pub(crate) fn get_import(
+        &self,
+        mods: &mut Imports,
+        _state: &mut State,
+        index: usize,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_module"))]
+        if let Some((path, lazy)) = mods.get_pending(index) {
+            // Guard against too many modules
+            #[cfg(not(feature = "unchecked"))]
+            if _state.modules >= self.max_modules() {
+                return EvalAltResult::ErrorTooManyModules(lazy.pos).into();
+            }
+
+            let module = self.resolve_import(
+                mods,
+                lazy.source.as_ref().map(|s| s.as_str()),
+                lazy.resolver.as_ref().map(|r| r.as_ref()),
+                &path,
+                lazy.pos,
+            )?;
+
+            let module: Shared<Module> = if !module.is_indexed() {
+                // Index the module (making a clone copy if necessary) if it is not indexed
+                let mut module = crate::fn_native::shared_take_or_clone(module);
+                module.build_index();
+                module.into()
+            } else {
+                module
+            };
+
+            mods.set_resolved(index, module.clone());
+            _state.modules += 1;
+
+            return Ok(module);
+        }
+
+        Ok(mods.get(index).expect("invalid index in Imports"))
+    }
+
+    /// Resolve all the lazily-imported [modules][Module] within an imports stack that are not yet
+    /// resolved.
+    ///
+    /// This is done when looking up a function (or a type iterator) in the global namespace
+    /// fails, as it may be exposed to the global namespace by one of those [modules][Module].
+    ///
+    /// Returns `true` if any [module][Module] is resolved.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn resolve_pending_imports(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        if !mods.has_pending() {
+            return Ok(false);
         }
+
+        for index in 0..mods.len() {
+            self.get_import(mods, state, index)?;
+        }
+
+        // Functions exposed by the resolved modules take part in function resolution
+        state.clear_fn_resolution_caches();
+
+        Ok(true)
+    }
+
+    /// Resolve the [module][Module] of an `import` statement from a path, trying the embedded
+    /// module resolver of the script (if any) before the module resolver of the [`Engine`].
+    ///
+    /// `mods` is the stack of imported [modules][Module] the script is evaluated with, which
+    /// carries the chain of `import` statements being resolved.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn resolve_import(
+        &self,
+        mods: &Imports,
+        source: Option<&str>,
+        resolver: Option<&crate::module::resolvers::StaticModuleResolver>,
+        path: &ImmutableString,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        use crate::ModuleResolver;
+
+        let mods = mods.for_import(source, path, pos)?;
+
+        resolver
+            .and_then(
+                |r| match r.resolve_with_imports(self, &mods, source, path, pos) {
+                    Ok(m) => return Some(Ok(m)),
+                    Err(err) => match *err {
+                        EvalAltResult::ErrorModuleNotFound(_, _) => None,
+                        _ => return Some(Err(err)),
+                    },
+                },
+            )
+            .unwrap_or_else(|| {
+                self.module_resolver
+                    .resolve_with_imports(self, &mods, source, path, pos)
+            })
     }
 
     /// Search for a variable within the scope or within imports,
@@ -952,12 +1631,7 @@ impl Engine {
             Expr::Variable(v) => match v.as_ref() {
                 // Qualified variable
                 (_, Some((hash_var, modules)), Ident { name, pos, .. }) => {
-                    let module = self.search_imports(mods, state, modules).ok_or_else(|| {
-                        EvalAltResult::ErrorModuleNotFound(
-                            modules[0].name.to_string(),
-                            modules[0].pos,
-                        )
-                    })?;
+                    let module = self.search_imports(mods, state, modules)?;
                     let target = module.get_qualified_var(*hash_var).map_err(|mut err| {
                         match *err {
                             EvalAltResult::ErrorVariableNotFound(ref mut err_name, _) => {
@@ -1169,6 +1843,7 @@ impl Engine {
                         self.make_method_call(
                             mods, state, lib, name, *hash, target, &mut args, *pos, level,
                         )
+                        .map_err(|err| suspended_in_chain(err, idx_values))
                     }
                     // xxx.fn_name(...) = ???
                     Expr::FnCall(_, _) if new_val.is_some() => {
@@ -1179,7 +1854,7 @@ impl Engine {
                         unreachable!("function call in dot chain should not be namespace-qualified")
                     }
                     // {xxx:map}.id op= ???
-                    Expr::Property(x) if target_val.is::<Map>() && new_val.is_some() => {
+                    Expr::Property(x) if is_map_like(target_val) && new_val.is_some() => {
                         let Ident { name, pos, .. } = &x.2;
                         let index = name.clone().into();
                         let val = self.get_indexed_mut(
@@ -1192,7 +1867,7 @@ impl Engine {
                         Ok((Dynamic::UNIT, true))
                     }
                     // {xxx:map}.id
-                    Expr::Property(x) if target_val.is::<Map>() => {
+                    Expr::Property(x) if is_map_like(target_val) => {
                         let Ident { name, pos, .. } = &x.2;
                         let index = name.clone().into();
                         let val = self.get_indexed_mut(
@@ -1225,7 +1900,7 @@ impl Engine {
                         .map(|(v, _)| (v, false))
                     }
                     // {xxx:map}.sub_lhs[expr] | {xxx:map}.sub_lhs.expr
-                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) if target_val.is::<Map>() => {
+                    Expr::Index(x, x_pos) | Expr::Dot(x, x_pos) if is_map_like(target_val) => {
                         let mut val = match &x.lhs {
                             Expr::Property(p) => {
                                 let Ident { name, pos, .. } = &p.2;
@@ -1239,9 +1914,12 @@ impl Engine {
                             Expr::FnCall(x, pos) if x.namespace.is_none() => {
                                 let FnCallExpr { name, hash, .. } = x.as_ref();
                                 let mut args = idx_val.as_fn_call_args();
-                                let (val, _) = self.make_method_call(
-                                    mods, state, lib, name, *hash, target, &mut args, *pos, level,
-                                )?;
+                                let (val, _) = self
+                                    .make_method_call(
+                                        mods, state, lib, name, *hash, target, &mut args, *pos,
+                                        level,
+                                    )
+                                    .map_err(|err| suspended_in_chain(err, idx_values))?;
                                 val.into()
                             }
                             // {xxx:map}.module::fn_name(...) - syntax error
@@ -1317,9 +1995,12 @@ impl Engine {
                             Expr::FnCall(f, pos) if f.namespace.is_none() => {
                                 let FnCallExpr { name, hash, .. } = f.as_ref();
                                 let mut args = idx_val.as_fn_call_args();
-                                let (mut val, _) = self.make_method_call(
-                                    mods, state, lib, name, *hash, target, &mut args, *pos, level,
-                                )?;
+                                let (mut val, _) = self
+                                    .make_method_call(
+                                        mods, state, lib, name, *hash, target, &mut args, *pos,
+                                        level,
+                                    )
+                                    .map_err(|err| suspended_in_chain(err, idx_values))?;
                                 let val = &mut val;
                                 let target = &mut val.into();
 
@@ -1365,16 +2046,64 @@ impl Engine {
             _ => unreachable!("index or dot chain expected, but gets {:?}", expr),
         };
 
-        let idx_values = &mut Default::default();
+        // When resuming at a method call within the chain, skip to it
+        #[cfg(not(feature = "no_object"))]
+        if let Some((levels, values)) = state.take_resumed_method() {
+            return self
+                .resume_dot_index_chain(
+                    mods, state, lib, this_ptr, rhs, chain_type, levels, values, level, new_val,
+                )
+                .map(|(v, _)| v)
+                .map_err(|err| check_chain_suspension(err).fill_position(op_pos));
+        }
 
-        self.eval_indexed_chain(
-            scope, mods, state, lib, this_ptr, rhs, chain_type, idx_values, 0, level,
-        )?;
+        // The new value of an assignment is evaluated before the chain, so keep it as well
+        // when suspended
+        let suspended = |err, values: StaticVec<Dynamic>| {
+            let err = suspended_with(err, values);
+            match new_val {
+                Some(((ref value, _), _)) => suspended_with(err, once(value.clone())),
+                None => err,
+            }
+        };
 
-        match lhs {
-            // id.??? or id[???]
-            Expr::Variable(x) => {
-                let Ident {
+        let mut resumed = state.take_resumed_values().into_iter();
+        let mut exprs = StaticVec::new();
+        let mut values = StaticVec::new();
+        collect_chain_exprs(rhs, chain_type, &mut exprs);
+
+        if let Err(err) = self.eval_exprs(
+            scope,
+            mods,
+            state,
+            lib,
+            this_ptr,
+            exprs.iter().copied(),
+            &mut resumed,
+            &mut values,
+            level,
+        ) {
+            return Err(suspended(err, values));
+        }
+
+        let value = match lhs {
+            Expr::Variable(_) => Dynamic::UNIT,
+            // {expr}.??? = ??? or {expr}[???] = ???
+            _ if new_val.is_some() => unreachable!("cannot assign to an expression"),
+            // {expr}.??? or {expr}[???]
+            expr => match self.eval_expr(scope, mods, state, lib, this_ptr, expr, level) {
+                Ok(value) => value,
+                Err(err) => return Err(suspended(err, values)),
+            },
+        };
+
+        let idx_values = &mut Default::default();
+        self.build_indexed_chain(state, rhs, chain_type, &mut values.into_iter(), idx_values)?;
+
+        match lhs {
+            // id.??? or id[???]
+            Expr::Variable(x) => {
+                let Ident {
                     name: var_name,
                     pos: var_pos,
                     ..
@@ -1396,59 +2125,104 @@ impl Engine {
                     mods, state, lib, &mut None, obj_ptr, rhs, idx_values, chain_type, level,
                     new_val,
                 )
-                .map(|(v, _)| v)
-                .map_err(|err| err.fill_position(op_pos))
             }
-            // {expr}.??? = ??? or {expr}[???] = ???
-            _ if new_val.is_some() => unreachable!("cannot assign to an expression"),
             // {expr}.??? or {expr}[???]
-            expr => {
-                let value = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
+            _ => {
                 let obj_ptr = &mut value.into();
                 self.eval_dot_index_chain_helper(
                     mods, state, lib, this_ptr, obj_ptr, rhs, idx_values, chain_type, level,
                     new_val,
                 )
-                .map(|(v, _)| v)
-                .map_err(|err| err.fill_position(op_pos))
             }
         }
+        .map(|(v, _)| v)
+        .map_err(|err| check_chain_suspension(err).fill_position(op_pos))
     }
 
-    /// Evaluate a chain of indexes and store the results in a [`StaticVec`].
-    /// [`StaticVec`] is used to avoid an allocation in the overwhelming cases of
-    /// just a few levels of indexing.
-    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
-    fn eval_indexed_chain(
+    /// Resume a dot/index chain at the suspended method call, followed by `levels` levels of the
+    /// chain with the index values and arguments in `values`.
+    ///
+    /// The levels before the method call are skipped, as the call already has its result.
+    #[cfg(not(feature = "no_object"))]
+    fn resume_dot_index_chain(
         &self,
-        scope: &mut Scope,
         mods: &mut Imports,
         state: &mut State,
         lib: &[&Module],
         this_ptr: &mut Option<&mut Dynamic>,
+        mut rhs: &Expr,
+        mut chain_type: ChainType,
+        levels: usize,
+        values: Vec<Dynamic>,
+        level: usize,
+        new_val: Option<((Dynamic, Position), (&Option<OpAssignment>, Position))>,
+    ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
+        for _ in levels + 1..chain_levels(rhs) {
+            match rhs {
+                Expr::Index(x, _) => {
+                    chain_type = ChainType::Index;
+                    rhs = &x.rhs;
+                }
+                Expr::Dot(x, _) => {
+                    chain_type = ChainType::Dot;
+                    rhs = &x.rhs;
+                }
+                _ => unreachable!("index or dot chain expected, but gets {:?}", rhs),
+            }
+        }
+
+        let idx_values = &mut Default::default();
+
+        if let Expr::Index(x, _) | Expr::Dot(x, _) = rhs {
+            let next_chain = match rhs {
+                Expr::Index(_, _) => ChainType::Index,
+                _ => ChainType::Dot,
+            };
+            self.build_indexed_chain(
+                state,
+                &x.rhs,
+                next_chain,
+                &mut values.into_iter(),
+                idx_values,
+            )?;
+        }
+
+        // The suspended method call needs neither its arguments nor its target
+        idx_values.push(ChainArgument::FnCallArgs(
+            Default::default(),
+            Default::default(),
+        ));
+        let target = &mut Dynamic::UNIT.into();
+
+        self.eval_dot_index_chain_helper(
+            mods, state, lib, this_ptr, target, rhs, idx_values, chain_type, level, new_val,
+        )
+    }
+
+    /// Build a chain of indexes from their values, in evaluation order, and store them in a
+    /// [`StaticVec`].
+    /// [`StaticVec`] is used to avoid an allocation in the overwhelming cases of
+    /// just a few levels of indexing.
+    #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
+    fn build_indexed_chain(
+        &self,
+        state: &mut State,
         expr: &Expr,
         parent_chain_type: ChainType,
+        values: &mut impl Iterator<Item = Dynamic>,
         idx_values: &mut StaticVec<ChainArgument>,
-        size: usize,
-        level: usize,
     ) -> Result<(), Box<EvalAltResult>> {
         self.inc_operations(state, expr.position())?;
 
+        let fn_call_args = |args: &StaticVec<Expr>, values: &mut dyn Iterator<Item = Dynamic>| {
+            let arg_values = values.take(args.len()).collect();
+            let arg_positions = args.iter().map(Expr::position).collect();
+            ChainArgument::FnCallArgs(arg_values, arg_positions)
+        };
+
         match expr {
             Expr::FnCall(x, _) if parent_chain_type == ChainType::Dot && x.namespace.is_none() => {
-                let mut arg_positions: StaticVec<_> = Default::default();
-
-                let arg_values = x
-                    .args
-                    .iter()
-                    .map(|arg_expr| {
-                        arg_positions.push(arg_expr.position());
-                        self.eval_expr(scope, mods, state, lib, this_ptr, arg_expr, level)
-                            .map(Dynamic::flatten)
-                    })
-                    .collect::<Result<StaticVec<_>, _>>()?;
-
-                idx_values.push((arg_values, arg_positions).into());
+                idx_values.push(fn_call_args(&x.args, values));
             }
             Expr::FnCall(_, _) if parent_chain_type == ChainType::Dot => {
                 unreachable!("function call in dot chain should not be namespace-qualified")
@@ -1462,7 +2236,6 @@ impl Engine {
             Expr::Index(x, _) | Expr::Dot(x, _) => {
                 let crate::ast::BinaryExpr { lhs, rhs, .. } = x.as_ref();
 
-                // Evaluate in left-to-right order
                 let lhs_val = match lhs {
                     Expr::Property(x) if parent_chain_type == ChainType::Dot => {
                         ChainArgument::Property(x.2.pos)
@@ -1471,26 +2244,12 @@ impl Engine {
                     Expr::FnCall(x, _)
                         if parent_chain_type == ChainType::Dot && x.namespace.is_none() =>
                     {
-                        let mut arg_positions: StaticVec<_> = Default::default();
-
-                        let arg_values = x
-                            .args
-                            .iter()
-                            .map(|arg_expr| {
-                                arg_positions.push(arg_expr.position());
-                                self.eval_expr(scope, mods, state, lib, this_ptr, arg_expr, level)
-                                    .map(Dynamic::flatten)
-                            })
-                            .collect::<Result<StaticVec<_>, _>>()?;
-
-                        (arg_values, arg_positions).into()
+                        fn_call_args(&x.args, values)
                     }
                     Expr::FnCall(_, _) if parent_chain_type == ChainType::Dot => {
                         unreachable!("function call in dot chain should not be namespace-qualified")
                     }
-                    _ => self
-                        .eval_expr(scope, mods, state, lib, this_ptr, lhs, level)
-                        .map(|v| (v.flatten(), lhs.position()).into())?,
+                    _ => (values.next().unwrap(), lhs.position()).into(),
                 };
 
                 // Push in reverse order
@@ -1499,17 +2258,12 @@ impl Engine {
                     Expr::Dot(_, _) => ChainType::Dot,
                     _ => unreachable!("index or dot chain expected, but gets {:?}", expr),
                 };
-                self.eval_indexed_chain(
-                    scope, mods, state, lib, this_ptr, rhs, chain_type, idx_values, size, level,
-                )?;
+                self.build_indexed_chain(state, rhs, chain_type, values, idx_values)?;
 
                 idx_values.push(lhs_val);
             }
 
-            _ => idx_values.push(
-                self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
-                    .map(|v| (v.flatten(), expr.position()).into())?,
-            ),
+            _ => idx_values.push((values.next().unwrap(), expr.position()).into()),
         }
 
         Ok(())
@@ -1554,6 +2308,24 @@ impl Engine {
                 }
             }
 
+            #[cfg(not(feature = "no_index"))]
+            Dynamic(Union::Blob(blob, _)) => {
+                // val_blob[idx]
+                let index = idx
+                    .as_int()
+                    .map_err(|err| self.make_type_mismatch_err::<crate::INT>(err, idx_pos))?;
+
+                let blob_len = blob.len();
+
+                if index >= 0 && (index as usize) < blob_len {
+                    let offset = index as usize;
+                    let byte = blob[offset] as crate::INT;
+                    Ok(Target::BlobByte(target, offset, byte.into()))
+                } else {
+                    EvalAltResult::ErrorArrayBounds(blob_len, index, idx_pos).into()
+                }
+            }
+
             #[cfg(not(feature = "no_object"))]
             Dynamic(Union::Map(map, _)) => {
                 // val_map[idx]
@@ -1574,6 +2346,25 @@ impl Engine {
                 })
             }
 
+            #[cfg(not(feature = "no_object"))]
+            Dynamic(Union::Variant(_, _)) if target.is::<Record>() => {
+                // val_record[field]
+                let index = idx
+                    .read_lock::<ImmutableString>()
+                    .ok_or_else(|| self.make_type_mismatch_err::<ImmutableString>("", idx_pos))?;
+                let record = target.downcast_mut::<Record>().unwrap();
+
+                if !record.contains(&index) {
+                    return EvalAltResult::ErrorDotExpr(
+                        format!("Unknown field '{}' of type {}", *index, record.type_name()),
+                        idx_pos,
+                    )
+                    .into();
+                }
+
+                Ok(record.get_mut(&index).unwrap().into())
+            }
+
             #[cfg(not(feature = "no_index"))]
             Dynamic(Union::Str(s, _)) => {
                 // val_string[idx]
@@ -1623,6 +2414,35 @@ impl Engine {
         }
     }
 
+    /// Evaluate expressions in order, pushing their values into `values`.
+    ///
+    /// When resuming, the values of the expressions evaluated before the suspension are taken
+    /// from `resumed` instead.  On error, `values` holds the values of the expressions evaluated
+    /// so far.
+    pub(crate) fn eval_exprs<'e>(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        exprs: impl IntoIterator<Item = &'e Expr>,
+        resumed: &mut impl Iterator<Item = Dynamic>,
+        values: &mut StaticVec<Dynamic>,
+        level: usize,
+    ) -> Result<(), Box<EvalAltResult>> {
+        for expr in exprs {
+            let value = match resumed.next() {
+                Some(value) => value,
+                None => self
+                    .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                    .flatten(),
+            };
+            values.push(value);
+        }
+        Ok(())
+    }
+
     /// Evaluate an expression.
     pub(crate) fn eval_expr(
         &self,
@@ -1674,29 +2494,102 @@ impl Engine {
 
             #[cfg(not(feature = "no_index"))]
             Expr::Array(x, _) => {
-                let mut arr = Array::with_capacity(x.len());
-                for item in x.as_ref() {
-                    arr.push(
-                        self.eval_expr(scope, mods, state, lib, this_ptr, item, level)?
-                            .flatten(),
-                    );
-                }
+                let mut resumed = state.take_resumed_values().into_iter();
+                let mut arr = StaticVec::with_capacity(x.len());
+                let items = x.iter();
+                self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    items,
+                    &mut resumed,
+                    &mut arr,
+                    level,
+                )
+                .map_err(|err| suspended_with(err, arr.drain(..)))?;
+                let arr = arr.into_vec();
                 Ok(Dynamic(Union::Array(Box::new(arr), AccessMode::ReadWrite)))
             }
 
+            #[cfg(not(feature = "no_index"))]
+            Expr::Set(x, _) => {
+                let mut resumed = state.take_resumed_values().into_iter();
+                let mut values = StaticVec::with_capacity(x.len());
+                let items = x.iter();
+                self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    items,
+                    &mut resumed,
+                    &mut values,
+                    level,
+                )
+                .map_err(|err| suspended_with(err, values.drain(..)))?;
+
+                let mut set = crate::Set::new();
+                for (item, value) in x.iter().zip(values) {
+                    set.insert(value)
+                        .map_err(|err| err.fill_position(item.position()))?;
+                }
+                Ok(Dynamic(Union::Set(Box::new(set), AccessMode::ReadWrite)))
+            }
+
             #[cfg(not(feature = "no_object"))]
             Expr::Map(x, _) => {
+                let mut resumed = state.take_resumed_values().into_iter();
+                let mut values = StaticVec::with_capacity(x.len());
+                let exprs = x.iter().map(|(_, expr)| expr);
+                self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    exprs,
+                    &mut resumed,
+                    &mut values,
+                    level,
+                )
+                .map_err(|err| suspended_with(err, values.drain(..)))?;
+
                 let mut map = Map::new();
-                for (Ident { name: key, .. }, expr) in x.as_ref() {
-                    map.insert(
-                        key.clone(),
-                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
-                            .flatten(),
-                    );
+                for ((Ident { name: key, .. }, _), value) in x.iter().zip(values) {
+                    map.insert(key.clone(), value);
                 }
                 Ok(Dynamic(Union::Map(Box::new(map), AccessMode::ReadWrite)))
             }
 
+            #[cfg(not(feature = "no_object"))]
+            Expr::Record(x, _) => {
+                let mut resumed = state.take_resumed_values().into_iter();
+                let mut values = StaticVec::with_capacity(x.1.len());
+                let exprs = x.1.iter().map(|(_, expr)| expr);
+                self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    exprs,
+                    &mut resumed,
+                    &mut values,
+                    level,
+                )
+                .map_err(|err| suspended_with(err, values.drain(..)))?;
+
+                let fields =
+                    x.1.iter()
+                        .zip(values)
+                        .map(|((Ident { name: field, .. }, _), value)| (field.clone(), value))
+                        .collect::<StaticVec<_>>();
+                Ok(Dynamic::from(Record::new(x.0.name.clone(), fields)))
+            }
+
             // Normal function call
             Expr::FnCall(x, pos) if x.namespace.is_none() => {
                 let FnCallExpr {
@@ -1728,26 +2621,38 @@ impl Engine {
             }
 
             Expr::And(x, _) => {
-                Ok((self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)?
+                let lhs = match state.take_resumed_values().pop() {
+                    Some(value) => value,
+                    None => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)
+                        .map_err(|err| suspended_with(err, empty()))?,
+                };
+                Ok((lhs
                     .as_bool()
                     .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?
                     && // Short-circuit using &&
                 self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)?
+                    .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)
+                    .map_err(|err| suspended_with(err, once(true.into())))?
                     .as_bool()
                     .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.rhs.position()))?)
                 .into())
             }
 
             Expr::Or(x, _) => {
-                Ok((self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)?
+                let lhs = match state.take_resumed_values().pop() {
+                    Some(value) => value,
+                    None => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, &x.lhs, level)
+                        .map_err(|err| suspended_with(err, empty()))?,
+                };
+                Ok((lhs
                     .as_bool()
                     .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.lhs.position()))?
                     || // Short-circuit using ||
                 self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)?
+                    .eval_expr(scope, mods, state, lib, this_ptr, &x.rhs, level)
+                    .map_err(|err| suspended_with(err, once(false.into())))?
                     .as_bool()
                     .map_err(|err| self.make_type_mismatch_err::<bool>(err, x.rhs.position()))?)
                 .into())
@@ -1775,7 +2680,12 @@ impl Engine {
                     this_ptr,
                     level,
                 };
-                (custom_def.func)(&mut context, &expressions)
+                (custom_def.func)(&mut context, &expressions).map_err(|err| match *err {
+                    EvalAltResult::ErrorSuspended(_, _) => {
+                        make_unsupported_err("within custom syntax").fill_position(expr.position())
+                    }
+                    _ => err,
+                })
             }
 
             _ => unreachable!("expression cannot be evaluated: {:?}", expr),
@@ -1802,45 +2712,77 @@ impl Engine {
 
         let mut _extra_fn_resolution_cache = false;
         let prev_always_search = state.always_search;
-        let prev_scope_len = scope.len();
+        let mut prev_scope_len = scope.len();
         let prev_mods_len = mods.len();
 
+        // When resuming, skip to the statement leading to the suspended call
+        let mut resume_index = 0;
+
+        if let Some((index, scope_len)) = state.resume.as_mut().and_then(|r| r.next_stmt()) {
+            resume_index = index;
+            prev_scope_len = scope_len;
+        }
+
         if restore_prev_state {
             state.scope_level += 1;
         }
 
-        let result = statements.iter().try_fold(Dynamic::UNIT, |_, stmt| {
-            let _mods_len = mods.len();
-
-            let r = self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level)?;
+        let result = statements
+            .iter()
+            .enumerate()
+            .try_fold(Dynamic::UNIT, |_, (index, stmt)| {
+                let _mods_len = mods.len();
+                let stmt_scope_len = scope.len();
+
+                let r = if index < resume_index {
+                    match stmt {
+                        // Imports are not saved, so run them again
+                        #[cfg(not(feature = "no_module"))]
+                        Stmt::Import(_, _, _) => {
+                            let resume = state.resume.take();
+                            let r = self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level);
+                            state.resume = resume;
+                            r?
+                        }
+                        _ => return Ok(Dynamic::UNIT),
+                    }
+                } else {
+                    self.eval_stmt(scope, mods, state, lib, this_ptr, stmt, level)
+                        .map_err(|mut err| {
+                            if let EvalAltResult::ErrorSuspended(ref mut cont, pos) = *err {
+                                cont.record_stmt(scope, index, prev_scope_len, stmt_scope_len, pos);
+                            }
+                            err
+                        })?
+                };
 
-            #[cfg(not(feature = "no_module"))]
-            if matches!(stmt, Stmt::Import(_, _, _)) {
-                // Get the extra modules - see if any functions are marked global.
-                // Without global functions, the extra modules never affect function resolution.
-                if mods
-                    .scan_raw()
-                    .skip(_mods_len)
-                    .any(|(_, m)| m.contains_indexed_global_functions())
-                {
-                    if _extra_fn_resolution_cache {
-                        // When new module is imported with global functions and there is already
-                        // a new cache, clear it - notice that this is expensive as all function
-                        // resolutions must start again
-                        state.fn_resolution_cache_mut().clear();
-                    } else if restore_prev_state {
-                        // When new module is imported with global functions, push a new cache
-                        state.push_fn_resolution_cache();
-                        _extra_fn_resolution_cache = true;
-                    } else {
-                        // When the block is to be evaluated in-place, just clear the current cache
-                        state.fn_resolution_cache_mut().clear();
+                #[cfg(not(feature = "no_module"))]
+                if matches!(stmt, Stmt::Import(_, _, _)) {
+                    // Get the extra modules - see if any functions are marked global.
+                    // Without global functions, the extra modules never affect function resolution.
+                    if mods
+                        .scan_raw()
+                        .skip(_mods_len)
+                        .any(|(_, m)| m.contains_indexed_global_functions())
+                    {
+                        if _extra_fn_resolution_cache {
+                            // When new module is imported with global functions and there is already
+                            // a new cache, clear it - notice that this is expensive as all function
+                            // resolutions must start again
+                            state.fn_resolution_cache_mut().clear();
+                        } else if restore_prev_state {
+                            // When new module is imported with global functions, push a new cache
+                            state.push_fn_resolution_cache();
+                            _extra_fn_resolution_cache = true;
+                        } else {
+                            // When the block is to be evaluated in-place, just clear the current cache
+                            state.fn_resolution_cache_mut().clear();
+                        }
                     }
                 }
-            }
 
-            Ok(r)
-        });
+                Ok(r)
+            });
 
         if _extra_fn_resolution_cache {
             // If imports list is modified, pop the functions lookup cache
@@ -1990,9 +2932,14 @@ impl Engine {
             // lhs op= rhs
             Stmt::Assignment(x, op_pos) => {
                 let (lhs_expr, rhs_expr, op_info) = x.as_ref();
-                let rhs_val = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?
-                    .flatten();
+                // The new value is kept when suspended within the target
+                let rhs_val = match state.take_resumed_values().pop() {
+                    Some(value) => value,
+                    None => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)
+                        .map_err(|err| suspended_with(err, empty()))?
+                        .flatten(),
+                };
                 let _new_val = Some(((rhs_val, rhs_expr.position()), (op_info, *op_pos)));
 
                 // Must be either `var[index] op= val` or `var.prop op= val`
@@ -2024,7 +2971,9 @@ impl Engine {
             // Block scope
             Stmt::Block(statements, _) if statements.is_empty() => Ok(Dynamic::UNIT),
             Stmt::Block(statements, _) => {
+                state.take_resumed_branch();
                 self.eval_stmt_block(scope, mods, state, lib, this_ptr, statements, true, level)
+                    .map_err(|err| suspended_in(err, Branch::Block))
             }
 
             // If statement
@@ -2039,72 +2988,101 @@ impl Engine {
                         ..
                     },
                 ) = x.as_ref();
-                self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
-                    .as_bool()
-                    .map_err(|err| self.make_type_mismatch_err::<bool>(err, expr.position()))
-                    .and_then(|guard_val| {
-                        if guard_val {
-                            if !if_stmt.is_empty() {
-                                self.eval_stmt_block(
-                                    scope, mods, state, lib, this_ptr, if_stmt, true, level,
-                                )
-                            } else {
-                                Ok(Dynamic::UNIT)
-                            }
+
+                match state.take_resumed_branch() {
+                    Some(Branch::Then) => Ok(true),
+                    Some(Branch::Else) => Ok(false),
+                    _ => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                        .as_bool()
+                        .map_err(|err| self.make_type_mismatch_err::<bool>(err, expr.position())),
+                }
+                .and_then(|guard_val| {
+                    if guard_val {
+                        if !if_stmt.is_empty() {
+                            self.eval_stmt_block(
+                                scope, mods, state, lib, this_ptr, if_stmt, true, level,
+                            )
+                            .map_err(|err| suspended_in(err, Branch::Then))
                         } else {
-                            if !else_stmt.is_empty() {
-                                self.eval_stmt_block(
-                                    scope, mods, state, lib, this_ptr, else_stmt, true, level,
-                                )
-                            } else {
-                                Ok(Dynamic::UNIT)
-                            }
+                            Ok(Dynamic::UNIT)
                         }
-                    })
+                    } else {
+                        if !else_stmt.is_empty() {
+                            self.eval_stmt_block(
+                                scope, mods, state, lib, this_ptr, else_stmt, true, level,
+                            )
+                            .map_err(|err| suspended_in(err, Branch::Else))
+                        } else {
+                            Ok(Dynamic::UNIT)
+                        }
+                    }
+                })
             }
 
             // Switch statement
             Stmt::Switch(match_expr, x, _) => {
                 let (table, def_stmt) = x.as_ref();
 
-                let value = self.eval_expr(scope, mods, state, lib, this_ptr, match_expr, level)?;
-
-                if value.is_hashable() {
-                    let hasher = &mut get_hasher();
-                    value.hash(hasher);
-                    let hash = hasher.finish();
+                let hash = match state.take_resumed_branch() {
+                    Some(Branch::Case(hash)) => Some(hash),
+                    Some(_) => None,
+                    None => {
+                        let value =
+                            self.eval_expr(scope, mods, state, lib, this_ptr, match_expr, level)?;
+
+                        if value.is_hashable() {
+                            let hasher = &mut get_hasher();
+                            value.hash(hasher);
+                            Some(hasher.finish())
+                        } else if let Some(variant) = self
+                            .enum_variants
+                            .get(&value.type_id())
+                            .and_then(|f| f(&value))
+                        {
+                            // Registered enums match on the variant name
+                            let hasher = &mut get_hasher();
+                            variant.hash(hasher);
+                            Some(hasher.finish())
+                        } else {
+                            // Non-hashable values never match any specific clause
+                            None
+                        }
+                    }
+                };
 
-                    table.get(&hash).map(|StmtBlock { statements, .. }| {
+                hash.and_then(|hash| table.get(&hash).map(|block| (hash, block)))
+                    .map(|(hash, StmtBlock { statements, .. })| {
                         if !statements.is_empty() {
                             self.eval_stmt_block(
                                 scope, mods, state, lib, this_ptr, statements, true, level,
                             )
+                            .map_err(|err| suspended_in(err, Branch::Case(hash)))
+                        } else {
+                            Ok(Dynamic::UNIT)
+                        }
+                    })
+                    .unwrap_or_else(|| {
+                        // Default match clause
+                        let def_stmt = &def_stmt.statements;
+                        if !def_stmt.is_empty() {
+                            self.eval_stmt_block(
+                                scope, mods, state, lib, this_ptr, def_stmt, true, level,
+                            )
+                            .map_err(|err| suspended_in(err, Branch::Default))
                         } else {
                             Ok(Dynamic::UNIT)
                         }
                     })
-                } else {
-                    // Non-hashable values never match any specific clause
-                    None
-                }
-                .unwrap_or_else(|| {
-                    // Default match clause
-                    let def_stmt = &def_stmt.statements;
-                    if !def_stmt.is_empty() {
-                        self.eval_stmt_block(
-                            scope, mods, state, lib, this_ptr, def_stmt, true, level,
-                        )
-                    } else {
-                        Ok(Dynamic::UNIT)
-                    }
-                })
             }
 
             // While loop
             Stmt::While(expr, body, _) => {
                 let body = &body.statements;
+                let mut resumed = state.take_resumed_branch().is_some();
+
                 loop {
-                    let condition = if !expr.is_unit() {
+                    let condition = if !mem::take(&mut resumed) && !expr.is_unit() {
                         self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
                             .as_bool()
                             .map_err(|err| {
@@ -2127,7 +3105,7 @@ impl Engine {
                         Err(err) => match *err {
                             EvalAltResult::LoopBreak(false, _) => (),
                             EvalAltResult::LoopBreak(true, _) => return Ok(Dynamic::UNIT),
-                            _ => return Err(err),
+                            _ => return Err(suspended_in(err, Branch::Body)),
                         },
                     }
                 }
@@ -2136,9 +3114,11 @@ impl Engine {
             // Do loop
             Stmt::Do(body, expr, is_while, _) => {
                 let body = &body.statements;
+                // Skip the body when resuming within the condition
+                let mut resumed = matches!(state.take_resumed_branch(), Some(Branch::Condition));
 
                 loop {
-                    if !body.is_empty() {
+                    if !mem::take(&mut resumed) && !body.is_empty() {
                         match self
                             .eval_stmt_block(scope, mods, state, lib, this_ptr, body, true, level)
                         {
@@ -2146,13 +3126,14 @@ impl Engine {
                             Err(err) => match *err {
                                 EvalAltResult::LoopBreak(false, _) => continue,
                                 EvalAltResult::LoopBreak(true, _) => return Ok(Dynamic::UNIT),
-                                _ => return Err(err),
+                                _ => return Err(suspended_in(err, Branch::Body)),
                             },
                         }
                     }
 
                     if self
-                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)
+                        .map_err(|err| suspended_in(err, Branch::Condition))?
                         .as_bool()
                         .map_err(|err| self.make_type_mismatch_err::<bool>(err, expr.position()))?
                     {
@@ -2170,45 +3151,90 @@ impl Engine {
             // For loop
             Stmt::For(expr, x, _) => {
                 let (name, StmtBlock { statements, pos }) = x.as_ref();
-                let iter_obj = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
-                    .flatten();
-                let iter_type = iter_obj.type_id();
-
-                // lib should only contain scripts, so technically they cannot have iterators
-
-                // Search order:
-                // 1) Global namespace - functions registered via Engine::register_XXX
-                // 2) Global modules - packages
-                // 3) Imported modules - functions marked with global namespace
-                // 4) Global sub-modules - functions marked with global namespace
-                let func = self
-                    .global_namespace
-                    .get_iter(iter_type)
-                    .or_else(|| {
-                        self.global_modules
-                            .iter()
-                            .find_map(|m| m.get_iter(iter_type))
-                    })
-                    .or_else(|| mods.get_iter(iter_type))
-                    .or_else(|| {
-                        self.global_sub_modules
-                            .values()
-                            .find_map(|m| m.get_qualified_iter(iter_type))
-                    });
-
-                if let Some(func) = func {
-                    // Add the loop variable
-                    let var_name: Cow<'_, str> = if state.is_global() {
-                        name.clone().into()
-                    } else {
-                        unsafe_cast_var_name_to_lifetime(name).into()
-                    };
-                    scope.push(var_name, ());
-                    let index = scope.len() - 1;
-                    state.scope_level += 1;
+                let iter_pos = expr.position();
+
+                let (mut iter, index, mut resuming) = match state.take_resumed_branch() {
+                    // Continue with the values still to be iterated,
+                    // the loop variable is already in scope
+                    Some(Branch::For(for_loop)) => {
+                        let index = state
+                            .resume
+                            .as_ref()
+                            .and_then(|r| r.next_scope_len())
+                            .unwrap()
+                            - 1;
+                        (for_loop.take_iter(), index, true)
+                    }
+                    _ => {
+                        let iter_obj = self
+                            .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                            .flatten();
+                        let iter_type = iter_obj.type_id();
+
+                        // lib should only contain scripts, so technically they cannot have iterators
+
+                        // Search order:
+                        // 1) Global namespace - functions registered via Engine::register_XXX
+                        // 2) Global modules - packages
+                        // 3) Imported modules - functions marked with global namespace
+                        // 4) Global sub-modules - functions marked with global namespace
+                        let find_iter = |mods: &Imports| {
+                            self.global_namespace
+                                .get_iter(iter_type)
+                                .or_else(|| {
+                                    self.global_modules
+                                        .iter()
+                                        .find_map(|m| m.get_iter(iter_type))
+                                })
+                                .or_else(|| mods.get_iter(iter_type))
+                                .or_else(|| {
+                                    self.global_sub_modules
+                                        .values()
+                                        .find_map(|m| m.get_qualified_iter(iter_type))
+                                })
+                        };
+
+                        #[allow(unused_mut)]
+                        let mut func = find_iter(mods);
+
+                        // The iterator may be in a lazily-imported module not yet resolved
+                        #[cfg(not(feature = "no_module"))]
+                        if func.is_none() && self.resolve_pending_imports(mods, state)? {
+                            func = find_iter(mods);
+                        }
+
+                        let iter = match func {
+                            Some(func) => func(iter_obj),
+                            None => return EvalAltResult::ErrorFor(iter_pos).into(),
+                        };
+
+                        // Add the loop variable
+                        let var_name: Cow<'_, str> = if state.is_global() {
+                            name.clone().into()
+                        } else {
+                            unsafe_cast_var_name_to_lifetime(name).into()
+                        };
+                        scope.push(var_name, ());
+                        (iter, scope.len() - 1, false)
+                    }
+                };
 
-                    for iter_value in func(iter_obj) {
+                state.scope_level += 1;
+
+                loop {
+                    if !mem::take(&mut resuming) {
+                        let next_value = self.next_iter_value(
+                            mods,
+                            state,
+                            lib,
+                            iter.as_mut(),
+                            iter_pos,
+                            level + 1,
+                        )?;
+                        let iter_value = match next_value {
+                            Some(value) => value,
+                            None => break,
+                        };
                         let loop_var = scope.get_mut_by_index(index);
                         let value = iter_value.flatten();
 
@@ -2217,31 +3243,29 @@ impl Engine {
                         } else {
                             *loop_var = value;
                         }
+                    }
 
-                        self.inc_operations(state, *pos)?;
+                    self.inc_operations(state, *pos)?;
 
-                        if statements.is_empty() {
-                            continue;
-                        }
-
-                        match self.eval_stmt_block(
-                            scope, mods, state, lib, this_ptr, statements, true, level,
-                        ) {
-                            Ok(_) => (),
-                            Err(err) => match *err {
-                                EvalAltResult::LoopBreak(false, _) => (),
-                                EvalAltResult::LoopBreak(true, _) => break,
-                                _ => return Err(err),
-                            },
-                        }
+                    if statements.is_empty() {
+                        continue;
                     }
 
-                    state.scope_level -= 1;
-                    scope.rewind(scope.len() - 1);
-                    Ok(Dynamic::UNIT)
-                } else {
-                    EvalAltResult::ErrorFor(expr.position()).into()
+                    match self
+                        .eval_stmt_block(scope, mods, state, lib, this_ptr, statements, true, level)
+                    {
+                        Ok(_) => (),
+                        Err(err) => match *err {
+                            EvalAltResult::LoopBreak(false, _) => (),
+                            EvalAltResult::LoopBreak(true, _) => break,
+                            _ => return Err(suspended_in(err, Branch::For(ForLoop::new(iter)))),
+                        },
+                    }
                 }
+
+                state.scope_level -= 1;
+                scope.rewind(scope.len() - 1);
+                Ok(Dynamic::UNIT)
             }
 
             // Continue statement
@@ -2264,9 +3288,34 @@ impl Engine {
                     },
                 ) = x.as_ref();
 
-                let result = self
-                    .eval_stmt_block(scope, mods, state, lib, this_ptr, try_body, true, level)
-                    .map(|_| Dynamic::UNIT);
+                let resumed = state.take_resumed_branch();
+
+                let result = if matches!(resumed, Some(Branch::Catch)) {
+                    // The error variable is already in scope
+                    let orig_scope_len = state
+                        .resume
+                        .as_ref()
+                        .and_then(|r| r.next_scope_len())
+                        .unwrap()
+                        - if err_var.is_some() { 1 } else { 0 };
+
+                    state.scope_level += 1;
+
+                    let result = self
+                        .eval_stmt_block(scope, mods, state, lib, this_ptr, catch_body, true, level)
+                        .map(|_| Dynamic::UNIT)
+                        .map_err(|err| suspended_in(err, Branch::Catch));
+
+                    state.scope_level -= 1;
+                    scope.rewind(orig_scope_len);
+
+                    // The original error is not available to be re-thrown
+                    return result;
+                } else {
+                    self.eval_stmt_block(scope, mods, state, lib, this_ptr, try_body, true, level)
+                        .map(|_| Dynamic::UNIT)
+                        .map_err(|err| suspended_in(err, Branch::Try))
+                };
 
                 match result {
                     Ok(_) => result,
@@ -2324,9 +3373,11 @@ impl Engine {
                             scope.push(unsafe_cast_var_name_to_lifetime(&name), err_value);
                         }
 
-                        let result = self.eval_stmt_block(
-                            scope, mods, state, lib, this_ptr, catch_body, true, level,
-                        );
+                        let result = self
+                            .eval_stmt_block(
+                                scope, mods, state, lib, this_ptr, catch_body, true, level,
+                            )
+                            .map_err(|err| suspended_in(err, Branch::Catch));
 
                         state.scope_level -= 1;
                         scope.rewind(orig_scope_len);
@@ -2372,6 +3423,22 @@ impl Engine {
                 EvalAltResult::ErrorRuntime(Dynamic::UNIT, *pos).into()
             }
 
+            // Yield value
+            Stmt::Return(ReturnType::Yield, expr, pos) => {
+                // Resumed after this `yield`
+                if state.take_resumed_call(*pos).is_some() {
+                    return Ok(Dynamic::UNIT);
+                }
+
+                let value = match expr {
+                    Some(expr) => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                        .flatten(),
+                    None => Dynamic::UNIT,
+                };
+                EvalAltResult::ErrorSuspended(Continuation::new_yield(value).into(), *pos).into()
+            }
+
             // Let/const statement
             Stmt::Let(expr, Ident { name, .. }, export, _)
             | Stmt::Const(expr, Ident { name, .. }, export, _) => {
@@ -2408,9 +3475,17 @@ impl Engine {
             // Import statement
             #[cfg(not(feature = "no_module"))]
             Stmt::Import(expr, export, _pos) => {
+                // Only `import "path" as name` can be lazy - the module is resolved when
+                // `name::` is first accessed, and only then counts as a loaded module
+                let lazy = self.lazy_import
+                    && matches!(
+                        export.as_ref().map(|x| x.as_ref()),
+                        Some(ImportNames::Module(_))
+                    );
+
                 // Guard against too many modules
                 #[cfg(not(feature = "unchecked"))]
-                if state.modules >= self.max_modules() {
+                if !lazy && state.modules >= self.max_modules() {
                     return EvalAltResult::ErrorTooManyModules(*_pos).into();
                 }
 
@@ -2418,31 +3493,96 @@ impl Engine {
                     .eval_expr(scope, mods, state, lib, this_ptr, &expr, level)?
                     .try_cast::<ImmutableString>()
                 {
-                    use crate::ModuleResolver;
-
                     let expr_pos = expr.position();
 
-                    let module = state
-                        .resolver
-                        .as_ref()
-                        .and_then(|r| match r.resolve(self, &path, expr_pos) {
-                            Ok(m) => return Some(Ok(m)),
-                            Err(err) => match *err {
-                                EvalAltResult::ErrorModuleNotFound(_, _) => None,
-                                _ => return Some(Err(err)),
-                            },
-                        })
-                        .unwrap_or_else(|| self.module_resolver.resolve(self, &path, expr_pos))?;
-
-                    if let Some(name) = export.as_ref().map(|x| x.name.clone()) {
-                        if !module.is_indexed() {
-                            // Index the module (making a clone copy if necessary) if it is not indexed
-                            let mut module = crate::fn_native::shared_take_or_clone(module);
-                            module.build_index();
-                            mods.push(name, module);
-                        } else {
-                            mods.push(name, module);
+                    if let Some(ref permissions) = state.permissions {
+                        permissions.check(Capability::Import, &path, expr_pos)?;
+                    }
+
+                    if lazy {
+                        if let Some(ImportNames::Module(Ident { name, .. })) =
+                            export.as_ref().map(|x| x.as_ref())
+                        {
+                            let lazy = LazyImport {
+                                source: state.source.clone(),
+                                resolver: state.resolver.clone(),
+                                pos: expr_pos,
+                            };
+                            mods.push_lazy(name.clone(), path, lazy);
                         }
+                        return Ok(Dynamic::UNIT);
+                    }
+
+                    let module = self.resolve_import(
+                        mods,
+                        state.source.as_ref().map(|s| s.as_str()),
+                        state.resolver.as_ref().map(|r| r.as_ref()),
+                        &path,
+                        expr_pos,
+                    )?;
+
+                    match export.as_ref().map(|x| x.as_ref()) {
+                        Some(ImportNames::Module(Ident { name, .. })) => {
+                            let name = name.clone();
+
+                            if !module.is_indexed() {
+                                // Index the module (making a clone copy if necessary) if it is not indexed
+                                let mut module = crate::fn_native::shared_take_or_clone(module);
+                                module.build_index();
+                                mods.push_with_path(name, module, path, state.source.clone());
+                            } else {
+                                mods.push_with_path(name, module, path, state.source.clone());
+                            }
+                        }
+                        Some(ImportNames::Selected(names)) => {
+                            let prev_scope_len = scope.len();
+                            let mut selected = Module::new();
+                            let mut fns = SelectedFns::new();
+
+                            for (Ident { name, pos }, alias) in names.iter() {
+                                let alias = alias.as_ref().map_or(name, |x| &x.name);
+                                let has_fn = module.copy_fn_as(name, alias, &mut selected);
+
+                                if has_fn {
+                                    fns.push((name.clone(), alias.clone()));
+                                }
+
+                                match module.get_var(name) {
+                                    Some(value) => {
+                                        let var_name: Cow<'_, str> = if state.is_global() {
+                                            alias.to_string().into()
+                                        } else {
+                                            unsafe_cast_var_name_to_lifetime(alias).into()
+                                        };
+                                        scope.push_dynamic_value(
+                                            var_name,
+                                            AccessMode::ReadOnly,
+                                            value,
+                                        );
+                                    }
+                                    None if !has_fn => {
+                                        return EvalAltResult::ErrorNotExported(
+                                            name.to_string(),
+                                            path.to_string(),
+                                            *pos,
+                                        )
+                                        .into();
+                                    }
+                                    None => (),
+                                }
+                            }
+
+                            // Selected functions are kept in an unnamed module
+                            selected.build_index();
+                            mods.push_selected(selected, path, state.source.clone(), fns);
+
+                            // IMPORTANT! The parser does not know about the imported variables,
+                            //            so all variable offsets from this point on will be mis-aligned.
+                            if scope.len() != prev_scope_len {
+                                state.always_search = true;
+                            }
+                        }
+                        None => (),
                     }
 
                     state.modules += 1;
@@ -2453,6 +3593,51 @@ impl Engine {
                 }
             }
 
+            // Inline module declaration
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Module(x, _pos) => {
+                // Guard against too many modules
+                #[cfg(not(feature = "unchecked"))]
+                if state.modules >= self.max_modules() {
+                    return EvalAltResult::ErrorTooManyModules(*_pos).into();
+                }
+
+                let ModuleDef { name, ast } = x.as_ref();
+
+                // The module body runs under the same source, permissions and resolver as the script
+                let mut ast = ast.clone();
+                if let Some(ref source) = state.source {
+                    ast.set_source(source.clone());
+                }
+                if let Some(ref permissions) = state.permissions {
+                    ast.set_permissions(permissions.clone());
+                }
+                if let Some(ref resolver) = state.resolver {
+                    ast.set_resolver(resolver.clone());
+                }
+
+                let module = Module::eval_ast_as_new_with_imports(
+                    Default::default(),
+                    &ast,
+                    self,
+                    &mods.nested(),
+                )
+                .map_err(|err| match *err {
+                    EvalAltResult::ErrorCyclicImport(_, _) => err,
+                    _ => Box::new(EvalAltResult::ErrorInModule(
+                        name.name.to_string(),
+                        err,
+                        name.pos,
+                    )),
+                })?;
+
+                mods.push_inline(name.name.clone(), module);
+
+                state.modules += 1;
+
+                Ok(Dynamic::UNIT)
+            }
+
             // Export statement
             #[cfg(not(feature = "no_module"))]
             Stmt::Export(list, _) => {
@@ -2507,70 +3692,96 @@ impl Engine {
         let mut has_limit = self.limits.max_string_size.is_some();
         #[cfg(not(feature = "no_index"))]
         {
-            has_limit = has_limit || self.limits.max_array_size.is_some();
+            has_limit = has_limit
+                || self.limits.max_array_size.is_some()
+                || self.limits.max_set_size.is_some();
         }
         #[cfg(not(feature = "no_object"))]
         {
             has_limit = has_limit || self.limits.max_map_size.is_some();
         }
+        #[cfg(feature = "bigint")]
+        {
+            has_limit = has_limit || self.limits.max_bigint_bits.is_some();
+        }
 
         if !has_limit {
             return result;
         }
 
-        // Recursively calculate the size of a value (especially `Array` and `Map`)
-        fn calc_size(value: &Dynamic) -> (usize, usize, usize) {
-            match value {
-                #[cfg(not(feature = "no_index"))]
-                Dynamic(Union::Array(arr, _)) => {
-                    let mut arrays = 0;
-                    let mut maps = 0;
-
-                    arr.iter().for_each(|value| match value {
-                        Dynamic(Union::Array(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        #[cfg(not(feature = "no_object"))]
-                        Dynamic(Union::Map(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        _ => arrays += 1,
-                    });
-
-                    (arrays, maps, 0)
+        // Recursively calculate the size of a value (especially `Array`, `Map` and `Set`),
+        // returning the number of array items, map properties, set members and string bytes
+        fn calc_size(value: &Dynamic) -> (usize, usize, usize, usize) {
+            // Sizes of collections nested inside a collection
+            fn calc_nested_size(
+                value: &Dynamic,
+                arrays: &mut usize,
+                maps: &mut usize,
+                sets: &mut usize,
+            ) -> bool {
+                match value {
+                    #[cfg(not(feature = "no_index"))]
+                    Dynamic(Union::Array(_, _)) | Dynamic(Union::Set(_, _)) => (),
+                    #[cfg(not(feature = "no_object"))]
+                    Dynamic(Union::Map(_, _)) => (),
+                    #[cfg(not(feature = "no_index"))]
+                    Dynamic(Union::Blob(b, _)) => {
+                        *arrays += b.len();
+                        return true;
+                    }
+                    #[cfg(not(feature = "no_index"))]
+                    Dynamic(Union::Variant(_, _)) => {
+                        return crate::packed::packed_len(value)
+                            .map(|len| *arrays += len)
+                            .is_some();
+                    }
+                    _ => return false,
                 }
-                #[cfg(not(feature = "no_object"))]
-                Dynamic(Union::Map(map, _)) => {
-                    let mut arrays = 0;
-                    let mut maps = 0;
 
-                    map.values().for_each(|value| match value {
-                        #[cfg(not(feature = "no_index"))]
-                        Dynamic(Union::Array(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        Dynamic(Union::Map(_, _)) => {
-                            let (a, m, _) = calc_size(value);
-                            arrays += a;
-                            maps += m;
-                        }
-                        _ => maps += 1,
-                    });
+                let (a, m, s, _) = calc_size(value);
+                *arrays += a;
+                *maps += m;
+                *sets += s;
+                true
+            }
+
+            let mut arrays = 0;
+            let mut maps = 0;
+            let mut sets = 0;
 
-                    (arrays, maps, 0)
+            match value {
+                #[cfg(not(feature = "no_index"))]
+                Dynamic(Union::Array(arr, _)) => arr.iter().for_each(|value| {
+                    if !calc_nested_size(value, &mut arrays, &mut maps, &mut sets) {
+                        arrays += 1;
+                    }
+                }),
+                #[cfg(not(feature = "no_index"))]
+                Dynamic(Union::Blob(b, _)) => arrays += b.len(),
+                #[cfg(not(feature = "no_index"))]
+                Dynamic(Union::Variant(_, _)) => {
+                    arrays += crate::packed::packed_len(value).unwrap_or(0)
                 }
-                Dynamic(Union::Str(s, _)) => (0, 0, s.len()),
-                _ => (0, 0, 0),
+                #[cfg(not(feature = "no_index"))]
+                Dynamic(Union::Set(set, _)) => set.iter().for_each(|value| {
+                    if !calc_nested_size(value, &mut arrays, &mut maps, &mut sets) {
+                        sets += 1;
+                    }
+                }),
+                #[cfg(not(feature = "no_object"))]
+                Dynamic(Union::Map(map, _)) => map.values().for_each(|value| {
+                    if !calc_nested_size(value, &mut arrays, &mut maps, &mut sets) {
+                        maps += 1;
+                    }
+                }),
+                Dynamic(Union::Str(s, _)) => return (0, 0, 0, s.len()),
+                _ => (),
             }
+
+            (arrays, maps, sets, 0)
         }
 
-        let (_arr, _map, s) = calc_size(result.as_ref().unwrap());
+        let (_arr, _map, _set, s) = calc_size(result.as_ref().unwrap());
 
         if s > self
             .limits
@@ -2590,6 +3801,16 @@ impl Engine {
             return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), pos).into();
         }
 
+        #[cfg(not(feature = "no_index"))]
+        if _set
+            > self
+                .limits
+                .max_set_size
+                .map_or(usize::MAX, NonZeroUsize::get)
+        {
+            return EvalAltResult::ErrorDataTooLarge("Size of set".to_string(), pos).into();
+        }
+
         #[cfg(not(feature = "no_object"))]
         if _map
             > self
@@ -2600,6 +3821,18 @@ impl Engine {
             return EvalAltResult::ErrorDataTooLarge("Size of object map".to_string(), pos).into();
         }
 
+        #[cfg(feature = "bigint")]
+        if let Dynamic(Union::BigInt(n, _)) = result.as_ref().unwrap() {
+            if n.bits()
+                > self
+                    .limits
+                    .max_bigint_bits
+                    .map_or(u64::MAX, |max| max.get() as u64)
+            {
+                return EvalAltResult::ErrorDataTooLarge("Size of bigint".to_string(), pos).into();
+            }
+        }
+
         result
     }
 
@@ -2626,6 +3859,13 @@ impl Engine {
             }
         }
 
+        // Terminate script if cancelled from outside
+        if let Some(token) = &self.cancel_token {
+            if token.is_cancelled() {
+                return EvalAltResult::ErrorTerminated(Dynamic::UNIT, pos).into();
+            }
+        }
+
         Ok(())
     }
 
@@ -2641,6 +3881,37 @@ impl Engine {
             .unwrap_or_else(|| map_std_type_name(name))
     }
 
+    /// Pretty-print a type name, including the names of custom types set in imported
+    /// [modules][Module].
+    #[inline(always)]
+    pub(crate) fn map_type_name_with_imports<'a>(
+        &'a self,
+        mods: &Imports,
+        name: &'a str,
+    ) -> Cow<'a, str> {
+        if self.type_names.contains_key(name) {
+            return self.map_type_name(name).into();
+        }
+
+        mods.find_custom_type(name)
+            .map_or_else(|| self.map_type_name(name).into(), Into::into)
+    }
+
+    /// Pretty-print the type name of a value, which is the declared type name for records.
+    #[inline(always)]
+    pub(crate) fn map_value_type_name<'a>(
+        &'a self,
+        mods: &Imports,
+        value: &'a Dynamic,
+    ) -> Cow<'a, str> {
+        #[cfg(not(feature = "no_object"))]
+        if let Some(record) = value.read_lock::<Record>() {
+            return record.type_name().to_string().into();
+        }
+
+        self.map_type_name_with_imports(mods, value.type_name())
+    }
+
     /// Make a `Box<`[`EvalAltResult<ErrorMismatchDataType>`][EvalAltResult::ErrorMismatchDataType]`>`.
     #[inline(always)]
     pub(crate) fn make_type_mismatch_err<T>(&self, typ: &str, pos: Position) -> Box<EvalAltResult> {
@@ -1,9 +1,11 @@
 //! Module that defines the extern API of [`Engine`].
 
+use crate::continuation::Resumption;
+use crate::deterministic::VirtualClock;
 use crate::dynamic::Variant;
-use crate::engine::{EvalContext, Imports, State};
+use crate::engine::{CancellationToken, EvalContext, Imports, State};
 use crate::fn_native::{FnCallArgs, SendSync};
-use crate::fn_register::RegisterNativeFunction;
+use crate::fn_register::{RegisterAsyncFunction, RegisterNativeFunction};
 use crate::optimize::OptimizationLevel;
 use crate::stdlib::{
     any::{type_name, TypeId},
@@ -13,8 +15,9 @@ use crate::stdlib::{
     vec::Vec,
 };
 use crate::{
-    scope::Scope, Dynamic, Engine, EvalAltResult, FnAccess, FnNamespace, Module, NativeCallContext,
-    ParseError, Position, RhaiResult, Shared, StaticVec, AST,
+    scope::Scope, Continuation, Dynamic, Engine, EvalAltResult, FnAccess, FnNamespace, Module,
+    NativeCallContext, Nondeterministic, ParseError, Position, ReplayLog, RhaiResult, Shared,
+    StaticVec, AST,
 };
 
 #[cfg(not(feature = "no_index"))]
@@ -51,10 +54,19 @@ impl Engine {
     /// # Ok(())
     /// # }
     /// ```
-    #[inline]
+    #[inline(always)]
     pub fn register_fn<A, F>(&mut self, name: &str, func: F) -> &mut Self
     where
         F: RegisterNativeFunction<A, ()>,
+    {
+        self.set_native_fn(name, func);
+        self
+    }
+    /// Register a native Rust function into the global namespace of the [`Engine`],
+    /// returning its hash.
+    fn set_native_fn<A, R, F>(&mut self, name: &str, func: F) -> u64
+    where
+        F: RegisterNativeFunction<A, R>,
     {
         let param_types = F::param_types();
         let mut param_type_names: StaticVec<_> = F::param_names()
@@ -74,8 +86,7 @@ impl Engine {
             Some(&param_type_names),
             &param_types,
             func.into_callable_function(),
-        );
-        self
+        )
     }
     /// Register a custom fallible function with the [`Engine`].
     ///
@@ -101,12 +112,61 @@ impl Engine {
     /// engine.eval::<i64>("div(42, 0)")
     ///       .expect_err("expecting division by zero error!");
     /// ```
-    #[inline]
+    #[inline(always)]
     pub fn register_result_fn<A, F, R>(&mut self, name: &str, func: F) -> &mut Self
     where
         F: RegisterNativeFunction<A, Result<R, Box<EvalAltResult>>>,
+    {
+        self.set_native_fn(name, func);
+        self
+    }
+    /// Register a custom `async` function with the [`Engine`].
+    ///
+    /// The function returns a [`Future`][std::future::Future] that resolves to a result.
+    /// All parameters are passed by value.
+    ///
+    /// `&str` parameters are not supported because the [`Future`][std::future::Future] may
+    /// outlive the argument values, and fail to compile (see [`AsyncParam`][crate::AsyncParam]).
+    /// Use [`ImmutableString`][crate::ImmutableString] or [`String`] instead.
+    ///
+    /// A script calling the function must be evaluated via [`Engine::eval_ast_async`] or
+    /// [`Engine::eval_ast_with_scope_async`], which await the [`Future`][std::future::Future]
+    /// and then continue the evaluation with its result.
+    ///
+    /// Under other evaluation methods, calling the function
+    /// [suspends][crate::Continuation] the evaluation with an
+    /// [`EvalAltResult::ErrorSuspended`] error instead.
+    ///
+    /// Once the [`Future`][std::future::Future] resolves, the evaluation continues right after the
+    /// call, so nothing evaluated before it (including earlier iterations of enclosing loops) is
+    /// evaluated again.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use rhai::{Engine, EvalAltResult, INT};
+    ///
+    /// async fn fetch(id: INT) -> Result<INT, Box<EvalAltResult>> {
+    ///     let row = database.query(id).await.map_err(|err| err.to_string())?;
+    ///     Ok(row.value)
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("fetch", fetch);
+    ///
+    /// let ast = engine.compile("fetch(1) + fetch(2)")?;
+    ///
+    /// // Use any executor
+    /// let result = block_on(engine.eval_ast_async::<INT>(&ast))?;
+    /// ```
+    #[inline]
+    pub fn register_async_fn<A, F, R>(&mut self, name: &str, func: F) -> &mut Self
+    where
+        F: RegisterAsyncFunction<A, Result<R, Box<EvalAltResult>>>,
     {
         let param_types = F::param_types();
+
         let mut param_type_names: StaticVec<_> = F::param_names()
             .iter()
             .map(|ty| format!("_: {}", self.map_type_name(ty)))
@@ -125,6 +185,100 @@ impl Engine {
         );
         self
     }
+    /// Register a custom function with the [`Engine`] that is _nondeterministic_, i.e. one that
+    /// may return different results for the same arguments (e.g. random numbers, I/O).
+    ///
+    /// The function is otherwise the same as one registered via [`register_fn`][Engine::register_fn].
+    ///
+    /// In [deterministic mode][Engine::set_deterministic], calls to the function are handled
+    /// according to `handling` instead.
+    ///
+    /// The results of calls to the function are recorded in, and replayed from, the
+    /// [`ReplayLog`][crate::ReplayLog] set via [`set_replay_log`][Engine::set_replay_log].
+    ///
+    /// Each overload of a function is handled separately, so other overloads registered under
+    /// the same name are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use std::time::Duration;
+    /// use rhai::{Dynamic, Engine, Nondeterministic};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_nondeterministic_fn("random", || 4_i64, Nondeterministic::Stub(Dynamic::from(0_i64)))
+    ///       .register_nondeterministic_fn("read_file", || "hello", Nondeterministic::Reject);
+    ///
+    /// assert_eq!(engine.eval::<i64>("random()")?, 4);
+    ///
+    /// engine.set_deterministic(|| Duration::from_secs(0));
+    ///
+    /// assert_eq!(engine.eval::<i64>("random()")?, 0);
+    /// assert!(engine.eval::<String>("read_file()").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn register_nondeterministic_fn<A, F>(
+        &mut self,
+        name: &str,
+        func: F,
+        handling: Nondeterministic,
+    ) -> &mut Self
+    where
+        F: RegisterNativeFunction<A, ()>,
+    {
+        let hash = self.set_native_fn(name, func);
+        self.nondeterministic.insert(hash, handling);
+        self
+    }
+    /// Register a custom fallible function with the [`Engine`] that is _nondeterministic_.
+    ///
+    /// The function is otherwise the same as one registered via
+    /// [`register_result_fn`][Engine::register_result_fn].
+    ///
+    /// See [`register_nondeterministic_fn`][Engine::register_nondeterministic_fn] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalAltResult, Nondeterministic, ReplayLog};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_nondeterministic_result_fn(
+    ///     "read_file",
+    ///     |path: &str| -> Result<String, Box<EvalAltResult>> {
+    ///         Err(format!("cannot read '{}'", path).into())
+    ///     },
+    ///     Nondeterministic::Reject,
+    /// );
+    ///
+    /// let log = ReplayLog::new();
+    /// engine.set_replay_log(log.clone());
+    ///
+    /// assert!(engine.eval::<String>(r#"read_file("hello.txt")"#).is_err());
+    /// assert_eq!(log.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn register_nondeterministic_result_fn<A, F, R>(
+        &mut self,
+        name: &str,
+        func: F,
+        handling: Nondeterministic,
+    ) -> &mut Self
+    where
+        F: RegisterNativeFunction<A, Result<R, Box<EvalAltResult>>>,
+    {
+        let hash = self.set_native_fn(name, func);
+        self.nondeterministic.insert(hash, handling);
+        self
+    }
     /// Register a function of the [`Engine`].
     ///
     /// # WARNING - Low Level API
@@ -254,6 +408,7 @@ impl Engine {
     where
         T: Variant + Clone + IntoIterator,
         <T as IntoIterator>::Item: Variant + Clone,
+        <T as IntoIterator>::IntoIter: SendSync,
     {
         self.global_namespace.set_iterable::<T>();
         self
@@ -820,6 +975,214 @@ impl Engine {
         self.register_indexer_get(get_fn)
             .register_indexer_set(set_fn)
     }
+    /// Register a custom type, together with its API, with the [`Engine`].
+    ///
+    /// The type must implement [`CustomType`][crate::CustomType] (normally via
+    /// `#[derive(CustomType)]`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{CustomType, Engine, INT};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, CustomType)]
+    /// #[rhai(eq)]
+    /// struct Point {
+    ///     x: INT,
+    ///     #[rhai(readonly)]
+    ///     y: INT,
+    ///     #[rhai(skip)]
+    ///     tag: String,
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine
+    ///     .build_type::<Point>()
+    ///     .register_fn("new_point", |x: INT, y: INT| Point { x, y, tag: "".into() });
+    ///
+    /// assert_eq!(engine.eval::<INT>("let p = new_point(1, 2); p.x = 40; p.x + p.y")?, 42);
+    /// assert!(engine.eval::<INT>("let p = new_point(1, 2); p.y = 40; p.y").is_err());
+    /// assert!(engine.eval::<bool>("new_point(1, 2) == new_point(1, 2)")?);
+    /// assert_eq!(engine.eval::<String>("type_of(new_point(1, 2))")?, "Point");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn build_type<T: crate::CustomType>(&mut self) -> &mut Self {
+        T::build(crate::TypeBuilder::new(self));
+        self
+    }
+    /// Register a Rust `enum` type with the [`Engine`].
+    ///
+    /// The type must implement [`CustomEnum`][crate::CustomEnum] (normally via
+    /// `#[derive(CustomEnum)]`) and [`PartialEq`].
+    ///
+    /// The following are registered:
+    ///
+    /// * the type name (i.e. the name of the `enum`) for `type_of`,
+    ///
+    /// * a static module, with the same name as the type, containing a constructor function for
+    ///   each variant (plus a constant for each unit variant); under `no_module` the constructors
+    ///   are registered into the global namespace instead,
+    ///
+    /// * `==` and `!=` operators,
+    ///
+    /// * a `variant` property returning the name of the variant, plus a property for each named
+    ///   field (which fails with [`EvalAltResult::ErrorDotExpr`] if the variant does not have such
+    ///   a field),
+    ///
+    /// * indexers returning fields by position (for tuple variants) or by name.
+    ///
+    /// A `switch` statement on a value of a registered `enum` type matches the variant name
+    /// against string cases.
+    ///
+    /// With the `serde` feature, [`Engine::to_dynamic`] turns values of a registered `enum` type
+    /// into [`Dynamic`] values of that type (not object maps), and [`Engine::from_dynamic`]
+    /// turns them back.  The free functions `to_dynamic` and `from_dynamic` are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{CustomEnum, Engine, INT};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, CustomEnum)]
+    /// enum Shape {
+    ///     Empty,
+    ///     Square(INT),
+    ///     Rect { w: INT, h: INT },
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_enum::<Shape>();
+    ///
+    /// assert_eq!(
+    ///     engine.eval::<INT>(
+    ///         r#"
+    ///             let shape = Shape::Rect(6, 7);
+    ///
+    ///             switch shape {
+    ///                 "Empty" => 0,
+    ///                 "Square" => shape[0] * shape[0],
+    ///                 "Rect" => shape.w * shape.h
+    ///             }
+    ///         "#
+    ///     )?,
+    ///     42
+    /// );
+    ///
+    /// assert!(engine.eval::<bool>("Shape::Square(1) != Shape::Empty")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_enum<T: crate::CustomEnum + PartialEq>(&mut self) -> &mut Self {
+        let name = T::enum_name();
+
+        // Add the pretty-print type name into the map
+        self.type_names.insert(type_name::<T>().into(), name.into());
+
+        self.enum_variants.insert(TypeId::of::<T>(), |value| {
+            value.read_lock::<T>().map(|value| value.variant_name())
+        });
+
+        #[cfg(feature = "serde")]
+        self.custom_enums.register::<T>();
+
+        let mut module = Module::new();
+        T::register_variants(&mut module);
+
+        #[cfg(not(feature = "no_module"))]
+        self.register_static_module(name, module.into());
+        #[cfg(feature = "no_module")]
+        self.register_global_module(module.into());
+
+        self.register_fn(crate::engine::OP_EQUALS, |x: &mut T, y: T| *x == y)
+            .register_fn("!=", |x: &mut T, y: T| *x != y);
+
+        #[cfg(not(feature = "no_object"))]
+        {
+            self.register_get("variant", |x: &mut T| {
+                crate::ImmutableString::from(x.variant_name())
+            });
+
+            for &field in T::all_field_names() {
+                self.register_get_result(field, move |x: &mut T| {
+                    x.field_by_name(field)
+                        .ok_or_else(|| crate::custom_enum::make_field_err(x, field))
+                });
+            }
+        }
+
+        #[cfg(not(feature = "no_index"))]
+        {
+            self.register_indexer_get_result(|x: &mut T, index: crate::INT| {
+                match x.field(index as usize) {
+                    Some(value) if index >= 0 => Ok(value),
+                    _ => EvalAltResult::ErrorArrayBounds(x.field_count(), index, Position::NONE)
+                        .into(),
+                }
+            });
+            self.register_indexer_get_result(|x: &mut T, name: crate::ImmutableString| {
+                x.field_by_name(&name)
+                    .ok_or_else(|| crate::custom_enum::make_field_err(x, &name))
+            });
+        }
+
+        self
+    }
+    /// _(SERDE)_ Serialize a Rust type that implements [`serde::Serialize`] into a [`Dynamic`].
+    /// Exported under the `serde` feature only.
+    ///
+    /// Unlike [`to_dynamic`][crate::serde::to_dynamic], values of `enum` types registered via
+    /// [`Engine::register_enum`] are turned into [`Dynamic`] values of those types, not object
+    /// maps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{CustomEnum, Engine, INT};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CustomEnum)]
+    /// enum Shape {
+    ///     Empty,
+    ///     Square(INT),
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_enum::<Shape>();
+    ///
+    /// let value = engine.to_dynamic(Shape::Square(42))?;
+    ///
+    /// assert!(value.is::<Shape>());
+    /// assert_eq!(engine.from_dynamic::<Shape>(&value)?, Shape::Square(42));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    #[inline(always)]
+    pub fn to_dynamic<T: serde::Serialize>(&self, value: T) -> RhaiResult {
+        crate::serde::to_dynamic_with(value, Some(&self.custom_enums))
+    }
+    /// _(SERDE)_ Deserialize a [`Dynamic`] value into a Rust type that implements
+    /// [`serde::Deserialize`].
+    /// Exported under the `serde` feature only.
+    ///
+    /// Unlike [`from_dynamic`][crate::serde::from_dynamic], values of `enum` types registered via
+    /// [`Engine::register_enum`] are deserialized by variant.
+    #[cfg(feature = "serde")]
+    #[inline(always)]
+    pub fn from_dynamic<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+        value: &'de Dynamic,
+    ) -> Result<T, Box<EvalAltResult>> {
+        crate::serde::from_dynamic_with(value, Some(&self.custom_enums))
+    }
     /// Register a shared [`Module`] into the global namespace of [`Engine`].
     ///
     /// All functions and type iterators are automatically available to scripts without namespace
@@ -915,6 +1278,12 @@ impl Engine {
             }
         }
 
+        // Custom types in the module are named within its namespace
+        let type_names = &mut self.type_names;
+        module.walk_custom_types(name.trim(), &mut |type_name, name| {
+            type_names.insert(type_name.into(), name);
+        });
+
         register_static_module_raw(&mut self.global_sub_modules, name.as_ref(), module);
         self
     }
@@ -1002,6 +1371,10 @@ impl Engine {
     /// via the current [module resolver][crate::ModuleResolver] and embedded into the resultant
     /// [`AST`]. When it is evaluated later, `import` statement directly recall pre-resolved
     /// [modules][Module] and the resolution process is not performed again.
+    ///
+    /// Names selected by `import { ... } from "path"` statements in the script are also checked
+    /// against the resolved [modules][Module], failing with [`ParseErrorType::NotExported`][crate::ParseErrorType::NotExported]
+    /// if any of them is not exported.
     #[cfg(not(feature = "no_module"))]
     pub fn compile_into_self_contained(
         &self,
@@ -1009,60 +1382,156 @@ impl Engine {
         script: &str,
     ) -> Result<AST, Box<EvalAltResult>> {
         use crate::{
-            ast::{ASTNode, Expr, Stmt},
+            ast::{ASTNode, Expr, ImportNames, Stmt},
+            engine::import_key,
             fn_native::shared_take_or_clone,
             module::resolvers::StaticModuleResolver,
-            stdlib::collections::BTreeSet,
-            ImmutableString,
+            stdlib::collections::{BTreeMap, BTreeSet},
+            ImmutableString, ParseErrorType,
         };
 
+        /// An `import` statement: key of the importing module, key of the imported module,
+        /// path and position.
+        type ImportEdge = (String, String, ImmutableString, Position);
+
+        /// Imported modules, by key: path and source of the importing script.
+        type Imported = BTreeMap<String, (ImmutableString, Option<ImmutableString>)>;
+
         fn collect_imports(
             ast: &AST,
-            resolver: &StaticModuleResolver,
-            imports: &mut BTreeSet<ImmutableString>,
+            from: &str,
+            imports: &mut Imported,
+            edges: &mut Vec<ImportEdge>,
+            pending: &mut Vec<String>,
         ) {
-            ast.walk(&mut |path| match path.last().unwrap() {
-                // Collect all `import` statements with a string constant path
-                ASTNode::Stmt(Stmt::Import(Expr::StringConstant(s, _), _, _))
-                    if !resolver.contains_path(s) && !imports.contains(s) =>
+            let source = ast.source();
+
+            ast.walk(&mut |path| {
+                // Collect all `import` statements with a string constant path,
+                // keyed by the module they resolve to
+                if let ASTNode::Stmt(Stmt::Import(Expr::StringConstant(s, pos), _, _)) =
+                    path.last().unwrap()
                 {
-                    imports.insert(s.clone());
-                    true
+                    let key = import_key(source, s);
+                    edges.push((from.into(), key.clone(), s.clone(), *pos));
+
+                    if !imports.contains_key(&key) {
+                        imports.insert(key.clone(), (s.clone(), ast.clone_source()));
+                        pending.push(key);
+                    }
                 }
-                _ => true,
+                true
             });
         }
 
-        let mut resolver = StaticModuleResolver::new();
+        fn find_cycle(
+            edges: &[ImportEdge],
+            from: &str,
+            chain: &mut Vec<(String, String, Position)>,
+            visited: &mut BTreeSet<String>,
+        ) -> Option<Vec<(String, Position)>> {
+            for (_, to, path, pos) in edges.iter().filter(|(f, _, _, _)| f == from) {
+                if let Some(index) = chain.iter().position(|(key, _, _)| key == to) {
+                    let mut cycle: Vec<_> = chain[index..]
+                        .iter()
+                        .map(|(_, path, pos)| (path.clone(), *pos))
+                        .collect();
+                    cycle.push((path.to_string(), *pos));
+                    return Some(cycle);
+                }
+
+                // Modules already fully searched cannot lead to a cycle
+                if visited.insert(to.clone()) {
+                    chain.push((to.clone(), path.to_string(), *pos));
+
+                    if let Some(cycle) = find_cycle(edges, to, chain, visited) {
+                        return Some(cycle);
+                    }
+
+                    chain.pop();
+                }
+            }
+            None
+        }
+
         let mut ast = self.compile_scripts_with_scope(scope, &[script])?;
-        let mut imports = Default::default();
+        let mut imports = BTreeMap::new();
+        let mut edges = Vec::new();
+        let mut pending = Vec::new();
 
-        collect_imports(&ast, &mut resolver, &mut imports);
+        // The script itself is keyed by an empty string, which cannot be a module path
+        let root = "";
+        collect_imports(&ast, root, &mut imports, &mut edges, &mut pending);
 
         if !imports.is_empty() {
-            while let Some(path) = imports.iter().next() {
-                let path = path.clone();
+            // Follow the imports of all modules that are compiled from scripts
+            while let Some(key) = pending.pop() {
+                let (path, source) = imports[&key].clone();
+                let source = source.as_ref().map(|s| s.as_str());
 
                 match self
                     .module_resolver
-                    .resolve_ast(self, &path, Position::NONE)
+                    .resolve_ast(self, source, &path, Position::NONE)
                 {
                     Some(Ok(module_ast)) => {
-                        collect_imports(&module_ast, &mut resolver, &mut imports)
+                        collect_imports(&module_ast, &key, &mut imports, &mut edges, &mut pending)
                     }
-                    Some(err @ Err(_)) => return err,
+                    Some(Err(err)) => return Err(err),
                     None => (),
                 }
+            }
+
+            // Detect cyclic imports before evaluating any module
+            if let Some(cycle) = find_cycle(&edges, root, &mut Vec::new(), &mut BTreeSet::new()) {
+                let pos = cycle.last().unwrap().1;
+                return EvalAltResult::ErrorCyclicImport(cycle, pos).into();
+            }
+
+            // Only the modules imported by the script itself are looked up in the resolver;
+            // modules imported by other modules are already part of them
+            let mut resolver = StaticModuleResolver::new();
+            let source = ast.source();
+
+            // Names selected by `import { ... } from "path"` statements in the script itself
+            let mut selected = Vec::new();
+
+            ast.walk(&mut |path| {
+                if let ASTNode::Stmt(Stmt::Import(Expr::StringConstant(s, _), Some(names), _)) =
+                    path.last().unwrap()
+                {
+                    if let ImportNames::Selected(list) = names.as_ref() {
+                        selected.extend(list.iter().map(|(x, _)| (s.clone(), x.clone())));
+                    }
+                }
+                true
+            });
+
+            for (_, _, path, _) in edges.iter().filter(|(from, _, _, _)| from == root) {
+                if resolver.contains_path(path) {
+                    continue;
+                }
 
                 let module = shared_take_or_clone(self.module_resolver.resolve(
                     self,
-                    &path,
+                    source,
+                    path,
                     Position::NONE,
                 )?);
 
-                imports.remove(&path);
-                resolver.insert(path, module);
+                if let Some((_, x)) = selected
+                    .iter()
+                    .find(|(p, x)| p == path && !module.exports_name(&x.name))
+                {
+                    return EvalAltResult::ErrorParsing(
+                        ParseErrorType::NotExported(x.name.to_string(), path.to_string()),
+                        x.pos,
+                    )
+                    .into();
+                }
+
+                resolver.insert(path.clone(), module);
             }
+
             ast.set_resolver(resolver);
         }
 
@@ -1624,6 +2093,59 @@ impl Engine {
             .into()
         });
     }
+    /// Evaluate an [`AST`] with own scope and stack of imported [modules][Module].
+    ///
+    /// Modules imported by `import` statements at global level are kept in `mods`
+    /// after evaluation, so that they are available to later evaluations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_module"))]
+    /// # {
+    /// use rhai::{Engine, Imports, Module, Scope, INT};
+    /// use rhai::module_resolvers::StaticModuleResolver;
+    ///
+    /// let mut module = Module::new();
+    /// module.set_var("ANSWER", 42 as INT);
+    ///
+    /// let mut resolver = StaticModuleResolver::new();
+    /// resolver.insert("question", module);
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_module_resolver(resolver);
+    ///
+    /// let mut scope = Scope::new();
+    /// let mut mods = Imports::default();
+    ///
+    /// engine.eval_ast_with_imports::<()>(&mut scope, &mut mods, &engine.compile(r#"import "question" as q;"#)?)?;
+    ///
+    /// assert_eq!(engine.eval_ast_with_imports::<INT>(&mut scope, &mut mods, &engine.compile("q::ANSWER")?)?, 42);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn eval_ast_with_imports<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        ast: &AST,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let result = self.eval_ast_with_scope_raw(scope, mods, ast, 0)?;
+
+        let typ = self.map_type_name(result.type_name());
+
+        return result.try_cast::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                self.map_type_name(type_name::<T>()).into(),
+                typ.into(),
+                Position::NONE,
+            )
+            .into()
+        });
+    }
     /// Evaluate an [`AST`] with own scope.
     #[inline(always)]
     pub(crate) fn eval_ast_with_scope_raw<'a>(
@@ -1633,16 +2155,197 @@ impl Engine {
         ast: &'a AST,
         level: usize,
     ) -> RhaiResult {
+        let mut state = Self::new_eval_state(ast);
+        let statements = ast.statements();
+        let lib = &[ast.lib()];
+        self.eval_global_statements(scope, mods, &mut state, statements, lib, level)
+    }
+    /// Create a new [`State`] for evaluating an [`AST`].
+    #[inline(always)]
+    fn new_eval_state(ast: &AST) -> State {
         let mut state: State = Default::default();
         state.source = ast.clone_source();
+        state.permissions = ast.clone_permissions();
         #[cfg(not(feature = "no_module"))]
         {
             state.resolver = ast.resolver();
         }
+        state
+    }
+    /// Evaluate an [`AST`] asynchronously, awaiting calls to `async` functions registered via
+    /// [`Engine::register_async_fn`].
+    ///
+    /// The returned [`Future`][std::future::Future] does not depend on any particular executor.
+    ///
+    /// See [`Engine::eval_ast_with_scope_async`] for details.
+    #[inline(always)]
+    pub async fn eval_ast_async<T: Variant + Clone>(
+        &self,
+        ast: &AST,
+    ) -> Result<T, Box<EvalAltResult>> {
+        self.eval_ast_with_scope_async(&mut Default::default(), ast)
+            .await
+    }
+    /// Evaluate an [`AST`] asynchronously with own scope, awaiting calls to `async` functions
+    /// registered via [`Engine::register_async_fn`].
+    ///
+    /// The returned [`Future`][std::future::Future] does not depend on any particular executor.
+    ///
+    /// The evaluation is [suspended][Continuation] at each call to an `async` function, and
+    /// resumed with its result when awaited, so the same limitations apply.  In particular,
+    /// `async` functions cannot be called from within property getters, setters and indexers,
+    /// custom syntax, method calls to script-defined functions, functions defined in modules,
+    /// `eval`, generators and callbacks from native Rust functions.
+    ///
+    /// Suspensions requested by native Rust functions via [`Continuation::suspend`] are
+    /// returned as [`EvalAltResult::ErrorSuspended`] errors as usual.
+    pub async fn eval_ast_with_scope_async<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope<'_>,
+        ast: &AST,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mods = &mut Default::default();
+        let lib = &[ast.lib()];
 
-        let statements = ast.statements();
+        let mut state = Self::new_eval_state(ast);
+
+        let mut result =
+            self.eval_global_statements(scope, mods, &mut state, ast.statements(), lib, 0);
+
+        let result = loop {
+            let err = match result {
+                Ok(value) => break value,
+                Err(err) => err,
+            };
+
+            match *err {
+                EvalAltResult::ErrorSuspended(mut cont, pos) => match cont.async_call.take() {
+                    Some(call) => {
+                        let value = call.start().await.map_err(|err| err.fill_position(pos));
+                        result = self.resume_with_scope_raw(scope, mods, ast, *cont, value);
+                    }
+                    None => return Err(EvalAltResult::ErrorSuspended(cont, pos).into()),
+                },
+                err => return Err(err.into()),
+            }
+        };
+
+        let typ = self.map_type_name(result.type_name());
+
+        result.try_cast::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                self.map_type_name(type_name::<T>()).into(),
+                typ.into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Resume the evaluation of an [`AST`] suspended by a native Rust function.
+    ///
+    /// The [`AST`] must be the same one that was evaluated.
+    /// `value` is returned by the suspended function call.
+    ///
+    /// The [`Scope`] is cleared and variables at global level are restored into it.
+    ///
+    /// See [`Continuation`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Continuation, Engine, EvalAltResult, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_result_fn("approve", |_: &str| Continuation::suspend::<bool>(().into()));
+    ///
+    /// let ast = engine.compile(r#"
+    ///                 fn request(amount) {
+    ///                     if approve("refund") { amount } else { 0 }
+    ///                 }
+    ///                 let total = 0;
+    ///                 for amount in [10, 20, 30] {
+    ///                     total += request(amount);
+    ///                 }
+    ///                 total
+    ///             "#)?;
+    ///
+    /// let mut scope = Scope::new();
+    /// let mut result = engine.eval_ast_with_scope::<i64>(&mut scope, &ast);
+    /// let mut approved = false;
+    ///
+    /// // Approve every other request
+    /// while let Err(err) = result {
+    ///     match *err {
+    ///         EvalAltResult::ErrorSuspended(cont, _) => {
+    ///             approved = !approved;
+    ///             result = engine.resume_with_scope(&mut scope, &ast, *cont, approved.into());
+    ///         }
+    ///         err => return Err(err.into()),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(result?, 40);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn resume_with_scope<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        continuation: Continuation,
+        value: Dynamic,
+    ) -> Result<T, Box<EvalAltResult>> {
+        let mods = &mut Default::default();
+
+        let result = self.resume_with_scope_raw(scope, mods, ast, continuation, Ok(value))?;
+
+        let typ = self.map_type_name(result.type_name());
+
+        result.try_cast::<T>().ok_or_else(|| {
+            EvalAltResult::ErrorMismatchOutputType(
+                self.map_type_name(type_name::<T>()).into(),
+                typ.into(),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+    /// Resume the evaluation of an [`AST`] suspended by a native Rust function, with the result
+    /// of the suspended call.
+    pub(crate) fn resume_with_scope_raw(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        ast: &AST,
+        continuation: Continuation,
+        result: RhaiResult,
+    ) -> RhaiResult {
         let lib = &[ast.lib()];
-        self.eval_global_statements(scope, mods, &mut state, statements, lib, level)
+        let mut frames = continuation.frames;
+
+        // Resume the outer-most frame, which resumes the script-defined function calls
+        // leading to the suspended call as they are reached
+        let mut frame = match frames.pop() {
+            Some(frame) => frame,
+            None => return result,
+        };
+
+        let mut state = Self::new_eval_state(ast);
+
+        // The suspended call was made directly, such as via `call_fn`
+        if frame.path.is_empty() {
+            state.resume = Some(Resumption::with_frames(&mut frame, frames, result).into());
+            let call = state.take_resumed_call(frame.call_pos).unwrap();
+            return self.return_resumed_call(mods, &mut state, lib, call, frame.call_pos, 0);
+        }
+
+        *scope = frame.take_scope();
+        state.resume = Some(Resumption::with_frames(&mut frame, frames, result).into());
+
+        self.eval_global_statements(scope, mods, &mut state, ast.statements(), lib, 0)
     }
     /// Evaluate a file, but throw away the result and only return error (if any).
     /// Useful for when you don't need the result, but still need to keep track of possible errors.
@@ -1707,6 +2410,7 @@ impl Engine {
         let mods = &mut Default::default();
         let mut state: State = Default::default();
         state.source = ast.clone_source();
+        state.permissions = ast.clone_permissions();
         #[cfg(not(feature = "no_module"))]
         {
             state.resolver = ast.resolver();
@@ -1871,7 +2575,8 @@ impl Engine {
         this_ptr: &mut Option<&mut Dynamic>,
         args: &mut FnCallArgs,
     ) -> RhaiResult {
-        let state = &mut Default::default();
+        let state = &mut State::default();
+        state.permissions = ast.clone_permissions();
         let mods = &mut Default::default();
         let lib = &[ast.lib()];
 
@@ -1900,6 +2605,76 @@ impl Engine {
             0,
         )
     }
+    /// Iterate the values produced by a [`Generator`][crate::Generator] returned by a
+    /// generator function defined in an [`AST`].
+    ///
+    /// The iteration ends after the first error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, Generator, Scope, INT};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile(r"
+    ///     fn fib() {
+    ///         let a = 0;
+    ///         let b = 1;
+    ///         loop {
+    ///             yield a;
+    ///             let c = a + b;
+    ///             a = b;
+    ///             b = c;
+    ///         }
+    ///     }
+    /// ")?;
+    ///
+    /// let generator: Generator = engine.call_fn(&mut Scope::new(), &ast, "fib", ())?;
+    ///
+    /// let values = engine
+    ///     .iter_generator(&ast, generator)
+    ///     .take(8)
+    ///     .map(|v| v.map(|v| v.as_int().unwrap()))
+    ///     .collect::<Result<Vec<INT>, _>>()?;
+    ///
+    /// assert_eq!(values, [0, 1, 1, 2, 3, 5, 8, 13]);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    pub fn iter_generator<'a>(
+        &'a self,
+        ast: &'a AST,
+        mut generator: crate::Generator,
+    ) -> impl Iterator<Item = RhaiResult> + 'a {
+        let mut mods: Imports = Default::default();
+        let mut state: State = Default::default();
+        state.source = ast.clone_source();
+        state.permissions = ast.clone_permissions();
+        #[cfg(not(feature = "no_module"))]
+        {
+            state.resolver = ast.resolver();
+        }
+        let lib = [ast.lib()];
+
+        // A generator is done after an error, so it simply ends the iteration
+        crate::stdlib::iter::from_fn(move || {
+            self.resume_generator(
+                &mut mods,
+                &mut state,
+                &lib,
+                &mut generator,
+                Position::NONE,
+                0,
+            )
+            .transpose()
+        })
+    }
     /// Optimize the [`AST`] with constants defined in an external Scope.
     /// An optimized copy of the [`AST`] is returned while the original [`AST`] is consumed.
     ///
@@ -2044,6 +2819,131 @@ impl Engine {
         self.progress = Some(Box::new(callback));
         self
     }
+    /// Get a [`CancellationToken`] that terminates script evaluations running on this [`Engine`].
+    ///
+    /// A new token is created if none has been set via
+    /// [`set_cancellation_token`][Engine::set_cancellation_token].
+    ///
+    /// The token can be sent to another thread even without the `sync` feature.
+    /// After cancelling, call [`CancellationToken::reset`] before reusing the [`Engine`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_std"))]
+    /// # {
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use rhai::{Engine, EvalAltResult};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let token = engine.cancellation_token();
+    ///
+    /// // Cancel the script from another thread
+    /// let handle = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(10));
+    ///     token.cancel();
+    /// });
+    ///
+    /// assert!(matches!(
+    ///     *engine.consume("let x = 0; loop { x += 1; }").expect_err("should error"),
+    ///     EvalAltResult::ErrorTerminated(_, _)
+    /// ));
+    ///
+    /// handle.join().unwrap();
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn cancellation_token(&mut self) -> CancellationToken {
+        self.cancel_token
+            .get_or_insert_with(Default::default)
+            .clone()
+    }
+    /// Set the [`CancellationToken`] used to terminate script evaluations running on this [`Engine`].
+    ///
+    /// The same token can be shared by multiple [`Engine`]'s.
+    #[inline(always)]
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+    /// Put the [`Engine`] into _deterministic mode_, using a host-supplied virtual clock
+    /// as the only source of time.
+    ///
+    /// The clock returns the virtual time elapsed since the start of the run.
+    ///
+    /// In deterministic mode:
+    ///
+    /// * `timestamp()` and `elapsed` read the virtual clock instead of the system clock.
+    ///
+    /// * Calls to [nondeterministic functions][Engine::register_nondeterministic_fn] are
+    ///   rejected or stubbed out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_std"))]
+    /// # {
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use rhai::Engine;
+    ///
+    /// let ticks = Arc::new(AtomicU64::new(0));
+    /// let clock = ticks.clone();
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_deterministic(move || Duration::from_secs(clock.load(Ordering::SeqCst)));
+    ///
+    /// let mut scope = rhai::Scope::new();
+    /// engine.consume_with_scope(&mut scope, "let start = timestamp();")?;
+    ///
+    /// ticks.store(42, Ordering::SeqCst);
+    ///
+    /// assert_eq!(engine.eval_with_scope::<f64>(&mut scope, "start.elapsed")?, 42.0);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_deterministic(
+        &mut self,
+        clock: impl Fn() -> crate::stdlib::time::Duration + SendSync + 'static,
+    ) -> &mut Self {
+        self.clock = Some(VirtualClock::new(clock));
+        self
+    }
+    /// Take the [`Engine`] out of [deterministic mode][Engine::set_deterministic].
+    #[inline(always)]
+    pub fn clear_deterministic(&mut self) -> &mut Self {
+        self.clock = None;
+        self
+    }
+    /// Is the [`Engine`] in [deterministic mode][Engine::set_deterministic]?
+    #[inline(always)]
+    pub fn is_deterministic(&self) -> bool {
+        self.clock.is_some()
+    }
+    /// Set the [`ReplayLog`] that records, or replays, the results of calls to
+    /// [nondeterministic functions][Engine::register_nondeterministic_fn] and reads of the clock.
+    ///
+    /// See [`ReplayLog`] for details.
+    #[inline(always)]
+    pub fn set_replay_log(&mut self, log: ReplayLog) -> &mut Self {
+        self.replay_log = Some(log);
+        self
+    }
+    /// Remove the [`ReplayLog`] of the [`Engine`], if any.
+    #[inline(always)]
+    pub fn clear_replay_log(&mut self) -> &mut Self {
+        self.replay_log = None;
+        self
+    }
     /// Override default action of `print` (print to stdout using [`println!`])
     ///
     /// # Example
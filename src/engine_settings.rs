@@ -168,6 +168,25 @@ impl Engine {
     pub fn max_array_size(&self) -> usize {
         self.limits.max_array_size.map_or(0, NonZeroUsize::get)
     }
+    /// Set the maximum number of members in [sets][crate::Set] (0 for unlimited).
+    ///
+    /// Not available under `unchecked` or `no_index`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn set_max_set_size(&mut self, max_size: usize) -> &mut Self {
+        self.limits.max_set_size = NonZeroUsize::new(max_size);
+        self
+    }
+    /// The maximum number of members in [sets][crate::Set] (0 for unlimited).
+    ///
+    /// Not available under `unchecked` or `no_index`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn max_set_size(&self) -> usize {
+        self.limits.max_set_size.map_or(0, NonZeroUsize::get)
+    }
     /// Set the maximum size of [object maps][crate::Map] (0 for unlimited).
     ///
     /// Not available under `unchecked` or `no_object`.
@@ -187,6 +206,25 @@ impl Engine {
     pub fn max_map_size(&self) -> usize {
         self.limits.max_map_size.map_or(0, NonZeroUsize::get)
     }
+    /// Set the maximum number of bits in [`BigInt`][num_bigint::BigInt] values (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.  Exported under the `bigint` feature only.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(feature = "bigint")]
+    #[inline(always)]
+    pub fn set_max_bigint_bits(&mut self, max_bits: usize) -> &mut Self {
+        self.limits.max_bigint_bits = NonZeroUsize::new(max_bits);
+        self
+    }
+    /// The maximum number of bits in [`BigInt`][num_bigint::BigInt] values (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.  Exported under the `bigint` feature only.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(feature = "bigint")]
+    #[inline(always)]
+    pub fn max_bigint_bits(&self) -> usize {
+        self.limits.max_bigint_bits.map_or(0, NonZeroUsize::get)
+    }
     /// Set the module resolution service used by the [`Engine`].
     ///
     /// Not available under `no_module`.
@@ -199,6 +237,37 @@ impl Engine {
         self.module_resolver = Box::new(resolver);
         self
     }
+    /// Control whether `import` statements resolve [modules][crate::Module] lazily.
+    ///
+    /// When enabled, the [module][crate::Module] of an `import "path" as name;` statement is not
+    /// resolved until one of its members is first accessed via `name::`, and only counts against
+    /// the maximum number of imported modules once resolved.  Errors in resolving the
+    /// [module][crate::Module] are therefore also raised on first access.
+    ///
+    /// Functions exposed to the global namespace (including property getters/setters and
+    /// indexers) and type iterators are also looked up in the [modules][crate::Module] of lazy
+    /// imports, so when such a lookup is not satisfied elsewhere, all the [modules][crate::Module]
+    /// not yet resolved are resolved first.
+    ///
+    /// `import "path";` and `import { ... } from "path";` always resolve the
+    /// [module][crate::Module] immediately, as do `import` statements in a script evaluated as a
+    /// [module][crate::Module] (because the imported modules become its sub-modules).
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn set_lazy_import(&mut self, enable: bool) -> &mut Self {
+        self.lazy_import = enable;
+        self
+    }
+    /// Do `import` statements resolve [modules][crate::Module] lazily?
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn lazy_import(&self) -> bool {
+        self.lazy_import
+    }
     /// Disable a particular keyword or operator in the language.
     ///
     /// # Examples
@@ -11,6 +11,9 @@ use crate::FLOAT;
 #[cfg(feature = "decimal")]
 use rust_decimal::Decimal;
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+
 #[cfg(feature = "no_std")]
 #[cfg(not(feature = "no_float"))]
 use num_traits::float::Float;
@@ -36,6 +39,9 @@ fn is_numeric(type_id: TypeId) -> bool {
     #[cfg(feature = "decimal")]
     let result = result || type_id == TypeId::of::<rust_decimal::Decimal>();
 
+    #[cfg(feature = "bigint")]
+    let result = result || type_id == TypeId::of::<num_bigint::BigInt>();
+
     result
 }
 
@@ -48,6 +54,15 @@ pub fn get_builtin_binary_op_fn(
     let type1 = x.type_id();
     let type2 = y.type_id();
 
+    // set op any - any value can be tested for membership
+    #[cfg(not(feature = "no_index"))]
+    if type1 == TypeId::of::<crate::Set>() && op == OP_CONTAINS {
+        return Some(|_, args| {
+            let set = &*args[0].read_lock::<crate::Set>().unwrap();
+            Ok(set.contains(args[1]).into())
+        });
+    }
+
     // One of the operands is a custom type, so it is never built-in
     if x.is_variant() || y.is_variant() {
         if is_numeric(type1) && is_numeric(type2) {
@@ -205,6 +220,34 @@ pub fn get_builtin_binary_op_fn(
     impl_decimal!(Decimal, as_decimal, INT, as_int);
     impl_decimal!(INT, as_int, Decimal, as_decimal);
 
+    macro_rules! impl_bigint {
+        ($x:ty, $xx:ident, $y:ty, $yy:ident) => {
+            #[cfg(feature = "bigint")]
+            if types_pair == (TypeId::of::<$x>(), TypeId::of::<$y>()) {
+                use crate::packages::arithmetic::bigint_functions::*;
+
+                match op {
+                    "+" => impl_op!(from BigInt => add($xx, $yy)),
+                    "-" => impl_op!(from BigInt => subtract($xx, $yy)),
+                    "*" => impl_op!(from BigInt => multiply($xx, $yy)),
+                    "/" => impl_op!(from BigInt => divide($xx, $yy)),
+                    "%" => impl_op!(from BigInt => modulo($xx, $yy)),
+                    "==" => impl_op!(from BigInt => $xx == $yy),
+                    "!=" => impl_op!(from BigInt => $xx != $yy),
+                    ">" => impl_op!(from BigInt => $xx > $yy),
+                    ">=" => impl_op!(from BigInt => $xx >= $yy),
+                    "<" => impl_op!(from BigInt => $xx < $yy),
+                    "<=" => impl_op!(from BigInt => $xx <= $yy),
+                    _ => return None
+                }
+            }
+        };
+    }
+
+    impl_bigint!(BigInt, as_bigint, BigInt, as_bigint);
+    impl_bigint!(BigInt, as_bigint, INT, as_int);
+    impl_bigint!(INT, as_int, BigInt, as_bigint);
+
     // char op string
     if types_pair == (TypeId::of::<char>(), TypeId::of::<ImmutableString>()) {
         #[inline(always)]
@@ -528,6 +571,27 @@ pub fn get_builtin_op_assignment_fn(
     impl_decimal!(Decimal, as_decimal, Decimal, as_decimal);
     impl_decimal!(Decimal, as_decimal, INT, as_int);
 
+    macro_rules! impl_bigint {
+        ($x:ident, $xx:ident, $y:ty, $yy:ident) => {
+            #[cfg(feature = "bigint")]
+            if types_pair == (TypeId::of::<$x>(), TypeId::of::<$y>()) {
+                use crate::packages::arithmetic::bigint_functions::*;
+
+                match op {
+                    "+=" => impl_op!(from $x => add($xx, $yy)),
+                    "-=" => impl_op!(from $x => subtract($xx, $yy)),
+                    "*=" => impl_op!(from $x => multiply($xx, $yy)),
+                    "/=" => impl_op!(from $x => divide($xx, $yy)),
+                    "%=" => impl_op!(from $x => modulo($xx, $yy)),
+                    _ => return None,
+                }
+            }
+        };
+    }
+
+    impl_bigint!(BigInt, as_bigint, BigInt, as_bigint);
+    impl_bigint!(BigInt, as_bigint, INT, as_int);
+
     // string op= char
     if types_pair == (TypeId::of::<ImmutableString>(), TypeId::of::<char>()) {
         match op {
@@ -1,13 +1,17 @@
 //! Implement function-calling mechanism for [`Engine`].
 
 use crate::ast::FnCallHash;
+use crate::continuation::{
+    check_native_suspension, make_unsupported_err, suspended_with, ResumedCall, Resumption,
+};
 use crate::engine::{
     FnResolutionCacheEntry, Imports, State, KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR,
     KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY, KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_TYPE_OF,
     MAX_DYNAMIC_PARAMETERS,
 };
 use crate::fn_builtin::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn};
-use crate::fn_native::{FnAny, FnCallArgs};
+use crate::fn_native::{FnAny, FnCallArgs, NativeCallContext, TypeIterator};
+use crate::generator::{Generator, GeneratorState, GeneratorStep};
 use crate::module::NamespaceRef;
 use crate::optimize::OptimizationLevel;
 use crate::stdlib::{
@@ -26,8 +30,8 @@ use crate::{
     RhaiResult,
 };
 use crate::{
-    calc_fn_hash, calc_fn_params_hash, combine_hashes, Dynamic, Engine, EvalAltResult, FnPtr,
-    ImmutableString, Module, ParseErrorType, Position, Scope, StaticVec,
+    calc_fn_hash, calc_fn_params_hash, combine_hashes, Capability, Dynamic, Engine, EvalAltResult,
+    FnPtr, ImmutableString, Module, ParseErrorType, Position, Scope, StaticVec,
 };
 
 #[cfg(not(feature = "no_object"))]
@@ -119,11 +123,28 @@ pub fn ensure_no_data_race(
     Ok(())
 }
 
+/// Is the function a property getter/setter or an indexer?
+#[inline(always)]
+fn is_property_or_indexer(_fn_name: &str) -> bool {
+    #[cfg(not(feature = "no_object"))]
+    if _fn_name.starts_with(crate::engine::FN_GET) || _fn_name.starts_with(crate::engine::FN_SET) {
+        return true;
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    if _fn_name == crate::engine::FN_IDX_GET || _fn_name == crate::engine::FN_IDX_SET {
+        return true;
+    }
+
+    false
+}
+
 impl Engine {
     /// Generate the signature for a function call.
     #[inline]
     fn gen_call_signature(
         &self,
+        mods: &Imports,
         namespace: Option<&NamespaceRef>,
         fn_name: &str,
         args: &[&mut Dynamic],
@@ -134,9 +155,9 @@ impl Engine {
             fn_name,
             args.iter()
                 .map(|a| if a.is::<ImmutableString>() {
-                    "&str | ImmutableString | String"
+                    "&str | ImmutableString | String".into()
                 } else {
-                    self.map_type_name((*a).type_name())
+                    self.map_value_type_name(mods, a)
                 })
                 .collect::<Vec<_>>()
                 .join(", ")
@@ -188,35 +209,59 @@ impl Engine {
                         .find_map(|m| {
                             m.get_fn(hash).cloned().map(|func| {
                                 let source = m.id_raw().cloned();
-                                FnResolutionCacheEntry { func, source }
+                                FnResolutionCacheEntry {
+                                    func,
+                                    source,
+                                    registered_hash: None,
+                                    restricted: !m.is_package(),
+                                }
                             })
                         })
                         .or_else(|| {
-                            self.global_namespace
-                                .get_fn(hash)
-                                .cloned()
-                                .map(|func| FnResolutionCacheEntry { func, source: None })
+                            self.global_namespace.get_fn(hash).cloned().map(|func| {
+                                FnResolutionCacheEntry {
+                                    func,
+                                    source: None,
+                                    registered_hash: Some(hash),
+                                    restricted: true,
+                                }
+                            })
                         })
                         .or_else(|| {
                             self.global_modules.iter().find_map(|m| {
                                 m.get_fn(hash).cloned().map(|func| {
                                     let source = m.id_raw().cloned();
-                                    FnResolutionCacheEntry { func, source }
+                                    FnResolutionCacheEntry {
+                                        func,
+                                        source,
+                                        registered_hash: None,
+                                        restricted: !m.is_package(),
+                                    }
                                 })
                             })
                         })
                         .or_else(|| {
-                            mods.get_fn(hash).map(|(func, source)| {
+                            mods.get_fn_with_module(hash).map(|(func, m)| {
                                 let func = func.clone();
-                                let source = source.cloned();
-                                FnResolutionCacheEntry { func, source }
+                                let source = m.id_raw().cloned();
+                                FnResolutionCacheEntry {
+                                    func,
+                                    source,
+                                    registered_hash: None,
+                                    restricted: !m.is_package(),
+                                }
                             })
                         })
                         .or_else(|| {
                             self.global_sub_modules.values().find_map(|m| {
                                 m.get_qualified_fn(hash).cloned().map(|func| {
                                     let source = m.id_raw().cloned();
-                                    FnResolutionCacheEntry { func, source }
+                                    FnResolutionCacheEntry {
+                                        func,
+                                        source,
+                                        registered_hash: None,
+                                        restricted: !m.is_package(),
+                                    }
                                 })
                             })
                         });
@@ -237,7 +282,12 @@ impl Engine {
                                         let func = CallableFunction::from_method(
                                             Box::new(f) as Box<FnAny>
                                         );
-                                        FnResolutionCacheEntry { func, source: None }
+                                        FnResolutionCacheEntry {
+                                            func,
+                                            source: None,
+                                            registered_hash: None,
+                                            restricted: false,
+                                        }
                                     })
                                 } else {
                                     let (first, second) = args.split_first().unwrap();
@@ -247,7 +297,12 @@ impl Engine {
                                             let func = CallableFunction::from_method(
                                                 Box::new(f) as Box<FnAny>
                                             );
-                                            FnResolutionCacheEntry { func, source: None }
+                                            FnResolutionCacheEntry {
+                                                func,
+                                                source: None,
+                                                registered_hash: None,
+                                                restricted: false,
+                                            }
                                         },
                                     )
                                 }
@@ -285,7 +340,7 @@ impl Engine {
     /// **DO NOT** reuse the argument values unless for the first `&mut` argument - all others are silently replaced by `()`!
     pub(crate) fn call_native_fn(
         &self,
-        mods: &Imports,
+        mods: &mut Imports,
         state: &mut State,
         lib: &[&Module],
         fn_name: &str,
@@ -298,6 +353,7 @@ impl Engine {
         self.inc_operations(state, pos)?;
 
         let state_source = state.source.clone();
+        let state_permissions = state.permissions.clone();
 
         // Check if function access already in the cache
         let func = self.resolve_function(
@@ -311,9 +367,23 @@ impl Engine {
             is_op_assignment,
         );
 
-        if let Some(FnResolutionCacheEntry { func, source }) = func {
+        if let Some(FnResolutionCacheEntry {
+            func,
+            source,
+            registered_hash,
+            restricted,
+        }) = func
+        {
             assert!(func.is_native());
 
+            // Check the function allowlist - functions in packages, property getters/setters
+            // and indexers are exempt
+            if let Some(ref permissions) = state_permissions {
+                if *restricted && !is_property_or_indexer(fn_name) {
+                    permissions.check(Capability::Function, fn_name, pos)?;
+                }
+            }
+
             // Calling pure function but the first argument is a reference?
             let mut backup: Option<ArgBackup> = None;
             if is_ref && func.is_pure() && !args.is_empty() {
@@ -326,11 +396,22 @@ impl Engine {
                 .as_ref()
                 .or_else(|| state_source.as_ref())
                 .map(|s| s.as_str());
-            let result = if func.is_plugin_fn() {
-                func.get_plugin_fn()
-                    .call((self, fn_name, source, mods, lib).into(), args)
-            } else {
-                func.get_native_fn()((self, fn_name, source, mods, lib).into(), args)
+            let context = NativeCallContext::from((self, fn_name, source, &*mods, lib))
+                .with_permissions(state_permissions.as_ref());
+            let call = || {
+                if func.is_plugin_fn() {
+                    func.get_plugin_fn().call(context, args)
+                } else {
+                    func.get_native_fn()(context, args)
+                }
+            };
+            let handling = registered_hash
+                .and_then(|hash| self.nondeterministic.get(&hash).map(|h| (hash, h)));
+            let result = match handling {
+                Some((hash, handling)) => {
+                    self.call_nondeterministic_fn(fn_name, hash, handling, pos, call)
+                }
+                None => call(),
             };
 
             // Restore the original reference
@@ -338,7 +419,7 @@ impl Engine {
                 backup.restore_first_arg(args);
             }
 
-            let result = result.map_err(|err| err.fill_position(pos))?;
+            let result = result.map_err(|err| check_native_suspension(err).fill_position(pos))?;
 
             // See if the function match print/debug (which requires special processing)
             return Ok(match fn_name {
@@ -367,6 +448,22 @@ impl Engine {
             });
         }
 
+        // The function may be in a lazily-imported module not yet resolved
+        #[cfg(not(feature = "no_module"))]
+        if self.resolve_pending_imports(mods, state)? {
+            return self.call_native_fn(
+                mods,
+                state,
+                lib,
+                fn_name,
+                hash_native,
+                args,
+                is_ref,
+                is_op_assignment,
+                pos,
+            );
+        }
+
         match fn_name {
             // index getter function not found?
             #[cfg(not(feature = "no_index"))]
@@ -376,8 +473,8 @@ impl Engine {
                 EvalAltResult::ErrorFunctionNotFound(
                     format!(
                         "{} [{}]",
-                        self.map_type_name(args[0].type_name()),
-                        self.map_type_name(args[1].type_name()),
+                        self.map_value_type_name(mods, args[0]),
+                        self.map_value_type_name(mods, args[1]),
                     ),
                     pos,
                 )
@@ -392,8 +489,8 @@ impl Engine {
                 EvalAltResult::ErrorFunctionNotFound(
                     format!(
                         "{} [{}]=",
-                        self.map_type_name(args[0].type_name()),
-                        self.map_type_name(args[1].type_name()),
+                        self.map_value_type_name(mods, args[0]),
+                        self.map_value_type_name(mods, args[1]),
                     ),
                     pos,
                 )
@@ -409,7 +506,7 @@ impl Engine {
                     format!(
                         "Unknown property '{}' - a getter is not registered for type '{}'",
                         &fn_name[crate::engine::FN_GET.len()..],
-                        self.map_type_name(args[0].type_name())
+                        self.map_value_type_name(mods, args[0])
                     ),
                     pos,
                 )
@@ -425,8 +522,8 @@ impl Engine {
                     format!(
                         "No writable property '{}' - a setter is not registered for type '{}' to handle '{}'",
                         &fn_name[crate::engine::FN_SET.len()..],
-                        self.map_type_name(args[0].type_name()),
-                        self.map_type_name(args[1].type_name()),
+                        self.map_value_type_name(mods, args[0]),
+                        self.map_value_type_name(mods, args[1]),
                     ),
                     pos,
                 )
@@ -435,7 +532,7 @@ impl Engine {
 
             // Raise error
             _ => EvalAltResult::ErrorFunctionNotFound(
-                self.gen_call_signature(None, fn_name, args.as_ref()),
+                self.gen_call_signature(mods, None, fn_name, args.as_ref()),
                 pos,
             )
             .into(),
@@ -457,35 +554,20 @@ impl Engine {
         state: &mut State,
         lib: &[&Module],
         this_ptr: &mut Option<&mut Dynamic>,
-        fn_def: &crate::ast::ScriptFnDef,
+        fn_def: &crate::Shared<crate::ast::ScriptFnDef>,
         args: &mut FnCallArgs,
         pos: Position,
         level: usize,
     ) -> RhaiResult {
-        #[inline(always)]
-        fn make_error(
-            name: crate::stdlib::string::String,
-            fn_def: &crate::ast::ScriptFnDef,
-            state: &State,
-            err: Box<EvalAltResult>,
-            pos: Position,
-        ) -> RhaiResult {
-            EvalAltResult::ErrorInFunctionCall(
-                name,
-                fn_def
-                    .lib
-                    .as_ref()
-                    .and_then(|m| m.id())
-                    .unwrap_or_else(|| state.source.as_ref().map_or_else(|| "", |s| s.as_str()))
-                    .to_string(),
-                err,
-                pos,
-            )
-            .into()
-        }
-
         self.inc_operations(state, pos)?;
 
+        // Calling a generator function only creates the generator
+        if fn_def.is_generator {
+            let args = args.iter_mut().map(|v| mem::take(*v)).collect();
+            let this = this_ptr.as_ref().map(|v| (*v).clone());
+            return Ok(Dynamic::from(Generator::new(fn_def.clone(), args, this)));
+        }
+
         if fn_def.body.is_empty() {
             return Ok(Dynamic::UNIT);
         }
@@ -497,11 +579,7 @@ impl Engine {
             return EvalAltResult::ErrorStackOverflow(pos).into();
         }
 
-        let orig_scope_level = state.scope_level;
-        state.scope_level += 1;
-
         let prev_scope_len = scope.len();
-        let prev_mods_len = mods.len();
 
         // Put arguments into scope as variables
         // Actually consume the arguments instead of cloning them
@@ -517,6 +595,127 @@ impl Engine {
                 }),
         );
 
+        self.eval_script_fn_body(
+            scope,
+            mods,
+            state,
+            lib,
+            this_ptr,
+            fn_def,
+            prev_scope_len,
+            pos,
+            level,
+        )
+    }
+
+    /// Resume a call to a script-defined function from its call frame (the last one in `frames`),
+    /// with the frames of the calls within it (inner-most first) and the result of the inner-most
+    /// suspended call.
+    #[cfg(not(feature = "no_function"))]
+    fn resume_script_fn(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        mut frames: Vec<crate::continuation::Frame>,
+        result: RhaiResult,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
+        let mut frame = frames.pop().expect("there should be a function call frame");
+        let (name, num_params) = frame
+            .function
+            .clone()
+            .expect("the frame should be of a script-defined function");
+        let fn_def = lib
+            .iter()
+            .find_map(|m| m.get_script_fn(&name, num_params))
+            .cloned()
+            .ok_or_else(|| EvalAltResult::ErrorFunctionNotFound(name, pos))?;
+
+        // Check for stack overflow
+        #[cfg(not(feature = "unchecked"))]
+        if level > self.max_call_levels() {
+            return EvalAltResult::ErrorStackOverflow(pos).into();
+        }
+
+        let scope = &mut frame.take_scope();
+        let resume = Resumption::with_frames(&mut frame, frames, result);
+        let caller_resume = state.resume.replace(resume.into());
+
+        let result =
+            self.eval_script_fn_body(scope, mods, state, lib, &mut None, &fn_def, 0, pos, level);
+
+        state.resume = caller_resume;
+
+        result
+    }
+
+    /// Return a suspended function call when resuming.
+    ///
+    /// If the suspension happened within a call to a script-defined function, the function is
+    /// resumed (with the caller's imports) and its result returned.
+    #[inline(always)]
+    pub(crate) fn return_resumed_call(
+        &self,
+        _mods: &mut Imports,
+        _state: &mut State,
+        _lib: &[&Module],
+        call: ResumedCall,
+        _pos: Position,
+        _level: usize,
+    ) -> RhaiResult {
+        match call {
+            ResumedCall::Value(result) => result,
+            #[cfg(not(feature = "no_function"))]
+            ResumedCall::Function(frames, result) => {
+                self.resume_script_fn(_mods, _state, _lib, frames, result, _pos, _level + 1)
+            }
+        }
+    }
+
+    /// Evaluate the body of a script-defined function, whose parameters are already in `scope`
+    /// above `prev_scope_len`.
+    #[cfg(not(feature = "no_function"))]
+    fn eval_script_fn_body(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        fn_def: &crate::Shared<crate::ast::ScriptFnDef>,
+        prev_scope_len: usize,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
+        #[inline(always)]
+        fn make_error(
+            name: crate::stdlib::string::String,
+            fn_def: &crate::ast::ScriptFnDef,
+            state: &State,
+            err: Box<EvalAltResult>,
+            pos: Position,
+        ) -> RhaiResult {
+            EvalAltResult::ErrorInFunctionCall(
+                name,
+                fn_def
+                    .lib
+                    .as_ref()
+                    .and_then(|m| m.id())
+                    .unwrap_or_else(|| state.source.as_ref().map_or_else(|| "", |s| s.as_str()))
+                    .to_string(),
+                err,
+                pos,
+            )
+            .into()
+        }
+
+        let orig_scope_level = state.scope_level;
+        state.scope_level += 1;
+
+        let prev_mods_len = mods.len();
+
         // Merge in encapsulated environment, if any
         let lib_merged;
 
@@ -540,12 +739,25 @@ impl Engine {
 
         // Evaluate the function
         let body = &fn_def.body.statements;
+        let is_method = this_ptr.is_some();
 
         let result = self
             .eval_stmt_block(scope, mods, state, unified_lib, this_ptr, body, true, level)
             .or_else(|err| match *err {
                 // Convert return statement to return value
                 EvalAltResult::Return(x, _) => Ok(x),
+                // Suspension cannot be resumed with `this` or an encapsulated environment
+                EvalAltResult::ErrorSuspended(_, _) if is_method => {
+                    Err(make_unsupported_err("within a method call").fill_position(pos))
+                }
+                EvalAltResult::ErrorSuspended(_, _) if fn_def.lib.is_some() => {
+                    Err(make_unsupported_err("within a module function").fill_position(pos))
+                }
+                // Record the function call frame of the suspension
+                EvalAltResult::ErrorSuspended(mut cont, _) => {
+                    cont.enter_function(&fn_def.name, fn_def.params.len(), prev_scope_len, pos);
+                    EvalAltResult::ErrorSuspended(cont, pos).into()
+                }
                 // Error in sub function call
                 EvalAltResult::ErrorInFunctionCall(name, src, err, _) => {
                     let fn_name = if src.is_empty() {
@@ -577,6 +789,155 @@ impl Engine {
         result
     }
 
+    /// Run the body of a [`Generator`] up to the next `yield` statement.
+    ///
+    /// Returns the yielded value, or `None` if the [`Generator`] has run to completion.
+    pub(crate) fn resume_generator(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        generator: &mut Generator,
+        pos: Position,
+        level: usize,
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        self.inc_operations(state, pos)?;
+
+        // Check for stack overflow
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "unchecked"))]
+        if level > self.max_call_levels() {
+            return EvalAltResult::ErrorStackOverflow(pos).into();
+        }
+
+        let fn_def = generator.fn_def.clone();
+
+        let mut new_state: State = Default::default();
+        new_state.source = state.source.clone();
+        new_state.operations = state.operations;
+        new_state.permissions = state.permissions.clone();
+        new_state.modules = state.modules;
+        new_state.scope_level = 1;
+        #[cfg(not(feature = "no_module"))]
+        {
+            new_state.resolver = state.resolver.clone();
+        }
+
+        let mut scope = match generator.take_state() {
+            GeneratorState::Start(args) => fn_def
+                .params
+                .iter()
+                .map(|name| name.as_str())
+                .zip(args)
+                .fold(Scope::new(), |mut scope, (name, value)| {
+                    scope.push_dynamic(name, value);
+                    scope
+                }),
+            GeneratorState::Suspended(mut frame) => {
+                new_state.resume = Some(Resumption::new(&mut frame, Ok(Dynamic::UNIT)).into());
+                frame.take_scope()
+            }
+            GeneratorState::Done => return Ok(None),
+        };
+
+        // Merge in encapsulated environment, if any
+        let lib_merged;
+
+        let unified_lib = if let Some(ref env_lib) = fn_def.lib {
+            lib_merged = once(env_lib.as_ref())
+                .chain(lib.iter().cloned())
+                .collect::<StaticVec<_>>();
+            lib_merged.as_ref()
+        } else {
+            lib
+        };
+
+        let prev_mods_len = mods.len();
+
+        #[cfg(not(feature = "no_module"))]
+        if !fn_def.mods.is_empty() {
+            fn_def
+                .mods
+                .iter_raw()
+                .for_each(|(n, m)| mods.push(n.clone(), m.clone()));
+        }
+
+        // Evaluate the body up to the next `yield`
+        let body = &fn_def.body.statements;
+        let mut this_ptr = generator.this.as_mut();
+
+        let result = self.eval_stmt_block(
+            &mut scope,
+            mods,
+            &mut new_state,
+            unified_lib,
+            &mut this_ptr,
+            body,
+            true,
+            level,
+        );
+
+        mods.truncate(prev_mods_len);
+        state.operations = new_state.operations;
+        state.modules = new_state.modules;
+
+        match result {
+            Ok(_) => Ok(None),
+            Err(err) => match *err {
+                EvalAltResult::Return(_, _) => Ok(None),
+                // Keep the state of the generator at the `yield` statement
+                EvalAltResult::ErrorSuspended(mut cont, _) if cont.is_yield => {
+                    if let Some(frame) = cont.frames.pop() {
+                        generator.suspend(frame);
+                    }
+                    Ok(Some(mem::take(&mut cont.value)))
+                }
+                EvalAltResult::ErrorSuspended(_, _) => {
+                    Err(make_unsupported_err("within a generator").fill_position(pos))
+                }
+                // System errors are passed straight-through
+                mut err if err.is_system_exception() => {
+                    err.set_position(pos);
+                    Err(err.into())
+                }
+                // Other errors are wrapped in `ErrorInFunctionCall`
+                _ => EvalAltResult::ErrorInFunctionCall(
+                    fn_def.name.to_string(),
+                    fn_def
+                        .lib
+                        .as_ref()
+                        .and_then(|m| m.id())
+                        .unwrap_or_else(|| state.source.as_ref().map_or("", |s| s.as_str()))
+                        .to_string(),
+                    err,
+                    pos,
+                )
+                .into(),
+            },
+        }
+    }
+
+    /// Get the next value from the iterator of a `for` loop.
+    ///
+    /// A [`GeneratorStep`] taken from the iterator of a [`Generator`] is replaced by the next
+    /// value of the [`Generator`], which is run up to the next `yield` statement.
+    pub(crate) fn next_iter_value(
+        &self,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &[&Module],
+        iter: &mut TypeIterator,
+        pos: Position,
+        level: usize,
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        match iter.next() {
+            Some(value) if value.is::<GeneratorStep>() => value
+                .cast::<GeneratorStep>()
+                .with(|generator| self.resume_generator(mods, state, lib, generator, pos, level)),
+            value => Ok(value),
+        }
+    }
+
     // Does a scripted function exist?
     #[cfg(not(feature = "no_function"))]
     #[inline(always)]
@@ -641,7 +1002,7 @@ impl Engine {
             // Handle type_of()
             KEYWORD_TYPE_OF if args.len() == 1 => {
                 return Ok((
-                    self.map_type_name(args[0].type_name()).to_string().into(),
+                    self.map_value_type_name(mods, args[0]).to_string().into(),
                     false,
                 ));
             }
@@ -715,8 +1076,33 @@ impl Engine {
             Some(hash.script_hash())
         };
 
+        // A script-defined function may be in a lazily-imported module not yet resolved
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_module"))]
+        if let Some(hash_script) = hash_script {
+            if mods.has_pending()
+                && self
+                    .resolve_function(mods, state, lib, fn_name, hash_script, None, false, false)
+                    .is_none()
+                && self
+                    .resolve_function(
+                        mods,
+                        state,
+                        lib,
+                        fn_name,
+                        hash.native_hash(),
+                        Some(args),
+                        true,
+                        false,
+                    )
+                    .is_none()
+            {
+                self.resolve_pending_imports(mods, state)?;
+            }
+        }
+
         #[cfg(not(feature = "no_function"))]
-        if let Some(FnResolutionCacheEntry { func, source }) = hash_script.and_then(|hash| {
+        if let Some(FnResolutionCacheEntry { func, source, .. }) = hash_script.and_then(|hash| {
             self.resolve_function(mods, state, lib, fn_name, hash, None, false, false)
                 .clone()
         }) {
@@ -872,9 +1258,14 @@ impl Engine {
         let mut new_state: State = Default::default();
         new_state.source = state.source.clone();
         new_state.operations = state.operations;
+        new_state.permissions = state.permissions.clone();
 
-        let result =
-            self.eval_global_statements(scope, mods, &mut new_state, ast.statements(), lib, level);
+        let result = self
+            .eval_global_statements(scope, mods, &mut new_state, ast.statements(), lib, level)
+            .map_err(|err| match *err {
+                EvalAltResult::ErrorSuspended(_, _) => make_unsupported_err("within eval"),
+                _ => err,
+            });
 
         state.operations = new_state.operations;
 
@@ -895,6 +1286,12 @@ impl Engine {
         pos: Position,
         level: usize,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
+        // Return the result of the suspended call when resuming
+        if let Some(call) = state.take_resumed_call(pos) {
+            let value = self.return_resumed_call(mods, state, lib, call, pos, level)?;
+            return Ok((value, false));
+        }
+
         let is_ref = target.is_ref();
 
         // Get a reference to the mutation target Dynamic
@@ -1028,6 +1425,22 @@ impl Engine {
                     }
                 };
 
+                // Check if it is a method defined on a script-defined record type
+                let _method: String;
+
+                #[cfg(not(feature = "no_object"))]
+                #[cfg(not(feature = "no_function"))]
+                if let Some(record) = obj.read_lock::<crate::Record>() {
+                    let method = crate::engine::make_record_method(record.type_name(), fn_name);
+                    let hash_script = calc_fn_hash(empty(), &method, call_args.len());
+
+                    if self.has_script_fn(Some(mods), state, lib, hash_script) {
+                        _method = method;
+                        fn_name = &_method;
+                        hash = FnCallHash::from_script(hash_script);
+                    }
+                }
+
                 // Attached object pointer in front of the arguments
                 let mut arg_values = once(obj)
                     .chain(call_args.iter_mut())
@@ -1063,10 +1476,20 @@ impl Engine {
         capture_scope: bool,
         level: usize,
     ) -> RhaiResult {
+        // Return the result of the suspended call when resuming
+        if let Some(call) = state.take_resumed_call(pos) {
+            return self.return_resumed_call(mods, state, lib, call, pos, level);
+        }
+
+        // Values of the arguments evaluated before the suspension when resuming
+        let mut resumed = state.take_resumed_values().into_iter();
+        let mut values = StaticVec::new();
+
         let args_expr = args_expr.as_ref();
 
         // Handle call() - Redirect function call
         let redirected;
+        let mut is_redirected = false;
         let mut args_expr = args_expr.as_ref();
         let mut curry = StaticVec::new();
         let mut name = fn_name;
@@ -1074,8 +1497,12 @@ impl Engine {
         match name {
             // Handle call()
             KEYWORD_FN_PTR_CALL if args_expr.len() >= 1 => {
-                let fn_ptr =
-                    self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+                let fn_ptr = match resumed.next() {
+                    Some(fn_ptr) => fn_ptr,
+                    None => self
+                        .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)
+                        .map_err(|err| suspended_with(err, empty()))?,
+                };
 
                 if !fn_ptr.is::<FnPtr>() {
                     return Err(self.make_type_mismatch_err::<FnPtr>(
@@ -1090,6 +1517,7 @@ impl Engine {
                 // Redirect function name
                 redirected = fn_ptr.take_data().0;
                 name = &redirected;
+                is_redirected = true;
 
                 // Skip the first argument
                 args_expr = &args_expr.as_ref()[1..];
@@ -1106,7 +1534,8 @@ impl Engine {
             KEYWORD_FN_PTR if args_expr.len() == 1 => {
                 // Fn - only in function call style
                 return self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?
+                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)
+                    .map_err(|err| suspended_with(err, empty()))?
                     .take_immutable_string()
                     .map_err(|typ| {
                         self.make_type_mismatch_err::<ImmutableString>(typ, args_expr[0].position())
@@ -1118,8 +1547,18 @@ impl Engine {
 
             // Handle curry()
             KEYWORD_FN_PTR_CURRY if args_expr.len() > 1 => {
-                let fn_ptr =
-                    self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+                // The arguments are not flattened, so captured variables remain shared
+                values.extend(resumed);
+
+                for expr in &args_expr[values.len()..] {
+                    let value = self
+                        .eval_expr(scope, mods, state, lib, this_ptr, expr, level)
+                        .map_err(|err| suspended_with(err, values.drain(..)))?;
+                    values.push(value);
+                }
+
+                let mut values = values.into_iter();
+                let fn_ptr = values.next().unwrap();
 
                 if !fn_ptr.is::<FnPtr>() {
                     return Err(self.make_type_mismatch_err::<FnPtr>(
@@ -1131,14 +1570,7 @@ impl Engine {
                 let (name, mut fn_curry) = fn_ptr.cast::<FnPtr>().take_data();
 
                 // Append the new curried arguments to the existing list.
-
-                args_expr.iter().skip(1).try_for_each(
-                    |expr| -> Result<(), Box<EvalAltResult>> {
-                        fn_curry
-                            .push(self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?);
-                        Ok(())
-                    },
-                )?;
+                fn_curry.extend(values);
 
                 return Ok(FnPtr::new_unchecked(name, fn_curry).into());
             }
@@ -1146,26 +1578,39 @@ impl Engine {
             // Handle is_shared()
             #[cfg(not(feature = "no_closure"))]
             crate::engine::KEYWORD_IS_SHARED if args_expr.len() == 1 => {
-                let value =
-                    self.eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?;
+                let value = self
+                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)
+                    .map_err(|err| suspended_with(err, empty()))?;
                 return Ok(value.is_shared().into());
             }
 
             // Handle is_def_fn()
             #[cfg(not(feature = "no_function"))]
             crate::engine::KEYWORD_IS_DEF_FN if args_expr.len() == 2 => {
-                let fn_name = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?
+                self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    args_expr,
+                    &mut resumed,
+                    &mut values,
+                    level,
+                )
+                .map_err(|err| suspended_with(err, values.drain(..)))?;
+
+                let num_params = values.pop().unwrap();
+                let fn_name = values
+                    .pop()
+                    .unwrap()
                     .take_immutable_string()
                     .map_err(|err| {
                         self.make_type_mismatch_err::<ImmutableString>(err, args_expr[0].position())
                     })?;
-                let num_params = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[1], level)?
-                    .as_int()
-                    .map_err(|err| {
-                        self.make_type_mismatch_err::<crate::INT>(err, args_expr[0].position())
-                    })?;
+                let num_params = num_params.as_int().map_err(|err| {
+                    self.make_type_mismatch_err::<crate::INT>(err, args_expr[0].position())
+                })?;
 
                 return Ok(if num_params < 0 {
                     Dynamic::FALSE
@@ -1179,7 +1624,8 @@ impl Engine {
             // Handle is_def_var()
             KEYWORD_IS_DEF_VAR if args_expr.len() == 1 => {
                 let var_name = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)?
+                    .eval_expr(scope, mods, state, lib, this_ptr, &args_expr[0], level)
+                    .map_err(|err| suspended_with(err, empty()))?
                     .take_immutable_string()
                     .map_err(|err| {
                         self.make_type_mismatch_err::<ImmutableString>(err, args_expr[0].position())
@@ -1195,7 +1641,8 @@ impl Engine {
                 // eval - only in function call style
                 let prev_len = scope.len();
                 let script = self
-                    .eval_expr(scope, mods, state, lib, this_ptr, script_expr, level)?
+                    .eval_expr(scope, mods, state, lib, this_ptr, script_expr, level)
+                    .map_err(|err| suspended_with(err, empty()))?
                     .take_immutable_string()
                     .map_err(|typ| {
                         self.make_type_mismatch_err::<ImmutableString>(typ, script_pos)
@@ -1234,7 +1681,7 @@ impl Engine {
         }
 
         // Normal function call - except for Fn, curry, call and eval (handled above)
-        let mut arg_values: StaticVec<_>;
+        let mut arg_values = values;
         let mut args: StaticVec<_>;
         let mut is_ref = false;
         let capture = if capture_scope && !scope.is_empty() {
@@ -1243,6 +1690,16 @@ impl Engine {
             None
         };
 
+        // The function pointer of call() is kept as well when suspended
+        let suspended = |err, arg_values: StaticVec<Dynamic>| {
+            if is_redirected {
+                let fn_ptr = FnPtr::new_unchecked(ImmutableString::from(name), curry.to_vec());
+                suspended_with(err, once(fn_ptr.into()).chain(arg_values))
+            } else {
+                suspended_with(err, arg_values)
+            }
+        };
+
         if args_expr.is_empty() && curry.is_empty() {
             // No arguments
             args = Default::default();
@@ -1252,14 +1709,19 @@ impl Engine {
             // avoid cloning the value
             if curry.is_empty() && args_expr[0].get_variable_access(false).is_some() {
                 // func(x, ...) -> x.func(...)
-                arg_values = args_expr
-                    .iter()
-                    .skip(1)
-                    .map(|expr| {
-                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
-                            .map(Dynamic::flatten)
-                    })
-                    .collect::<Result<_, _>>()?;
+                if let Err(err) = self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    args_expr.iter().skip(1),
+                    &mut resumed,
+                    &mut arg_values,
+                    level,
+                ) {
+                    return Err(suspended(err, arg_values));
+                }
 
                 let (mut target, pos) =
                     self.search_namespace(scope, mods, state, lib, this_ptr, &args_expr[0])?;
@@ -1282,13 +1744,19 @@ impl Engine {
                 };
             } else {
                 // func(..., ...)
-                arg_values = args_expr
-                    .iter()
-                    .map(|expr| {
-                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
-                            .map(Dynamic::flatten)
-                    })
-                    .collect::<Result<_, _>>()?;
+                if let Err(err) = self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    args_expr,
+                    &mut resumed,
+                    &mut arg_values,
+                    level,
+                ) {
+                    return Err(suspended(err, arg_values));
+                }
 
                 args = curry.iter_mut().chain(arg_values.iter_mut()).collect();
             }
@@ -1317,10 +1785,18 @@ impl Engine {
         pos: Position,
         level: usize,
     ) -> RhaiResult {
+        // Return the result of the suspended call when resuming
+        if let Some(call) = state.take_resumed_call(pos) {
+            return self.return_resumed_call(mods, state, lib, call, pos, level);
+        }
+
+        // Values of the arguments evaluated before the suspension when resuming
+        let mut resumed = state.take_resumed_values().into_iter();
+
         let args_expr = args_expr.as_ref();
 
         let namespace = namespace.unwrap();
-        let mut arg_values: StaticVec<_>;
+        let mut arg_values = StaticVec::new();
         let mut first_arg_value = None;
         let mut args: StaticVec<_>;
 
@@ -1333,19 +1809,22 @@ impl Engine {
             // &mut first argument and avoid cloning the value
             if args_expr[0].get_variable_access(true).is_some() {
                 // func(x, ...) -> x.func(...)
-                arg_values = args_expr
-                    .iter()
-                    .enumerate()
-                    .map(|(i, expr)| {
-                        // Skip the first argument
-                        if i == 0 {
-                            Ok(Default::default())
-                        } else {
-                            self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
-                                .map(Dynamic::flatten)
-                        }
-                    })
-                    .collect::<Result<_, _>>()?;
+                // Skip the first argument
+                arg_values.push(Default::default());
+
+                if let Err(err) = self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    args_expr.iter().skip(1),
+                    &mut resumed,
+                    &mut arg_values,
+                    level,
+                ) {
+                    return Err(suspended_with(err, arg_values.into_iter().skip(1)));
+                }
 
                 // Get target reference to first argument
                 let (target, pos) =
@@ -1365,21 +1844,25 @@ impl Engine {
                 }
             } else {
                 // func(..., ...) or func(mod::x, ...)
-                arg_values = args_expr
-                    .iter()
-                    .map(|expr| {
-                        self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)
-                            .map(Dynamic::flatten)
-                    })
-                    .collect::<Result<_, _>>()?;
+                if let Err(err) = self.eval_exprs(
+                    scope,
+                    mods,
+                    state,
+                    lib,
+                    this_ptr,
+                    args_expr,
+                    &mut resumed,
+                    &mut arg_values,
+                    level,
+                ) {
+                    return Err(suspended_with(err, arg_values));
+                }
 
                 args = arg_values.iter_mut().collect();
             }
         }
 
-        let module = self.search_imports(mods, state, namespace).ok_or_else(|| {
-            EvalAltResult::ErrorModuleNotFound(namespace[0].name.to_string(), namespace[0].pos)
-        })?;
+        let module = self.search_imports(mods, state, namespace)?;
 
         // First search in script-defined functions (can override built-in)
         let func = match module.get_qualified_fn(hash) {
@@ -1404,6 +1887,13 @@ impl Engine {
             }
         }
 
+        // Check the function allowlist - functions in packages are exempt
+        if let Some(ref permissions) = state.permissions {
+            if func.map_or(false, |f| f.is_native()) && !module.is_package() {
+                permissions.check(Capability::Function, fn_name, pos)?;
+            }
+        }
+
         match func {
             #[cfg(not(feature = "no_function"))]
             Some(f) if f.is_script() => {
@@ -1434,21 +1924,23 @@ impl Engine {
                 .get_plugin_fn()
                 .clone()
                 .call(
-                    (self, fn_name, module.id(), &*mods, lib).into(),
+                    NativeCallContext::from((self, fn_name, module.id(), &*mods, lib))
+                        .with_permissions(state.permissions.as_ref()),
                     args.as_mut(),
                 )
-                .map_err(|err| err.fill_position(pos)),
+                .map_err(|err| check_native_suspension(err).fill_position(pos)),
 
             Some(f) if f.is_native() => f.get_native_fn()(
-                (self, fn_name, module.id(), &*mods, lib).into(),
+                NativeCallContext::from((self, fn_name, module.id(), &*mods, lib))
+                    .with_permissions(state.permissions.as_ref()),
                 args.as_mut(),
             )
-            .map_err(|err| err.fill_position(pos)),
+            .map_err(|err| check_native_suspension(err).fill_position(pos)),
 
             Some(f) => unreachable!("unknown function type: {:?}", f),
 
             None => EvalAltResult::ErrorFunctionNotFound(
-                self.gen_call_signature(Some(namespace), fn_name, args.as_ref()),
+                self.gen_call_signature(mods, Some(namespace), fn_name, args.as_ref()),
                 pos,
             )
             .into(),
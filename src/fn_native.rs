@@ -1,21 +1,23 @@
 //! Module defining interfaces to native-Rust functions.
 
 use crate::ast::{FnAccess, FnCallHash};
-use crate::engine::Imports;
+use crate::engine::{Imports, State};
 use crate::plugin::PluginFunction;
 use crate::stdlib::{
     boxed::Box,
     convert::{TryFrom, TryInto},
     fmt,
+    future::Future,
     iter::empty,
     mem,
+    pin::Pin,
     string::String,
     vec::Vec,
 };
 use crate::token::is_valid_identifier;
 use crate::{
-    calc_fn_hash, Dynamic, Engine, EvalAltResult, EvalContext, ImmutableString, Module, Position,
-    RhaiResult,
+    calc_fn_hash, Capability, Dynamic, Engine, EvalAltResult, EvalContext, ImmutableString, Module,
+    Permissions, Position, RhaiResult,
 };
 
 /// Trait that maps to `Send + Sync` only under the `sync` feature.
@@ -32,6 +34,20 @@ pub trait SendSync {}
 #[cfg(not(feature = "sync"))]
 impl<T> SendSync for T {}
 
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(feature = "sync")]
+pub trait SendOnly: Send {}
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(feature = "sync")]
+impl<T: Send> SendOnly for T {}
+
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(not(feature = "sync"))]
+pub trait SendOnly {}
+/// Trait that maps to `Send` only under the `sync` feature.
+#[cfg(not(feature = "sync"))]
+impl<T> SendOnly for T {}
+
 /// Immutable reference-counted container.
 #[cfg(not(feature = "sync"))]
 pub use crate::stdlib::rc::Rc as Shared;
@@ -40,11 +56,9 @@ pub use crate::stdlib::rc::Rc as Shared;
 pub use crate::stdlib::sync::Arc as Shared;
 
 /// Synchronized shared object.
-#[cfg(not(feature = "no_closure"))]
 #[cfg(not(feature = "sync"))]
 pub use crate::stdlib::cell::RefCell as Locked;
 /// Synchronized shared object.
-#[cfg(not(feature = "no_closure"))]
 #[cfg(feature = "sync")]
 pub use crate::stdlib::sync::RwLock as Locked;
 
@@ -56,6 +70,7 @@ pub struct NativeCallContext<'a> {
     source: Option<&'a str>,
     mods: Option<&'a Imports>,
     lib: &'a [&'a Module],
+    permissions: Option<&'a Shared<Permissions>>,
 }
 
 impl<'a, M: AsRef<[&'a Module]> + ?Sized>
@@ -69,6 +84,7 @@ impl<'a, M: AsRef<[&'a Module]> + ?Sized>
             source: value.2,
             mods: Some(value.3),
             lib: value.4.as_ref(),
+            permissions: None,
         }
     }
 }
@@ -84,6 +100,7 @@ impl<'a, M: AsRef<[&'a Module]> + ?Sized> From<(&'a Engine, &'a str, &'a M)>
             source: None,
             mods: None,
             lib: value.2.as_ref(),
+            permissions: None,
         }
     }
 }
@@ -98,6 +115,7 @@ impl<'a> NativeCallContext<'a> {
             source: None,
             mods: None,
             lib,
+            permissions: None,
         }
     }
     /// _(INTERNALS)_ Create a new [`NativeCallContext`].
@@ -118,8 +136,15 @@ impl<'a> NativeCallContext<'a> {
             source: source.clone(),
             mods: Some(imports),
             lib,
+            permissions: None,
         }
     }
+    /// Set the [`Permissions`] restricting the current evaluation.
+    #[inline(always)]
+    pub(crate) fn with_permissions(mut self, permissions: Option<&'a Shared<Permissions>>) -> Self {
+        self.permissions = permissions;
+        self
+    }
     /// The current [`Engine`].
     #[inline(always)]
     pub fn engine(&self) -> &Engine {
@@ -198,10 +223,13 @@ impl<'a> NativeCallContext<'a> {
             FnCallHash::from_script(calc_fn_hash(empty(), fn_name, args.len()))
         };
 
+        let mut state = State::default();
+        state.permissions = self.permissions.cloned();
+
         self.engine()
             .exec_fn_call(
                 &mut self.mods.cloned().unwrap_or_default(),
-                &mut Default::default(),
+                &mut state,
                 self.lib,
                 fn_name,
                 hash,
@@ -392,8 +420,54 @@ pub type FnAny = dyn Fn(NativeCallContext, &mut FnCallArgs) -> RhaiResult;
 #[cfg(feature = "sync")]
 pub type FnAny = dyn Fn(NativeCallContext, &mut FnCallArgs) -> RhaiResult + Send + Sync;
 
+/// A boxed [`Future`][crate::stdlib::future::Future] returned by an `async` native Rust function.
+#[cfg(not(feature = "sync"))]
+pub type FnAsyncFuture = Pin<Box<dyn Future<Output = RhaiResult>>>;
+/// A boxed [`Future`][crate::stdlib::future::Future] returned by an `async` native Rust function.
+#[cfg(feature = "sync")]
+pub type FnAsyncFuture = Pin<Box<dyn Future<Output = RhaiResult> + Send>>;
+
+/// A function that starts a call to an `async` native Rust function with bound arguments.
+#[cfg(not(feature = "sync"))]
+pub type FnAsyncCall = dyn Fn() -> FnAsyncFuture;
+/// A function that starts a call to an `async` native Rust function with bound arguments.
+#[cfg(feature = "sync")]
+pub type FnAsyncCall = dyn Fn() -> FnAsyncFuture + Send + Sync;
+
+/// A pending call to an `async` native Rust function, awaited by the [`Engine`] when evaluating
+/// a script asynchronously.
+#[derive(Clone)]
+pub(crate) struct AsyncCall(Shared<FnAsyncCall>);
+
+impl fmt::Debug for AsyncCall {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AsyncCall")
+    }
+}
+
+impl AsyncCall {
+    /// Create a new [`AsyncCall`].
+    #[inline(always)]
+    pub(crate) fn new(call: impl Fn() -> FnAsyncFuture + SendSync + 'static) -> Self {
+        Self(Shared::new(call))
+    }
+    /// Start the call, returning a [`Future`][crate::stdlib::future::Future] for its result.
+    #[inline(always)]
+    pub(crate) fn start(&self) -> FnAsyncFuture {
+        (self.0)()
+    }
+}
+
+/// An iterator over the values of a type, returned by an [`IteratorFn`].
+#[cfg(not(feature = "sync"))]
+pub type TypeIterator = dyn Iterator<Item = Dynamic>;
+/// An iterator over the values of a type, returned by an [`IteratorFn`].
+#[cfg(feature = "sync")]
+pub type TypeIterator = dyn Iterator<Item = Dynamic> + Send + Sync;
+
 /// A standard function that gets an iterator from a type.
-pub type IteratorFn = fn(Dynamic) -> Box<dyn Iterator<Item = Dynamic>>;
+pub type IteratorFn = fn(Dynamic) -> Box<TypeIterator>;
 
 #[cfg(not(feature = "sync"))]
 pub type FnPlugin = dyn PluginFunction;
@@ -421,6 +495,44 @@ pub type OnDebugCallback = Box<dyn Fn(&str, Option<&str>, Position) + 'static>;
 #[cfg(feature = "sync")]
 pub type OnDebugCallback = Box<dyn Fn(&str, Option<&str>, Position) + Send + Sync + 'static>;
 
+/// A standard callback function for a virtual clock.
+#[cfg(not(feature = "sync"))]
+pub type ClockCallback = Box<dyn Fn() -> crate::stdlib::time::Duration + 'static>;
+/// A standard callback function for a virtual clock.
+#[cfg(feature = "sync")]
+pub type ClockCallback = Box<dyn Fn() -> crate::stdlib::time::Duration + Send + Sync + 'static>;
+
+/// A standard callback function for auditing denied accesses.
+#[cfg(not(feature = "sync"))]
+pub type OnDeniedCallback = dyn Fn(Capability, &str, Position) + 'static;
+/// A standard callback function for auditing denied accesses.
+#[cfg(feature = "sync")]
+pub type OnDeniedCallback = dyn Fn(Capability, &str, Position) + Send + Sync + 'static;
+
+/// A standard callback function for notification of reloaded module script files.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[cfg(not(feature = "sync"))]
+pub type OnReloadCallback = dyn Fn(&crate::stdlib::path::Path) + 'static;
+/// A standard callback function for notification of reloaded module script files.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[cfg(feature = "sync")]
+pub type OnReloadCallback = dyn Fn(&crate::stdlib::path::Path) + Send + Sync + 'static;
+
+/// A standard callback function for loading the text of module scripts.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "sync"))]
+pub type OnLoadScriptCallback =
+    dyn Fn(&Engine, &str) -> Result<String, Box<EvalAltResult>> + 'static;
+/// A standard callback function for loading the text of module scripts.
+#[cfg(not(feature = "no_module"))]
+#[cfg(feature = "sync")]
+pub type OnLoadScriptCallback =
+    dyn Fn(&Engine, &str) -> Result<String, Box<EvalAltResult>> + Send + Sync + 'static;
+
 /// A standard callback function for variable access.
 #[cfg(not(feature = "sync"))]
 pub type OnVarCallback =
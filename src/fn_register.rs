@@ -2,17 +2,21 @@
 
 #![allow(non_snake_case)]
 
+use crate::continuation::Continuation;
 use crate::dynamic::{DynamicWriteLock, Variant};
-use crate::fn_native::{CallableFunction, FnAny, FnCallArgs, SendSync};
+use crate::fn_native::{
+    AsyncCall, CallableFunction, FnAny, FnAsyncFuture, FnCallArgs, SendOnly, SendSync, Shared,
+};
 use crate::r#unsafe::unsafe_try_cast;
 use crate::stdlib::{
     any::{type_name, TypeId},
     boxed::Box,
+    future::Future,
     mem,
     string::String,
     vec,
 };
-use crate::{Dynamic, EvalAltResult, NativeCallContext};
+use crate::{Dynamic, EvalAltResult, NativeCallContext, Position, StaticVec};
 
 // These types are used to build a unique _marker_ tuple type for each combination
 // of function parameter types in order to make each trait implementation unique.
@@ -193,3 +197,98 @@ macro_rules! def_register {
 }
 
 def_register!(A, B, C, D, E, F, G, H, J, K, L, M, N, P, Q, R, S, T, U, V);
+
+/// Trait to register custom `async` Rust functions.
+pub trait RegisterAsyncFunction<Args, Result> {
+    /// Get the type ID's of this function's parameters.
+    fn param_types() -> Box<[TypeId]>;
+    /// Get the type names of this function's parameters.
+    fn param_names() -> Box<[&'static str]>;
+    /// Get the type name of the output of this function's [`Future`].
+    fn return_type_name() -> &'static str;
+    /// Convert this function into a [`CallableFunction`].
+    ///
+    /// Calling it suspends the script evaluation with a pending call to this function,
+    /// to be awaited by the [`Engine`][crate::Engine].
+    fn into_callable_function(self) -> CallableFunction;
+}
+
+/// Trait for the parameter types of custom `async` Rust functions, which are passed by value.
+///
+/// It is implemented for all types except `&str`, as the [`Future`] of an `async` function may
+/// outlive the argument values.  Use [`ImmutableString`][crate::ImmutableString] or [`String`]
+/// instead.
+///
+/// The marker type `M` is `()` for all supported types.  `&str` also has an implementation with
+/// a different marker type, so the marker type for `&str` is ambiguous and registering an `async`
+/// function taking a `&str` parameter fails to compile:
+///
+/// ```compile_fail
+/// use rhai::{Engine, EvalAltResult};
+///
+/// let mut engine = Engine::new();
+///
+/// engine.register_async_fn("shout", |s: &'static str| async move {
+///     Ok::<_, Box<EvalAltResult>>(s.to_uppercase())
+/// });
+/// ```
+pub trait AsyncParam<M>: Variant + Clone {}
+
+impl<T: Variant + Clone> AsyncParam<()> for T {}
+
+impl AsyncParam<&'static str> for &'static str {}
+
+macro_rules! def_register_async {
+    () => {
+        def_register_async!(imp :);
+    };
+    (imp : $($par:ident => $mark:ident),*) => {
+    //     ^ function parameter generic type name (A, B, C etc.), all passed by value
+    //                  ^ marker type of the parameter's `AsyncParam` implementation
+
+        impl<
+            FN: Fn($($par),*) -> FUT + SendSync + 'static,
+            FUT: Future<Output = Result<RET, Box<EvalAltResult>>> + SendOnly + 'static,
+            $($par: AsyncParam<$mark>, $mark,)*
+            RET: Variant + Clone
+        > RegisterAsyncFunction<($(($par, $mark),)*), Result<RET, Box<EvalAltResult>>> for FN {
+            #[inline(always)] fn param_types() -> Box<[TypeId]> { vec![$(TypeId::of::<$par>()),*].into_boxed_slice() }
+            #[inline(always)] fn param_names() -> Box<[&'static str]> { vec![$(type_name::<$par>()),*].into_boxed_slice() }
+            #[inline(always)] fn return_type_name() -> &'static str { type_name::<Result<RET, Box<EvalAltResult>>>() }
+            #[inline(always)] fn into_callable_function(self) -> CallableFunction {
+                let func = Shared::new(self);
+
+                CallableFunction::from_pure(Box::new(move |_: NativeCallContext, args: &mut FnCallArgs| {
+                    // Bind the argument values to a call that is started when awaited
+                    let args: StaticVec<Dynamic> = args.iter_mut().map(|arg| mem::take(*arg)).collect();
+                    let func = func.clone();
+
+                    let call = AsyncCall::new(move || {
+                        let func = func.clone();
+                        let mut args = args.clone();
+
+                        Box::pin(async move {
+                            // The arguments are assumed to be of the correct number and types!
+                            let future = {
+                                let mut _drain = args.iter_mut();
+                                $(let $par = by_value::<$par>(_drain.next().unwrap()); )*
+                                func($($par),*)
+                            };
+                            future.await.map(Dynamic::from)
+                        }) as FnAsyncFuture
+                    });
+
+                    EvalAltResult::ErrorSuspended(Continuation::new_async(call).into(), Position::NONE).into()
+                }) as Box<FnAny>)
+            }
+        }
+    };
+    ($p0:ident => $m0:ident $(, $p:ident => $m:ident)*) => {
+        def_register_async!(imp : $p0 => $m0 $(, $p => $m)*);
+        def_register_async!($($p => $m),*);
+    };
+}
+
+def_register_async!(A => MA, B => MB, C => MC, D => MD, E => ME, F => MF, G => MG, H => MH, J => MJ,
+                    K => MK, L => ML, M => MM, N => MN, P => MP, Q => MQ, R => MR, S => MS,
+                    T => MT, U => MU, V => MV);
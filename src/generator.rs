@@ -0,0 +1,160 @@
+//! Module defining generators created by script-defined functions that `yield` values.
+
+use crate::ast::ScriptFnDef;
+use crate::continuation::Frame;
+use crate::fn_native::Locked;
+use crate::stdlib::mem;
+use crate::{Dynamic, Shared, StaticVec};
+
+#[cfg(not(feature = "no_function"))]
+use crate::fn_native::TypeIterator;
+
+#[cfg(not(feature = "no_function"))]
+use crate::stdlib::{boxed::Box, iter};
+
+/// State of a [`Generator`].
+#[derive(Debug, Clone)]
+pub(crate) enum GeneratorState {
+    /// Not yet started.  Wrapped value is the list of arguments.
+    Start(StaticVec<Dynamic>),
+    /// Suspended at a `yield` statement.
+    Suspended(Frame),
+    /// Run to completion.
+    Done,
+}
+
+/// A lazy sequence of values produced by a script-defined generator function.
+///
+/// A script-defined function whose body contains `yield` statements is a generator function.
+/// Calling it does not run its body, but returns a [`Generator`] instead.
+///
+/// The body is run when the next value is requested, up to the next `yield` statement.
+/// The value of the `yield` statement becomes the next value of the sequence.
+/// The sequence ends when the body runs to completion or hits a `return` statement
+/// (whose value is discarded).
+///
+/// A [`Generator`] can be iterated in a `for` loop, or from Rust via
+/// [`Engine::iter_generator`][crate::Engine::iter_generator].
+/// The iterator used in `for` loops is registered by
+/// [`BasicIteratorPackage`][crate::packages::BasicIteratorPackage].
+///
+/// Each `for` loop (or call to [`Engine::iter_generator`][crate::Engine::iter_generator])
+/// iterates a fresh copy of the [`Generator`] value, so iterating the same variable again
+/// restarts the sequence.
+///
+/// # Limitations
+///
+/// * `yield` is a statement and cannot be used as an expression.
+///
+/// * A native Rust function cannot [suspend][crate::Continuation] the script evaluation
+///   while the body of a generator function is running.
+///
+/// * When the generator function is called as a method, changes made to `this` are not
+///   visible to the caller.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, INT};
+///
+/// let engine = Engine::new();
+///
+/// let result = engine.eval::<INT>(r"
+///     fn evens(n) {
+///         let x = 0;
+///         while x < n {
+///             yield x;
+///             x += 2;
+///         }
+///     }
+///
+///     let total = 0;
+///     for x in evens(10) { total += x; }
+///     total
+/// ")?;
+///
+/// assert_eq!(result, 0 + 2 + 4 + 6 + 8);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    /// The generator function.
+    pub(crate) fn_def: Shared<ScriptFnDef>,
+    /// The `this` value if the generator function is called as a method.
+    pub(crate) this: Option<Dynamic>,
+    /// State of the generator.
+    state: GeneratorState,
+}
+
+impl Generator {
+    /// Create a new [`Generator`] for a call to a generator function.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub(crate) fn new(
+        fn_def: Shared<ScriptFnDef>,
+        args: StaticVec<Dynamic>,
+        this: Option<Dynamic>,
+    ) -> Self {
+        Self {
+            fn_def,
+            this,
+            state: GeneratorState::Start(args),
+        }
+    }
+    /// Get the name of the generator function.
+    #[inline(always)]
+    pub fn fn_name(&self) -> &str {
+        &self.fn_def.name
+    }
+    /// Has the [`Generator`] run to completion?
+    #[inline(always)]
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, GeneratorState::Done)
+    }
+    /// Take the state of the [`Generator`], leaving it [done][GeneratorState::Done].
+    #[inline(always)]
+    pub(crate) fn take_state(&mut self) -> GeneratorState {
+        mem::replace(&mut self.state, GeneratorState::Done)
+    }
+    /// Suspend the [`Generator`] at a `yield` statement.
+    #[inline(always)]
+    pub(crate) fn suspend(&mut self, frame: Frame) {
+        self.state = GeneratorState::Suspended(frame);
+    }
+}
+
+/// A request for the next value of a [`Generator`], yielded by the iterator of a [`Generator`].
+///
+/// An iterator function has no access to the [`Engine`][crate::Engine], so the [`Generator`] is
+/// resumed by the [`Engine`][crate::Engine] when a [`GeneratorStep`] is taken from the iterator.
+#[derive(Debug, Clone)]
+pub(crate) struct GeneratorStep(Shared<Locked<Generator>>);
+
+impl GeneratorStep {
+    /// Run a closure on the underlying [`Generator`].
+    #[inline(always)]
+    pub(crate) fn with<T>(&self, f: impl FnOnce(&mut Generator) -> T) -> T {
+        #[cfg(not(feature = "sync"))]
+        return f(&mut self.0.borrow_mut());
+        #[cfg(feature = "sync")]
+        return f(&mut self.0.write().unwrap());
+    }
+}
+
+/// Iterator function for [`Generator`], yielding a [`GeneratorStep`] for each value.
+///
+/// The sequence ends once the [`Generator`] has run to completion.
+#[cfg(not(feature = "no_function"))]
+pub(crate) fn iter_steps(generator: Dynamic) -> Box<TypeIterator> {
+    let step = GeneratorStep(Shared::new(Locked::new(generator.cast::<Generator>())));
+
+    Box::new(iter::from_fn(move || {
+        if step.with(|generator| generator.is_done()) {
+            None
+        } else {
+            Some(Dynamic::from(step.clone()))
+        }
+    }))
+}
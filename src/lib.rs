@@ -64,6 +64,11 @@ extern crate alloc;
 // Internal modules
 
 mod ast;
+mod build_type;
+mod continuation;
+mod custom_enum;
+mod datetime;
+mod deterministic;
 mod dynamic;
 mod engine;
 mod engine_api;
@@ -74,14 +79,20 @@ mod fn_call;
 mod fn_func;
 mod fn_native;
 mod fn_register;
+mod generator;
 mod module;
 mod optimize;
 pub mod packages;
+mod packed;
 mod parse_error;
 mod parser;
+mod permissions;
 pub mod plugin;
+mod record;
 mod result;
 mod scope;
+mod set;
+mod snapshot;
 mod stdlib;
 mod syntax;
 mod token;
@@ -123,14 +134,31 @@ pub type FLOAT = f64;
 pub type FLOAT = f32;
 
 pub use ast::{FnAccess, AST};
+pub use build_type::{CustomType, TypeBuilder};
+pub use continuation::Continuation;
+pub use custom_enum::CustomEnum;
+pub use datetime::{DateTime, Duration};
+pub use deterministic::{Nondeterministic, ReplayLog};
 pub use dynamic::Dynamic;
-pub use engine::{Engine, EvalContext, OP_CONTAINS, OP_EQUALS};
+pub use engine::{CancellationToken, Engine, EvalContext, Imports, OP_CONTAINS, OP_EQUALS};
 pub use fn_native::{FnPtr, NativeCallContext};
-pub use fn_register::RegisterNativeFunction;
+pub use fn_register::{AsyncParam, RegisterAsyncFunction, RegisterNativeFunction};
+pub use generator::Generator;
 pub use module::{FnNamespace, Module};
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_float"))]
+pub use packed::FloatArray;
+#[cfg(not(feature = "no_index"))]
+pub use packed::{IntArray, PackedArray};
 pub use parse_error::{LexError, ParseError, ParseErrorType};
+pub use permissions::{Capability, Permissions};
+#[cfg(not(feature = "no_object"))]
+pub use record::Record;
 pub use result::EvalAltResult;
 pub use scope::Scope;
+#[cfg(not(feature = "no_index"))]
+pub use set::Set;
+pub use snapshot::Snapshot;
 pub use syntax::Expression;
 pub use token::Position;
 pub use utils::ImmutableString;
@@ -160,6 +188,12 @@ pub use ast::ScriptFnMetadata;
 #[cfg(not(feature = "no_index"))]
 pub type Array = stdlib::vec::Vec<Dynamic>;
 
+/// Variable-sized buffer of bytes (a _blob_).
+///
+/// Not available under `no_index`.
+#[cfg(not(feature = "no_index"))]
+pub type Blob = stdlib::vec::Vec<u8>;
+
 /// Hash map of [`Dynamic`] values with [`ImmutableString`] keys.
 ///
 /// Not available under `no_object`.
@@ -195,9 +229,14 @@ pub use ast::{
     OpAssignment, ReturnType, ScriptFnDef, Stmt, StmtBlock,
 };
 
+#[cfg(feature = "internals")]
+#[cfg(not(feature = "no_module"))]
+#[deprecated = "this type is volatile and may change"]
+pub use ast::{ImportNames, ModuleDef};
+
 #[cfg(feature = "internals")]
 #[deprecated = "this type is volatile and may change"]
-pub use engine::{Imports, State as EvalState};
+pub use engine::State as EvalState;
 
 #[cfg(feature = "internals")]
 #[cfg(not(feature = "unchecked"))]
@@ -143,10 +143,14 @@ pub struct Module {
     type_iterators: BTreeMap<TypeId, IteratorFn>,
     /// Flattened collection of iterator functions, including those in sub-modules.
     all_type_iterators: BTreeMap<TypeId, IteratorFn>,
+    /// Names of custom types, keyed by the Rust type name.
+    custom_types: BTreeMap<String, String>,
     /// Is the [`Module`] indexed?
     indexed: bool,
     /// Does the [`Module`] contain indexed functions that have been exposed to the global namespace?
     contains_indexed_global_functions: bool,
+    /// Is the [`Module`] a package?
+    package: bool,
 }
 
 impl Default for Module {
@@ -161,8 +165,10 @@ impl Default for Module {
             all_functions: Default::default(),
             type_iterators: Default::default(),
             all_type_iterators: Default::default(),
+            custom_types: Default::default(),
             indexed: false,
             contains_indexed_global_functions: false,
+            package: false,
         }
     }
 }
@@ -336,6 +342,7 @@ impl Module {
             && self.modules.is_empty()
             && self.type_iterators.is_empty()
             && self.all_type_iterators.is_empty()
+            && self.custom_types.is_empty()
     }
 
     /// Is the [`Module`] indexed?
@@ -359,6 +366,47 @@ impl Module {
         self.indexed
     }
 
+    /// Is the [`Module`] a package?
+    ///
+    /// Native Rust functions in packages are not restricted by the function allowlist of
+    /// [`Permissions`][crate::Permissions].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    /// use rhai::packages::{Package, CorePackage};
+    ///
+    /// let module = Module::new();
+    /// assert!(!module.is_package());
+    ///
+    /// let package = CorePackage::new();
+    /// assert!(package.as_shared_module().is_package());
+    /// ```
+    #[inline(always)]
+    pub fn is_package(&self) -> bool {
+        self.package
+    }
+
+    /// Mark the [`Module`] as a package, or not.
+    ///
+    /// Packages defined via [`def_package!`][crate::def_package] are marked automatically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// module.set_package(true);
+    /// assert!(module.is_package());
+    /// ```
+    #[inline(always)]
+    pub fn set_package(&mut self, package: bool) -> &mut Self {
+        self.package = package;
+        self
+    }
+
     /// Generate signatures for all the non-private functions in the [`Module`].
     #[inline(always)]
     pub fn gen_fn_signatures(&self) -> impl Iterator<Item = String> + '_ {
@@ -419,6 +467,19 @@ impl Module {
         self.variables.get(name).cloned()
     }
 
+    /// Does the [`Module`] contain a variable or a public function with a particular name?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn exports_name(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+            || self.functions.values().any(|f| {
+                f.name == name
+                    && match f.access {
+                        FnAccess::Public => true,
+                        FnAccess::Private => false,
+                    }
+            })
+    }
+
     /// Set a variable into the [`Module`].
     ///
     /// If there is an existing variable of the same name, it is replaced.
@@ -1104,6 +1165,7 @@ impl Module {
         self.variables.extend(other.variables.into_iter());
         self.functions.extend(other.functions.into_iter());
         self.type_iterators.extend(other.type_iterators.into_iter());
+        self.custom_types.extend(other.custom_types.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1123,6 +1185,7 @@ impl Module {
         self.variables.extend(other.variables.into_iter());
         self.functions.extend(other.functions.into_iter());
         self.type_iterators.extend(other.type_iterators.into_iter());
+        self.custom_types.extend(other.custom_types.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1151,6 +1214,11 @@ impl Module {
         other.type_iterators.iter().for_each(|(&k, &v)| {
             self.type_iterators.entry(k).or_insert(v);
         });
+        other.custom_types.iter().for_each(|(k, v)| {
+            if !self.custom_types.contains_key(k) {
+                self.custom_types.insert(k.clone(), v.clone());
+            }
+        });
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1200,6 +1268,12 @@ impl Module {
         );
 
         self.type_iterators.extend(other.type_iterators.iter());
+        self.custom_types.extend(
+            other
+                .custom_types
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1256,6 +1330,43 @@ impl Module {
         self.variables.iter().map(|(k, v)| (k.as_str(), v))
     }
 
+    /// Copy all public functions with a particular name into another [`Module`] under a new name,
+    /// exposing them to the global namespace.
+    ///
+    /// Returns `true` if any function is copied.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn copy_fn_as(&self, name: &str, alias: &str, target: &mut Module) -> bool {
+        let mut copied = false;
+
+        self.functions
+            .values()
+            .filter(|f| f.name == name)
+            .filter(|f| match f.access {
+                FnAccess::Public => true,
+                FnAccess::Private => false,
+            })
+            .for_each(|f| {
+                let hash = if f.func.is_script() {
+                    crate::calc_fn_hash(empty(), alias, f.params)
+                } else {
+                    calc_native_fn_hash(empty(), alias, &f.param_types)
+                };
+
+                let mut func = f.clone();
+                func.name = alias.into();
+                func.namespace = FnNamespace::Global;
+                target.functions.insert(hash, func);
+                copied = true;
+            });
+
+        if copied {
+            target.indexed = false;
+            target.contains_indexed_global_functions = false;
+        }
+
+        copied
+    }
+
     /// Get an iterator to the functions in the [`Module`].
     #[inline(always)]
     #[allow(dead_code)]
@@ -1365,16 +1476,51 @@ impl Module {
     /// # }
     /// ```
     #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
     pub fn eval_ast_as_new(
+        scope: crate::Scope,
+        ast: &crate::AST,
+        engine: &crate::Engine,
+    ) -> Result<Self, Box<EvalAltResult>> {
+        Self::eval_ast_as_new_with_imports(scope, ast, engine, &Default::default())
+    }
+
+    /// Create a new [`Module`] by evaluating an [`AST`][crate::AST] with a stack of imported
+    /// [modules][Module].
+    ///
+    /// Module resolvers evaluating scripts should pass on the [`Imports`][crate::Imports] given
+    /// to [`ModuleResolver::resolve_with_imports`][crate::ModuleResolver::resolve_with_imports],
+    /// which carry the chain of `import` statements being resolved, so that cyclic imports are
+    /// detected.
+    ///
+    /// [Modules][Module] in the stack are available to the script but do not become
+    /// sub-modules of the new [`Module`].
+    #[cfg(not(feature = "no_module"))]
+    pub fn eval_ast_as_new_with_imports(
         mut scope: crate::Scope,
         ast: &crate::AST,
         engine: &crate::Engine,
+        mods: &crate::Imports,
     ) -> Result<Self, Box<EvalAltResult>> {
-        let mut mods: crate::engine::Imports = Default::default();
+        let mut mods = mods.clone();
         let orig_mods_len = mods.len();
 
         // Run the script
-        engine.eval_ast_with_scope_raw(&mut scope, &mut mods, &ast, 0)?;
+        engine
+            .eval_ast_with_scope_raw(&mut scope, &mut mods, &ast, 0)
+            .map_err(|err| match *err {
+                EvalAltResult::ErrorSuspended(_, _) => {
+                    crate::continuation::make_unsupported_err("within a module")
+                }
+                _ => err,
+            })?;
+
+        // Lazy imports become sub-modules, so resolve them now
+        let mut state: crate::engine::State = Default::default();
+
+        for index in orig_mods_len..mods.len() {
+            engine.get_import(&mut mods, &mut state, index)?;
+        }
 
         // Create new module
         let mut module = Module::new();
@@ -1397,7 +1543,11 @@ impl Module {
 
         mods.into_iter().skip(orig_mods_len).for_each(|(alias, m)| {
             func_mods.push(alias.clone(), m.clone());
-            module.set_sub_module(alias, m);
+
+            // Functions imported via `import { ... } from` are not kept under a name
+            if !alias.is_empty() {
+                module.set_sub_module(alias, m);
+            }
         });
 
         // Non-private functions defined become module functions
@@ -1537,6 +1687,9 @@ impl Module {
     }
 
     /// Set a type iterator into the [`Module`].
+    ///
+    /// Under the `sync` feature, the iterator must be `Send + Sync`, as it is kept in the
+    /// [`Continuation`][crate::Continuation] of a script suspended within a `for` loop.
     #[inline(always)]
     pub fn set_iter(&mut self, typ: TypeId, func: IteratorFn) -> &mut Self {
         self.type_iterators.insert(typ, func);
@@ -1551,6 +1704,7 @@ impl Module {
     where
         T: Variant + Clone + IntoIterator,
         <T as IntoIterator>::Item: Variant + Clone,
+        <T as IntoIterator>::IntoIter: SendSync,
     {
         self.set_iter(TypeId::of::<T>(), |obj: Dynamic| {
             Box::new(obj.cast::<T>().into_iter().map(Dynamic::from))
@@ -1580,6 +1734,75 @@ impl Module {
     pub(crate) fn get_iter(&self, id: TypeId) -> Option<IteratorFn> {
         self.type_iterators.get(&id).cloned()
     }
+
+    /// Set a name for a custom type into the [`Module`].
+    ///
+    /// Unlike [`Engine::register_type_with_name`][crate::Engine::register_type_with_name], the
+    /// name is qualified by the namespace of the [`Module`] (e.g. `net::Config`) in `type_of` and
+    /// in error messages, so that modules can use the same name for different types.  It becomes
+    /// active when the [`Module`] is imported, or registered via
+    /// [`Engine::register_static_module`][crate::Engine::register_static_module].
+    ///
+    /// Getters, setters, indexers and iterators for the type can be set into the same [`Module`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, Module, INT};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Config {
+    ///     port: INT,
+    /// }
+    ///
+    /// let mut module = Module::new();
+    /// module.set_custom_type::<Config>("Config");
+    /// module.set_native_fn("config", || Ok(Config { port: 80 }));
+    /// module.set_getter_fn("port", |c: &mut Config| Ok(c.port));
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_static_module("net", module.into());
+    ///
+    /// assert_eq!(engine.eval::<String>("type_of(net::config())").unwrap(), "net::Config");
+    /// assert_eq!(engine.eval::<INT>("net::config().port").unwrap(), 80);
+    /// ```
+    #[inline(always)]
+    pub fn set_custom_type<T: Variant + Clone>(&mut self, name: &str) -> &mut Self {
+        self.custom_types
+            .insert(crate::stdlib::any::type_name::<T>().into(), name.into());
+        self
+    }
+
+    /// Get the name set for a custom type in the [`Module`], if any.
+    #[inline(always)]
+    pub fn get_custom_type(&self, type_name: &str) -> Option<&str> {
+        self.custom_types.get(type_name).map(|s| s.as_str())
+    }
+
+    /// Find the name set for a custom type in the [`Module`] or any of its sub-modules,
+    /// qualified by the path of the sub-module (e.g. `sub::Config`).
+    pub(crate) fn find_custom_type(&self, type_name: &str) -> Option<String> {
+        self.custom_types.get(type_name).cloned().or_else(|| {
+            self.modules.iter().find_map(|(name, m)| {
+                m.find_custom_type(type_name)
+                    .map(|typ| format!("{}{}{}", name, Token::DoubleColon.syntax(), typ))
+            })
+        })
+    }
+
+    /// Visit all the custom types set in the [`Module`] and its sub-modules, together with their
+    /// names qualified by `namespace`.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn walk_custom_types(&self, namespace: &str, f: &mut impl FnMut(&str, String)) {
+        let separator = Token::DoubleColon.syntax();
+
+        self.custom_types.iter().for_each(|(type_name, name)| {
+            f(type_name, format!("{}{}{}", namespace, separator, name))
+        });
+        self.modules.iter().for_each(|(name, m)| {
+            m.walk_custom_types(&format!("{}{}{}", namespace, separator, name), f)
+        });
+    }
 }
 
 /// _(INTERNALS)_ A chain of [module][Module] names to namespace-qualify a variable or function call.
@@ -0,0 +1,435 @@
+use super::ModuleCache;
+use crate::stdlib::{
+    boxed::Box,
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::{Engine, EvalAltResult, Imports, Module, ModuleResolver, Position, Shared, AST};
+
+/// Magic bytes at the start of a [`Bundle`] file.
+const BUNDLE_MAGIC: &[u8] = b"RHAIBNDL";
+
+/// Version of the [`Bundle`] file format.
+const BUNDLE_VERSION: u8 = 1;
+
+/// A collection of named Rhai scripts, plus a manifest of key-value pairs, packed into a single
+/// file for deployment.
+///
+/// Scripts are named by their paths (e.g. `utils/math`), which are the paths used to `import`
+/// them via a [`BundleModuleResolver`].
+///
+/// ## Syntax Checking
+///
+/// Use [`check_syntax`][Bundle::check_syntax] when building a bundle to compile every script and
+/// reject the bundle if any of them contains a syntax error, so that such errors are found
+/// before the bundle is deployed rather than when a script is first imported.
+///
+/// Checking is validation only: nothing is recorded in the bundle.  Scripts are always stored as
+/// text, and are compiled by the [`Engine`] importing them (with its own optimization level,
+/// custom syntax and disabled symbols) when they are first imported.
+///
+/// ## File Format
+///
+/// A bundle file starts with the bytes `RHAIBNDL` followed by a format version byte, then the
+/// manifest entries and scripts, each list preceded by its number of entries.  Each entry is a
+/// sequence of length-prefixed UTF-8 strings: the key and value of a manifest entry, or the path
+/// and text of a script.  All numbers are little-endian `u32`.
+///
+/// # Example
+///
+/// ```
+/// use rhai::module_resolvers::Bundle;
+///
+/// let mut bundle = Bundle::new();
+///
+/// bundle.set_manifest_entry("version", "1.0");
+/// bundle.insert("utils/math", "fn add(x, y) { x + y }");
+///
+/// let bundle = Bundle::from_bytes(&bundle.to_bytes()).unwrap();
+///
+/// assert_eq!(bundle.get("utils/math"), Some("fn add(x, y) { x + y }"));
+/// assert_eq!(bundle.manifest()["version"], "1.0");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Bundle {
+    manifest: BTreeMap<String, String>,
+    scripts: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    /// Create a new, empty [`Bundle`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Create a [`Bundle`] from all the script files with a particular extension under a
+    /// directory, including sub-directories.
+    ///
+    /// Each script is named by its path relative to the directory, using `/` as separator and
+    /// without the extension.
+    pub fn from_dir(path: impl AsRef<Path>, extension: &str) -> Result<Self, Box<EvalAltResult>> {
+        fn add_dir(
+            bundle: &mut Bundle,
+            dir: &Path,
+            prefix: &str,
+            extension: &str,
+        ) -> Result<(), Box<EvalAltResult>> {
+            let entries = fs::read_dir(dir).map_err(|err| {
+                EvalAltResult::ErrorSystem(
+                    format!("Cannot read directory '{}'", dir.to_string_lossy()),
+                    err.into(),
+                )
+            })?;
+
+            for entry in entries {
+                let path = entry
+                    .map_err(|err| {
+                        EvalAltResult::ErrorSystem(
+                            format!("Cannot read directory '{}'", dir.to_string_lossy()),
+                            err.into(),
+                        )
+                    })?
+                    .path();
+
+                if path.is_dir() {
+                    let name = path.file_name().unwrap().to_string_lossy();
+                    add_dir(bundle, &path, &format!("{}{}/", prefix, name), extension)?;
+                } else if path.extension().map_or(false, |ext| ext == extension) {
+                    let name = format!("{}{}", prefix, path.file_stem().unwrap().to_string_lossy());
+                    let script = fs::read_to_string(&path).map_err(|err| {
+                        EvalAltResult::ErrorSystem(
+                            format!("Cannot read script file '{}'", path.to_string_lossy()),
+                            err.into(),
+                        )
+                    })?;
+                    bundle.insert(name, script);
+                }
+            }
+
+            Ok(())
+        }
+
+        let mut bundle = Self::new();
+        add_dir(&mut bundle, path.as_ref(), "", extension)?;
+        Ok(bundle)
+    }
+    /// Add a script keyed by its path, replacing any existing script with the same path.
+    #[inline(always)]
+    pub fn insert(&mut self, path: impl Into<String>, script: impl Into<String>) -> &mut Self {
+        self.scripts.insert(path.into(), script.into());
+        self
+    }
+    /// Remove a script given its path.
+    #[inline(always)]
+    pub fn remove(&mut self, path: &str) -> Option<String> {
+        self.scripts.remove(path)
+    }
+    /// Does the path exist?
+    #[inline(always)]
+    pub fn contains_path(&self, path: &str) -> bool {
+        self.scripts.contains_key(path)
+    }
+    /// Get the text of a script given its path.
+    #[inline(always)]
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.scripts.get(path).map(|s| s.as_str())
+    }
+    /// Get an iterator of all the script paths.
+    #[inline(always)]
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.scripts.keys().map(|s| s.as_str())
+    }
+    /// Get an iterator of all the scripts, together with their paths.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.scripts.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    /// Get the number of scripts in the [`Bundle`].
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+    /// Is this [`Bundle`] empty?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+    /// Get the manifest of the [`Bundle`].
+    #[inline(always)]
+    pub fn manifest(&self) -> &BTreeMap<String, String> {
+        &self.manifest
+    }
+    /// Set an entry in the manifest of the [`Bundle`].
+    #[inline(always)]
+    pub fn set_manifest_entry(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> &mut Self {
+        self.manifest.insert(key.into(), value.into());
+        self
+    }
+    /// Compile all scripts in the [`Bundle`] with an [`Engine`] to check them for syntax errors.
+    /// The compiled [`AST`]s are not kept and the [`Bundle`] is not modified.
+    ///
+    /// Returns an [`EvalAltResult::ErrorInModule`] wrapping the parse error of the first script
+    /// that fails to compile.
+    pub fn check_syntax(&self, engine: &Engine) -> Result<(), Box<EvalAltResult>> {
+        for (path, script) in self.scripts.iter() {
+            engine.compile(script).map_err(|err| {
+                EvalAltResult::ErrorInModule(path.clone(), err.into(), Position::NONE)
+            })?;
+        }
+        Ok(())
+    }
+    /// Encode the [`Bundle`] into bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn write_str(bytes: &mut Vec<u8>, s: &str) {
+            bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(s.as_bytes());
+        }
+
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(BUNDLE_MAGIC);
+        bytes.push(BUNDLE_VERSION);
+
+        bytes.extend_from_slice(&(self.manifest.len() as u32).to_le_bytes());
+        self.manifest.iter().for_each(|(key, value)| {
+            write_str(&mut bytes, key);
+            write_str(&mut bytes, value);
+        });
+
+        bytes.extend_from_slice(&(self.scripts.len() as u32).to_le_bytes());
+        self.scripts.iter().for_each(|(path, script)| {
+            write_str(&mut bytes, path);
+            write_str(&mut bytes, script);
+        });
+
+        bytes
+    }
+    /// Decode a [`Bundle`] from bytes previously encoded via [`to_bytes`][Bundle::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<EvalAltResult>> {
+        fn invalid(msg: &str) -> Box<EvalAltResult> {
+            EvalAltResult::ErrorSystem("Invalid script bundle".into(), msg.into()).into()
+        }
+        fn read_bytes<'a>(
+            bytes: &mut &'a [u8],
+            len: usize,
+        ) -> Result<&'a [u8], Box<EvalAltResult>> {
+            if bytes.len() < len {
+                return Err(invalid("unexpected end of data"));
+            }
+            let (data, rest) = bytes.split_at(len);
+            *bytes = rest;
+            Ok(data)
+        }
+        fn read_u32(bytes: &mut &[u8]) -> Result<usize, Box<EvalAltResult>> {
+            let mut buf = [0_u8; 4];
+            buf.copy_from_slice(read_bytes(bytes, 4)?);
+            Ok(u32::from_le_bytes(buf) as usize)
+        }
+        fn read_str(bytes: &mut &[u8]) -> Result<String, Box<EvalAltResult>> {
+            let len = read_u32(bytes)?;
+            crate::stdlib::str::from_utf8(read_bytes(bytes, len)?)
+                .map(|s| s.to_string())
+                .map_err(|_| invalid("string is not valid UTF-8"))
+        }
+
+        let mut bytes = bytes;
+
+        if read_bytes(&mut bytes, BUNDLE_MAGIC.len()).ok() != Some(BUNDLE_MAGIC) {
+            return Err(invalid("not a script bundle"));
+        }
+        if read_bytes(&mut bytes, 1)?[0] != BUNDLE_VERSION {
+            return Err(invalid("unsupported format version"));
+        }
+
+        let mut bundle = Self::new();
+
+        for _ in 0..read_u32(&mut bytes)? {
+            let key = read_str(&mut bytes)?;
+            let value = read_str(&mut bytes)?;
+            bundle.manifest.insert(key, value);
+        }
+
+        for _ in 0..read_u32(&mut bytes)? {
+            let path = read_str(&mut bytes)?;
+            let script = read_str(&mut bytes)?;
+            bundle.scripts.insert(path, script);
+        }
+
+        if !bytes.is_empty() {
+            return Err(invalid("unexpected data after the end of the bundle"));
+        }
+
+        Ok(bundle)
+    }
+    /// Load a [`Bundle`] from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<EvalAltResult>> {
+        let path = path.as_ref();
+
+        let bytes = fs::read(path).map_err(|err| {
+            EvalAltResult::ErrorSystem(
+                format!("Cannot read bundle file '{}'", path.to_string_lossy()),
+                err.into(),
+            )
+        })?;
+
+        Self::from_bytes(&bytes)
+    }
+    /// Save the [`Bundle`] into a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<EvalAltResult>> {
+        let path = path.as_ref();
+
+        fs::write(path, self.to_bytes()).map_err(|err| {
+            EvalAltResult::ErrorSystem(
+                format!("Cannot write bundle file '{}'", path.to_string_lossy()),
+                err.into(),
+            )
+            .into()
+        })
+    }
+}
+
+/// A [module][Module] resolution service that serves [modules][Module] from the scripts in a
+/// [`Bundle`].
+///
+/// ## Caching
+///
+/// Resolved [Modules][Module] are cached internally so scripts are not recompiled for subsequent
+/// requests.
+///
+/// Use [`clear_cache`][BundleModuleResolver::clear_cache] to clear the internal cache.
+///
+/// ## Relative Paths
+///
+/// Paths starting with `./` or `../` are resolved relative to the path of the script containing
+/// the `import` statement, if that script was itself loaded from the [`Bundle`].
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Engine, INT};
+/// use rhai::module_resolvers::{Bundle, BundleModuleResolver};
+///
+/// let mut bundle = Bundle::new();
+/// bundle.insert("utils/math", "fn add(x, y) { x + y }");
+///
+/// let mut engine = Engine::new();
+///
+/// engine.set_module_resolver(BundleModuleResolver::new(bundle));
+///
+/// # #[cfg(not(feature = "no_function"))]
+/// assert_eq!(engine.eval::<INT>(r#"import "utils/math" as m; m::add(40, 2)"#).unwrap(), 42);
+/// ```
+#[derive(Debug)]
+pub struct BundleModuleResolver {
+    bundle: Bundle,
+    cache: ModuleCache<String>,
+}
+
+impl BundleModuleResolver {
+    /// Create a new [`BundleModuleResolver`] serving the scripts in a [`Bundle`].
+    #[inline(always)]
+    pub fn new(bundle: Bundle) -> Self {
+        Self {
+            bundle,
+            cache: Default::default(),
+        }
+    }
+    /// Create a new [`BundleModuleResolver`] serving the scripts in a [`Bundle`] file.
+    #[inline(always)]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<EvalAltResult>> {
+        Bundle::load(path).map(Self::new)
+    }
+    /// Get the [`Bundle`] served by this [`BundleModuleResolver`].
+    #[inline(always)]
+    pub fn bundle(&self) -> &Bundle {
+        &self.bundle
+    }
+
+    /// Enable/disable the cache.
+    #[inline(always)]
+    pub fn enable_cache(&mut self, enable: bool) -> &mut Self {
+        self.cache.enable(enable);
+        self
+    }
+    /// Is the cache enabled?
+    #[inline(always)]
+    pub fn is_cache_enabled(&self) -> bool {
+        self.cache.is_enabled()
+    }
+
+    /// Is a particular path cached?
+    #[inline(always)]
+    pub fn is_cached(&self, path: &str) -> bool {
+        self.cache.contains(&super::script_path(path, None))
+    }
+    /// Empty the internal cache.
+    #[inline(always)]
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+    /// Compile a script in the [`Bundle`] into an [`AST`], with its path as source.
+    fn compile(
+        &self,
+        engine: &Engine,
+        script_path: &str,
+        path: &str,
+        pos: Position,
+    ) -> Result<AST, Box<EvalAltResult>> {
+        let script = self
+            .bundle
+            .get(script_path)
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))?;
+
+        super::compile_script(engine, script, script_path, path, pos)
+    }
+}
+
+impl ModuleResolver for BundleModuleResolver {
+    #[inline(always)]
+    fn resolve(
+        &self,
+        engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_with_imports(engine, &Default::default(), source_path, path, pos)
+    }
+
+    fn resolve_with_imports(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let script_path = super::script_path(path, source_path);
+
+        self.cache
+            .get_or_eval(engine, mods, script_path.clone(), path, pos, || {
+                self.compile(engine, &script_path, path, pos)
+            })
+    }
+
+    /// Resolve an `AST` based on a path string.
+    ///
+    /// The script is compiled during each call; the internal cache is by-passed.
+    fn resolve_ast(
+        &self,
+        engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Option<Result<AST, Box<EvalAltResult>>> {
+        let script_path = super::script_path(path, source_path);
+        Some(self.compile(engine, &script_path, path, pos))
+    }
+}
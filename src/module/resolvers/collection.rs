@@ -1,5 +1,5 @@
 use crate::stdlib::{boxed::Box, ops::AddAssign, vec::Vec};
-use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
+use crate::{Engine, EvalAltResult, Imports, Module, ModuleResolver, Position, Shared};
 
 /// [Module] resolution service that holds a collection of module resolvers,
 /// to be searched in sequential order.
@@ -108,19 +108,33 @@ impl ModuleResolversCollection {
 }
 
 impl ModuleResolver for ModuleResolversCollection {
+    #[inline(always)]
     fn resolve(
         &self,
         engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_with_imports(engine, &Default::default(), source_path, path, pos)
+    }
+
+    fn resolve_with_imports(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        source_path: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
         for resolver in self.0.iter() {
-            match resolver.resolve(engine, path, pos) {
+            match resolver.resolve_with_imports(engine, mods, source_path, path, pos) {
                 Ok(module) => return Ok(module),
                 Err(err) => match *err {
                     EvalAltResult::ErrorModuleNotFound(_, _) => continue,
                     EvalAltResult::ErrorInModule(_, err, _) => return Err(err),
-                    _ => panic!("ModuleResolver::resolve returns error that is not ErrorModuleNotFound or ErrorInModule"),
+                    EvalAltResult::ErrorCyclicImport(_, _) => return Err(err),
+                    _ => panic!("ModuleResolver::resolve returns error that is not ErrorModuleNotFound, ErrorInModule or ErrorCyclicImport"),
                 },
             }
         }
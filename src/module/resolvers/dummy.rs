@@ -40,6 +40,7 @@ impl ModuleResolver for DummyModuleResolver {
     fn resolve(
         &self,
         _: &Engine,
+        _: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
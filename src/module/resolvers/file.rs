@@ -1,11 +1,16 @@
+use super::ModuleCache;
+use crate::ast::{ASTNode, Expr, Stmt};
+use crate::fn_native::{OnReloadCallback, SendSync};
 use crate::stdlib::{
     boxed::Box,
     collections::BTreeMap,
-    io::Error as IoError,
+    fmt, fs,
     path::{Path, PathBuf},
-    string::String,
+    string::{String, ToString},
+    time::SystemTime,
+    vec::Vec,
 };
-use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
+use crate::{Engine, EvalAltResult, Imports, Module, ModuleResolver, Position, Shared, AST};
 
 /// A [module][Module] resolution service that loads [module][Module] script files from the file system.
 ///
@@ -17,6 +22,23 @@ use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
 /// Use [`clear_cache`][FileModuleResolver::clear_cache] or
 /// [`clear_cache_for_path`][FileModuleResolver::clear_cache_for_path] to clear the internal cache.
 ///
+/// The cache is keyed by the canonical path of each script file, so the same file imported via
+/// different relative paths is only loaded once.
+///
+/// ## Hot Reloading
+///
+/// When [hot reloading][FileModuleResolver::enable_hot_reload] is enabled, the modification time
+/// and size of each cached script file are checked whenever it is resolved.  A module is
+/// recompiled if its script file has changed, or if any module it imports (directly or
+/// indirectly) has been changed or reloaded.  Use [`on_reload`][FileModuleResolver::on_reload] to
+/// be notified of reloaded modules.
+///
+/// ## Relative Paths
+///
+/// Paths starting with `./` or `../` are resolved relative to the directory of the script
+/// containing the `import` statement, if that script was itself loaded from a file.
+/// All other paths are resolved relative to the base path.
+///
 /// ## Namespace
 ///
 /// When a function within a script file module is called, all functions defined within the same
@@ -37,16 +59,38 @@ use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
 ///
 /// engine.set_module_resolver(resolver);
 /// ```
-#[derive(Debug)]
 pub struct FileModuleResolver {
     base_path: PathBuf,
     extension: String,
-    cache_enabled: bool,
+    hot_reload: bool,
+    on_reload: Option<Box<OnReloadCallback>>,
+    cache: ModuleCache<PathBuf, CacheEntry>,
+}
 
-    #[cfg(not(feature = "sync"))]
-    cache: crate::stdlib::cell::RefCell<BTreeMap<PathBuf, Shared<Module>>>,
-    #[cfg(feature = "sync")]
-    cache: crate::stdlib::sync::RwLock<BTreeMap<PathBuf, Shared<Module>>>,
+/// Modification time and size of a script file.
+type FileStamp = (SystemTime, u64);
+
+/// A [module][Module] held in the internal cache of a [`FileModuleResolver`].
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// The resolved [module][Module].
+    module: Shared<Module>,
+    /// Modification time and size of the script file when it was loaded.
+    modified: Option<FileStamp>,
+    /// Canonical paths, modification times and sizes, and [modules][Module] (if loaded at the
+    /// time) of the script files imported by the script.
+    imports: Vec<(PathBuf, Option<FileStamp>, Option<Shared<Module>>)>,
+}
+
+impl fmt::Debug for FileModuleResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileModuleResolver")
+            .field("base_path", &self.base_path)
+            .field("extension", &self.extension)
+            .field("hot_reload", &self.hot_reload)
+            .field("cache", &self.cache)
+            .finish()
+    }
 }
 
 impl Default for FileModuleResolver {
@@ -102,7 +146,8 @@ impl FileModuleResolver {
         Self {
             base_path: path.into(),
             extension: extension.into(),
-            cache_enabled: true,
+            hot_reload: false,
+            on_reload: None,
             cache: Default::default(),
         }
     }
@@ -157,114 +202,256 @@ impl FileModuleResolver {
     /// Enable/disable the cache.
     #[inline(always)]
     pub fn enable_cache(&mut self, enable: bool) -> &mut Self {
-        self.cache_enabled = enable;
+        self.cache.enable(enable);
         self
     }
     /// Is the cache enabled?
     #[inline(always)]
     pub fn is_cache_enabled(&self) -> bool {
-        self.cache_enabled
+        self.cache.is_enabled()
+    }
+
+    /// Enable/disable hot reloading of changed script files.
+    ///
+    /// Hot reloading has no effect when the cache is disabled.
+    #[inline(always)]
+    pub fn enable_hot_reload(&mut self, enable: bool) -> &mut Self {
+        self.hot_reload = enable;
+        self
+    }
+    /// Is hot reloading enabled?
+    #[inline(always)]
+    pub fn is_hot_reload_enabled(&self) -> bool {
+        self.hot_reload
+    }
+    /// Register a callback that is called with the file path of each [module][Module] that is
+    /// reloaded because it is out of date.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, RwLock};
+    /// use rhai::Engine;
+    /// use rhai::module_resolvers::FileModuleResolver;
+    ///
+    /// let reloaded = Arc::new(RwLock::new(Vec::new()));
+    /// let log = reloaded.clone();
+    ///
+    /// let mut resolver = FileModuleResolver::new_with_path("./scripts");
+    ///
+    /// resolver
+    ///     .enable_hot_reload(true)
+    ///     .on_reload(move |path| log.write().unwrap().push(path.to_path_buf()));
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_module_resolver(resolver);
+    /// ```
+    #[inline(always)]
+    pub fn on_reload(&mut self, callback: impl Fn(&Path) + SendSync + 'static) -> &mut Self {
+        self.on_reload = Some(Box::new(callback));
+        self
     }
 
     /// Is a particular path cached?
     #[inline(always)]
     pub fn is_cached(&self, path: &str) -> bool {
-        if !self.cache_enabled {
-            return false;
-        }
-
-        let file_path = self.get_file_path(path);
-
-        #[cfg(not(feature = "sync"))]
-        return self.cache.borrow_mut().contains_key(&file_path);
-        #[cfg(feature = "sync")]
-        return self.cache.write().unwrap().contains_key(&file_path);
+        self.cache
+            .contains(&canonicalize(self.get_file_path(path, None)))
     }
     /// Empty the internal cache.
     #[inline(always)]
     pub fn clear_cache(&mut self) {
-        #[cfg(not(feature = "sync"))]
-        self.cache.borrow_mut().clear();
-        #[cfg(feature = "sync")]
-        self.cache.write().unwrap().clear();
+        self.cache.clear();
     }
     /// Remove the specified path from internal cache.
     ///
     /// The next time this path is resolved, the script file will be loaded once again.
     #[inline(always)]
     pub fn clear_cache_for_path(&mut self, path: &str) -> Option<Shared<Module>> {
-        let file_path = self.get_file_path(path);
-
-        #[cfg(not(feature = "sync"))]
-        return self
-            .cache
-            .borrow_mut()
-            .remove_entry(&file_path)
-            .map(|(_, v)| v);
-        #[cfg(feature = "sync")]
-        return self
-            .cache
-            .write()
-            .unwrap()
-            .remove_entry(&file_path)
-            .map(|(_, v)| v);
+        self.cache
+            .remove(&canonicalize(self.get_file_path(path, None)))
+            .map(|entry| entry.module)
     }
     /// Construct a full file path.
-    fn get_file_path(&self, path: &str) -> PathBuf {
-        let mut file_path = self.base_path.clone();
+    ///
+    /// Paths starting with `./` or `../` are relative to the directory of `source_path`, if any.
+    fn get_file_path(&self, path: &str, source_path: Option<&str>) -> PathBuf {
+        let path = Path::new(path);
+
+        let mut file_path = match source_path.and_then(|p| Path::new(p).parent()) {
+            Some(dir)
+                if !dir.as_os_str().is_empty()
+                    && (path.starts_with(".") || path.starts_with("..")) =>
+            {
+                dir.to_path_buf()
+            }
+            _ => self.base_path.clone(),
+        };
+
         file_path.push(path);
         file_path.set_extension(&self.extension); // Force extension
         file_path
     }
+    /// Load a script file and compile it into an [`AST`], with its file path as source.
+    fn compile(
+        &self,
+        engine: &Engine,
+        file_path: &Path,
+        path: &str,
+        pos: Position,
+    ) -> Result<AST, Box<EvalAltResult>> {
+        let script = fs::read_to_string(file_path)
+            .map_err(|_| EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))?;
+
+        super::compile_script(engine, &script, &file_path.to_string_lossy(), path, pos)
+    }
+}
+
+/// Get the modification time and size of a file, if available.
+#[inline(always)]
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    path.metadata()
+        .and_then(|m| m.modified().map(|t| (t, m.len())))
+        .ok()
+}
+
+/// Is a cached [module][Module] out of date?
+///
+/// A [module][Module] is out of date if its script file has been modified, or if any
+/// [module][Module] it imports (directly or indirectly) has been modified or reloaded.
+fn is_stale(
+    cache: &BTreeMap<PathBuf, CacheEntry>,
+    path: &Path,
+    visited: &mut Vec<PathBuf>,
+) -> bool {
+    // Guard against cyclic imports
+    if visited.iter().any(|p| p == path) {
+        return false;
+    }
+    visited.push(path.to_path_buf());
+
+    let entry = match cache.get(path) {
+        Some(entry) => entry,
+        None => return false,
+    };
+
+    entry.modified != file_stamp(path)
+        || entry.imports.iter().any(|(import, modified, module)| {
+            *modified != file_stamp(import)
+                || module.as_ref().map_or(false, |m| {
+                    cache
+                        .get(import)
+                        .map_or(true, |e| !Shared::ptr_eq(&e.module, m))
+                })
+                || is_stale(cache, import, visited)
+        })
+}
+
+/// Get the canonical form of a file path, or the path itself if it cannot be canonicalized
+/// (e.g. it does not exist).
+#[inline(always)]
+fn canonicalize(path: PathBuf) -> PathBuf {
+    path.canonicalize().unwrap_or(path)
 }
 
 impl ModuleResolver for FileModuleResolver {
+    #[inline(always)]
     fn resolve(
         &self,
         engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_with_imports(engine, &Default::default(), source_path, path, pos)
+    }
+
+    fn resolve_with_imports(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        source_path: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
         // Construct the script file path
-        let file_path = self.get_file_path(path);
+        let file_path = canonicalize(self.get_file_path(path, source_path));
+
+        let mut reloading = false;
 
         // See if it is cached
         if self.is_cache_enabled() {
-            #[cfg(not(feature = "sync"))]
-            let c = self.cache.borrow();
-            #[cfg(feature = "sync")]
-            let c = self.cache.read().unwrap();
+            let cached = self.cache.with(|c| {
+                let module = c.get(&file_path).map(|entry| entry.module.clone())?;
+
+                if !self.hot_reload || !is_stale(c, &file_path, &mut Vec::new()) {
+                    return Some(module);
+                }
+
+                // Remove the out-of-date module so it is reloaded
+                c.remove(&file_path);
+                reloading = true;
+                None
+            });
 
-            if let Some(module) = c.get(&file_path) {
-                return Ok(module.clone());
+            if let Some(module) = cached {
+                return Ok(module);
             }
         }
 
-        // Load the script file and compile it
-        let scope = Default::default();
+        // Take the stamp before reading the file, so that changes made while it is being
+        // compiled and evaluated are detected the next time it is resolved
+        let modified = file_stamp(&file_path);
 
-        let mut ast = engine
-            .compile_file(file_path.clone())
-            .map_err(|err| match *err {
-                EvalAltResult::ErrorSystem(_, err) if err.is::<IoError>() => {
-                    Box::new(EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))
-                }
-                _ => Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos)),
-            })?;
+        let ast = self.compile(engine, &file_path, path, pos)?;
+
+        // Collect the script files imported by the module
+        let mut imports = Vec::new();
+
+        if self.hot_reload {
+            let source = file_path.to_string_lossy();
 
-        ast.set_source(path);
+            ast.walk(&mut |path| {
+                if let ASTNode::Stmt(Stmt::Import(Expr::StringConstant(s, _), _, _)) =
+                    path.last().unwrap()
+                {
+                    imports.push(canonicalize(self.get_file_path(s, Some(&source))));
+                }
+                true
+            });
+        }
 
-        // Make a module from the AST
-        let m: Shared<Module> = Module::eval_ast_as_new(scope, &ast, engine)
-            .map_err(|err| Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos)))?
-            .into();
+        let m = super::eval_script(engine, mods, &ast, path, pos)?;
 
         // Put it into the cache
         if self.is_cache_enabled() {
-            #[cfg(not(feature = "sync"))]
-            self.cache.borrow_mut().insert(file_path, m.clone());
-            #[cfg(feature = "sync")]
-            self.cache.write().unwrap().insert(file_path, m.clone());
+            self.cache.with(|c| {
+                let imports = imports
+                    .into_iter()
+                    .map(|path| match c.get(&path) {
+                        // Use the stamp taken when the imported module was loaded
+                        Some(entry) => (path, entry.modified, Some(entry.module.clone())),
+                        None => {
+                            let modified = file_stamp(&path);
+                            (path, modified, None)
+                        }
+                    })
+                    .collect();
+
+                let entry = CacheEntry {
+                    module: m.clone(),
+                    modified,
+                    imports,
+                };
+
+                c.insert(file_path.clone(), entry);
+            });
+        }
+
+        if reloading {
+            if let Some(ref callback) = self.on_reload {
+                callback(&file_path);
+            }
         }
 
         Ok(m)
@@ -276,24 +463,13 @@ impl ModuleResolver for FileModuleResolver {
     fn resolve_ast(
         &self,
         engine: &Engine,
+        source_path: Option<&str>,
         path: &str,
         pos: Position,
-    ) -> Option<Result<crate::AST, Box<EvalAltResult>>> {
+    ) -> Option<Result<AST, Box<EvalAltResult>>> {
         // Construct the script file path
-        let file_path = self.get_file_path(path);
+        let file_path = canonicalize(self.get_file_path(path, source_path));
 
-        // Load the script file and compile it
-        match engine.compile_file(file_path).map_err(|err| match *err {
-            EvalAltResult::ErrorSystem(_, err) if err.is::<IoError>() => {
-                Box::new(EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))
-            }
-            _ => Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos)),
-        }) {
-            Ok(mut ast) => {
-                ast.set_source(path);
-                Some(Ok(ast))
-            }
-            err @ Err(_) => Some(err),
-        }
+        Some(self.compile(engine, &file_path, path, pos))
     }
 }
@@ -0,0 +1,165 @@
+use super::ModuleCache;
+use crate::fn_native::{OnLoadScriptCallback, SendSync};
+use crate::stdlib::{
+    boxed::Box,
+    fmt,
+    string::{String, ToString},
+};
+use crate::{Engine, EvalAltResult, Imports, Module, ModuleResolver, Position, Shared, AST};
+
+/// A [module][Module] resolution service that loads the text of [module][Module] scripts via a
+/// host-provided loader function (e.g. from a database).
+///
+/// The loader is called with the full path of the script and returns its text.  It should return
+/// [`EvalAltResult::ErrorModuleNotFound`] if there is no script under that path; all other errors
+/// are reported nested within [`EvalAltResult::ErrorInModule`].
+///
+/// ## Caching
+///
+/// Resolved [Modules][Module] are cached internally so scripts are not reloaded and recompiled
+/// for subsequent requests.
+///
+/// Use [`clear_cache`][FnModuleResolver::clear_cache] or
+/// [`clear_cache_for_path`][FnModuleResolver::clear_cache_for_path] to clear the internal cache
+/// when scripts change.
+///
+/// ## Relative Paths
+///
+/// Paths starting with `./` or `../` are resolved relative to the path of the script containing
+/// the `import` statement, if that script was itself loaded by this resolver.  The full path
+/// becomes the source of the loaded script.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Engine, EvalAltResult, INT};
+/// use rhai::module_resolvers::FnModuleResolver;
+///
+/// let resolver = FnModuleResolver::new(|_, path| match path {
+///     "utils/math" => Ok("fn add(x, y) { x + y }".into()),
+///     _ => Err(EvalAltResult::ErrorModuleNotFound(path.into(), Default::default()).into()),
+/// });
+///
+/// let mut engine = Engine::new();
+///
+/// engine.set_module_resolver(resolver);
+///
+/// # #[cfg(not(feature = "no_function"))]
+/// assert_eq!(engine.eval::<INT>(r#"import "utils/math" as m; m::add(40, 2)"#).unwrap(), 42);
+/// ```
+pub struct FnModuleResolver {
+    loader: Box<OnLoadScriptCallback>,
+    cache: ModuleCache<String>,
+}
+
+impl fmt::Debug for FnModuleResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnModuleResolver")
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl FnModuleResolver {
+    /// Create a new [`FnModuleResolver`] loading scripts via a loader function.
+    #[inline(always)]
+    pub fn new(
+        loader: impl Fn(&Engine, &str) -> Result<String, Box<EvalAltResult>> + SendSync + 'static,
+    ) -> Self {
+        Self {
+            loader: Box::new(loader),
+            cache: Default::default(),
+        }
+    }
+
+    /// Enable/disable the cache.
+    #[inline(always)]
+    pub fn enable_cache(&mut self, enable: bool) -> &mut Self {
+        self.cache.enable(enable);
+        self
+    }
+    /// Is the cache enabled?
+    #[inline(always)]
+    pub fn is_cache_enabled(&self) -> bool {
+        self.cache.is_enabled()
+    }
+
+    /// Is a particular path cached?
+    #[inline(always)]
+    pub fn is_cached(&self, path: &str) -> bool {
+        self.cache.contains(&super::script_path(path, None))
+    }
+    /// Empty the internal cache.
+    #[inline(always)]
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+    /// Remove the specified path from internal cache.
+    ///
+    /// The next time this path is resolved, the script will be loaded once again.
+    #[inline(always)]
+    pub fn clear_cache_for_path(&mut self, path: &str) -> Option<Shared<Module>> {
+        self.cache.remove(&super::script_path(path, None))
+    }
+    /// Load a script via the loader function and compile it into an [`AST`], with its path as
+    /// source.
+    fn compile(
+        &self,
+        engine: &Engine,
+        script_path: &str,
+        path: &str,
+        pos: Position,
+    ) -> Result<AST, Box<EvalAltResult>> {
+        let script = (self.loader)(engine, script_path).map_err(|err| match *err {
+            EvalAltResult::ErrorModuleNotFound(_, _) => {
+                Box::new(EvalAltResult::ErrorModuleNotFound(path.to_string(), pos))
+            }
+            _ => Box::new(EvalAltResult::ErrorInModule(path.to_string(), err, pos)),
+        })?;
+
+        super::compile_script(engine, &script, script_path, path, pos)
+    }
+}
+
+impl ModuleResolver for FnModuleResolver {
+    #[inline(always)]
+    fn resolve(
+        &self,
+        engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve_with_imports(engine, &Default::default(), source_path, path, pos)
+    }
+
+    fn resolve_with_imports(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        let script_path = super::script_path(path, source_path);
+
+        self.cache
+            .get_or_eval(engine, mods, script_path.clone(), path, pos, || {
+                self.compile(engine, &script_path, path, pos)
+            })
+    }
+
+    /// Resolve an `AST` based on a path string.
+    ///
+    /// The script is loaded and compiled during each call; the internal cache is by-passed.
+    fn resolve_ast(
+        &self,
+        engine: &Engine,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Option<Result<AST, Box<EvalAltResult>>> {
+        let script_path = super::script_path(path, source_path);
+        Some(self.compile(engine, &script_path, path, pos))
+    }
+}
@@ -1,6 +1,11 @@
 use crate::fn_native::SendSync;
-use crate::stdlib::boxed::Box;
-use crate::{Engine, EvalAltResult, Module, Position, Shared, AST};
+use crate::stdlib::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::{Engine, EvalAltResult, Imports, Module, Position, Shared, AST};
 
 mod dummy;
 pub use dummy::DummyModuleResolver;
@@ -19,16 +24,206 @@ pub use file::FileModuleResolver;
 mod stat;
 pub use stat::StaticModuleResolver;
 
+mod func;
+pub use func::FnModuleResolver;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+mod bundle;
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+pub use bundle::{Bundle, BundleModuleResolver};
+
+/// Construct the full path of a script named by a `/`-separated path.
+///
+/// Paths starting with `./` or `../` are relative to the directory of `source_path`, if any.
+pub(crate) fn script_path(path: &str, source_path: Option<&str>) -> String {
+    let mut segments = Vec::new();
+
+    if path.starts_with("./") || path.starts_with("../") {
+        if let Some(source) = source_path {
+            segments.extend(source.split('/'));
+            segments.pop();
+        }
+    }
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+/// Compile the text of a [module][Module] script into an [`AST`].
+///
+/// `source` (the full path of the script) becomes the source of the [`AST`], so that relative
+/// imports within the [module][Module] can be resolved.
+pub(crate) fn compile_script(
+    engine: &Engine,
+    script: &str,
+    source: &str,
+    path: &str,
+    pos: Position,
+) -> Result<AST, Box<EvalAltResult>> {
+    let mut ast = engine
+        .compile(script)
+        .map_err(|err| EvalAltResult::ErrorInModule(path.to_string(), err.into(), pos))?;
+
+    ast.set_source(source);
+
+    Ok(ast)
+}
+
+/// Evaluate the [`AST`] of a [module][Module] script into a [module][Module].
+///
+/// Errors are nested within [`EvalAltResult::ErrorInModule`], except cyclic imports which are
+/// reported as they are rather than nested within each [module][Module] in the cycle.
+pub(crate) fn eval_script(
+    engine: &Engine,
+    mods: &Imports,
+    ast: &AST,
+    path: &str,
+    pos: Position,
+) -> Result<Shared<Module>, Box<EvalAltResult>> {
+    Module::eval_ast_as_new_with_imports(Default::default(), ast, engine, mods)
+        .map(Into::into)
+        .map_err(|err| match *err {
+            EvalAltResult::ErrorCyclicImport(_, _) => err,
+            _ => EvalAltResult::ErrorInModule(path.to_string(), err, pos).into(),
+        })
+}
+
+/// A cache of resolved [modules][Module] held by a [module resolver][ModuleResolver], keyed by
+/// the full path of each script.
+#[derive(Debug)]
+pub(crate) struct ModuleCache<K: Ord, V = Shared<Module>> {
+    enabled: bool,
+
+    #[cfg(not(feature = "sync"))]
+    map: crate::stdlib::cell::RefCell<BTreeMap<K, V>>,
+    #[cfg(feature = "sync")]
+    map: crate::stdlib::sync::RwLock<BTreeMap<K, V>>,
+}
+
+impl<K: Ord, V> Default for ModuleCache<K, V> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            map: Default::default(),
+        }
+    }
+}
+
+impl<K: Ord, V> ModuleCache<K, V> {
+    /// Enable/disable the cache.
+    #[inline(always)]
+    pub fn enable(&mut self, enable: bool) {
+        self.enabled = enable;
+    }
+    /// Is the cache enabled?
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    /// Access the cached entries.
+    #[inline(always)]
+    pub fn with<R>(&self, f: impl FnOnce(&mut BTreeMap<K, V>) -> R) -> R {
+        #[cfg(not(feature = "sync"))]
+        return f(&mut self.map.borrow_mut());
+        #[cfg(feature = "sync")]
+        return f(&mut self.map.write().unwrap());
+    }
+    /// Is a particular path cached?
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.enabled && self.with(|c| c.contains_key(key))
+    }
+    /// Empty the cache.
+    #[inline(always)]
+    pub fn clear(&self) {
+        self.with(|c| c.clear());
+    }
+    /// Remove a particular path from the cache.
+    #[inline(always)]
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.with(|c| c.remove(key))
+    }
+}
+
+impl<K: Ord + Clone> ModuleCache<K> {
+    /// Get the cached [module][Module] for a path, or compile the script via `compile`, evaluate
+    /// it into a [module][Module] and cache it.
+    pub fn get_or_eval(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        key: K,
+        path: &str,
+        pos: Position,
+        compile: impl FnOnce() -> Result<AST, Box<EvalAltResult>>,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        if self.enabled {
+            if let Some(module) = self.with(|c| c.get(&key).cloned()) {
+                return Ok(module);
+            }
+        }
+
+        let module = eval_script(engine, mods, &compile()?, path, pos)?;
+
+        if self.enabled {
+            self.with(|c| c.insert(key, module.clone()));
+        }
+
+        Ok(module)
+    }
+}
+
 /// Trait that encapsulates a module resolution service.
 pub trait ModuleResolver: SendSync {
     /// Resolve a module based on a path string.
+    ///
+    /// `source_path` is the source of the script containing the `import` statement (if any),
+    /// which can be used to resolve relative paths.
     fn resolve(
         &self,
         engine: &Engine,
+        source_path: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>>;
 
+    /// Resolve a module based on a path string, as part of evaluating a script with a stack of
+    /// imported modules.
+    ///
+    /// `mods` carries the chain of `import` statements being resolved.  Module resolvers that
+    /// evaluate scripts should pass it on to
+    /// [`Module::eval_ast_as_new_with_imports`][Module::eval_ast_as_new_with_imports], so that
+    /// cyclic imports are detected.
+    ///
+    /// ## Low-Level API
+    ///
+    /// The default implementation calls [`resolve`][ModuleResolver::resolve], so cyclic imports
+    /// through script modules evaluated by this module resolver are not detected.
+    #[allow(unused_variables)]
+    fn resolve_with_imports(
+        &self,
+        engine: &Engine,
+        mods: &Imports,
+        source_path: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve(engine, source_path, path, pos)
+    }
+
     /// Resolve an `AST` based on a path string.
     ///
     /// Returns [`None`] (default) if such resolution is not supported
@@ -42,6 +237,7 @@ pub trait ModuleResolver: SendSync {
     fn resolve_ast(
         &self,
         engine: &Engine,
+        source_path: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Option<Result<AST, Box<EvalAltResult>>> {
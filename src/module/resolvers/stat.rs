@@ -1,5 +1,13 @@
-use crate::stdlib::{boxed::Box, collections::BTreeMap, ops::AddAssign, string::String};
-use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared};
+use crate::ast::{ASTNode, Expr, Stmt};
+use crate::stdlib::{
+    boxed::Box,
+    collections::BTreeMap,
+    ops::AddAssign,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use crate::{Engine, EvalAltResult, Module, ModuleResolver, Position, Shared, AST};
 
 /// A static [module][Module] resolution service that serves [modules][Module] added into it.
 ///
@@ -48,6 +56,64 @@ impl StaticModuleResolver {
         module.build_index();
         self.0.insert(path.into(), module.into());
     }
+    /// Compile and evaluate scripts into [modules][Module], each keyed by its path.
+    ///
+    /// Paths are `/`-separated (e.g. `utils/math`).  A script importing another script in the
+    /// list (via its path, or a path starting with `./` or `../` relative to its own path) is
+    /// evaluated after the script it imports.  Other imports are resolved as usual when each
+    /// script is evaluated.
+    ///
+    /// All the scripts are compiled before any is evaluated.  A script with a syntax error fails
+    /// with [`ErrorInModule`][EvalAltResult::ErrorInModule] naming the script, and no
+    /// [module][Module] is added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, INT};
+    /// use rhai::module_resolvers::StaticModuleResolver;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let mut resolver = StaticModuleResolver::new();
+    /// resolver.insert_scripts(&engine, &[
+    ///     ("utils/math", "export const ANSWER = 40;"),
+    ///     ("main", r#"import "utils/math" as m; export const ANSWER = m::ANSWER + 2;"#),
+    /// ]).unwrap();
+    ///
+    /// engine.set_module_resolver(resolver);
+    ///
+    /// assert_eq!(engine.eval::<INT>(r#"import "main" as m; m::ANSWER"#).unwrap(), 42);
+    /// ```
+    pub fn insert_scripts(
+        &mut self,
+        engine: &Engine,
+        scripts: &[(&str, &str)],
+    ) -> Result<(), Box<EvalAltResult>> {
+        // Compile all the scripts first
+        let mut asts = BTreeMap::new();
+
+        for &(path, script) in scripts {
+            let mut ast = engine.compile(script).map_err(|err| {
+                EvalAltResult::ErrorInModule(path.to_string(), err.into(), Position::NONE)
+            })?;
+            ast.set_source(path);
+            asts.insert(path.to_string(), ast);
+        }
+
+        let mut evaluated = BTreeMap::new();
+
+        for path in asts.keys() {
+            let mut chain = vec![(path.clone(), path.clone(), Position::NONE)];
+            eval_script(engine, &asts, path, &mut evaluated, &mut chain)?;
+        }
+
+        evaluated.into_iter().for_each(|(path, module)| {
+            self.0.insert(path, module);
+        });
+
+        Ok(())
+    }
     /// Remove a [module][Module] given its path.
     #[inline(always)]
     pub fn remove(&mut self, path: &str) -> Option<Shared<Module>> {
@@ -110,11 +176,78 @@ impl StaticModuleResolver {
     }
 }
 
+/// Evaluate a script into a [module][Module], after evaluating the scripts it imports.
+///
+/// `chain` holds the path of each script being evaluated, together with the `import` path and
+/// position that led to it.
+fn eval_script(
+    engine: &Engine,
+    asts: &BTreeMap<String, AST>,
+    path: &str,
+    evaluated: &mut BTreeMap<String, Shared<Module>>,
+    chain: &mut Vec<(String, String, Position)>,
+) -> Result<(), Box<EvalAltResult>> {
+    if evaluated.contains_key(path) {
+        return Ok(());
+    }
+
+    // Guard against cyclic imports
+    if let Some(index) = chain[..chain.len() - 1]
+        .iter()
+        .position(|(script, _, _)| script == path)
+    {
+        let cycle: Vec<_> = chain[index + 1..]
+            .iter()
+            .map(|(_, import, pos)| (import.clone(), *pos))
+            .collect();
+        let pos = cycle.last().unwrap().1;
+        return Err(EvalAltResult::ErrorCyclicImport(cycle, pos).into());
+    }
+
+    let mut ast = asts[path].clone();
+
+    // Find the imported scripts
+    let mut imports = Vec::new();
+
+    ast.walk(&mut |nodes| {
+        if let ASTNode::Stmt(Stmt::Import(Expr::StringConstant(s, pos), _, _)) =
+            nodes.last().unwrap()
+        {
+            let script = super::script_path(s, Some(path));
+
+            if asts.contains_key(&script) {
+                imports.push((script, s.to_string(), *pos));
+            }
+        }
+        true
+    });
+
+    // Evaluate the imported scripts first
+    let mut resolver = StaticModuleResolver::new();
+
+    for (script, import, pos) in imports {
+        chain.push((script.clone(), import.clone(), pos));
+        eval_script(engine, asts, &script, evaluated, chain)?;
+        chain.pop();
+
+        resolver.0.insert(import, evaluated[&script].clone());
+    }
+
+    ast.set_resolver(resolver);
+
+    let module = super::eval_script(engine, &Default::default(), &ast, path, Position::NONE)?;
+
+    evaluated.insert(path.to_string(), module);
+
+    Ok(())
+}
+
 impl ModuleResolver for StaticModuleResolver {
     #[inline(always)]
     fn resolve(
         &self,
         _: &Engine,
+        _: Option<&str>,
         path: &str,
         pos: Position,
     ) -> Result<Shared<Module>, Box<EvalAltResult>> {
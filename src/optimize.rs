@@ -673,6 +673,15 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
         // [ items .. ]
         #[cfg(not(feature = "no_index"))]
         Expr::Array(x, _) => x.iter_mut().for_each(|expr| optimize_expr(expr, state)),
+        // #[ constant .. ]
+        #[cfg(not(feature = "no_index"))]
+        Expr::Set(_, _) if expr.is_constant() => {
+            state.set_dirty();
+            *expr = Expr::DynamicConstant(Box::new(expr.get_constant_value().unwrap()), expr.position());
+        }
+        // #[ items .. ]
+        #[cfg(not(feature = "no_index"))]
+        Expr::Set(x, _) => x.iter_mut().for_each(|expr| optimize_expr(expr, state)),
         // #{ key:constant, .. }
         #[cfg(not(feature = "no_object"))]
         Expr::Map(_, _) if expr.is_constant() => {
@@ -682,6 +691,9 @@ fn optimize_expr(expr: &mut Expr, state: &mut State) {
         // #{ key:value, .. }
         #[cfg(not(feature = "no_object"))]
         Expr::Map(x, _) => x.iter_mut().for_each(|(_, expr)| optimize_expr(expr, state)),
+        // Type { field:value, .. }
+        #[cfg(not(feature = "no_object"))]
+        Expr::Record(x, _) => x.1.iter_mut().for_each(|(_, expr)| optimize_expr(expr, state)),
         // lhs && rhs
         Expr::And(x, _) => match (&mut x.lhs, &mut x.rhs) {
             // true && rhs -> rhs
@@ -902,6 +914,7 @@ pub fn optimize_into_ast(
                     #[cfg(not(feature = "no_module"))]
                     mods: Default::default(),
                     comments: Default::default(),
+                    is_generator: fn_def.is_generator,
                 })
                 .for_each(|fn_def| {
                     lib2.set_script_fn(fn_def);
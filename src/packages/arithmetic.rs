@@ -201,6 +201,10 @@ def_package!(crate:ArithmeticPackage:"Basic arithmetic", lib, {
     // Decimal functions
     #[cfg(feature = "decimal")]
     combine_with_exported_module!(lib, "decimal", decimal_functions);
+
+    // BigInt functions
+    #[cfg(feature = "bigint")]
+    combine_with_exported_module!(lib, "bigint", bigint_functions);
 });
 
 gen_arithmetic_functions!(arith_basic => INT);
@@ -515,3 +519,90 @@ pub mod decimal_functions {
         }
     }
 }
+
+#[cfg(feature = "bigint")]
+#[export_module]
+pub mod bigint_functions {
+    use num_bigint::{BigInt, Sign};
+    use num_traits::{Signed, Zero};
+
+    #[rhai_fn(skip, return_raw)]
+    pub fn add(x: BigInt, y: BigInt) -> Result<BigInt, Box<EvalAltResult>> {
+        Ok(x + y)
+    }
+    #[rhai_fn(skip, return_raw)]
+    pub fn subtract(x: BigInt, y: BigInt) -> Result<BigInt, Box<EvalAltResult>> {
+        Ok(x - y)
+    }
+    #[rhai_fn(skip, return_raw)]
+    pub fn multiply(x: BigInt, y: BigInt) -> Result<BigInt, Box<EvalAltResult>> {
+        Ok(x * y)
+    }
+    #[rhai_fn(skip, return_raw)]
+    pub fn divide(x: BigInt, y: BigInt) -> Result<BigInt, Box<EvalAltResult>> {
+        // Division by zero always panics, even when unchecked
+        if y.is_zero() {
+            Err(make_err(format!("Division by zero: {} / {}", x, y)))
+        } else {
+            Ok(x / y)
+        }
+    }
+    #[rhai_fn(skip, return_raw)]
+    pub fn modulo(x: BigInt, y: BigInt) -> Result<BigInt, Box<EvalAltResult>> {
+        // Division by zero always panics, even when unchecked
+        if y.is_zero() {
+            Err(make_err(format!("Modulo division by zero: {} % {}", x, y)))
+        } else {
+            Ok(x % y)
+        }
+    }
+    #[rhai_fn(name = "**", return_raw)]
+    pub fn power(_ctx: NativeCallContext, x: BigInt, y: INT) -> Result<BigInt, Box<EvalAltResult>> {
+        if y < 0 {
+            Err(make_err(format!(
+                "Integer raised to a negative index: {} ~ {}",
+                x, y
+            )))
+        } else if cfg!(not(feature = "only_i32")) && y > (u32::MAX as INT) {
+            Err(make_err(format!(
+                "Integer raised to too large an index: {} ~ {}",
+                x, y
+            )))
+        } else {
+            // Check if the result will be over the max size limit before computing it
+            #[cfg(not(feature = "unchecked"))]
+            if _ctx.engine().max_bigint_bits() > 0 && x.bits() > 1 {
+                // |x| >= 2^(bits - 1), so |x|^y has at least (bits - 1) * y + 1 bits
+                let min_bits = (x.bits() - 1).saturating_mul(y as u64).saturating_add(1);
+
+                if min_bits > _ctx.engine().max_bigint_bits() as u64 {
+                    return EvalAltResult::ErrorDataTooLarge(
+                        "Size of bigint".to_string(),
+                        Position::NONE,
+                    )
+                    .into();
+                }
+            }
+
+            Ok(x.pow(y as u32))
+        }
+    }
+    #[rhai_fn(name = "-")]
+    pub fn neg(x: BigInt) -> BigInt {
+        -x
+    }
+    #[rhai_fn(name = "+")]
+    pub fn plus(x: BigInt) -> BigInt {
+        x
+    }
+    pub fn abs(x: BigInt) -> BigInt {
+        x.abs()
+    }
+    pub fn sign(x: BigInt) -> INT {
+        match x.sign() {
+            Sign::Minus => -1,
+            Sign::NoSign => 0,
+            Sign::Plus => 1,
+        }
+    }
+}
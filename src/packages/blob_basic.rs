@@ -0,0 +1,317 @@
+#![cfg(not(feature = "no_index"))]
+#![allow(non_snake_case)]
+
+use super::arithmetic::make_err;
+use crate::plugin::*;
+use crate::stdlib::{any::TypeId, boxed::Box, format, mem, string::String, string::ToString};
+use crate::{def_package, Blob, Dynamic, EvalAltResult, NativeCallContext, Position, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+/// Number of bytes in an [`INT`].
+const INT_BYTES: usize = mem::size_of::<INT>();
+
+/// Number of bytes in a [`FLOAT`].
+#[cfg(not(feature = "no_float"))]
+const FLOAT_BYTES: usize = mem::size_of::<FLOAT>();
+
+/// Characters used in base64 encoding.
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+def_package!(crate:BasicBlobPackage:"Basic blob (byte buffer) utilities.", lib, {
+    combine_with_exported_module!(lib, "blob", blob_functions);
+
+    // Register blob iterator
+    lib.set_iter(
+        TypeId::of::<Blob>(),
+        |blob| Box::new(blob.cast::<Blob>().into_iter().map(|b| (b as INT).into()))
+    );
+});
+
+/// Clamp a range of bytes within a [`Blob`] of a particular length,
+/// returning the start offset and the number of bytes.
+pub(super) fn calc_range(blob_len: usize, start: INT, len: INT) -> (usize, usize) {
+    let start = if start < 0 {
+        0
+    } else if start as usize >= blob_len {
+        blob_len
+    } else {
+        start as usize
+    };
+
+    let len = if len < 0 {
+        0
+    } else if len as usize > blob_len - start {
+        blob_len - start
+    } else {
+        len as usize
+    };
+
+    (start, len)
+}
+
+/// Copy bytes from a [`Blob`] into a buffer, starting at `start`.
+/// Bytes beyond the end of the [`Blob`] are left as zero.
+fn read_bytes(blob: &Blob, start: INT, buf: &mut [u8]) {
+    let (start, len) = calc_range(blob.len(), start, buf.len() as INT);
+    buf[..len].copy_from_slice(&blob[start..start + len]);
+}
+
+/// Copy bytes from a buffer into a [`Blob`], starting at `start`.
+/// Bytes beyond the end of the [`Blob`] are discarded.
+fn write_bytes(blob: &mut Blob, start: INT, buf: &[u8]) {
+    let (start, len) = calc_range(blob.len(), start, buf.len() as INT);
+    blob[start..start + len].copy_from_slice(&buf[..len]);
+}
+
+#[export_module]
+mod blob_functions {
+    #[rhai_fn(name = "blob")]
+    pub fn new_blob() -> Blob {
+        Blob::new()
+    }
+    #[rhai_fn(name = "blob", return_raw)]
+    pub fn new_blob_with_len(ctx: NativeCallContext, len: INT) -> Result<Blob, Box<EvalAltResult>> {
+        new_blob_with_value(ctx, len, 0)
+    }
+    #[rhai_fn(name = "blob", return_raw)]
+    pub fn new_blob_with_value(
+        _ctx: NativeCallContext,
+        len: INT,
+        value: INT,
+    ) -> Result<Blob, Box<EvalAltResult>> {
+        // Check if blob will be over max size limit
+        #[cfg(not(feature = "unchecked"))]
+        if _ctx.engine().max_array_size() > 0
+            && len > 0
+            && (len as usize) > _ctx.engine().max_array_size()
+        {
+            return EvalAltResult::ErrorDataTooLarge("Size of blob".to_string(), Position::NONE)
+                .into();
+        }
+
+        let mut blob = Blob::new();
+
+        if len > 0 {
+            blob.resize(len as usize, (value & 0xff) as u8);
+        }
+
+        Ok(blob)
+    }
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(blob: &mut Blob) -> INT {
+        blob.len() as INT
+    }
+    #[rhai_fn(name = "push", name = "+=")]
+    pub fn push(blob: &mut Blob, value: INT) {
+        blob.push((value & 0xff) as u8);
+    }
+    #[rhai_fn(name = "append", name = "+=")]
+    pub fn append(blob: &mut Blob, y: Blob) {
+        blob.extend(y);
+    }
+    #[rhai_fn(name = "+")]
+    pub fn concat(mut blob: Blob, y: Blob) -> Blob {
+        blob.extend(y);
+        blob
+    }
+    pub fn insert(blob: &mut Blob, position: INT, value: INT) {
+        let value = (value & 0xff) as u8;
+
+        if position <= 0 {
+            blob.insert(0, value);
+        } else if (position as usize) >= blob.len() {
+            blob.push(value);
+        } else {
+            blob.insert(position as usize, value);
+        }
+    }
+    pub fn pop(blob: &mut Blob) -> Dynamic {
+        blob.pop()
+            .map(|b| (b as INT).into())
+            .unwrap_or_else(|| ().into())
+    }
+    pub fn shift(blob: &mut Blob) -> Dynamic {
+        if blob.is_empty() {
+            ().into()
+        } else {
+            (blob.remove(0) as INT).into()
+        }
+    }
+    pub fn remove(blob: &mut Blob, index: INT) -> Dynamic {
+        if index < 0 || (index as usize) >= blob.len() {
+            ().into()
+        } else {
+            (blob.remove(index as usize) as INT).into()
+        }
+    }
+    pub fn clear(blob: &mut Blob) {
+        blob.clear();
+    }
+    pub fn truncate(blob: &mut Blob, len: INT) {
+        if len >= 0 {
+            blob.truncate(len as usize);
+        } else {
+            blob.clear();
+        }
+    }
+    pub fn reverse(blob: &mut Blob) {
+        blob.reverse();
+    }
+    #[rhai_fn(pure)]
+    pub fn extract(blob: &mut Blob, start: INT, len: INT) -> Blob {
+        let (start, len) = calc_range(blob.len(), start, len);
+        blob[start..start + len].to_vec()
+    }
+    #[rhai_fn(name = "extract", pure)]
+    pub fn extract_tail(blob: &mut Blob, start: INT) -> Blob {
+        let (start, _) = calc_range(blob.len(), start, 0);
+        blob[start..].to_vec()
+    }
+    #[rhai_fn(name = "split")]
+    pub fn split_at(blob: &mut Blob, start: INT) -> Blob {
+        if start <= 0 {
+            mem::take(blob)
+        } else if start as usize >= blob.len() {
+            Default::default()
+        } else {
+            blob.split_off(start as usize)
+        }
+    }
+    #[rhai_fn(name = "==", pure)]
+    pub fn equals(blob: &mut Blob, blob2: Blob) -> bool {
+        *blob == blob2
+    }
+    #[rhai_fn(name = "!=", pure)]
+    pub fn not_equals(blob: &mut Blob, blob2: Blob) -> bool {
+        *blob != blob2
+    }
+
+    #[rhai_fn(pure)]
+    pub fn parse_le_int(blob: &mut Blob, start: INT, len: INT) -> INT {
+        let (_, len) = calc_range(INT_BYTES, 0, len);
+        let mut buf = [0_u8; INT_BYTES];
+        read_bytes(blob, start, &mut buf[..len]);
+        INT::from_le_bytes(buf)
+    }
+    #[rhai_fn(pure)]
+    pub fn parse_be_int(blob: &mut Blob, start: INT, len: INT) -> INT {
+        let (_, len) = calc_range(INT_BYTES, 0, len);
+        let mut buf = [0_u8; INT_BYTES];
+        read_bytes(blob, start, &mut buf[INT_BYTES - len..]);
+        INT::from_be_bytes(buf)
+    }
+    pub fn write_le_int(blob: &mut Blob, start: INT, len: INT, value: INT) {
+        let (_, len) = calc_range(INT_BYTES, 0, len);
+        write_bytes(blob, start, &value.to_le_bytes()[..len]);
+    }
+    pub fn write_be_int(blob: &mut Blob, start: INT, len: INT, value: INT) {
+        let (_, len) = calc_range(INT_BYTES, 0, len);
+        write_bytes(blob, start, &value.to_be_bytes()[INT_BYTES - len..]);
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        #[rhai_fn(pure)]
+        pub fn parse_le_float(blob: &mut Blob, start: INT) -> FLOAT {
+            let mut buf = [0_u8; FLOAT_BYTES];
+            read_bytes(blob, start, &mut buf);
+            FLOAT::from_le_bytes(buf)
+        }
+        #[rhai_fn(pure)]
+        pub fn parse_be_float(blob: &mut Blob, start: INT) -> FLOAT {
+            let mut buf = [0_u8; FLOAT_BYTES];
+            read_bytes(blob, start, &mut buf);
+            FLOAT::from_be_bytes(buf)
+        }
+        pub fn write_le_float(blob: &mut Blob, start: INT, value: FLOAT) {
+            write_bytes(blob, start, &value.to_le_bytes());
+        }
+        pub fn write_be_float(blob: &mut Blob, start: INT, value: FLOAT) {
+            write_bytes(blob, start, &value.to_be_bytes());
+        }
+    }
+
+    pub fn to_blob(string: &str) -> Blob {
+        string.as_bytes().to_vec()
+    }
+    #[rhai_fn(pure)]
+    pub fn as_string(blob: &mut Blob) -> String {
+        String::from_utf8_lossy(blob).into_owned()
+    }
+    #[rhai_fn(pure)]
+    pub fn to_hex(blob: &mut Blob) -> String {
+        blob.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+    #[rhai_fn(return_raw)]
+    pub fn parse_hex(string: &str) -> Result<Blob, Box<EvalAltResult>> {
+        let digits = string.trim().as_bytes();
+
+        if digits.len() % 2 != 0 {
+            return Err(make_err(format!("Error parsing hex string '{}'", string)));
+        }
+
+        digits
+            .chunks(2)
+            .map(|pair| {
+                crate::stdlib::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| make_err(format!("Error parsing hex string '{}'", string)))
+            })
+            .collect()
+    }
+    #[rhai_fn(pure)]
+    pub fn to_base64(blob: &mut Blob) -> String {
+        let mut result = String::with_capacity((blob.len() + 2) / 3 * 4);
+
+        for chunk in blob.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0_u32, |n, (i, &b)| n | (b as u32) << (16 - i * 8));
+
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    result.push(BASE64_CHARS[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+                } else {
+                    result.push('=');
+                }
+            }
+        }
+
+        result
+    }
+    #[rhai_fn(return_raw)]
+    pub fn parse_base64(string: &str) -> Result<Blob, Box<EvalAltResult>> {
+        let data = string.trim().trim_end_matches('=').as_bytes();
+
+        if data.len() % 4 == 1 {
+            return Err(make_err(format!(
+                "Error parsing base64 string '{}'",
+                string
+            )));
+        }
+
+        let mut blob = Blob::with_capacity(data.len() * 3 / 4);
+
+        for chunk in data.chunks(4) {
+            let mut n = 0_u32;
+
+            for (i, &ch) in chunk.iter().enumerate() {
+                let value = BASE64_CHARS
+                    .iter()
+                    .position(|&c| c == ch)
+                    .ok_or_else(|| make_err(format!("Error parsing base64 string '{}'", string)))?;
+                n |= (value as u32) << (18 - i * 6);
+            }
+
+            for i in 0..chunk.len() - 1 {
+                blob.push((n >> (16 - i * 8)) as u8);
+            }
+        }
+
+        Ok(blob)
+    }
+}
@@ -0,0 +1,342 @@
+#![allow(non_snake_case)]
+
+use super::arithmetic::make_err;
+use crate::plugin::*;
+use crate::stdlib::{boxed::Box, convert::TryFrom, format, string::ToString};
+use crate::{def_package, DateTime, Duration, EvalAltResult, ImmutableString, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+def_package!(crate:BasicDateTimePackage:"Basic calendar date/time and duration utilities.", lib, {
+    combine_with_exported_module!(lib, "datetime", datetime_functions);
+    combine_with_exported_module!(lib, "duration", duration_functions);
+});
+
+/// Convert an [`INT`] into a calendar component, if not negative.
+#[inline(always)]
+fn to_component(value: INT) -> Option<u32> {
+    u32::try_from(value).ok()
+}
+
+#[export_module]
+mod datetime_functions {
+    #[rhai_fn(name = "datetime", return_raw)]
+    pub fn new_date(year: INT, month: INT, day: INT) -> Result<DateTime, Box<EvalAltResult>> {
+        new_datetime(year, month, day, 0, 0, 0)
+    }
+    #[rhai_fn(name = "datetime", return_raw)]
+    pub fn new_datetime(
+        year: INT,
+        month: INT,
+        day: INT,
+        hour: INT,
+        minute: INT,
+        second: INT,
+    ) -> Result<DateTime, Box<EvalAltResult>> {
+        to_component(month)
+            .zip(to_component(day))
+            .zip(to_component(hour))
+            .zip(to_component(minute))
+            .zip(to_component(second))
+            .and_then(|((((month, day), hour), minute), second)| {
+                DateTime::from_ymd_hms(year as i64, month, day, hour, minute, second)
+            })
+            .ok_or_else(|| {
+                make_err(format!(
+                    "Invalid date/time: {}-{}-{} {}:{}:{}",
+                    year, month, day, hour, minute, second
+                ))
+            })
+    }
+    #[rhai_fn(return_raw)]
+    pub fn datetime_from_unix(seconds: INT) -> Result<DateTime, Box<EvalAltResult>> {
+        DateTime::from_unix(seconds as i64, 0)
+            .ok_or_else(|| make_err(format!("Date/time out of range: {}", seconds)))
+    }
+    #[rhai_fn(return_raw)]
+    pub fn parse_datetime(string: &str) -> Result<DateTime, Box<EvalAltResult>> {
+        string
+            .parse()
+            .map_err(|err| make_err(format!("Error parsing date/time '{}': {}", string, err)))
+    }
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub mod clock_functions {
+        #[rhai_fn(return_raw)]
+        pub fn now_utc(context: NativeCallContext) -> Result<DateTime, Box<EvalAltResult>> {
+            context.engine().now_utc("now_utc")
+        }
+    }
+
+    #[rhai_fn(get = "year", pure)]
+    pub fn year(dt: &mut DateTime) -> INT {
+        dt.year() as INT
+    }
+    #[rhai_fn(get = "month", pure)]
+    pub fn month(dt: &mut DateTime) -> INT {
+        dt.month() as INT
+    }
+    #[rhai_fn(get = "day", pure)]
+    pub fn day(dt: &mut DateTime) -> INT {
+        dt.day() as INT
+    }
+    #[rhai_fn(get = "hour", pure)]
+    pub fn hour(dt: &mut DateTime) -> INT {
+        dt.hour() as INT
+    }
+    #[rhai_fn(get = "minute", pure)]
+    pub fn minute(dt: &mut DateTime) -> INT {
+        dt.minute() as INT
+    }
+    #[rhai_fn(get = "second", pure)]
+    pub fn second(dt: &mut DateTime) -> INT {
+        dt.second() as INT
+    }
+    #[rhai_fn(get = "nanosecond", pure)]
+    pub fn nanosecond(dt: &mut DateTime) -> INT {
+        dt.nanosecond() as INT
+    }
+    #[rhai_fn(get = "weekday", pure)]
+    pub fn weekday(dt: &mut DateTime) -> INT {
+        dt.weekday() as INT
+    }
+    #[rhai_fn(get = "day_of_year", pure)]
+    pub fn day_of_year(dt: &mut DateTime) -> INT {
+        dt.ordinal() as INT
+    }
+    #[rhai_fn(get = "offset", pure)]
+    pub fn offset(dt: &mut DateTime) -> INT {
+        dt.offset() as INT
+    }
+    #[rhai_fn(get = "unix_timestamp", pure)]
+    pub fn unix_timestamp(dt: &mut DateTime) -> INT {
+        dt.unix_timestamp() as INT
+    }
+    #[rhai_fn(return_raw)]
+    pub fn with_offset(dt: DateTime, seconds: INT) -> Result<DateTime, Box<EvalAltResult>> {
+        i32::try_from(seconds)
+            .ok()
+            .and_then(|offset| dt.with_offset(offset))
+            .ok_or_else(|| make_err(format!("Invalid offset from UTC: {} seconds", seconds)))
+    }
+    pub fn to_utc(dt: DateTime) -> DateTime {
+        dt.to_utc()
+    }
+
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add(dt: DateTime, duration: Duration) -> Result<DateTime, Box<EvalAltResult>> {
+        dt.checked_add(duration)
+            .ok_or_else(|| make_err(format!("Date/time overflow: {} + {}", dt, duration)))
+    }
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract(dt: DateTime, duration: Duration) -> Result<DateTime, Box<EvalAltResult>> {
+        dt.checked_sub(duration)
+            .ok_or_else(|| make_err(format!("Date/time overflow: {} - {}", dt, duration)))
+    }
+    #[rhai_fn(name = "+=", return_raw)]
+    pub fn add_assign(dt: &mut DateTime, duration: Duration) -> Result<(), Box<EvalAltResult>> {
+        *dt = add(*dt, duration)?;
+        Ok(())
+    }
+    #[rhai_fn(name = "-=", return_raw)]
+    pub fn subtract_assign(
+        dt: &mut DateTime,
+        duration: Duration,
+    ) -> Result<(), Box<EvalAltResult>> {
+        *dt = subtract(*dt, duration)?;
+        Ok(())
+    }
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn diff(dt: DateTime, dt2: DateTime) -> Result<Duration, Box<EvalAltResult>> {
+        dt.duration_since(&dt2)
+            .ok_or_else(|| make_err(format!("Duration overflow: {} - {}", dt, dt2)))
+    }
+
+    #[rhai_fn(name = "==")]
+    pub fn eq(dt: DateTime, dt2: DateTime) -> bool {
+        dt == dt2
+    }
+    #[rhai_fn(name = "!=")]
+    pub fn ne(dt: DateTime, dt2: DateTime) -> bool {
+        dt != dt2
+    }
+    #[rhai_fn(name = ">")]
+    pub fn gt(dt: DateTime, dt2: DateTime) -> bool {
+        dt > dt2
+    }
+    #[rhai_fn(name = ">=")]
+    pub fn gte(dt: DateTime, dt2: DateTime) -> bool {
+        dt >= dt2
+    }
+    #[rhai_fn(name = "<")]
+    pub fn lt(dt: DateTime, dt2: DateTime) -> bool {
+        dt < dt2
+    }
+    #[rhai_fn(name = "<=")]
+    pub fn lte(dt: DateTime, dt2: DateTime) -> bool {
+        dt <= dt2
+    }
+
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn to_string(dt: &mut DateTime) -> ImmutableString {
+        dt.to_string().into()
+    }
+}
+
+#[export_module]
+mod duration_functions {
+    #[rhai_fn(name = "duration")]
+    pub fn from_secs(seconds: INT) -> Duration {
+        Duration::from_secs(seconds as i64)
+    }
+    #[rhai_fn(name = "duration", return_raw)]
+    pub fn from_secs_nanos(seconds: INT, nanos: INT) -> Result<Duration, Box<EvalAltResult>> {
+        Duration::from_nanos(nanos as i128)
+            .and_then(|nanos| Duration::from_secs(seconds as i64).checked_add(nanos))
+            .ok_or_else(|| make_err(format!("Duration overflow: {}s + {}ns", seconds, nanos)))
+    }
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        #[rhai_fn(name = "duration", return_raw)]
+        pub fn from_float_secs(seconds: FLOAT) -> Result<Duration, Box<EvalAltResult>> {
+            #[cfg(feature = "no_std")]
+            use num_traits::Float;
+
+            let nanos = (seconds as f64 * 1e9).round();
+
+            if !nanos.is_finite() || nanos.abs() >= i128::MAX as f64 {
+                Err(make_err(format!("Duration overflow: {}s", seconds)))
+            } else {
+                Duration::from_nanos(nanos as i128)
+                    .ok_or_else(|| make_err(format!("Duration overflow: {}s", seconds)))
+            }
+        }
+        #[rhai_fn(get = "total_seconds", pure)]
+        pub fn total_seconds(duration: &mut Duration) -> FLOAT {
+            (duration.secs() as f64 + duration.subsec_nanos() as f64 / 1e9) as FLOAT
+        }
+    }
+    pub fn duration_ms(milliseconds: INT) -> Duration {
+        Duration::from_millis(milliseconds as i64)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn parse_duration(string: &str) -> Result<Duration, Box<EvalAltResult>> {
+        string
+            .parse()
+            .map_err(|err| make_err(format!("Error parsing duration '{}': {}", string, err)))
+    }
+
+    #[rhai_fn(get = "seconds", pure, return_raw)]
+    #[allow(clippy::useless_conversion)]
+    pub fn seconds(duration: &mut Duration) -> Result<INT, Box<EvalAltResult>> {
+        INT::try_from(duration.secs()).map_err(|_| {
+            make_err(format!(
+                "Duration too large to be expressed in seconds: {}",
+                duration
+            ))
+        })
+    }
+    #[rhai_fn(get = "nanoseconds", pure)]
+    pub fn nanoseconds(duration: &mut Duration) -> INT {
+        duration.subsec_nanos() as INT
+    }
+    #[rhai_fn(get = "milliseconds", pure, return_raw)]
+    pub fn milliseconds(duration: &mut Duration) -> Result<INT, Box<EvalAltResult>> {
+        INT::try_from(duration.as_millis()).map_err(|_| {
+            make_err(format!(
+                "Duration too large to be expressed in milliseconds: {}",
+                duration
+            ))
+        })
+    }
+
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn add(duration: Duration, duration2: Duration) -> Result<Duration, Box<EvalAltResult>> {
+        duration
+            .checked_add(duration2)
+            .ok_or_else(|| make_err(format!("Duration overflow: {} + {}", duration, duration2)))
+    }
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn subtract(
+        duration: Duration,
+        duration2: Duration,
+    ) -> Result<Duration, Box<EvalAltResult>> {
+        duration
+            .checked_sub(duration2)
+            .ok_or_else(|| make_err(format!("Duration overflow: {} - {}", duration, duration2)))
+    }
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn multiply(duration: Duration, factor: INT) -> Result<Duration, Box<EvalAltResult>> {
+        duration
+            .checked_mul(factor as i64)
+            .ok_or_else(|| make_err(format!("Duration overflow: {} * {}", duration, factor)))
+    }
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn divide(duration: Duration, divisor: INT) -> Result<Duration, Box<EvalAltResult>> {
+        if divisor == 0 {
+            Err(make_err(format!(
+                "Division by zero: {} / {}",
+                duration, divisor
+            )))
+        } else {
+            duration
+                .checked_div(divisor as i64)
+                .ok_or_else(|| make_err(format!("Duration overflow: {} / {}", duration, divisor)))
+        }
+    }
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn neg(duration: Duration) -> Result<Duration, Box<EvalAltResult>> {
+        duration
+            .checked_neg()
+            .ok_or_else(|| make_err(format!("Duration overflow: -{}", duration)))
+    }
+    #[rhai_fn(return_raw)]
+    pub fn abs(duration: Duration) -> Result<Duration, Box<EvalAltResult>> {
+        duration
+            .checked_abs()
+            .ok_or_else(|| make_err(format!("Duration overflow: abs({})", duration)))
+    }
+
+    #[rhai_fn(name = "==")]
+    pub fn eq(duration: Duration, duration2: Duration) -> bool {
+        duration == duration2
+    }
+    #[rhai_fn(name = "!=")]
+    pub fn ne(duration: Duration, duration2: Duration) -> bool {
+        duration != duration2
+    }
+    #[rhai_fn(name = ">")]
+    pub fn gt(duration: Duration, duration2: Duration) -> bool {
+        duration > duration2
+    }
+    #[rhai_fn(name = ">=")]
+    pub fn gte(duration: Duration, duration2: Duration) -> bool {
+        duration >= duration2
+    }
+    #[rhai_fn(name = "<")]
+    pub fn lt(duration: Duration, duration2: Duration) -> bool {
+        duration < duration2
+    }
+    #[rhai_fn(name = "<=")]
+    pub fn lte(duration: Duration, duration2: Duration) -> bool {
+        duration <= duration2
+    }
+
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn to_string(duration: &mut Duration) -> ImmutableString {
+        duration.to_string().into()
+    }
+}
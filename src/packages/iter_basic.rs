@@ -167,6 +167,10 @@ macro_rules! reg_range {
 def_package!(crate:BasicIteratorPackage:"Basic range iterators.", lib, {
     reg_range!(lib | "range" => INT);
 
+    // Register generator iterator
+    #[cfg(not(feature = "no_function"))]
+    lib.set_iter(crate::stdlib::any::TypeId::of::<crate::Generator>(), crate::generator::iter_steps);
+
     #[cfg(not(feature = "only_i32"))]
     #[cfg(not(feature = "only_i64"))]
     {
@@ -2,13 +2,14 @@
 
 use crate::engine::OP_EQUALS;
 use crate::plugin::*;
-use crate::{def_package, Dynamic, ImmutableString, Map, INT};
+use crate::{def_package, Dynamic, ImmutableString, Map, Record, INT};
 
 #[cfg(not(feature = "no_index"))]
 use crate::Array;
 
 def_package!(crate:BasicMapPackage:"Basic object map utilities.", lib, {
     combine_with_exported_module!(lib, "map", map_functions);
+    combine_with_exported_module!(lib, "record", record_functions);
 });
 
 #[export_module]
@@ -91,3 +92,38 @@ mod map_functions {
         }
     }
 }
+
+#[export_module]
+mod record_functions {
+    #[rhai_fn(name = "==", return_raw, pure)]
+    pub fn equals(
+        ctx: NativeCallContext,
+        record: &mut Record,
+        record2: Record,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        if record.type_name() != record2.type_name() || record.len() != record2.len() {
+            return Ok(false);
+        }
+
+        // Records of the same type have the same fields in the same order
+        for ((_, v1), (_, v2)) in record.iter().zip(record2.iter()) {
+            let equals = ctx
+                .call_fn_dynamic_raw(OP_EQUALS, true, &mut [&mut v1.clone(), &mut v2.clone()])
+                .map(|v| v.as_bool().unwrap_or(false))?;
+
+            if !equals {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+    #[rhai_fn(name = "!=", return_raw, pure)]
+    pub fn not_equals(
+        ctx: NativeCallContext,
+        record: &mut Record,
+        record2: Record,
+    ) -> Result<bool, Box<EvalAltResult>> {
+        equals(ctx, record, record2).map(|r| !r)
+    }
+}
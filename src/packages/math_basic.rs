@@ -19,7 +19,7 @@ use crate::stdlib::format;
 #[cfg(feature = "decimal")]
 use rust_decimal::Decimal;
 
-#[cfg(feature = "decimal")]
+#[cfg(any(feature = "decimal", feature = "bigint"))]
 use super::arithmetic::make_err;
 
 #[allow(dead_code)]
@@ -108,6 +108,10 @@ def_package!(crate:BasicMathPackage:"Basic mathematic functions.", lib, {
         #[cfg(not(feature = "only_i64"))]
         reg_functions!(lib += numbers_to_decimal::to_decimal(i8, u8, i16, u16, i32, u32, i64, u64));
     }
+
+    // BigInt functions
+    #[cfg(feature = "bigint")]
+    combine_with_exported_module!(lib, "bigint", bigint_functions);
 });
 
 #[export_module]
@@ -458,6 +462,73 @@ mod decimal_functions {
     }
 }
 
+#[cfg(feature = "bigint")]
+#[export_module]
+mod bigint_functions {
+    use crate::stdlib::str::FromStr;
+    use num_bigint::BigInt;
+    use num_traits::ToPrimitive;
+
+    pub fn to_bigint(x: INT) -> BigInt {
+        x.into()
+    }
+    #[rhai_fn(name = "to_int", return_raw)]
+    pub fn bigint_to_int(x: BigInt) -> Result<INT, Box<EvalAltResult>> {
+        #[cfg(not(feature = "only_i32"))]
+        let n = x.to_i64();
+        #[cfg(feature = "only_i32")]
+        let n = x.to_i32();
+
+        n.ok_or_else(|| make_err(format!("Integer overflow: to_int({})", x)))
+    }
+    #[rhai_fn(return_raw)]
+    pub fn parse_bigint(s: &str) -> Result<BigInt, Box<EvalAltResult>> {
+        BigInt::from_str(s.trim()).map_err(|err| {
+            EvalAltResult::ErrorArithmetic(
+                format!("Error parsing integer number '{}': {}", s, err),
+                Position::NONE,
+            )
+            .into()
+        })
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    pub mod float {
+        pub fn to_float(x: BigInt) -> FLOAT {
+            #[cfg(not(feature = "f32_float"))]
+            return x.to_f64().unwrap_or(FLOAT::NAN);
+            #[cfg(feature = "f32_float")]
+            return x.to_f32().unwrap_or(FLOAT::NAN);
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    pub mod decimal {
+        use num_traits::FromPrimitive;
+        use rust_decimal::Decimal;
+
+        #[rhai_fn(name = "to_decimal", return_raw)]
+        pub fn bigint_to_decimal(x: BigInt) -> Result<Decimal, Box<EvalAltResult>> {
+            x.to_i128()
+                .and_then(Decimal::from_i128)
+                .ok_or_else(|| make_err(format!("Cannot convert to Decimal: to_decimal({})", x)))
+        }
+        #[rhai_fn(name = "to_bigint", return_raw)]
+        pub fn decimal_to_bigint(x: Decimal) -> Result<BigInt, Box<EvalAltResult>> {
+            if !x.fract().is_zero() {
+                return Err(make_err(format!(
+                    "Cannot convert a number with a fraction to an integer: to_bigint({})",
+                    x
+                )));
+            }
+
+            x.to_i128()
+                .map(Into::into)
+                .ok_or_else(|| make_err(format!("Integer overflow: to_bigint({})", x)))
+        }
+    }
+}
+
 #[cfg(not(feature = "no_float"))]
 gen_conversion_as_functions!(basic_to_float => to_float (INT) -> FLOAT);
 
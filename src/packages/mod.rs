@@ -4,13 +4,17 @@ use crate::{Module, Shared};
 
 pub(crate) mod arithmetic;
 mod array_basic;
+mod blob_basic;
+mod datetime_basic;
 mod fn_basic;
 mod iter_basic;
 mod logic;
 mod map_basic;
 mod math_basic;
+mod packed_basic;
 mod pkg_core;
 mod pkg_std;
+mod set_basic;
 mod string_basic;
 mod string_more;
 mod time_basic;
@@ -18,14 +22,21 @@ mod time_basic;
 pub use arithmetic::ArithmeticPackage;
 #[cfg(not(feature = "no_index"))]
 pub use array_basic::BasicArrayPackage;
+#[cfg(not(feature = "no_index"))]
+pub use blob_basic::BasicBlobPackage;
+pub use datetime_basic::BasicDateTimePackage;
 pub use fn_basic::BasicFnPackage;
 pub use iter_basic::BasicIteratorPackage;
 pub use logic::LogicPackage;
 #[cfg(not(feature = "no_object"))]
 pub use map_basic::BasicMapPackage;
 pub use math_basic::BasicMathPackage;
+#[cfg(not(feature = "no_index"))]
+pub use packed_basic::BasicPackedArrayPackage;
 pub use pkg_core::CorePackage;
 pub use pkg_std::StandardPackage;
+#[cfg(not(feature = "no_index"))]
+pub use set_basic::BasicSetPackage;
 pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
 #[cfg(not(feature = "no_std"))]
@@ -91,6 +102,7 @@ macro_rules! def_package {
             pub fn new() -> Self {
                 let mut module = $root::Module::new();
                 <Self as $root::packages::Package>::init(&mut module);
+                module.set_package(true);
                 module.build_index();
                 Self(module.into())
             }
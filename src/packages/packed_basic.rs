@@ -0,0 +1,512 @@
+#![cfg(not(feature = "no_index"))]
+#![allow(non_snake_case)]
+
+use super::{arithmetic::make_err, blob_basic::calc_range};
+use crate::plugin::*;
+use crate::stdlib::{any::type_name, any::TypeId, boxed::Box, format, string::ToString};
+use crate::{
+    def_package, Array, Dynamic, EvalAltResult, ImmutableString, IntArray, NativeCallContext,
+    PackedArray, Position, INT,
+};
+
+#[cfg(not(feature = "no_float"))]
+use crate::{FloatArray, FLOAT};
+
+def_package!(crate:BasicPackedArrayPackage:"Basic packed array utilities.", lib, {
+    combine_with_exported_module!(lib, "int_array", int_array_functions);
+    lib.set_indexer_set_fn(set_item::<INT>);
+
+    // Register int array iterator
+    lib.set_iter(
+        TypeId::of::<IntArray>(),
+        |array| Box::new(array.cast::<IntArray>().into_iter().map(Into::into))
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        combine_with_exported_module!(lib, "float_array", float_array_functions);
+        lib.set_indexer_set_fn(set_item::<FLOAT>);
+
+        // Register float array iterator
+        lib.set_iter(
+            TypeId::of::<FloatArray>(),
+            |array| Box::new(array.cast::<FloatArray>().into_iter().map(Into::into))
+        );
+    }
+});
+
+/// Create a [`PackedArray`] of a particular length filled with a value.
+fn new_packed<T: Copy>(
+    _ctx: &NativeCallContext,
+    len: INT,
+    value: T,
+) -> Result<PackedArray<T>, Box<EvalAltResult>> {
+    // Check if array will be over max size limit
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_array_size() > 0
+        && len > 0
+        && (len as usize) > _ctx.engine().max_array_size()
+    {
+        return EvalAltResult::ErrorDataTooLarge("Size of array".to_string(), Position::NONE)
+            .into();
+    }
+
+    let mut array = PackedArray::new();
+
+    if len > 0 {
+        array.resize(len as usize, value);
+    }
+
+    Ok(array)
+}
+
+/// Convert an [`Array`] into a [`PackedArray`], converting each item with a function.
+fn from_array<T>(
+    array: Array,
+    f: impl Fn(&Dynamic) -> Result<T, &'static str>,
+) -> Result<PackedArray<T>, Box<EvalAltResult>> {
+    array
+        .iter()
+        .map(|item| {
+            f(item).map_err(|typ| {
+                EvalAltResult::ErrorMismatchDataType(
+                    type_name::<T>().to_string(),
+                    typ.to_string(),
+                    Position::NONE,
+                )
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Check that an index is within the bounds of a [`PackedArray`].
+fn check_index<T>(array: &PackedArray<T>, index: INT) -> Result<usize, Box<EvalAltResult>> {
+    if index >= 0 && (index as usize) < array.len() {
+        Ok(index as usize)
+    } else {
+        EvalAltResult::ErrorArrayBounds(array.len(), index, Position::NONE).into()
+    }
+}
+
+/// Set an item of a [`PackedArray`].
+fn set_item<T>(array: &mut PackedArray<T>, index: INT, value: T) -> Result<(), Box<EvalAltResult>> {
+    check_index(array, index).map(|index| array[index] = value)
+}
+
+/// Apply a function to each item of a [`PackedArray`].
+fn map_items<T: Copy>(
+    array: &PackedArray<T>,
+    f: impl Fn(T) -> Result<T, Box<EvalAltResult>>,
+) -> Result<PackedArray<T>, Box<EvalAltResult>> {
+    array.iter().map(|&x| f(x)).collect()
+}
+
+/// Apply a function to each pair of items in two [`PackedArray`]'s of the same length.
+fn zip_items<T: Copy>(
+    x: &PackedArray<T>,
+    y: &PackedArray<T>,
+    f: impl Fn(T, T) -> Result<T, Box<EvalAltResult>>,
+) -> Result<PackedArray<T>, Box<EvalAltResult>> {
+    if x.len() != y.len() {
+        return Err(make_err(format!(
+            "Packed arrays have different lengths: {} and {}",
+            x.len(),
+            y.len()
+        )));
+    }
+
+    x.iter().zip(y.iter()).map(|(&x, &y)| f(x, y)).collect()
+}
+
+#[inline(always)]
+fn int_add(x: INT, y: INT) -> Result<INT, Box<EvalAltResult>> {
+    if cfg!(not(feature = "unchecked")) {
+        x.checked_add(y)
+            .ok_or_else(|| make_err(format!("Addition overflow: {} + {}", x, y)))
+    } else {
+        Ok(x + y)
+    }
+}
+#[inline(always)]
+fn int_subtract(x: INT, y: INT) -> Result<INT, Box<EvalAltResult>> {
+    if cfg!(not(feature = "unchecked")) {
+        x.checked_sub(y)
+            .ok_or_else(|| make_err(format!("Subtraction overflow: {} - {}", x, y)))
+    } else {
+        Ok(x - y)
+    }
+}
+#[inline(always)]
+fn int_multiply(x: INT, y: INT) -> Result<INT, Box<EvalAltResult>> {
+    if cfg!(not(feature = "unchecked")) {
+        x.checked_mul(y)
+            .ok_or_else(|| make_err(format!("Multiplication overflow: {} * {}", x, y)))
+    } else {
+        Ok(x * y)
+    }
+}
+#[inline(always)]
+fn int_divide(x: INT, y: INT) -> Result<INT, Box<EvalAltResult>> {
+    if cfg!(not(feature = "unchecked")) {
+        // Detect division by zero
+        if y == 0 {
+            Err(make_err(format!("Division by zero: {} / {}", x, y)))
+        } else {
+            x.checked_div(y)
+                .ok_or_else(|| make_err(format!("Division overflow: {} / {}", x, y)))
+        }
+    } else {
+        Ok(x / y)
+    }
+}
+
+#[export_module]
+mod int_array_functions {
+    #[rhai_fn(name = "int_array")]
+    pub fn new_int_array() -> IntArray {
+        IntArray::new()
+    }
+    #[rhai_fn(name = "int_array", return_raw)]
+    pub fn new_int_array_with_len(
+        ctx: NativeCallContext,
+        len: INT,
+    ) -> Result<IntArray, Box<EvalAltResult>> {
+        new_packed(&ctx, len, 0)
+    }
+    #[rhai_fn(name = "int_array", return_raw)]
+    pub fn new_int_array_with_value(
+        ctx: NativeCallContext,
+        len: INT,
+        value: INT,
+    ) -> Result<IntArray, Box<EvalAltResult>> {
+        new_packed(&ctx, len, value)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn to_int_array(array: Array) -> Result<IntArray, Box<EvalAltResult>> {
+        from_array(array, Dynamic::as_int)
+    }
+    #[rhai_fn(pure)]
+    pub fn to_array(array: &mut IntArray) -> Array {
+        array.to_array()
+    }
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(array: &mut IntArray) -> INT {
+        array.len() as INT
+    }
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(array: &mut IntArray) -> bool {
+        array.is_empty()
+    }
+    pub fn push(array: &mut IntArray, value: INT) {
+        array.push(value);
+    }
+    pub fn append(array: &mut IntArray, y: IntArray) {
+        array.extend(y);
+    }
+    pub fn clear(array: &mut IntArray) {
+        array.clear();
+    }
+    #[rhai_fn(index_get, return_raw, pure)]
+    pub fn get(array: &mut IntArray, index: INT) -> Result<INT, Box<EvalAltResult>> {
+        check_index(array, index).map(|index| array[index])
+    }
+    #[rhai_fn(name = "slice", pure)]
+    pub fn slice(array: &mut IntArray, start: INT, len: INT) -> IntArray {
+        let (start, len) = calc_range(array.len(), start, len);
+        array[start..start + len].iter().copied().collect()
+    }
+    #[rhai_fn(name = "slice", pure)]
+    pub fn slice_tail(array: &mut IntArray, start: INT) -> IntArray {
+        slice(array, start, INT::MAX)
+    }
+
+    #[rhai_fn(name = "+", return_raw, pure)]
+    pub fn add(x: &mut IntArray, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        zip_items(x, &y, int_add)
+    }
+    #[rhai_fn(name = "-", return_raw, pure)]
+    pub fn subtract(x: &mut IntArray, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        zip_items(x, &y, int_subtract)
+    }
+    #[rhai_fn(name = "*", return_raw, pure)]
+    pub fn multiply(x: &mut IntArray, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        zip_items(x, &y, int_multiply)
+    }
+    #[rhai_fn(name = "/", return_raw, pure)]
+    pub fn divide(x: &mut IntArray, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        zip_items(x, &y, int_divide)
+    }
+    #[rhai_fn(name = "+", return_raw, pure)]
+    pub fn add_scalar(x: &mut IntArray, y: INT) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(x, |x| int_add(x, y))
+    }
+    #[rhai_fn(name = "-", return_raw, pure)]
+    pub fn subtract_scalar(x: &mut IntArray, y: INT) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(x, |x| int_subtract(x, y))
+    }
+    #[rhai_fn(name = "*", return_raw, pure)]
+    pub fn multiply_scalar(x: &mut IntArray, y: INT) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(x, |x| int_multiply(x, y))
+    }
+    #[rhai_fn(name = "/", return_raw, pure)]
+    pub fn divide_scalar(x: &mut IntArray, y: INT) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(x, |x| int_divide(x, y))
+    }
+    #[rhai_fn(name = "+", return_raw)]
+    pub fn scalar_add(x: INT, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(&y, |y| int_add(x, y))
+    }
+    #[rhai_fn(name = "-", return_raw)]
+    pub fn scalar_subtract(x: INT, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(&y, |y| int_subtract(x, y))
+    }
+    #[rhai_fn(name = "*", return_raw)]
+    pub fn scalar_multiply(x: INT, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(&y, |y| int_multiply(x, y))
+    }
+    #[rhai_fn(name = "/", return_raw)]
+    pub fn scalar_divide(x: INT, y: IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(&y, |y| int_divide(x, y))
+    }
+    #[rhai_fn(name = "-", return_raw, pure)]
+    pub fn neg(x: &mut IntArray) -> Result<IntArray, Box<EvalAltResult>> {
+        map_items(x, |x| {
+            if cfg!(not(feature = "unchecked")) {
+                x.checked_neg()
+                    .ok_or_else(|| make_err(format!("Negation overflow: -{}", x)))
+            } else {
+                Ok(-x)
+            }
+        })
+    }
+
+    #[rhai_fn(return_raw, pure)]
+    pub fn sum(array: &mut IntArray) -> Result<INT, Box<EvalAltResult>> {
+        array.iter().try_fold(0, |sum, &x| int_add(sum, x))
+    }
+    #[rhai_fn(pure)]
+    pub fn min(array: &mut IntArray) -> Dynamic {
+        array.iter().min().map_or(Dynamic::UNIT, |&x| x.into())
+    }
+    #[rhai_fn(pure)]
+    pub fn max(array: &mut IntArray) -> Dynamic {
+        array.iter().max().map_or(Dynamic::UNIT, |&x| x.into())
+    }
+    #[rhai_fn(return_raw, pure)]
+    pub fn dot(x: &mut IntArray, y: IntArray) -> Result<INT, Box<EvalAltResult>> {
+        zip_items(x, &y, int_multiply)?
+            .iter()
+            .try_fold(0, |sum, &x| int_add(sum, x))
+    }
+
+    #[rhai_fn(name = "==", pure)]
+    pub fn equals(x: &mut IntArray, y: IntArray) -> bool {
+        *x == y
+    }
+    #[rhai_fn(name = "!=", pure)]
+    pub fn not_equals(x: &mut IntArray, y: IntArray) -> bool {
+        *x != y
+    }
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_int_array(array: &mut IntArray) -> ImmutableString {
+        format!("{:?}", array).into()
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    pub mod float_functions {
+        #[rhai_fn(pure)]
+        pub fn mean(array: &mut IntArray) -> Dynamic {
+            if array.is_empty() {
+                Dynamic::UNIT
+            } else {
+                let sum: FLOAT = array.iter().map(|&x| x as FLOAT).sum();
+                (sum / array.len() as FLOAT).into()
+            }
+        }
+        #[rhai_fn(pure)]
+        pub fn to_float_array(array: &mut IntArray) -> FloatArray {
+            array.iter().map(|&x| x as FLOAT).collect()
+        }
+    }
+}
+
+#[cfg(not(feature = "no_float"))]
+#[export_module]
+mod float_array_functions {
+    #[rhai_fn(name = "float_array")]
+    pub fn new_float_array() -> FloatArray {
+        FloatArray::new()
+    }
+    #[rhai_fn(name = "float_array", return_raw)]
+    pub fn new_float_array_with_len(
+        ctx: NativeCallContext,
+        len: INT,
+    ) -> Result<FloatArray, Box<EvalAltResult>> {
+        new_packed(&ctx, len, 0.0)
+    }
+    #[rhai_fn(name = "float_array", return_raw)]
+    pub fn new_float_array_with_value(
+        ctx: NativeCallContext,
+        len: INT,
+        value: FLOAT,
+    ) -> Result<FloatArray, Box<EvalAltResult>> {
+        new_packed(&ctx, len, value)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn to_float_array(array: Array) -> Result<FloatArray, Box<EvalAltResult>> {
+        from_array(array, |item| {
+            item.as_float()
+                .or_else(|_| item.as_int().map(|x| x as FLOAT))
+        })
+    }
+    #[rhai_fn(pure)]
+    pub fn to_array(array: &mut FloatArray) -> Array {
+        array.to_array()
+    }
+    #[rhai_fn(pure)]
+    pub fn to_int_array(array: &mut FloatArray) -> IntArray {
+        array.iter().map(|&x| x as INT).collect()
+    }
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(array: &mut FloatArray) -> INT {
+        array.len() as INT
+    }
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(array: &mut FloatArray) -> bool {
+        array.is_empty()
+    }
+    pub fn push(array: &mut FloatArray, value: FLOAT) {
+        array.push(value);
+    }
+    pub fn append(array: &mut FloatArray, y: FloatArray) {
+        array.extend(y);
+    }
+    pub fn clear(array: &mut FloatArray) {
+        array.clear();
+    }
+    #[rhai_fn(index_get, return_raw, pure)]
+    pub fn get(array: &mut FloatArray, index: INT) -> Result<FLOAT, Box<EvalAltResult>> {
+        check_index(array, index).map(|index| array[index])
+    }
+    #[rhai_fn(name = "slice", pure)]
+    pub fn slice(array: &mut FloatArray, start: INT, len: INT) -> FloatArray {
+        let (start, len) = calc_range(array.len(), start, len);
+        array[start..start + len].iter().copied().collect()
+    }
+    #[rhai_fn(name = "slice", pure)]
+    pub fn slice_tail(array: &mut FloatArray, start: INT) -> FloatArray {
+        slice(array, start, INT::MAX)
+    }
+
+    #[rhai_fn(name = "+", return_raw, pure)]
+    pub fn add(x: &mut FloatArray, y: FloatArray) -> Result<FloatArray, Box<EvalAltResult>> {
+        zip_items(x, &y, |x, y| Ok(x + y))
+    }
+    #[rhai_fn(name = "-", return_raw, pure)]
+    pub fn subtract(x: &mut FloatArray, y: FloatArray) -> Result<FloatArray, Box<EvalAltResult>> {
+        zip_items(x, &y, |x, y| Ok(x - y))
+    }
+    #[rhai_fn(name = "*", return_raw, pure)]
+    pub fn multiply(x: &mut FloatArray, y: FloatArray) -> Result<FloatArray, Box<EvalAltResult>> {
+        zip_items(x, &y, |x, y| Ok(x * y))
+    }
+    #[rhai_fn(name = "/", return_raw, pure)]
+    pub fn divide(x: &mut FloatArray, y: FloatArray) -> Result<FloatArray, Box<EvalAltResult>> {
+        zip_items(x, &y, |x, y| Ok(x / y))
+    }
+    #[rhai_fn(name = "+", pure)]
+    pub fn add_scalar(x: &mut FloatArray, y: FLOAT) -> FloatArray {
+        x.iter().map(|&x| x + y).collect()
+    }
+    #[rhai_fn(name = "-", pure)]
+    pub fn subtract_scalar(x: &mut FloatArray, y: FLOAT) -> FloatArray {
+        x.iter().map(|&x| x - y).collect()
+    }
+    #[rhai_fn(name = "*", pure)]
+    pub fn multiply_scalar(x: &mut FloatArray, y: FLOAT) -> FloatArray {
+        x.iter().map(|&x| x * y).collect()
+    }
+    #[rhai_fn(name = "/", pure)]
+    pub fn divide_scalar(x: &mut FloatArray, y: FLOAT) -> FloatArray {
+        x.iter().map(|&x| x / y).collect()
+    }
+    #[rhai_fn(name = "+")]
+    pub fn scalar_add(x: FLOAT, y: FloatArray) -> FloatArray {
+        y.iter().map(|&y| x + y).collect()
+    }
+    #[rhai_fn(name = "-")]
+    pub fn scalar_subtract(x: FLOAT, y: FloatArray) -> FloatArray {
+        y.iter().map(|&y| x - y).collect()
+    }
+    #[rhai_fn(name = "*")]
+    pub fn scalar_multiply(x: FLOAT, y: FloatArray) -> FloatArray {
+        y.iter().map(|&y| x * y).collect()
+    }
+    #[rhai_fn(name = "/")]
+    pub fn scalar_divide(x: FLOAT, y: FloatArray) -> FloatArray {
+        y.iter().map(|&y| x / y).collect()
+    }
+    #[rhai_fn(name = "-", pure)]
+    pub fn neg(x: &mut FloatArray) -> FloatArray {
+        x.iter().map(|&x| -x).collect()
+    }
+
+    #[rhai_fn(pure)]
+    pub fn sum(array: &mut FloatArray) -> FLOAT {
+        array.iter().sum()
+    }
+    #[rhai_fn(pure)]
+    pub fn mean(array: &mut FloatArray) -> Dynamic {
+        if array.is_empty() {
+            Dynamic::UNIT
+        } else {
+            (sum(array) / array.len() as FLOAT).into()
+        }
+    }
+    #[rhai_fn(pure)]
+    pub fn min(array: &mut FloatArray) -> Dynamic {
+        array
+            .iter()
+            .copied()
+            .reduce(FLOAT::min)
+            .map_or(Dynamic::UNIT, Into::into)
+    }
+    #[rhai_fn(pure)]
+    pub fn max(array: &mut FloatArray) -> Dynamic {
+        array
+            .iter()
+            .copied()
+            .reduce(FLOAT::max)
+            .map_or(Dynamic::UNIT, Into::into)
+    }
+    #[rhai_fn(return_raw, pure)]
+    pub fn dot(x: &mut FloatArray, y: FloatArray) -> Result<FLOAT, Box<EvalAltResult>> {
+        zip_items(x, &y, |x, y| Ok(x * y)).map(|products| products.iter().sum())
+    }
+
+    #[rhai_fn(name = "==", pure)]
+    pub fn equals(x: &mut FloatArray, y: FloatArray) -> bool {
+        *x == y
+    }
+    #[rhai_fn(name = "!=", pure)]
+    pub fn not_equals(x: &mut FloatArray, y: FloatArray) -> bool {
+        *x != y
+    }
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_float_array(array: &mut FloatArray) -> ImmutableString {
+        format!("{:?}", array).into()
+    }
+}
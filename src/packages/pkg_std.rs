@@ -1,9 +1,16 @@
 #[cfg(not(feature = "no_index"))]
 use super::array_basic::BasicArrayPackage;
+#[cfg(not(feature = "no_index"))]
+use super::blob_basic::BasicBlobPackage;
+use super::datetime_basic::BasicDateTimePackage;
 #[cfg(not(feature = "no_object"))]
 use super::map_basic::BasicMapPackage;
 use super::math_basic::BasicMathPackage;
+#[cfg(not(feature = "no_index"))]
+use super::packed_basic::BasicPackedArrayPackage;
 use super::pkg_core::CorePackage;
+#[cfg(not(feature = "no_index"))]
+use super::set_basic::BasicSetPackage;
 use super::string_more::MoreStringPackage;
 #[cfg(not(feature = "no_std"))]
 use super::time_basic::BasicTimePackage;
@@ -15,9 +22,16 @@ def_package!(crate:StandardPackage:"_Standard_ package containing all built-in f
     BasicMathPackage::init(lib);
     #[cfg(not(feature = "no_index"))]
     BasicArrayPackage::init(lib);
+    #[cfg(not(feature = "no_index"))]
+    BasicBlobPackage::init(lib);
+    #[cfg(not(feature = "no_index"))]
+    BasicSetPackage::init(lib);
+    #[cfg(not(feature = "no_index"))]
+    BasicPackedArrayPackage::init(lib);
     #[cfg(not(feature = "no_object"))]
     BasicMapPackage::init(lib);
     #[cfg(not(feature = "no_std"))]
     BasicTimePackage::init(lib);
+    BasicDateTimePackage::init(lib);
     MoreStringPackage::init(lib);
 });
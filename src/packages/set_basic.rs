@@ -0,0 +1,108 @@
+#![cfg(not(feature = "no_index"))]
+#![allow(non_snake_case)]
+
+use crate::plugin::*;
+use crate::stdlib::{any::TypeId, boxed::Box};
+use crate::{def_package, Array, Dynamic, EvalAltResult, Set, INT};
+
+def_package!(crate:BasicSetPackage:"Basic set utilities.", lib, {
+    combine_with_exported_module!(lib, "set", set_functions);
+
+    // Register set iterator
+    lib.set_iter(
+        TypeId::of::<Set>(),
+        |set| Box::new(set.cast::<Set>().into_iter())
+    );
+});
+
+#[export_module]
+mod set_functions {
+    #[rhai_fn(name = "set")]
+    pub fn new_set() -> Set {
+        Set::new()
+    }
+    #[rhai_fn(return_raw)]
+    pub fn to_set(array: Array) -> Result<Set, Box<EvalAltResult>> {
+        Set::from_array(array)
+    }
+    #[rhai_fn(pure)]
+    pub fn to_array(set: &mut Set) -> Array {
+        set.clone().into_array()
+    }
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(set: &mut Set) -> INT {
+        set.len() as INT
+    }
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(set: &mut Set) -> bool {
+        set.is_empty()
+    }
+    #[rhai_fn(pure)]
+    pub fn contains(set: &mut Set, value: Dynamic) -> bool {
+        set.contains(&value)
+    }
+    #[rhai_fn(return_raw)]
+    pub fn insert(set: &mut Set, value: Dynamic) -> Result<bool, Box<EvalAltResult>> {
+        set.insert(value)
+    }
+    pub fn remove(set: &mut Set, value: Dynamic) -> bool {
+        set.remove(&value).is_some()
+    }
+    pub fn clear(set: &mut Set) {
+        set.clear();
+    }
+
+    #[rhai_fn(name = "|", name = "union", pure)]
+    pub fn union(set: &mut Set, set2: Set) -> Set {
+        set.union(&set2)
+    }
+    #[rhai_fn(name = "&", name = "intersection", pure)]
+    pub fn intersection(set: &mut Set, set2: Set) -> Set {
+        set.intersection(&set2)
+    }
+    #[rhai_fn(name = "-", name = "difference", pure)]
+    pub fn difference(set: &mut Set, set2: Set) -> Set {
+        set.difference(&set2)
+    }
+    #[rhai_fn(name = "^", name = "symmetric_difference", pure)]
+    pub fn symmetric_difference(set: &mut Set, set2: Set) -> Set {
+        set.symmetric_difference(&set2)
+    }
+    #[rhai_fn(name = "|=")]
+    pub fn union_assign(set: &mut Set, set2: Set) {
+        set.extend(set2);
+    }
+    #[rhai_fn(name = "&=")]
+    pub fn intersection_assign(set: &mut Set, set2: Set) {
+        set.retain(|value| set2.contains(value));
+    }
+    #[rhai_fn(name = "-=")]
+    pub fn difference_assign(set: &mut Set, set2: Set) {
+        set.retain(|value| !set2.contains(value));
+    }
+    #[rhai_fn(name = "^=")]
+    pub fn symmetric_difference_assign(set: &mut Set, set2: Set) {
+        *set = set.symmetric_difference(&set2);
+    }
+
+    #[rhai_fn(pure)]
+    pub fn is_subset(set: &mut Set, set2: Set) -> bool {
+        set.is_subset(&set2)
+    }
+    #[rhai_fn(pure)]
+    pub fn is_superset(set: &mut Set, set2: Set) -> bool {
+        set2.is_subset(set)
+    }
+    #[rhai_fn(pure)]
+    pub fn is_disjoint(set: &mut Set, set2: Set) -> bool {
+        set.intersection(&set2).is_empty()
+    }
+    #[rhai_fn(name = "==", pure)]
+    pub fn equals(set: &mut Set, set2: Set) -> bool {
+        *set == set2
+    }
+    #[rhai_fn(name = "!=", pure)]
+    pub fn not_equals(set: &mut Set, set2: Set) -> bool {
+        *set != set2
+    }
+}
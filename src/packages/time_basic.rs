@@ -21,30 +21,38 @@ def_package!(crate:BasicTimePackage:"Basic timing utilities.", lib, {
 
 #[export_module]
 mod time_functions {
-    pub fn timestamp() -> Instant {
-        Instant::now()
+    #[rhai_fn(return_raw)]
+    pub fn timestamp(context: NativeCallContext) -> Result<Instant, Box<EvalAltResult>> {
+        context.engine().now("timestamp")
     }
 
     #[rhai_fn(name = "elapsed", get = "elapsed", return_raw)]
-    pub fn elapsed(timestamp: Instant) -> Result<Dynamic, Box<EvalAltResult>> {
+    pub fn elapsed(
+        context: NativeCallContext,
+        timestamp: Instant,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let now = context.engine().now("elapsed")?;
+
         #[cfg(not(feature = "no_float"))]
-        if timestamp > Instant::now() {
+        if timestamp > now {
             Err(make_arithmetic_err("Time-stamp is later than now"))
         } else {
-            Ok((timestamp.elapsed().as_secs_f64() as FLOAT).into())
+            Ok(((now - timestamp).as_secs_f64() as FLOAT).into())
         }
 
         #[cfg(feature = "no_float")]
         {
-            let seconds = timestamp.elapsed().as_secs();
+            if timestamp > now {
+                return Err(make_arithmetic_err("Time-stamp is later than now"));
+            }
+
+            let seconds = (now - timestamp).as_secs();
 
             if cfg!(not(feature = "unchecked")) && seconds > (MAX_INT as u64) {
                 Err(make_arithmetic_err(format!(
                     "Integer overflow for timestamp.elapsed: {}",
                     seconds
                 )))
-            } else if timestamp > Instant::now() {
-                Err(make_arithmetic_err("Time-stamp is later than now"))
             } else {
                 Ok((seconds as INT).into())
             }
@@ -0,0 +1,136 @@
+//! Module defining packed arrays of numbers.
+#![cfg(not(feature = "no_index"))]
+
+use crate::stdlib::{
+    fmt,
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+    vec::{IntoIter, Vec},
+};
+use crate::{Array, Dynamic, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+/// Variable-sized array of unboxed numbers of the same type (a _packed array_).
+///
+/// Unlike an [`Array`], which holds a [`Dynamic`] for each item, a [`PackedArray`] stores its
+/// items contiguously, making element-wise arithmetic and reductions over large arrays fast.
+///
+/// A [`PackedArray`] dereferences to the underlying [`Vec`].
+///
+/// Not available under `no_index`.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{IntArray, INT};
+///
+/// let mut array = IntArray::from(vec![1 as INT, 2, 3]);
+///
+/// array.push(4);
+///
+/// assert_eq!(array.len(), 4);
+/// assert_eq!(array.iter().sum::<INT>(), 10);
+/// ```
+#[derive(Clone, Default, PartialEq)]
+pub struct PackedArray<T>(Vec<T>);
+
+/// Packed array of [`INT`] (an _int array_).
+///
+/// Not available under `no_index`.
+pub type IntArray = PackedArray<INT>;
+
+/// Packed array of [`FLOAT`] (a _float array_).
+///
+/// Not available under `no_index` or `no_float`.
+#[cfg(not(feature = "no_float"))]
+pub type FloatArray = PackedArray<FLOAT>;
+
+impl<T> PackedArray<T> {
+    /// Create a new, empty [`PackedArray`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+    /// Create a new, empty [`PackedArray`] with a particular capacity.
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+    /// Convert the [`PackedArray`] into a [`Vec`] of its items.
+    #[inline(always)]
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T: Copy + Into<Dynamic>> PackedArray<T> {
+    /// Convert the [`PackedArray`] into an [`Array`] of its items.
+    #[inline(always)]
+    pub fn to_array(&self) -> Array {
+        self.0.iter().map(|&x| x.into()).collect()
+    }
+}
+
+impl<T> Deref for PackedArray<T> {
+    type Target = Vec<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for PackedArray<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for PackedArray<T> {
+    #[inline(always)]
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> FromIterator<T> for PackedArray<T> {
+    #[inline(always)]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T> IntoIterator for PackedArray<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PackedArray<T> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+/// Get the number of items in a [`Dynamic`] holding a packed array, if any.
+#[cfg(not(feature = "unchecked"))]
+pub(crate) fn packed_len(value: &Dynamic) -> Option<usize> {
+    if let Some(array) = value.downcast_ref::<IntArray>() {
+        return Some(array.len());
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    if let Some(array) = value.downcast_ref::<FloatArray>() {
+        return Some(array.len());
+    }
+
+    None
+}
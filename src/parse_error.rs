@@ -155,10 +155,42 @@ pub enum ParseErrorType {
     ///
     /// Never appears under the `no_function` feature.
     FnMissingBody(String),
+    /// Defining a record type via `type` or `impl` in an appropriate place (e.g. inside a function).
+    ///
+    /// Never appears under the `no_function` or `no_object` features.
+    TypeWrongDefinition,
+    /// Missing a type name after the `type` or `impl` keywords.
+    ///
+    /// Never appears under the `no_function` or `no_object` features.
+    TypeMissingName,
+    /// A record type definition has duplicated fields. Wrapped values are the type name and
+    /// field name.
+    ///
+    /// Never appears under the `no_function` or `no_object` features.
+    TypeDuplicatedField(String, String),
+    /// Methods are defined in an `impl` block for a record type that is not declared.
+    /// Wrapped value is the type name.
+    ///
+    /// Never appears under the `no_function` or `no_object` features.
+    TypeUndefined(String),
     /// Export statement not at global level.
     ///
     /// Never appears under the `no_module` feature.
     WrongExport,
+    /// A name selected by an `import` statement is not exported by the [module][crate::Module].
+    /// Wrapped values are the name and the path of the [module][crate::Module].
+    ///
+    /// Never appears under the `no_module` feature.
+    NotExported(String, String),
+    /// Declaring a [module][crate::Module] via `module` in an inappropriate place (e.g. inside a
+    /// block or a function).
+    ///
+    /// Never appears under the `no_module` feature.
+    ModuleWrongDefinition,
+    /// Missing a [module][crate::Module] name after the `module` keyword.
+    ///
+    /// Never appears under the `no_module` feature.
+    ModuleMissingName,
     /// Assignment to an a constant variable. Wrapped value is the constant variable name.
     AssignmentToConstant(String),
     /// Assignment to an inappropriate LHS (left-hand-side) expression.
@@ -174,6 +206,10 @@ pub enum ParseErrorType {
     LiteralTooLarge(String, usize),
     /// Break statement not inside a loop.
     LoopBreak,
+    /// Yield statement not inside a function.
+    ///
+    /// Never appears under the `no_function` feature.
+    WrongYield,
 }
 
 impl ParseErrorType {
@@ -206,12 +242,20 @@ impl ParseErrorType {
             Self::FnDuplicatedParam(_,_) => "Duplicated parameters in function declaration",
             Self::FnMissingBody(_) => "Expecting body statement block for function declaration",
             Self::WrongDocComment => "Doc-comment must be followed immediately by a function definition",
+            Self::TypeWrongDefinition => "Type definitions must be at global level and cannot be inside a block or a function",
+            Self::TypeMissingName => "Expecting type name in type declaration",
+            Self::TypeDuplicatedField(_, _) => "Duplicated field in type declaration",
+            Self::TypeUndefined(_) => "Undefined type in impl block",
             Self::WrongExport => "Export statement can only appear at global level",
+            Self::NotExported(_, _) => "Name is not exported by the module",
+            Self::ModuleWrongDefinition => "Module declarations must be at global level and cannot be inside a block or a function",
+            Self::ModuleMissingName => "Expecting module name in module declaration",
             Self::AssignmentToConstant(_) => "Cannot assign to a constant value",
             Self::AssignmentToInvalidLHS(_) => "Expression cannot be assigned to",
             Self::ExprTooDeep => "Expression exceeds maximum complexity",
             Self::LiteralTooLarge(_, _) => "Literal exceeds maximum limit",
-            Self::LoopBreak => "Break statement should only be used inside a loop"
+            Self::LoopBreak => "Break statement should only be used inside a loop",
+            Self::WrongYield => "Yield statement should only be used inside a function"
         }
     }
 }
@@ -256,6 +300,17 @@ impl fmt::Display for ParseErrorType {
                 write!(f, "Duplicated parameter '{}' for function '{}'", arg, s)
             }
 
+            Self::TypeDuplicatedField(s, field) => {
+                write!(f, "Duplicated field '{}' for type '{}'", field, s)
+            }
+            Self::TypeUndefined(s) => {
+                write!(f, "Type '{}' must be declared before its methods", s)
+            }
+
+            Self::NotExported(s, path) => {
+                write!(f, "'{}' is not exported by module '{}'", s, path)
+            }
+
             Self::MissingToken(token, s) => write!(f, "Expecting '{}' {}", token, s),
 
             Self::AssignmentToConstant(s) if s.is_empty() => f.write_str(self.desc()),
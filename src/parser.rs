@@ -6,6 +6,12 @@ use crate::ast::{
 };
 use crate::dynamic::{AccessMode, Union};
 use crate::engine::{Precedence, KEYWORD_THIS, OP_CONTAINS};
+
+#[cfg(not(feature = "no_module"))]
+use crate::ast::{ImportNames, ModuleDef};
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+use crate::engine::make_record_method;
 use crate::module::NamespaceRef;
 use crate::optimize::optimize_into_ast;
 use crate::optimize::OptimizationLevel;
@@ -69,6 +75,13 @@ struct ParseState<'e> {
     #[cfg(not(feature = "unchecked"))]
     #[cfg(not(feature = "no_function"))]
     max_function_expr_depth: Option<NonZeroUsize>,
+    /// Does the function body being parsed contain `yield` statements?
+    #[cfg(not(feature = "no_function"))]
+    is_generator: bool,
+    /// Names of the record types declared so far.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    types: StaticVec<ImmutableString>,
 }
 
 impl<'e> ParseState<'e> {
@@ -97,6 +110,11 @@ impl<'e> ParseState<'e> {
             entry_stack_len: 0,
             #[cfg(not(feature = "no_module"))]
             modules: Default::default(),
+            #[cfg(not(feature = "no_function"))]
+            is_generator: false,
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_object"))]
+            types: Default::default(),
         }
     }
 
@@ -675,6 +693,76 @@ fn parse_array_literal(
     Ok(Expr::Array(Box::new(arr), settings.pos))
 }
 
+/// Parse a set literal.
+#[cfg(not(feature = "no_index"))]
+fn parse_set_literal(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    mut settings: ParseSettings,
+) -> Result<Expr, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // #[ ...
+    settings.pos = eat_token(input, Token::SetStart);
+
+    let mut set = StaticVec::new();
+
+    loop {
+        const MISSING_RBRACKET: &str = "to end this set literal";
+
+        match input.peek().unwrap() {
+            (Token::RightBracket, _) => {
+                eat_token(input, Token::RightBracket);
+                break;
+            }
+            (Token::EOF, pos) => {
+                return Err(
+                    PERR::MissingToken(Token::RightBracket.into(), MISSING_RBRACKET.into())
+                        .into_err(*pos),
+                )
+            }
+            _ => {
+                #[cfg(not(feature = "unchecked"))]
+                if state.engine.max_set_size() > 0 && set.len() >= state.engine.max_set_size() {
+                    return Err(PERR::LiteralTooLarge(
+                        "Size of set literal".to_string(),
+                        state.engine.max_set_size(),
+                    )
+                    .into_err(input.peek().unwrap().1));
+                }
+
+                let expr = parse_expr(input, state, lib, settings.level_up())?;
+                set.push(expr);
+            }
+        }
+
+        match input.peek().unwrap() {
+            (Token::Comma, _) => {
+                eat_token(input, Token::Comma);
+            }
+            (Token::RightBracket, _) => (),
+            (Token::EOF, pos) => {
+                return Err(
+                    PERR::MissingToken(Token::RightBracket.into(), MISSING_RBRACKET.into())
+                        .into_err(*pos),
+                )
+            }
+            (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::Comma.into(),
+                    "to separate the items of this set literal".into(),
+                )
+                .into_err(*pos))
+            }
+        };
+    }
+
+    Ok(Expr::Set(Box::new(set), settings.pos))
+}
+
 /// Parse a map literal.
 #[cfg(not(feature = "no_object"))]
 fn parse_map_literal(
@@ -955,6 +1043,18 @@ fn parse_primary(
             input.next().unwrap();
             Expr::DynamicConstant(Box::new(x), settings.pos)
         }
+        #[cfg(feature = "bigint")]
+        Token::BigIntConstant(x) => {
+            let x = (**x).clone().into();
+            input.next().unwrap();
+            Expr::DynamicConstant(Box::new(x), settings.pos)
+        }
+        #[cfg(not(feature = "no_index"))]
+        Token::BlobConstant(x) => {
+            let x = Dynamic::from_blob(x.clone());
+            input.next().unwrap();
+            Expr::DynamicConstant(Box::new(x), settings.pos)
+        }
 
         // { - block statement as expression
         Token::LeftBrace if settings.allow_stmt_expr => {
@@ -1015,6 +1115,10 @@ fn parse_primary(
         #[cfg(not(feature = "no_index"))]
         Token::LeftBracket => parse_array_literal(input, state, lib, settings.level_up())?,
 
+        // Set literal
+        #[cfg(not(feature = "no_index"))]
+        Token::SetStart => parse_set_literal(input, state, lib, settings.level_up())?,
+
         // Map literal
         #[cfg(not(feature = "no_object"))]
         Token::MapStart => parse_map_literal(input, state, lib, settings.level_up())?,
@@ -2200,6 +2304,86 @@ fn parse_import(
     // import ...
     settings.pos = eat_token(input, Token::Import);
 
+    // import { ...
+    if match_token(input, Token::LeftBrace).0 {
+        let mut names = StaticVec::new();
+
+        loop {
+            // import { name ...
+            let (name, name_pos) = match input.next().unwrap() {
+                (Token::Identifier(s), pos) => (s, pos),
+                (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+                    return Err(PERR::Reserved(s).into_err(pos));
+                }
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+            };
+
+            // import { name as alias ...
+            let alias = if match_token(input, Token::As).0 {
+                match input.next().unwrap() {
+                    (Token::Identifier(s), pos) => Some(Ident {
+                        name: state.get_interned_string(s),
+                        pos,
+                    }),
+                    (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+                        return Err(PERR::Reserved(s).into_err(pos));
+                    }
+                    (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                    (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+                }
+            } else {
+                None
+            };
+
+            names.push((
+                Ident {
+                    name: state.get_interned_string(name),
+                    pos: name_pos,
+                },
+                alias,
+            ));
+
+            match input.next().unwrap() {
+                (Token::Comma, _) => (),
+                (Token::RightBrace, _) => break,
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (_, pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::RightBrace.into(),
+                        "to end the list of names to import".into(),
+                    )
+                    .into_err(pos))
+                }
+            }
+        }
+
+        // import { ... } from ...
+        match input.next().unwrap() {
+            (Token::Identifier(s), _) if s == "from" => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    "from".into(),
+                    "after the list of names to import".into(),
+                )
+                .into_err(pos))
+            }
+        }
+
+        // import { ... } from expr
+        let expr = parse_expr(input, state, lib, settings.level_up())?;
+
+        // The selected functions are kept in an unnamed module
+        state.modules.push(Default::default());
+
+        return Ok(Stmt::Import(
+            expr,
+            Some(Box::new(ImportNames::Selected(names))),
+            settings.pos,
+        ));
+    }
+
     // import expr ...
     let expr = parse_expr(input, state, lib, settings.level_up())?;
 
@@ -2223,10 +2407,10 @@ fn parse_import(
 
     Ok(Stmt::Import(
         expr,
-        Some(Ident {
+        Some(Box::new(ImportNames::Module(Ident {
             name,
             pos: name_pos,
-        }),
+        }))),
         settings.pos,
     ))
 }
@@ -2314,6 +2498,143 @@ fn parse_export(
     Ok(Stmt::Export(exports, settings.pos))
 }
 
+/// Parse an inline module declaration.
+#[cfg(not(feature = "no_module"))]
+fn parse_module(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    mut settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // module ...
+    settings.pos = eat_token(input, Token::Module);
+
+    // module name ...
+    let (name, name_pos) = match input.next().unwrap() {
+        (Token::Identifier(s), pos) => (s, pos),
+        (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+            return Err(PERR::Reserved(s).into_err(pos));
+        }
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => return Err(PERR::ModuleMissingName.into_err(pos)),
+    };
+
+    // module name { ...
+    match input.next().unwrap() {
+        (Token::LeftBrace, _) => (),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => {
+            return Err(PERR::MissingToken(
+                Token::LeftBrace.into(),
+                format!("to start the body of module '{}'", name),
+            )
+            .into_err(pos))
+        }
+    }
+
+    // The module body is parsed as a separate script, just like a module file
+    let mut new_state = ParseState::new(
+        state.engine,
+        #[cfg(not(feature = "unchecked"))]
+        state.max_expr_depth,
+        #[cfg(not(feature = "unchecked"))]
+        #[cfg(not(feature = "no_function"))]
+        state.max_function_expr_depth,
+    );
+    let mut functions = Default::default();
+    let mut statements = Vec::with_capacity(8);
+
+    loop {
+        let settings = ParseSettings {
+            allow_if_expr: true,
+            allow_switch_expr: true,
+            allow_stmt_expr: true,
+            allow_anonymous_fn: true,
+            is_global: true,
+            is_function_scope: false,
+            is_breakable: false,
+            level: settings.level,
+            pos: Position::NONE,
+        };
+
+        match input.peek().unwrap() {
+            // module name { ... }
+            (Token::RightBrace, _) => {
+                eat_token(input, Token::RightBrace);
+                break;
+            }
+            // module name { ... <EOF>
+            (Token::EOF, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::RightBrace.into(),
+                    format!("to end the body of module '{}'", name),
+                )
+                .into_err(*pos))
+            }
+            _ => (),
+        }
+
+        let stmt = parse_stmt(input, &mut new_state, &mut functions, settings.level_up())?;
+
+        if stmt.is_noop() {
+            continue;
+        }
+
+        let need_semicolon = !stmt.is_self_terminated();
+
+        statements.push(stmt);
+
+        match input.peek().unwrap() {
+            // module name { ... stmt }
+            (Token::RightBrace, _) => (),
+            // module name { ... stmt;
+            (Token::SemiColon, _) if need_semicolon => {
+                eat_token(input, Token::SemiColon);
+            }
+            // module name { ... { stmt } ;
+            (Token::SemiColon, _) if !need_semicolon => (),
+            // module name { ... { stmt } ???
+            (_, _) if !need_semicolon => (),
+            // module name { ... stmt <error>
+            (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+            // module name { ... stmt ???
+            (_, pos) => {
+                // Semicolons are not optional between statements
+                return Err(PERR::MissingToken(
+                    Token::SemiColon.into(),
+                    "to terminate this statement".into(),
+                )
+                .into_err(*pos));
+            }
+        }
+    }
+
+    let functions = functions.into_iter().map(|(_, v)| v).collect();
+    let ast = optimize_into_ast(
+        state.engine,
+        &Default::default(),
+        statements,
+        functions,
+        state.engine.optimization_level,
+    );
+
+    let name = state.get_interned_string(name);
+    state.modules.push(name.clone());
+
+    Ok(Stmt::Module(
+        Box::new(ModuleDef {
+            name: Ident {
+                name,
+                pos: name_pos,
+            },
+            ast,
+        }),
+        settings.pos,
+    ))
+}
+
 /// Parse a statement block.
 fn parse_block(
     input: &mut TokenStream,
@@ -2536,6 +2857,21 @@ fn parse_stmt(
             }
         }
 
+        // type ... | impl ...
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_object"))]
+        Token::Type | Token::Impl if !settings.is_global => {
+            Err(PERR::TypeWrongDefinition.into_err(settings.pos))
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_object"))]
+        Token::Type => parse_type(input, state, lib, settings.level_up()),
+
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_object"))]
+        Token::Impl => parse_impl(input, state, lib, settings.level_up()),
+
         Token::If => parse_if(input, state, lib, settings.level_up()),
         Token::Switch => parse_switch(input, state, lib, settings.level_up()),
         Token::While | Token::Loop => parse_while_loop(input, state, lib, settings.level_up()),
@@ -2580,6 +2916,26 @@ fn parse_stmt(
             }
         }
 
+        #[cfg(not(feature = "no_function"))]
+        Token::Yield if settings.is_function_scope => {
+            let pos = eat_token(input, Token::Yield);
+            state.is_generator = true;
+
+            match input.peek().unwrap() {
+                // `yield` at <EOF>
+                (Token::EOF, _) => Ok(Stmt::Return(ReturnType::Yield, None, pos)),
+                // `yield;`
+                (Token::SemiColon, _) => Ok(Stmt::Return(ReturnType::Yield, None, pos)),
+                // `yield` with expression
+                (_, _) => {
+                    let expr = parse_expr(input, state, lib, settings.level_up())?;
+                    Ok(Stmt::Return(ReturnType::Yield, Some(expr), pos))
+                }
+            }
+        }
+        #[cfg(not(feature = "no_function"))]
+        Token::Yield => Err(PERR::WrongYield.into_err(settings.pos)),
+
         Token::Try => parse_try_catch(input, state, lib, settings.level_up()),
 
         Token::Let => parse_let(input, state, lib, ReadWrite, false, settings.level_up()),
@@ -2594,6 +2950,14 @@ fn parse_stmt(
         #[cfg(not(feature = "no_module"))]
         Token::Export => parse_export(input, state, lib, settings.level_up()),
 
+        #[cfg(not(feature = "no_module"))]
+        Token::Module if !settings.is_global => {
+            Err(PERR::ModuleWrongDefinition.into_err(settings.pos))
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        Token::Module => parse_module(input, state, settings.level_up()),
+
         _ => parse_expr_stmt(input, state, lib, settings.level_up()),
     }
 }
@@ -2755,9 +3119,228 @@ fn parse_fn(
         #[cfg(not(feature = "no_module"))]
         mods: Default::default(),
         comments,
+        is_generator: state.is_generator,
     })
 }
 
+/// Parse the name of a record type following the `type` or `impl` keywords, plus the opening `{`.
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+fn parse_type_name(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    what: &str,
+) -> Result<Ident, ParseError> {
+    let (name, pos) = match input.next().unwrap() {
+        (Token::Identifier(s), pos) => (s, pos),
+        (Token::Reserved(s), pos) => return Err(PERR::Reserved(s).into_err(pos)),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => return Err(PERR::TypeMissingName.into_err(pos)),
+    };
+
+    match input.next().unwrap() {
+        (Token::LeftBrace, _) => (),
+        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+        (_, pos) => {
+            return Err(PERR::MissingToken(
+                Token::LeftBrace.into(),
+                format!("to start the {} of type '{}'", what, name),
+            )
+            .into_err(pos))
+        }
+    }
+
+    Ok(Ident {
+        name: state.get_interned_string(name),
+        pos,
+    })
+}
+
+/// Parse a record type definition.
+///
+/// `type Name { field, ... }` defines a constructor function `Name(field, ...)`.
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+fn parse_type(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    mut settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // type ...
+    settings.pos = eat_token(input, Token::Type);
+
+    let name = parse_type_name(input, state, "fields")?;
+    let mut fields: StaticVec<Ident> = Default::default();
+
+    loop {
+        match input.next().unwrap() {
+            (Token::RightBrace, _) => break,
+            (Token::Identifier(s), pos) => {
+                if fields.iter().any(|f| f.name == s) {
+                    return Err(PERR::TypeDuplicatedField(name.name.to_string(), s).into_err(pos));
+                }
+                fields.push(Ident {
+                    name: state.get_interned_string(s),
+                    pos,
+                });
+            }
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::RightBrace.into(),
+                    format!("to close the fields of type '{}'", name.name),
+                )
+                .into_err(pos))
+            }
+        }
+
+        match input.next().unwrap() {
+            (Token::RightBrace, _) => break,
+            (Token::Comma, _) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::Comma.into(),
+                    format!("to separate the fields of type '{}'", name.name),
+                )
+                .into_err(pos))
+            }
+        }
+    }
+
+    let hash = calc_fn_hash(empty(), &name.name, fields.len());
+
+    if lib.contains_key(&hash) {
+        return Err(
+            PERR::FnDuplicatedDefinition(name.name.to_string(), fields.len()).into_err(name.pos),
+        );
+    }
+
+    // The constructor simply collects its parameters into a record
+    let params = fields.iter().map(|f| f.name.clone()).collect();
+    let values = fields
+        .into_iter()
+        .map(|f| (f.clone(), Expr::Variable(Box::new((None, None, f)))))
+        .collect();
+    let body = Stmt::Expr(Expr::Record(Box::new((name.clone(), values)), settings.pos));
+
+    let func = ScriptFnDef {
+        name: name.name.clone(),
+        access: FnAccess::Public,
+        params,
+        #[cfg(not(feature = "no_closure"))]
+        externals: Default::default(),
+        body: body.into(),
+        lib: None,
+        #[cfg(not(feature = "no_module"))]
+        mods: Default::default(),
+        comments: Default::default(),
+        is_generator: false,
+    };
+
+    lib.insert(hash, func.into());
+    state.types.push(name.name);
+
+    Ok(Stmt::Noop(settings.pos))
+}
+
+/// Parse the methods of a record type.
+///
+/// Each method in `impl Name { fn method(...) { ... } ... }` is defined as a script function
+/// which is called in method-call style on records of that type, with `this` bound to the record.
+/// The type must be declared before its methods.
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_object"))]
+fn parse_impl(
+    input: &mut TokenStream,
+    state: &mut ParseState,
+    lib: &mut FunctionsLib,
+    mut settings: ParseSettings,
+) -> Result<Stmt, ParseError> {
+    #[cfg(not(feature = "unchecked"))]
+    settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+    // impl ...
+    settings.pos = eat_token(input, Token::Impl);
+
+    let name = parse_type_name(input, state, "methods")?;
+
+    if !state.types.contains(&name.name) {
+        return Err(PERR::TypeUndefined(name.name.to_string()).into_err(name.pos));
+    }
+
+    loop {
+        let access = match input.next().unwrap() {
+            (Token::RightBrace, _) => break,
+            (Token::Fn, _) => FnAccess::Public,
+            (Token::Private, _) => match input.next().unwrap() {
+                (Token::Fn, _) => FnAccess::Private,
+                (_, pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::Fn.into(),
+                        format!("following '{}'", Token::Private.syntax()),
+                    )
+                    .into_err(pos))
+                }
+            },
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (_, pos) => {
+                return Err(PERR::MissingToken(
+                    Token::RightBrace.into(),
+                    format!("to close the methods of type '{}'", name.name),
+                )
+                .into_err(pos))
+            }
+        };
+
+        let mut new_state = ParseState::new(
+            state.engine,
+            #[cfg(not(feature = "unchecked"))]
+            state.max_function_expr_depth,
+            #[cfg(not(feature = "unchecked"))]
+            state.max_function_expr_depth,
+        );
+
+        let fn_settings = ParseSettings {
+            allow_if_expr: true,
+            allow_switch_expr: true,
+            allow_stmt_expr: true,
+            allow_anonymous_fn: true,
+            is_global: false,
+            is_function_scope: true,
+            is_breakable: false,
+            level: 0,
+            pos: input.peek().unwrap().1,
+        };
+
+        let mut func = parse_fn(
+            input,
+            &mut new_state,
+            lib,
+            access,
+            fn_settings,
+            Default::default(),
+        )?;
+        func.name = make_record_method(&name.name, &func.name).into();
+        let hash = calc_fn_hash(empty(), &func.name, func.params.len());
+
+        if lib.contains_key(&hash) {
+            return Err(
+                PERR::FnDuplicatedDefinition(func.name.into_owned(), func.params.len())
+                    .into_err(fn_settings.pos),
+            );
+        }
+
+        lib.insert(hash, func.into());
+    }
+
+    Ok(Stmt::Noop(settings.pos))
+}
+
 /// Creates a curried expression from a list of external variables
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_closure"))]
@@ -2900,6 +3483,7 @@ fn parse_anon_fn(
         #[cfg(not(feature = "no_module"))]
         mods: Default::default(),
         comments: Default::default(),
+        is_generator: state.is_generator,
     };
 
     let expr = Expr::FnPointer(fn_name, settings.pos);
@@ -3053,6 +3637,9 @@ pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
         #[cfg(feature = "decimal")]
         Union::Decimal(value, _) => Some(Expr::DynamicConstant(Box::new((*value).into()), pos)),
 
+        #[cfg(feature = "bigint")]
+        Union::BigInt(value, _) => Some(Expr::DynamicConstant(Box::new((*value).into()), pos)),
+
         Union::Unit(_, _) => Some(Expr::Unit(pos)),
         Union::Int(value, _) => Some(Expr::IntegerConstant(value, pos)),
         Union::Char(value, _) => Some(Expr::CharConstant(value, pos)),
@@ -3062,6 +3649,15 @@ pub fn map_dynamic_to_expr(value: Dynamic, pos: Position) -> Option<Expr> {
         #[cfg(not(feature = "no_index"))]
         Union::Array(array, _) => Some(Expr::DynamicConstant(Box::new((*array).into()), pos)),
 
+        #[cfg(not(feature = "no_index"))]
+        Union::Blob(blob, _) => Some(Expr::DynamicConstant(
+            Box::new(Dynamic::from_blob(*blob)),
+            pos,
+        )),
+
+        #[cfg(not(feature = "no_index"))]
+        Union::Set(set, _) => Some(Expr::DynamicConstant(Box::new((*set).into()), pos)),
+
         #[cfg(not(feature = "no_object"))]
         Union::Map(map, _) => Some(Expr::DynamicConstant(Box::new((*map).into()), pos)),
 
@@ -0,0 +1,224 @@
+//! Module defining capability-based permissions restricting what a script evaluation may access.
+
+use crate::fn_native::{OnDeniedCallback, SendSync};
+use crate::stdlib::{boxed::Box, collections::BTreeSet, fmt};
+use crate::{EvalAltResult, ImmutableString, Position, Shared};
+
+/// A capability that can be restricted by [`Permissions`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Capability {
+    /// Calling a native Rust function that is not in a package.
+    Function,
+    /// Accessing a [module][crate::Module] registered via
+    /// [`Engine::register_static_module`][crate::Engine::register_static_module].
+    Module,
+    /// Loading a [module][crate::Module] via an `import` statement.
+    Import,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Function => "function",
+            Self::Module => "module",
+            Self::Import => "import path",
+        })
+    }
+}
+
+/// Allowlists of the native Rust functions, [modules][crate::Module] and `import` paths
+/// that evaluations of an [`AST`][crate::AST] may access.
+///
+/// Attach to an [`AST`][crate::AST] via [`AST::set_permissions`][crate::AST::set_permissions].
+/// All evaluations of that [`AST`][crate::AST] (including calls to its functions, `eval`
+/// statements and resumed [continuations][crate::Continuation]) are then checked against it.
+///
+/// Each kind of [`Capability`] is unrestricted until an allowlist is set for it.
+/// Setting an empty allowlist denies everything of that kind.
+///
+/// * The function allowlist restricts native Rust functions by name, whether registered directly
+///   with the [`Engine`][crate::Engine] via `Engine::register_XXX`, in global modules registered
+///   via [`Engine::register_global_module`][crate::Engine::register_global_module] (including
+///   plugin modules), or in static and imported [modules][crate::Module].
+///   Functions in packages (see [`Module::is_package`][crate::Module::is_package]),
+///   script-defined functions, property getters/setters and indexers are not restricted.
+///
+/// * The module allowlist restricts, by name, namespace-qualified access (e.g. `foo::bar()`)
+///   to modules registered via
+///   [`Engine::register_static_module`][crate::Engine::register_static_module].
+///
+/// * The import allowlist restricts the paths that `import` statements may load.
+///   Statements within the imported module itself are not restricted.
+///
+/// Accessing anything not allowed fails with
+/// [`ErrorPermissionDenied`][EvalAltResult::ErrorPermissionDenied], which cannot be caught
+/// in a `try` ... `catch` block. Denied accesses can be audited via
+/// [`on_denied`][Permissions::on_denied].
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, EvalAltResult, Permissions, INT};
+///
+/// let mut engine = Engine::new();
+///
+/// engine.register_fn("get_user", |id: INT| id * 10)
+///       .register_fn("delete_user", |_: INT| ());
+///
+/// let mut permissions = Permissions::new();
+/// permissions.allow_functions(["get_user", "log"]);
+///
+/// let mut ast = engine.compile("get_user(4) + 2")?;
+/// ast.set_permissions(permissions.clone());
+///
+/// assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+///
+/// let mut ast = engine.compile("delete_user(4)")?;
+/// ast.set_permissions(permissions);
+///
+/// assert!(matches!(
+///     *engine.eval_ast::<()>(&ast).expect_err("should error"),
+///     EvalAltResult::ErrorPermissionDenied(_, name, _) if name == "delete_user"
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Permissions {
+    /// Allowed native Rust functions, if restricted.
+    functions: Option<BTreeSet<ImmutableString>>,
+    /// Allowed modules, if restricted.
+    modules: Option<BTreeSet<ImmutableString>>,
+    /// Allowed `import` paths, if restricted.
+    imports: Option<BTreeSet<ImmutableString>>,
+    /// Callback for denied accesses.
+    on_denied: Option<Shared<OnDeniedCallback>>,
+}
+
+impl fmt::Debug for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permissions")
+            .field("functions", &self.functions)
+            .field("modules", &self.modules)
+            .field("imports", &self.imports)
+            .finish()
+    }
+}
+
+impl Permissions {
+    /// Create a new [`Permissions`] with nothing restricted.
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Allow calling the specified native Rust functions, restricting all others.
+    #[inline(always)]
+    pub fn allow_functions(
+        &mut self,
+        names: impl IntoIterator<Item = impl Into<ImmutableString>>,
+    ) -> &mut Self {
+        Self::allow(&mut self.functions, names);
+        self
+    }
+    /// Allow accessing the specified [modules][crate::Module], restricting all others.
+    #[inline(always)]
+    pub fn allow_modules(
+        &mut self,
+        names: impl IntoIterator<Item = impl Into<ImmutableString>>,
+    ) -> &mut Self {
+        Self::allow(&mut self.modules, names);
+        self
+    }
+    /// Allow importing the specified paths, restricting all others.
+    ///
+    /// Paths must match exactly the path strings used in `import` statements.
+    #[inline(always)]
+    pub fn allow_imports(
+        &mut self,
+        paths: impl IntoIterator<Item = impl Into<ImmutableString>>,
+    ) -> &mut Self {
+        Self::allow(&mut self.imports, paths);
+        self
+    }
+    /// Add names to an allowlist, creating it if necessary.
+    #[inline(always)]
+    fn allow(
+        list: &mut Option<BTreeSet<ImmutableString>>,
+        names: impl IntoIterator<Item = impl Into<ImmutableString>>,
+    ) {
+        list.get_or_insert_with(Default::default)
+            .extend(names.into_iter().map(Into::into));
+    }
+    /// Register a callback that is called for every denied access, for auditing.
+    ///
+    /// The callback receives the kind of [`Capability`] accessed, the name accessed,
+    /// and the [position][Position] of the access in the script.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use std::sync::{Arc, RwLock};
+    /// use rhai::{Capability, Engine, Permissions};
+    ///
+    /// let denied = Arc::new(RwLock::new(Vec::new()));
+    /// let log = denied.clone();
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_fn("secret", || 42_i64);
+    ///
+    /// let mut permissions = Permissions::new();
+    /// permissions
+    ///     .allow_functions(Vec::<&str>::new())
+    ///     .on_denied(move |capability, name, pos| {
+    ///         log.write().unwrap().push(format!("{} '{}' at {}", capability, name, pos))
+    ///     });
+    ///
+    /// let mut ast = engine.compile("secret()")?;
+    /// ast.set_permissions(permissions);
+    ///
+    /// assert!(engine.eval_ast::<i64>(&ast).is_err());
+    /// assert_eq!(*denied.read().unwrap(), ["function 'secret' at line 1, position 1"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_denied(
+        &mut self,
+        callback: impl Fn(Capability, &str, Position) + SendSync + 'static,
+    ) -> &mut Self {
+        self.on_denied = Some(Shared::new(callback));
+        self
+    }
+    /// Is access to the specified name allowed?
+    #[inline(always)]
+    pub fn is_allowed(&self, capability: Capability, name: &str) -> bool {
+        let list = match capability {
+            Capability::Function => &self.functions,
+            Capability::Module => &self.modules,
+            Capability::Import => &self.imports,
+        };
+
+        list.as_ref().map_or(true, |list| list.contains(name))
+    }
+    /// Check access to the specified name, auditing and returning an error if it is denied.
+    #[inline]
+    pub(crate) fn check(
+        &self,
+        capability: Capability,
+        name: &str,
+        pos: Position,
+    ) -> Result<(), Box<EvalAltResult>> {
+        if self.is_allowed(capability, name) {
+            return Ok(());
+        }
+
+        if let Some(ref callback) = self.on_denied {
+            callback(capability, name, pos);
+        }
+
+        EvalAltResult::ErrorPermissionDenied(capability, name.into(), pos).into()
+    }
+}
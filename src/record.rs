@@ -0,0 +1,91 @@
+//! Module defining script-defined record types.
+#![cfg(not(feature = "no_object"))]
+
+use crate::stdlib::fmt;
+use crate::{Dynamic, ImmutableString, StaticVec};
+
+/// A value of a record type declared in script via `type Name { field, ... }`.
+///
+/// A record has a fixed set of fields, in declaration order. Fields can be read and
+/// assigned, but no new fields can be added.
+///
+/// Not available under `no_object`.
+#[derive(Clone)]
+pub struct Record {
+    /// Name of the record type.
+    name: ImmutableString,
+    /// Field names and values, in declaration order.
+    fields: StaticVec<(ImmutableString, Dynamic)>,
+}
+
+impl Record {
+    /// Create a new [`Record`] of a particular type with fields in declaration order.
+    #[inline(always)]
+    pub fn new(
+        name: impl Into<ImmutableString>,
+        fields: impl IntoIterator<Item = (ImmutableString, Dynamic)>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            fields: fields.into_iter().collect(),
+        }
+    }
+    /// Get the name of the record type.
+    #[inline(always)]
+    pub fn type_name(&self) -> &str {
+        &self.name
+    }
+    /// Get the number of fields.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+    /// Does this record have no fields?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+    /// Does this record have a particular field?
+    #[inline(always)]
+    pub fn contains(&self, field: &str) -> bool {
+        self.fields.iter().any(|(k, _)| k.as_str() == field)
+    }
+    /// Get a reference to the value of a field.
+    #[inline(always)]
+    pub fn get(&self, field: &str) -> Option<&Dynamic> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k.as_str() == field)
+            .map(|(_, v)| v)
+    }
+    /// Get a mutable reference to the value of a field.
+    #[inline(always)]
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Dynamic> {
+        self.fields
+            .iter_mut()
+            .find(|(k, _)| k.as_str() == field)
+            .map(|(_, v)| v)
+    }
+    /// Iterate through all the fields, in declaration order.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Dynamic)> {
+        self.fields.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl fmt::Debug for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct(&self.name);
+        self.fields.iter().for_each(|(k, v)| {
+            s.field(k, v);
+        });
+        s.finish()
+    }
+}
+
+impl fmt::Display for Record {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
@@ -5,8 +5,9 @@ use crate::stdlib::{
     error::Error,
     fmt,
     string::{String, ToString},
+    vec::Vec,
 };
-use crate::{Dynamic, ImmutableString, ParseErrorType, Position, INT};
+use crate::{Capability, Continuation, Dynamic, ImmutableString, ParseErrorType, Position, INT};
 
 /// Evaluation result.
 ///
@@ -41,6 +42,13 @@ pub enum EvalAltResult {
     /// An error has occurred while loading a [module][crate::Module].
     /// Wrapped value are the [module][crate::Module] name and the interior error.
     ErrorInModule(String, Box<EvalAltResult>, Position),
+    /// A [module][crate::Module] imports itself, directly or indirectly.
+    /// Wrapped value is the chain of `import` paths forming the cycle, each with the position of
+    /// its `import` statement, ending with the path that closes the cycle.
+    ErrorCyclicImport(Vec<(String, Position)>, Position),
+    /// A name selected by an `import` statement is not exported by the [module][crate::Module].
+    /// Wrapped values are the name and the path of the [module][crate::Module].
+    ErrorNotExported(String, String, Position),
     /// Access to `this` that is not bound.
     ErrorUnboundThis(Position),
     /// Data is not of the required type.
@@ -76,10 +84,16 @@ pub enum EvalAltResult {
     ErrorStackOverflow(Position),
     /// Data value over maximum size limit. Wrapped value is the type name.
     ErrorDataTooLarge(String, Position),
+    /// Access denied by the [`Permissions`][crate::Permissions] of the evaluation.
+    /// Wrapped values are the kind of [`Capability`] and the name accessed.
+    ErrorPermissionDenied(Capability, String, Position),
     /// The script is prematurely terminated. Wrapped value is the termination token.
     ErrorTerminated(Dynamic, Position),
     /// Run-time error encountered. Wrapped value is the error token.
     ErrorRuntime(Dynamic, Position),
+    /// The script is suspended by a native Rust function.
+    /// Wrapped value is the [`Continuation`] to resume the script with.
+    ErrorSuspended(Box<Continuation>, Position),
 
     /// Breaking out of loops - not an error if within a loop.
     /// The wrapped value, if true, means breaking clean out of the loop (i.e. a `break` statement).
@@ -98,6 +112,8 @@ impl EvalAltResult {
             Self::ErrorParsing(p, _) => p.desc(),
             Self::ErrorInFunctionCall(_,_, _, _) => "Error in called function",
             Self::ErrorInModule(_, _, _) => "Error in module",
+            Self::ErrorCyclicImport(_, _) => "Cyclic module import",
+            Self::ErrorNotExported(_, _, _) => "Name is not exported by the module",
             Self::ErrorFunctionNotFound(_, _) => "Function not found",
             Self::ErrorUnboundThis(_) => "'this' is not bound",
             Self::ErrorMismatchDataType(_, _, _) => "Data type is incorrect",
@@ -126,8 +142,10 @@ impl EvalAltResult {
             Self::ErrorTooManyModules(_) => "Too many modules imported",
             Self::ErrorStackOverflow(_) => "Stack overflow",
             Self::ErrorDataTooLarge(_, _) => "Data size exceeds maximum limit",
+            Self::ErrorPermissionDenied(_, _, _) => "Permission denied",
             Self::ErrorTerminated(_,_) => "Script terminated.",
             Self::ErrorRuntime(_, _) => "Runtime error",
+            Self::ErrorSuspended(_, _) => "Script suspended",
             Self::LoopBreak(true, _) => "Break statement not inside a loop",
             Self::LoopBreak(false, _) => "Continue statement not inside a loop",
             Self::Return(_, _) => "[Not Error] Function returns value",
@@ -172,6 +190,23 @@ impl fmt::Display for EvalAltResult {
             | Self::ErrorDataRace(s, _) => write!(f, "{}: {}", desc, s)?,
 
             Self::ErrorModuleNotFound(s, _) => write!(f, "{}: '{}'", desc, s)?,
+            Self::ErrorNotExported(s, path, _) => {
+                write!(f, "'{}' is not exported by module '{}'", s, path)?
+            }
+            Self::ErrorCyclicImport(chain, _) => {
+                write!(f, "{}: ", desc)?;
+                for (i, (path, pos)) in chain.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" -> ")?;
+                    }
+                    write!(f, "'{}'", path)?;
+                    // The position of the last import is written at the end
+                    if i < chain.len() - 1 && !pos.is_none() {
+                        write!(f, " ({})", pos)?;
+                    }
+                }
+            }
+            Self::ErrorPermissionDenied(c, s, _) => write!(f, "{}: {} '{}'", desc, c, s)?,
 
             Self::ErrorDotExpr(s, _) if !s.is_empty() => write!(f, "{}", s)?,
 
@@ -183,7 +218,8 @@ impl fmt::Display for EvalAltResult {
             | Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
             | Self::ErrorStackOverflow(_)
-            | Self::ErrorTerminated(_, _) => f.write_str(desc)?,
+            | Self::ErrorTerminated(_, _)
+            | Self::ErrorSuspended(_, _) => f.write_str(desc)?,
 
             Self::ErrorRuntime(d, _) if d.is::<ImmutableString>() => {
                 let s = &*d.read_lock::<ImmutableString>().unwrap();
@@ -298,6 +334,8 @@ impl EvalAltResult {
             | Self::ErrorFor(_)
             | Self::ErrorVariableNotFound(_, _)
             | Self::ErrorModuleNotFound(_, _)
+            | Self::ErrorCyclicImport(_, _)
+            | Self::ErrorNotExported(_, _, _)
             | Self::ErrorDataRace(_, _)
             | Self::ErrorAssignmentToConstant(_, _)
             | Self::ErrorMismatchOutputType(_, _, _)
@@ -309,7 +347,9 @@ impl EvalAltResult {
             | Self::ErrorTooManyModules(_)
             | Self::ErrorStackOverflow(_)
             | Self::ErrorDataTooLarge(_, _)
-            | Self::ErrorTerminated(_, _) => false,
+            | Self::ErrorPermissionDenied(_, _, _)
+            | Self::ErrorTerminated(_, _)
+            | Self::ErrorSuspended(_, _) => false,
 
             Self::LoopBreak(_, _) => panic!("EvalAltResult::LoopBreak should not occur naturally"),
             Self::Return(_, _) => panic!("EvalAltResult::Return should not occur naturally"),
@@ -330,7 +370,7 @@ impl EvalAltResult {
             | Self::ErrorStackOverflow(_)
             | Self::ErrorDataTooLarge(_, _) => true,
 
-            Self::ErrorTerminated(_, _) => true,
+            Self::ErrorTerminated(_, _) | Self::ErrorSuspended(_, _) => true,
 
             Self::LoopBreak(_, _) => panic!("EvalAltResult::LoopBreak should not occur naturally"),
             Self::Return(_, _) => panic!("EvalAltResult::Return should not occur naturally"),
@@ -361,7 +401,8 @@ impl EvalAltResult {
             | Self::ErrorTooManyOperations(_)
             | Self::ErrorTooManyModules(_)
             | Self::ErrorStackOverflow(_)
-            | Self::ErrorRuntime(_, _) => (),
+            | Self::ErrorRuntime(_, _)
+            | Self::ErrorSuspended(_, _) => (),
 
             Self::ErrorFunctionNotFound(f, _) => {
                 map.insert("function".into(), f.into());
@@ -392,6 +433,18 @@ impl EvalAltResult {
             Self::ErrorModuleNotFound(m, _) => {
                 map.insert("module".into(), m.into());
             }
+            Self::ErrorNotExported(n, m, _) => {
+                map.insert("name".into(), n.into());
+                map.insert("module".into(), m.into());
+            }
+            Self::ErrorCyclicImport(chain, _) => {
+                let cycle: Vec<_> = chain.iter().map(|(path, _)| path.as_str()).collect();
+                map.insert("cycle".into(), cycle.join(" -> ").into());
+            }
+            Self::ErrorPermissionDenied(c, n, _) => {
+                map.insert("capability".into(), c.to_string().into());
+                map.insert("name".into(), n.into());
+            }
             Self::ErrorDotExpr(p, _) => {
                 map.insert("property".into(), p.into());
             }
@@ -421,6 +474,8 @@ impl EvalAltResult {
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorCyclicImport(_, pos)
+            | Self::ErrorNotExported(_, _, pos)
             | Self::ErrorDataRace(_, pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -430,8 +485,10 @@ impl EvalAltResult {
             | Self::ErrorTooManyModules(pos)
             | Self::ErrorStackOverflow(pos)
             | Self::ErrorDataTooLarge(_, pos)
+            | Self::ErrorPermissionDenied(_, _, pos)
             | Self::ErrorTerminated(_, pos)
             | Self::ErrorRuntime(_, pos)
+            | Self::ErrorSuspended(_, pos)
             | Self::LoopBreak(_, pos)
             | Self::Return(_, pos) => *pos,
         }
@@ -461,6 +518,8 @@ impl EvalAltResult {
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorCyclicImport(_, pos)
+            | Self::ErrorNotExported(_, _, pos)
             | Self::ErrorDataRace(_, pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -470,8 +529,10 @@ impl EvalAltResult {
             | Self::ErrorTooManyModules(pos)
             | Self::ErrorStackOverflow(pos)
             | Self::ErrorDataTooLarge(_, pos)
+            | Self::ErrorPermissionDenied(_, _, pos)
             | Self::ErrorTerminated(_, pos)
             | Self::ErrorRuntime(_, pos)
+            | Self::ErrorSuspended(_, pos)
             | Self::LoopBreak(_, pos)
             | Self::Return(_, pos) => *pos = new_position,
         }
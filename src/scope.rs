@@ -1,9 +1,12 @@
 //! Module that defines the [`Scope`] type representing a function call-stack scope.
 
 use crate::dynamic::{AccessMode, Variant};
-use crate::stdlib::{borrow::Cow, boxed::Box, iter, vec::Vec};
+use crate::stdlib::{borrow::Cow, boxed::Box, iter, string::ToString, vec::Vec};
 use crate::{Dynamic, ImmutableString, StaticVec};
 
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
+
 /// Keep a number of entries inline (since [`Dynamic`] is usually small enough).
 const SCOPE_SIZE: usize = 16;
 
@@ -443,6 +446,51 @@ impl<'a> Scope<'a> {
 
         entries
     }
+    /// Clone the [`Scope`] into a copy that owns all its variable names and is detached from it.
+    ///
+    /// Shared values are copied into new shared values, so that modifying one copy does not
+    /// affect the other. Variables sharing the same value in the [`Scope`] still share the same
+    /// (new) value in the copy.
+    pub(crate) fn clone_detached(&self) -> Scope<'static> {
+        #[cfg(not(feature = "no_closure"))]
+        let mut shared_values: Vec<(&crate::Shared<crate::Locked<Dynamic>>, Dynamic)> = Vec::new();
+
+        let values = self
+            .values
+            .iter()
+            .map(|value| match value.0 {
+                #[cfg(not(feature = "no_closure"))]
+                crate::dynamic::Union::Shared(ref cell, access) => {
+                    if let Some((_, copy)) = shared_values
+                        .iter()
+                        .find(|(v, _)| crate::Shared::ptr_eq(v, cell))
+                    {
+                        return copy.clone();
+                    }
+
+                    let mut copy = value.flatten_clone().into_shared();
+                    copy.set_access_mode(access);
+                    shared_values.push((cell, copy.clone()));
+                    copy
+                }
+                _ => {
+                    // Cloning a value does not keep its access mode
+                    let mut copy = value.clone();
+                    copy.set_access_mode(value.access_mode());
+                    copy
+                }
+            })
+            .collect();
+
+        Scope {
+            values,
+            names: self
+                .names
+                .iter()
+                .map(|(name, aliases)| (Cow::Owned(name.to_string()), aliases.clone()))
+                .collect(),
+        }
+    }
     /// Get an iterator to entries in the [`Scope`].
     #[inline(always)]
     #[allow(dead_code)]
@@ -506,3 +554,115 @@ impl<'a, K: Into<Cow<'a, str>>> iter::Extend<(K, Dynamic)> for Scope<'a> {
         });
     }
 }
+
+/// A serialized entry of a [`Scope`].
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct ScopeEntry<'a> {
+    /// Name of the variable.
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    /// Is the variable a constant?
+    #[serde(default, skip_serializing_if = "is_false")]
+    is_constant: bool,
+    /// Index of the shared value, among all shared values in the [`Scope`], if the value is shared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shared: Option<usize>,
+    /// Aliases the variable is exported under.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<ImmutableString>,
+    /// Value of the variable.
+    value: Dynamic,
+}
+
+#[cfg(feature = "serde")]
+#[inline(always)]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Serialize a [`Scope`] as a sequence of its entries: name, constness, aliases and value.
+///
+/// Variables holding the same shared value share it again after deserialization.
+#[cfg(feature = "serde")]
+impl Serialize for Scope<'_> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        #[cfg(not(feature = "no_closure"))]
+        let mut shared_values: Vec<&crate::Shared<crate::Locked<Dynamic>>> = Vec::new();
+
+        let mut seq = ser.serialize_seq(Some(self.len()))?;
+
+        for ((name, aliases), value) in self.names.iter().zip(self.values.iter()) {
+            #[cfg(not(feature = "no_closure"))]
+            let shared = match value.0 {
+                crate::dynamic::Union::Shared(ref cell, _) => Some(
+                    match shared_values
+                        .iter()
+                        .position(|v| crate::Shared::ptr_eq(v, cell))
+                    {
+                        Some(index) => index,
+                        None => {
+                            shared_values.push(cell);
+                            shared_values.len() - 1
+                        }
+                    },
+                ),
+                _ => None,
+            };
+            #[cfg(feature = "no_closure")]
+            let shared = None;
+
+            seq.serialize_element(&ScopeEntry {
+                name: Cow::Borrowed(name.as_ref()),
+                is_constant: value.is_read_only(),
+                shared,
+                aliases: aliases.as_ref().map(|a| a.to_vec()).unwrap_or_default(),
+                value: value.flatten_clone(),
+            })?;
+        }
+
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Scope<'static> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let entries: Vec<ScopeEntry> = Deserialize::deserialize(de)?;
+
+        #[cfg(not(feature = "no_closure"))]
+        let mut shared_values: Vec<Dynamic> = Vec::new();
+
+        let mut scope = Self::new();
+
+        for entry in entries {
+            let access = if entry.is_constant {
+                AccessMode::ReadOnly
+            } else {
+                AccessMode::ReadWrite
+            };
+
+            #[cfg(not(feature = "no_closure"))]
+            let value = match entry.shared {
+                Some(index) if index < shared_values.len() => shared_values[index].clone(),
+                Some(_) => {
+                    let value = entry.value.into_shared();
+                    shared_values.push(value.clone());
+                    value
+                }
+                None => entry.value,
+            };
+            #[cfg(feature = "no_closure")]
+            let value = entry.value;
+
+            scope.push_dynamic_value(entry.name.into_owned(), access, value);
+
+            #[cfg(not(feature = "no_module"))]
+            for alias in entry.aliases {
+                scope.add_entry_alias(scope.len() - 1, alias);
+            }
+        }
+
+        Ok(scope)
+    }
+}
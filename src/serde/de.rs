@@ -1,14 +1,15 @@
 //! Implement deserialization support of [`Dynamic`][crate::Dynamic] for [`serde`].
 
+use super::enums::EnumRegistry;
 use super::str::ImmutableStringDeserializer;
 use crate::dynamic::Union;
-use crate::stdlib::{any::type_name, boxed::Box, fmt, string::ToString};
-use crate::{Dynamic, EvalAltResult, ImmutableString, LexError, Position};
+use crate::stdlib::{any::type_name, boxed::Box, fmt, format, string::ToString, vec::Vec};
+use crate::{DateTime, Duration, Dynamic, EvalAltResult, ImmutableString, LexError, Position};
 use serde::de::{DeserializeSeed, Error, IntoDeserializer, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 
 #[cfg(not(feature = "no_index"))]
-use crate::Array;
+use crate::{Array, Blob, Set};
 
 #[cfg(not(feature = "no_object"))]
 use crate::Map;
@@ -19,6 +20,8 @@ use crate::Map;
 /// (especially `&str`) to the source [`Dynamic`][crate::Dynamic].
 struct DynamicDeserializer<'a> {
     value: &'a Dynamic,
+    /// Registered `enum` types to split values of, if any.
+    enums: Option<&'a EnumRegistry>,
 }
 
 impl<'de> DynamicDeserializer<'de> {
@@ -26,8 +29,8 @@ impl<'de> DynamicDeserializer<'de> {
     ///
     /// The reference is necessary because the deserialized type may hold references
     /// (especially `&str`) to the source [`Dynamic`][crate::Dynamic].
-    pub fn from_dynamic(value: &'de Dynamic) -> Self {
-        Self { value }
+    pub fn from_dynamic(value: &'de Dynamic, enums: Option<&'de EnumRegistry>) -> Self {
+        Self { value, enums }
     }
     /// Shortcut for a type conversion error.
     fn type_error<T>(&self) -> Result<T, Box<EvalAltResult>> {
@@ -108,7 +111,16 @@ impl<'de> DynamicDeserializer<'de> {
 pub fn from_dynamic<'de, T: Deserialize<'de>>(
     value: &'de Dynamic,
 ) -> Result<T, Box<EvalAltResult>> {
-    T::deserialize(&mut DynamicDeserializer::from_dynamic(value))
+    from_dynamic_with(value, None)
+}
+
+/// Deserialize a [`Dynamic`][crate::Dynamic] value into a Rust type that implements
+/// [`serde::Deserialize`], splitting values of the `enum` types in `enums` (if any) into variants.
+pub(crate) fn from_dynamic_with<'de, T: Deserialize<'de>>(
+    value: &'de Dynamic,
+    enums: Option<&'de EnumRegistry>,
+) -> Result<T, Box<EvalAltResult>> {
+    T::deserialize(&mut DynamicDeserializer::from_dynamic(value, enums))
 }
 
 impl Error for Box<EvalAltResult> {
@@ -148,8 +160,25 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
             #[cfg(feature = "f32_float")]
             Union::Decimal(_, _) => self.deserialize_f32(visitor),
 
+            #[cfg(feature = "bigint")]
+            Union::BigInt(x, _) => {
+                use num_traits::ToPrimitive;
+
+                if let Some(v) = x.to_i64() {
+                    visitor.visit_i64(v)
+                } else if let Some(v) = x.to_i128() {
+                    visitor.visit_i128(v)
+                } else {
+                    visitor.visit_string(x.to_string())
+                }
+            }
+
             #[cfg(not(feature = "no_index"))]
             Union::Array(_, _) => self.deserialize_seq(visitor),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(_, _) => self.deserialize_bytes(visitor),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(_, _) => self.deserialize_seq(visitor),
             #[cfg(not(feature = "no_object"))]
             Union::Map(_, _) => self.deserialize_map(visitor),
             Union::FnPtr(_, _) => self.type_error(),
@@ -166,6 +195,8 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
             Union::Variant(value, _) if value.is::<u32>() => self.deserialize_u32(visitor),
             Union::Variant(value, _) if value.is::<u64>() => self.deserialize_u64(visitor),
             Union::Variant(value, _) if value.is::<u128>() => self.deserialize_u128(visitor),
+            Union::Variant(value, _) if value.is::<DateTime>() => self.deserialize_str(visitor),
+            Union::Variant(value, _) if value.is::<Duration>() => self.deserialize_str(visitor),
 
             Union::Variant(_, _) => self.type_error(),
 
@@ -211,6 +242,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(feature = "bigint")]
+        if let Some(x) = self.value.downcast_ref::<num_bigint::BigInt>() {
+            use num_traits::ToPrimitive;
+
+            return x
+                .to_i64()
+                .map_or_else(|| self.type_error(), |v| visitor.visit_i64(v));
+        }
+
         if let Ok(v) = self.value.as_int() {
             self.deserialize_int(v, visitor)
         } else if cfg!(not(feature = "only_i32")) {
@@ -223,6 +263,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(feature = "bigint")]
+        if let Some(x) = self.value.downcast_ref::<num_bigint::BigInt>() {
+            use num_traits::ToPrimitive;
+
+            return x
+                .to_i128()
+                .map_or_else(|| self.type_error(), |v| visitor.visit_i128(v));
+        }
+
         if let Ok(v) = self.value.as_int() {
             self.deserialize_int(v, visitor)
         } else if cfg!(not(feature = "only_i32")) {
@@ -265,6 +314,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(feature = "bigint")]
+        if let Some(x) = self.value.downcast_ref::<num_bigint::BigInt>() {
+            use num_traits::ToPrimitive;
+
+            return x
+                .to_u64()
+                .map_or_else(|| self.type_error(), |v| visitor.visit_u64(v));
+        }
+
         if let Ok(v) = self.value.as_int() {
             self.deserialize_int(v, visitor)
         } else {
@@ -275,6 +333,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(feature = "bigint")]
+        if let Some(x) = self.value.downcast_ref::<num_bigint::BigInt>() {
+            use num_traits::ToPrimitive;
+
+            return x
+                .to_u128()
+                .map_or_else(|| self.type_error(), |v| visitor.visit_u128(v));
+        }
+
         if let Ok(v) = self.value.as_int() {
             self.deserialize_int(v, visitor)
         } else {
@@ -339,6 +406,13 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        if let Some(dt) = self.value.downcast_ref::<DateTime>() {
+            return visitor.visit_string(dt.to_string());
+        }
+        if let Some(duration) = self.value.downcast_ref::<Duration>() {
+            return visitor.visit_string(duration.to_string());
+        }
+
         self.value.downcast_ref::<ImmutableString>().map_or_else(
             || self.type_error(),
             |x| visitor.visit_borrowed_str(x.as_str()),
@@ -352,16 +426,36 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
         self.deserialize_str(visitor)
     }
 
-    fn deserialize_bytes<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Box<EvalAltResult>> {
-        self.type_error()
+    fn deserialize_bytes<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_index"))]
+        return self
+            .value
+            .downcast_ref::<Blob>()
+            .map_or_else(|| self.type_error(), |x| _visitor.visit_borrowed_bytes(x));
+
+        #[cfg(feature = "no_index")]
+        return self.type_error();
     }
 
-    fn deserialize_byte_buf<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Box<EvalAltResult>> {
-        self.type_error()
+    fn deserialize_byte_buf<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        self.deserialize_bytes(visitor)
     }
 
-    fn deserialize_option<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Box<EvalAltResult>> {
-        self.type_error()
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        if self.value.is::<()>() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
     fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
@@ -387,10 +481,15 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
     }
 
     fn deserialize_seq<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_index"))]
+        if let Some(set) = self.value.downcast_ref::<Set>() {
+            return _visitor.visit_seq(IterateArray::new(set.iter(), self.enums));
+        }
+
         #[cfg(not(feature = "no_index"))]
         return self.value.downcast_ref::<Array>().map_or_else(
             || self.type_error(),
-            |arr| _visitor.visit_seq(IterateArray::new(arr.iter())),
+            |arr| _visitor.visit_seq(IterateArray::new(arr.iter(), self.enums)),
         );
 
         #[cfg(feature = "no_index")]
@@ -418,7 +517,7 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
         #[cfg(not(feature = "no_object"))]
         return self.value.downcast_ref::<Map>().map_or_else(
             || self.type_error(),
-            |map| _visitor.visit_map(IterateMap::new(map.keys(), map.values())),
+            |map| _visitor.visit_map(IterateMap::new(map.keys(), map.values(), self.enums)),
         );
 
         #[cfg(feature = "no_object")]
@@ -440,7 +539,9 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
         _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Box<EvalAltResult>> {
-        if let Some(s) = self.value.read_lock::<ImmutableString>() {
+        if let Some(parts) = EnumParts::split(self.value, self.enums) {
+            visitor.visit_enum(parts)
+        } else if let Some(s) = self.value.read_lock::<ImmutableString>() {
             visitor.visit_enum(s.as_str().into_deserializer())
         } else {
             #[cfg(not(feature = "no_object"))]
@@ -451,7 +552,7 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
                 if let (Some((key, value)), None) = (first, second) {
                     visitor.visit_enum(EnumDeserializer {
                         tag: &key,
-                        content: DynamicDeserializer::from_dynamic(value),
+                        content: DynamicDeserializer::from_dynamic(value, self.enums),
                     })
                 } else {
                     self.type_error()
@@ -483,12 +584,14 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
 struct IterateArray<'a, ITER: Iterator<Item = &'a Dynamic>> {
     /// Iterator for a stream of [`Dynamic`][crate::Dynamic] values.
     iter: ITER,
+    /// Registered `enum` types to split values of, if any.
+    enums: Option<&'a EnumRegistry>,
 }
 
 #[cfg(not(feature = "no_index"))]
 impl<'a, ITER: Iterator<Item = &'a Dynamic>> IterateArray<'a, ITER> {
-    pub fn new(iter: ITER) -> Self {
-        Self { iter }
+    pub fn new(iter: ITER, enums: Option<&'a EnumRegistry>) -> Self {
+        Self { iter, enums }
     }
 }
 
@@ -503,7 +606,7 @@ impl<'a: 'de, 'de, ITER: Iterator<Item = &'a Dynamic>> SeqAccess<'de> for Iterat
         match self.iter.next() {
             None => Ok(None),
             Some(item) => seed
-                .deserialize(&mut DynamicDeserializer::from_dynamic(item))
+                .deserialize(&mut DynamicDeserializer::from_dynamic(item, self.enums))
                 .map(Some),
         }
     }
@@ -519,6 +622,8 @@ where
     keys: KEYS,
     // Iterator for a stream of [`Dynamic`][crate::Dynamic] values.
     values: VALUES,
+    /// Registered `enum` types to split values of, if any.
+    enums: Option<&'a EnumRegistry>,
 }
 
 #[cfg(not(feature = "no_object"))]
@@ -527,8 +632,12 @@ where
     KEYS: Iterator<Item = &'a ImmutableString>,
     VALUES: Iterator<Item = &'a Dynamic>,
 {
-    pub fn new(keys: KEYS, values: VALUES) -> Self {
-        Self { keys, values }
+    pub fn new(keys: KEYS, values: VALUES, enums: Option<&'a EnumRegistry>) -> Self {
+        Self {
+            keys,
+            values,
+            enums,
+        }
     }
 }
 
@@ -559,6 +668,7 @@ where
         // Deserialize each value item coming out of the iterator.
         seed.deserialize(&mut DynamicDeserializer::from_dynamic(
             self.values.next().unwrap(),
+            self.enums,
         ))
     }
 }
@@ -614,3 +724,243 @@ impl<'t, 'de> serde::de::VariantAccess<'de> for EnumDeserializer<'t, 'de> {
         self.content.deserialize_struct("", fields, visitor)
     }
 }
+
+/// Deserializer for an owned [`Dynamic`][crate::Dynamic] value, such as the value of a field of a
+/// registered `enum`, which cannot be borrowed from.
+struct OwnedDeserializer<'e>(Dynamic, Option<&'e EnumRegistry>);
+
+impl<'de, 'e> IntoDeserializer<'de, Box<EvalAltResult>> for OwnedDeserializer<'e> {
+    type Deserializer = Self;
+
+    #[inline(always)]
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for OwnedDeserializer<'_> {
+    type Error = Box<EvalAltResult>;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Box<EvalAltResult>> {
+        let value = self.0.flatten();
+        let enums = self.1;
+
+        if let Some(parts) = EnumParts::split(&value, enums) {
+            return visitor.visit_enum(parts);
+        }
+
+        match value.0 {
+            Union::Unit(_, _) => visitor.visit_unit(),
+            Union::Bool(x, _) => visitor.visit_bool(x),
+            Union::Str(x, _) => visitor.visit_str(&x),
+            Union::Char(x, _) => visitor.visit_char(x),
+
+            #[cfg(not(feature = "only_i32"))]
+            Union::Int(x, _) => visitor.visit_i64(x),
+            #[cfg(feature = "only_i32")]
+            Union::Int(x, _) => visitor.visit_i32(x),
+
+            #[cfg(not(feature = "no_float"))]
+            #[cfg(not(feature = "f32_float"))]
+            Union::Float(x, _) => visitor.visit_f64(*x),
+            #[cfg(not(feature = "no_float"))]
+            #[cfg(feature = "f32_float")]
+            Union::Float(x, _) => visitor.visit_f32(*x),
+
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(x, _) => visit_seq(visitor, x.into_iter(), enums),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(x, _) => visitor.visit_byte_buf(*x),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(x, _) => visit_seq(visitor, x.into_iter(), enums),
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(x, _) => {
+                let mut map = serde::de::value::MapDeserializer::new(
+                    x.into_iter()
+                        .map(|(k, v)| (k.to_string(), OwnedDeserializer(v, enums))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+
+            ref union => {
+                macro_rules! visit {
+                    ($($t:ty => $visit:ident),*) => {
+                        $(
+                            if let Some(&x) = value.downcast_ref::<$t>() {
+                                return visitor.$visit(x);
+                            }
+                        )*
+                    };
+                }
+
+                visit!(
+                    i8 => visit_i8, i16 => visit_i16, i32 => visit_i32, i64 => visit_i64,
+                    i128 => visit_i128, u8 => visit_u8, u16 => visit_u16, u32 => visit_u32,
+                    u64 => visit_u64, u128 => visit_u128
+                );
+
+                if let Some(dt) = value.downcast_ref::<DateTime>() {
+                    return visitor.visit_string(dt.to_string());
+                }
+                if let Some(duration) = value.downcast_ref::<Duration>() {
+                    return visitor.visit_string(duration.to_string());
+                }
+
+                let _ = union;
+                DynamicDeserializer::from_dynamic(&value, None).type_error()
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        if self.0.is::<()>() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Box<EvalAltResult>> {
+        match self.0.clone().try_cast::<ImmutableString>() {
+            Some(s) => visitor.visit_enum(s.to_string().into_deserializer()),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Visit a sequence of owned [`Dynamic`][crate::Dynamic] values.
+fn visit_seq<'de, V: Visitor<'de>>(
+    visitor: V,
+    iter: impl Iterator<Item = Dynamic>,
+    enums: Option<&EnumRegistry>,
+) -> Result<V::Value, Box<EvalAltResult>> {
+    let mut seq =
+        serde::de::value::SeqDeserializer::new(iter.map(|value| OwnedDeserializer(value, enums)));
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+/// `EnumAccess` implementation for a value of an `enum` type registered via
+/// [`Engine::register_enum`][crate::Engine::register_enum].
+struct EnumParts<'e> {
+    variant: &'static str,
+    field_names: &'static [&'static str],
+    fields: Vec<Dynamic>,
+    enums: Option<&'e EnumRegistry>,
+}
+
+impl<'e> EnumParts<'e> {
+    /// Split a value of an `enum` type in `enums` (if any) into its variant name and fields.
+    #[inline(always)]
+    fn split(value: &Dynamic, enums: Option<&'e EnumRegistry>) -> Option<Self> {
+        enums
+            .and_then(|enums| enums.by_value(value))
+            .and_then(|info| (info.split)(value))
+            .map(|(variant, field_names, fields)| Self {
+                variant,
+                field_names,
+                fields,
+                enums,
+            })
+    }
+    /// Shortcut for an error on a mismatched kind of variant.
+    fn invalid<T>(&self, expected: &str) -> Result<T, Box<EvalAltResult>> {
+        Err(Error::custom(format!(
+            "variant '{}' with {} field(s) is not a {}",
+            self.variant,
+            self.fields.len(),
+            expected
+        )))
+    }
+}
+
+impl<'de, 'e> serde::de::EnumAccess<'de> for EnumParts<'e> {
+    type Error = Box<EvalAltResult>;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        seed.deserialize(self.variant.into_deserializer())
+            .map(|v| (v, self))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for EnumParts<'_> {
+    type Error = Box<EvalAltResult>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        if self.fields.is_empty() {
+            Ok(())
+        } else {
+            self.invalid("unit variant")
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        mut self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        match self.fields.pop() {
+            Some(value) if self.fields.is_empty() => {
+                seed.deserialize(OwnedDeserializer(value, self.enums))
+            }
+            _ => self.invalid("newtype variant"),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visit_seq(visitor, self.fields.into_iter(), self.enums)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.field_names.len() != self.fields.len() {
+            return self.invalid("struct variant");
+        }
+
+        let enums = self.enums;
+        let mut map = serde::de::value::MapDeserializer::new(
+            self.field_names.iter().cloned().zip(
+                self.fields
+                    .into_iter()
+                    .map(|value| OwnedDeserializer(value, enums)),
+            ),
+        );
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+}
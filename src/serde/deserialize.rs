@@ -1,5 +1,6 @@
 //! Implementations of [`serde::Deserialize`].
 
+use super::from_large_int;
 use crate::stdlib::{fmt, string::ToString};
 use crate::{Dynamic, ImmutableString, INT};
 use serde::de::{Deserialize, Deserializer, Error, MapAccess, SeqAccess, Visitor};
@@ -35,7 +36,7 @@ impl<'d> Visitor<'d> for DynamicVisitor {
         return Ok(v.into());
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as i64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.visit_i32(v as i32);
         }
@@ -51,7 +52,7 @@ impl<'d> Visitor<'d> for DynamicVisitor {
         return Ok(INT::from(v).into());
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as u32 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.visit_i32(v as i32);
         }
@@ -59,13 +60,43 @@ impl<'d> Visitor<'d> for DynamicVisitor {
     fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
         #[cfg(not(feature = "only_i32"))]
         if v > i64::MAX as u64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.visit_i64(v as i64);
         }
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as u64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
+        } else {
+            return self.visit_i32(v as i32);
+        }
+    }
+    #[cfg(feature = "bigint")]
+    fn visit_i128<E: Error>(self, v: i128) -> Result<Self::Value, E> {
+        #[cfg(not(feature = "only_i32"))]
+        if v > i64::MAX as i128 || v < i64::MIN as i128 {
+            return Ok(from_large_int(v));
+        } else {
+            return self.visit_i64(v as i64);
+        }
+        #[cfg(feature = "only_i32")]
+        if v > i32::MAX as i128 || v < i32::MIN as i128 {
+            return Ok(from_large_int(v));
+        } else {
+            return self.visit_i32(v as i32);
+        }
+    }
+    #[cfg(feature = "bigint")]
+    fn visit_u128<E: Error>(self, v: u128) -> Result<Self::Value, E> {
+        #[cfg(not(feature = "only_i32"))]
+        if v > i64::MAX as u128 {
+            return Ok(from_large_int(v));
+        } else {
+            return self.visit_i64(v as i64);
+        }
+        #[cfg(feature = "only_i32")]
+        if v > i32::MAX as u128 {
+            return Ok(from_large_int(v));
         } else {
             return self.visit_i32(v as i32);
         }
@@ -120,6 +151,15 @@ impl<'d> Visitor<'d> for DynamicVisitor {
         Ok(v.into())
     }
 
+    #[cfg(not(feature = "no_index"))]
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Dynamic::from_blob(v.to_vec()))
+    }
+    #[cfg(not(feature = "no_index"))]
+    fn visit_byte_buf<E: Error>(self, v: crate::Blob) -> Result<Self::Value, E> {
+        Ok(Dynamic::from_blob(v))
+    }
+
     fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
         Ok(Dynamic::UNIT)
     }
@@ -0,0 +1,53 @@
+//! Registry of `enum` types registered via [`Engine::register_enum`][crate::Engine::register_enum],
+//! used by [`Engine::to_dynamic`][crate::Engine::to_dynamic] and
+//! [`Engine::from_dynamic`][crate::Engine::from_dynamic].
+
+use crate::stdlib::{any::TypeId, collections::BTreeMap, vec::Vec};
+use crate::{CustomEnum, Dynamic};
+
+/// Type-erased functions of a registered `enum` type.
+#[derive(Clone, Copy)]
+pub(crate) struct EnumInfo {
+    /// Name of the `enum` type.
+    pub name: &'static str,
+    /// Split a value into its variant name, field names (empty unless the fields are named)
+    /// and field values.
+    pub split: fn(&Dynamic) -> Option<(&'static str, &'static [&'static str], Vec<Dynamic>)>,
+    /// Build a value from a variant name and field values.
+    pub build: fn(&str, Vec<Dynamic>) -> Option<Dynamic>,
+}
+
+/// `enum` types registered with an [`Engine`][crate::Engine], keyed by [`TypeId`].
+#[derive(Clone, Default)]
+pub(crate) struct EnumRegistry(BTreeMap<TypeId, EnumInfo>);
+
+impl EnumRegistry {
+    /// Register an `enum` type.
+    pub fn register<T: CustomEnum>(&mut self) {
+        self.0.insert(
+            TypeId::of::<T>(),
+            EnumInfo {
+                name: T::enum_name(),
+                split: |value| {
+                    value.read_lock::<T>().map(|value| {
+                        let fields = (0..value.field_count())
+                            .filter_map(|index| value.field(index))
+                            .collect();
+                        (value.variant_name(), value.field_names(), fields)
+                    })
+                },
+                build: |variant, fields| T::from_fields(variant, fields).map(Dynamic::from),
+            },
+        );
+    }
+    /// Get the registered `enum` type of a value.
+    #[inline(always)]
+    pub fn by_value(&self, value: &Dynamic) -> Option<&EnumInfo> {
+        self.0.get(&value.type_id())
+    }
+    /// Get a registered `enum` type by name.
+    #[inline(always)]
+    pub fn by_name(&self, name: &str) -> Option<&EnumInfo> {
+        self.0.values().find(|info| info.name == name)
+    }
+}
@@ -3,6 +3,7 @@
 
 mod de;
 mod deserialize;
+pub(crate) mod enums;
 mod ser;
 mod serialize;
 mod str;
@@ -11,4 +12,21 @@ mod str;
 mod metadata;
 
 pub use de::from_dynamic;
+pub(crate) use de::from_dynamic_with;
 pub use ser::to_dynamic;
+pub(crate) use ser::to_dynamic_with;
+
+/// Convert an integer value too large for an [`INT`][crate::INT] into a [`Dynamic`][crate::Dynamic].
+#[cfg(not(feature = "bigint"))]
+#[inline(always)]
+fn from_large_int<T: crate::dynamic::Variant + Clone>(v: T) -> crate::Dynamic {
+    crate::Dynamic::from(v)
+}
+/// Convert an integer value too large for an [`INT`][crate::INT] into a [`Dynamic`][crate::Dynamic].
+///
+/// Under the `bigint` feature, the value is converted into a `BigInt`.
+#[cfg(feature = "bigint")]
+#[inline(always)]
+fn from_large_int<T: Into<num_bigint::BigInt>>(v: T) -> crate::Dynamic {
+    v.into().into()
+}
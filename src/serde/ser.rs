@@ -1,6 +1,8 @@
 //! Implement serialization support of [`Dynamic`][crate::Dynamic] for [`serde`].
 
-use crate::stdlib::{boxed::Box, fmt, string::ToString};
+use super::enums::EnumRegistry;
+use super::from_large_int;
+use crate::stdlib::{boxed::Box, fmt, string::ToString, vec::Vec};
 use crate::{Dynamic, EvalAltResult, Position, RhaiResult};
 use serde::ser::{
     Error, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple, SerializeTupleStruct,
@@ -13,20 +15,26 @@ use crate::Array;
 #[cfg(not(feature = "no_object"))]
 use crate::Map;
 
+#[cfg(not(feature = "no_object"))]
+use crate::stdlib::vec;
+
 /// Serializer for [`Dynamic`][crate::Dynamic] which is kept as a reference.
-struct DynamicSerializer {
+struct DynamicSerializer<'e> {
     /// Buffer to hold a temporary key.
     _key: Dynamic,
     /// Buffer to hold a temporary value.
     _value: Dynamic,
+    /// Registered `enum` types to build values of, if any.
+    enums: Option<&'e EnumRegistry>,
 }
 
-impl DynamicSerializer {
+impl<'e> DynamicSerializer<'e> {
     /// Create a [`DynamicSerializer`] from a [`Dynamic`][crate::Dynamic] value.
-    pub fn new(_value: Dynamic) -> Self {
+    pub fn new(_value: Dynamic, enums: Option<&'e EnumRegistry>) -> Self {
         Self {
             _key: Default::default(),
             _value,
+            enums,
         }
     }
 }
@@ -80,7 +88,13 @@ impl DynamicSerializer {
 /// # }
 /// ```
 pub fn to_dynamic<T: Serialize>(value: T) -> RhaiResult {
-    let mut s = DynamicSerializer::new(Default::default());
+    to_dynamic_with(value, None)
+}
+
+/// Serialize a Rust type that implements [`serde::Serialize`] into a [`Dynamic`][crate::Dynamic],
+/// building values of the `enum` types in `enums` (if any) instead of object maps.
+pub(crate) fn to_dynamic_with<T: Serialize>(value: T, enums: Option<&EnumRegistry>) -> RhaiResult {
+    let mut s = DynamicSerializer::new(Default::default(), enums);
     value.serialize(&mut s)
 }
 
@@ -90,20 +104,20 @@ impl Error for Box<EvalAltResult> {
     }
 }
 
-impl Serializer for &mut DynamicSerializer {
+impl<'e> Serializer for &mut DynamicSerializer<'e> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
-    type SerializeSeq = DynamicSerializer;
-    type SerializeTuple = DynamicSerializer;
-    type SerializeTupleStruct = DynamicSerializer;
+    type SerializeSeq = DynamicSerializer<'e>;
+    type SerializeTuple = DynamicSerializer<'e>;
+    type SerializeTupleStruct = DynamicSerializer<'e>;
     #[cfg(not(any(feature = "no_object", feature = "no_index")))]
-    type SerializeTupleVariant = TupleVariantSerializer;
+    type SerializeTupleVariant = TupleVariantSerializer<'e>;
     #[cfg(any(feature = "no_object", feature = "no_index"))]
     type SerializeTupleVariant = serde::ser::Impossible<Dynamic, Box<EvalAltResult>>;
-    type SerializeMap = DynamicSerializer;
-    type SerializeStruct = DynamicSerializer;
+    type SerializeMap = DynamicSerializer<'e>;
+    type SerializeStruct = DynamicSerializer<'e>;
     #[cfg(not(feature = "no_object"))]
-    type SerializeStructVariant = StructVariantSerializer;
+    type SerializeStructVariant = StructVariantSerializer<'e>;
     #[cfg(feature = "no_object")]
     type SerializeStructVariant = serde::ser::Impossible<Dynamic, Box<EvalAltResult>>;
 
@@ -137,7 +151,7 @@ impl Serializer for &mut DynamicSerializer {
         return Ok(v.into());
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as i64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i32(v as i32);
         }
@@ -146,13 +160,13 @@ impl Serializer for &mut DynamicSerializer {
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Box<EvalAltResult>> {
         #[cfg(not(feature = "only_i32"))]
         if v > i64::MAX as i128 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i64(v as i64);
         }
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as i128 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i32(v as i32);
         }
@@ -177,7 +191,7 @@ impl Serializer for &mut DynamicSerializer {
         return self.serialize_i64(i64::from(v));
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as u32 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i32(v as i32);
         }
@@ -186,13 +200,13 @@ impl Serializer for &mut DynamicSerializer {
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Box<EvalAltResult>> {
         #[cfg(not(feature = "only_i32"))]
         if v > i64::MAX as u64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i64(v as i64);
         }
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as u64 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i32(v as i32);
         }
@@ -201,13 +215,13 @@ impl Serializer for &mut DynamicSerializer {
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Box<EvalAltResult>> {
         #[cfg(not(feature = "only_i32"))]
         if v > i64::MAX as u128 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i64(v as i64);
         }
         #[cfg(feature = "only_i32")]
         if v > i32::MAX as u128 {
-            return Ok(Dynamic::from(v));
+            return Ok(from_large_int(v));
         } else {
             return self.serialize_i32(v as i32);
         }
@@ -278,11 +292,14 @@ impl Serializer for &mut DynamicSerializer {
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
+        name: &'static str,
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Box<EvalAltResult>> {
-        self.serialize_str(variant)
+        match build_enum(self.enums, name, variant, Vec::new()) {
+            Some(value) => Ok(value),
+            None => self.serialize_str(variant),
+        }
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
@@ -302,8 +319,11 @@ impl Serializer for &mut DynamicSerializer {
     ) -> Result<Self::Ok, Box<EvalAltResult>> {
         #[cfg(not(feature = "no_object"))]
         {
-            let content = to_dynamic(_value)?;
-            make_variant(_variant, content)
+            let content = to_dynamic_with(_value, self.enums)?;
+            match build_enum(self.enums, _name, _variant, vec![content.clone()]) {
+                Some(value) => Ok(value),
+                None => make_variant(_variant, content),
+            }
         }
         #[cfg(feature = "no_object")]
         return EvalAltResult::ErrorMismatchDataType(
@@ -316,7 +336,7 @@ impl Serializer for &mut DynamicSerializer {
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Box<EvalAltResult>> {
         #[cfg(not(feature = "no_index"))]
-        return Ok(DynamicSerializer::new(Array::new().into()));
+        return Ok(DynamicSerializer::new(Array::new().into(), self.enums));
         #[cfg(feature = "no_index")]
         return EvalAltResult::ErrorMismatchDataType(
             "".into(),
@@ -348,6 +368,8 @@ impl Serializer for &mut DynamicSerializer {
         #[cfg(not(feature = "no_object"))]
         #[cfg(not(feature = "no_index"))]
         return Ok(TupleVariantSerializer {
+            enums: self.enums,
+            name: _name,
             variant: _variant,
             array: Array::with_capacity(_len),
         });
@@ -362,7 +384,7 @@ impl Serializer for &mut DynamicSerializer {
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Box<EvalAltResult>> {
         #[cfg(not(feature = "no_object"))]
-        return Ok(DynamicSerializer::new(Map::new().into()));
+        return Ok(DynamicSerializer::new(Map::new().into(), self.enums));
         #[cfg(feature = "no_object")]
         return EvalAltResult::ErrorMismatchDataType(
             "".into(),
@@ -389,8 +411,11 @@ impl Serializer for &mut DynamicSerializer {
     ) -> Result<Self::SerializeStructVariant, Box<EvalAltResult>> {
         #[cfg(not(feature = "no_object"))]
         return Ok(StructVariantSerializer {
+            enums: self.enums,
+            name: _name,
             variant: _variant,
             map: Default::default(),
+            fields: Vec::with_capacity(_len),
         });
         #[cfg(feature = "no_object")]
         return EvalAltResult::ErrorMismatchDataType(
@@ -402,7 +427,7 @@ impl Serializer for &mut DynamicSerializer {
     }
 }
 
-impl SerializeSeq for DynamicSerializer {
+impl SerializeSeq for DynamicSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -440,7 +465,7 @@ impl SerializeSeq for DynamicSerializer {
     }
 }
 
-impl SerializeTuple for DynamicSerializer {
+impl SerializeTuple for DynamicSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -477,7 +502,7 @@ impl SerializeTuple for DynamicSerializer {
     }
 }
 
-impl SerializeTupleStruct for DynamicSerializer {
+impl SerializeTupleStruct for DynamicSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -514,7 +539,7 @@ impl SerializeTupleStruct for DynamicSerializer {
     }
 }
 
-impl SerializeMap for DynamicSerializer {
+impl SerializeMap for DynamicSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -600,7 +625,7 @@ impl SerializeMap for DynamicSerializer {
     }
 }
 
-impl SerializeStruct for DynamicSerializer {
+impl SerializeStruct for DynamicSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -639,13 +664,15 @@ impl SerializeStruct for DynamicSerializer {
 }
 
 #[cfg(not(any(feature = "no_object", feature = "no_index")))]
-struct TupleVariantSerializer {
+struct TupleVariantSerializer<'e> {
+    enums: Option<&'e EnumRegistry>,
+    name: &'static str,
     variant: &'static str,
     array: Array,
 }
 
 #[cfg(not(any(feature = "no_object", feature = "no_index")))]
-impl serde::ser::SerializeTupleVariant for TupleVariantSerializer {
+impl serde::ser::SerializeTupleVariant for TupleVariantSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -653,24 +680,31 @@ impl serde::ser::SerializeTupleVariant for TupleVariantSerializer {
         &mut self,
         value: &T,
     ) -> Result<(), Box<EvalAltResult>> {
-        let value = to_dynamic(value)?;
+        let value = to_dynamic_with(value, self.enums)?;
         self.array.push(value);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Box<EvalAltResult>> {
-        make_variant(self.variant, self.array.into())
+        match build_enum(self.enums, self.name, self.variant, self.array.clone()) {
+            Some(value) => Ok(value),
+            None => make_variant(self.variant, self.array.into()),
+        }
     }
 }
 
 #[cfg(not(feature = "no_object"))]
-struct StructVariantSerializer {
+struct StructVariantSerializer<'e> {
+    enums: Option<&'e EnumRegistry>,
+    name: &'static str,
     variant: &'static str,
     map: Map,
+    /// Field values in declaration order.
+    fields: Vec<Dynamic>,
 }
 
 #[cfg(not(feature = "no_object"))]
-impl serde::ser::SerializeStructVariant for StructVariantSerializer {
+impl serde::ser::SerializeStructVariant for StructVariantSerializer<'_> {
     type Ok = Dynamic;
     type Error = Box<EvalAltResult>;
 
@@ -679,16 +713,33 @@ impl serde::ser::SerializeStructVariant for StructVariantSerializer {
         key: &'static str,
         value: &T,
     ) -> Result<(), Box<EvalAltResult>> {
-        let value = to_dynamic(value)?;
+        let value = to_dynamic_with(value, self.enums)?;
+        self.fields.push(value.clone());
         self.map.insert(key.into(), value);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Box<EvalAltResult>> {
-        make_variant(self.variant, self.map.into())
+        match build_enum(self.enums, self.name, self.variant, self.fields) {
+            Some(value) => Ok(value),
+            None => make_variant(self.variant, self.map.into()),
+        }
     }
 }
 
+/// Build a value of an `enum` type registered via
+/// [`Engine::register_enum`][crate::Engine::register_enum], if `name` is one of `enums`.
+fn build_enum(
+    enums: Option<&EnumRegistry>,
+    name: &str,
+    variant: &str,
+    fields: Vec<Dynamic>,
+) -> Option<Dynamic> {
+    enums
+        .and_then(|enums| enums.by_name(name))
+        .and_then(|info| (info.build)(variant, fields))
+}
+
 #[cfg(not(feature = "no_object"))]
 fn make_variant(variant: &'static str, value: Dynamic) -> RhaiResult {
     let mut map = Map::new();
@@ -2,7 +2,7 @@
 
 use crate::dynamic::{Union, Variant};
 use crate::stdlib::string::ToString;
-use crate::{Dynamic, ImmutableString};
+use crate::{DateTime, Duration, Dynamic, ImmutableString};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 
 impl Serialize for Dynamic {
@@ -48,8 +48,23 @@ impl Serialize for Dynamic {
                 }
             }
 
+            #[cfg(feature = "bigint")]
+            Union::BigInt(x, _) => {
+                use num_traits::ToPrimitive;
+
+                if let Some(v) = x.to_i64() {
+                    ser.serialize_i64(v)
+                } else {
+                    ser.serialize_str(&x.to_string())
+                }
+            }
+
             #[cfg(not(feature = "no_index"))]
             Union::Array(a, _) => (**a).serialize(ser),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(a, _) => ser.serialize_bytes(&**a),
+            #[cfg(not(feature = "no_index"))]
+            Union::Set(a, _) => ser.collect_seq(a.iter()),
             #[cfg(not(feature = "no_object"))]
             Union::Map(m, _) => {
                 let mut map = ser.serialize_map(Some(m.len()))?;
@@ -62,6 +77,16 @@ impl Serialize for Dynamic {
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(x, _) => ser.serialize_str(x.as_ref().type_name()),
 
+            Union::Variant(v, _) if v.is::<DateTime>() => (***v)
+                .as_any()
+                .downcast_ref::<DateTime>()
+                .unwrap()
+                .serialize(ser),
+            Union::Variant(v, _) if v.is::<Duration>() => (***v)
+                .as_any()
+                .downcast_ref::<Duration>()
+                .unwrap()
+                .serialize(ser),
             Union::Variant(v, _) => ser.serialize_str((***v).type_name()),
 
             #[cfg(not(feature = "no_closure"))]
@@ -0,0 +1,339 @@
+//! Module defining the hash set type.
+#![cfg(not(feature = "no_index"))]
+
+use crate::dynamic::Union;
+use crate::stdlib::{
+    boxed::Box,
+    collections::{btree_map, BTreeMap},
+    fmt,
+    hash::{Hash, Hasher},
+    iter::Flatten,
+    string::ToString,
+    vec::Vec,
+};
+use crate::utils::get_hasher;
+use crate::{Array, Dynamic, EvalAltResult, Position};
+
+/// Hash set of [`Dynamic`] values (a _set_).
+///
+/// Only hashable values (i.e. `()`, booleans, characters, numbers, strings, and arrays, blobs,
+/// object maps and sets containing only hashable values) can be members of a set.
+///
+/// Members are looked up by their hashes, in the same way that `switch` cases are matched, and
+/// then compared for equality.  Values of different types (e.g. `1` and `1.0`) are always distinct.
+///
+/// Iteration order is not the insertion order but is stable.
+///
+/// Not available under `no_index`.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Dynamic, Set, INT};
+///
+/// let mut set = Set::new();
+///
+/// assert!(set.insert(Dynamic::from(42 as INT)).unwrap());
+/// assert!(set.insert(Dynamic::from("hello")).unwrap());
+/// assert!(!set.insert(Dynamic::from(42 as INT)).unwrap());
+///
+/// assert_eq!(set.len(), 2);
+/// assert!(set.contains(&Dynamic::from("hello")));
+/// assert!(!set.contains(&Dynamic::from("42")));
+/// ```
+#[derive(Clone, Default)]
+pub struct Set(BTreeMap<u64, Vec<Dynamic>>);
+
+/// Are two hashable values the same member of a [`Set`]?
+///
+/// Floating-point numbers are compared bit-wise, consistent with their hashes.
+fn is_same_member(x: &Dynamic, y: &Dynamic) -> bool {
+    #[cfg(not(feature = "no_closure"))]
+    if x.is_shared() || y.is_shared() {
+        return is_same_member(&x.flatten_clone(), &y.flatten_clone());
+    }
+
+    match (&x.0, &y.0) {
+        (Union::Unit(_, _), Union::Unit(_, _)) => true,
+        (Union::Bool(x, _), Union::Bool(y, _)) => x == y,
+        (Union::Str(x, _), Union::Str(y, _)) => x == y,
+        (Union::Char(x, _), Union::Char(y, _)) => x == y,
+        (Union::Int(x, _), Union::Int(y, _)) => x == y,
+        #[cfg(not(feature = "no_float"))]
+        (Union::Float(x, _), Union::Float(y, _)) => x.as_ref().to_bits() == y.as_ref().to_bits(),
+        #[cfg(feature = "bigint")]
+        (Union::BigInt(x, _), Union::BigInt(y, _)) => x == y,
+        (Union::Array(x, _), Union::Array(y, _)) => {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| is_same_member(x, y))
+        }
+        (Union::Blob(x, _), Union::Blob(y, _)) => x == y,
+        (Union::Set(x, _), Union::Set(y, _)) => x == y,
+        #[cfg(not(feature = "no_object"))]
+        (Union::Map(x, _), Union::Map(y, _)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y.iter())
+                    .all(|((kx, x), (ky, y))| kx == ky && is_same_member(x, y))
+        }
+        _ => false,
+    }
+}
+
+impl Set {
+    /// Create a new, empty [`Set`].
+    #[inline(always)]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Calculate the hash of a member value, or [`None`] if it is not hashable.
+    fn calc_hash(value: &Dynamic) -> Option<u64> {
+        if !value.is_hashable() {
+            return None;
+        }
+
+        let hasher = &mut get_hasher();
+        value.type_name().hash(hasher);
+        value.hash(hasher);
+        Some(hasher.finish())
+    }
+    /// Is a value with a particular hash a member of the [`Set`]?
+    #[inline(always)]
+    fn contains_hashed(&self, hash: u64, value: &Dynamic) -> bool {
+        self.0.get(&hash).map_or(false, |bucket| {
+            bucket.iter().any(|m| is_same_member(m, value))
+        })
+    }
+    /// Add a value with a particular hash to the [`Set`].
+    ///
+    /// Returns `true` if the value was not already a member.
+    #[inline(always)]
+    fn insert_hashed(&mut self, hash: u64, value: Dynamic) -> bool {
+        let bucket = self.0.entry(hash).or_default();
+
+        if bucket.iter().any(|m| is_same_member(m, &value)) {
+            false
+        } else {
+            bucket.push(value);
+            true
+        }
+    }
+    /// Get an iterator over the members of the [`Set`] and their hashes.
+    #[inline(always)]
+    fn iter_hashed(&self) -> impl Iterator<Item = (u64, &Dynamic)> {
+        self.0
+            .iter()
+            .flat_map(|(&hash, bucket)| bucket.iter().map(move |value| (hash, value)))
+    }
+    /// Number of members in the [`Set`].
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+    /// Is the [`Set`] empty?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Add a value to the [`Set`].
+    ///
+    /// Returns `true` if the value was not already a member.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorMismatchDataType`][EvalAltResult::ErrorMismatchDataType] if the value
+    /// is not hashable.
+    pub fn insert(&mut self, value: Dynamic) -> Result<bool, Box<EvalAltResult>> {
+        let value = value.flatten();
+
+        match Self::calc_hash(&value) {
+            Some(hash) => Ok(self.insert_hashed(hash, value)),
+            None => EvalAltResult::ErrorMismatchDataType(
+                "a hashable value".to_string(),
+                value.type_name().to_string(),
+                Position::NONE,
+            )
+            .into(),
+        }
+    }
+    /// Is a value a member of the [`Set`]?
+    ///
+    /// Values that are not hashable are never members.
+    #[inline(always)]
+    pub fn contains(&self, value: &Dynamic) -> bool {
+        let value = value.flatten_clone();
+
+        Self::calc_hash(&value).map_or(false, |hash| self.contains_hashed(hash, &value))
+    }
+    /// Remove a value from the [`Set`], returning the member if it exists.
+    pub fn remove(&mut self, value: &Dynamic) -> Option<Dynamic> {
+        let value = value.flatten_clone();
+        let hash = Self::calc_hash(&value)?;
+        let bucket = self.0.get_mut(&hash)?;
+        let index = bucket.iter().position(|m| is_same_member(m, &value))?;
+        let member = bucket.remove(index);
+
+        if bucket.is_empty() {
+            self.0.remove(&hash);
+        }
+
+        Some(member)
+    }
+    /// Remove all members from the [`Set`].
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+    /// Get an iterator over the members of the [`Set`].
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = &Dynamic> {
+        self.0.values().flatten()
+    }
+    /// Keep only the members that satisfy a predicate.
+    #[inline(always)]
+    pub fn retain(&mut self, mut f: impl FnMut(&Dynamic) -> bool) {
+        self.0.retain(|_, bucket| {
+            bucket.retain(|value| f(value));
+            !bucket.is_empty()
+        });
+    }
+    /// Add all members of another [`Set`] into this [`Set`].
+    #[inline(always)]
+    pub fn extend(&mut self, other: Set) {
+        other.0.into_iter().for_each(|(hash, bucket)| {
+            bucket.into_iter().for_each(|value| {
+                self.insert_hashed(hash, value);
+            })
+        });
+    }
+    /// Get the union of two [`Set`]'s, i.e. members in either.
+    #[inline]
+    pub fn union(&self, other: &Set) -> Set {
+        let mut set = self.clone();
+        other.iter_hashed().for_each(|(hash, value)| {
+            set.insert_hashed(hash, value.clone());
+        });
+        set
+    }
+    /// Get the intersection of two [`Set`]'s, i.e. members in both.
+    #[inline]
+    pub fn intersection(&self, other: &Set) -> Set {
+        let mut set = Set::new();
+        self.iter_hashed()
+            .filter(|&(hash, value)| other.contains_hashed(hash, value))
+            .for_each(|(hash, value)| {
+                set.insert_hashed(hash, value.clone());
+            });
+        set
+    }
+    /// Get the difference of two [`Set`]'s, i.e. members in this [`Set`] but not the other.
+    #[inline]
+    pub fn difference(&self, other: &Set) -> Set {
+        let mut set = Set::new();
+        self.iter_hashed()
+            .filter(|&(hash, value)| !other.contains_hashed(hash, value))
+            .for_each(|(hash, value)| {
+                set.insert_hashed(hash, value.clone());
+            });
+        set
+    }
+    /// Get the symmetric difference of two [`Set`]'s, i.e. members in either but not both.
+    #[inline]
+    pub fn symmetric_difference(&self, other: &Set) -> Set {
+        let mut set = self.difference(other);
+        other
+            .iter_hashed()
+            .filter(|&(hash, value)| !self.contains_hashed(hash, value))
+            .for_each(|(hash, value)| {
+                set.insert_hashed(hash, value.clone());
+            });
+        set
+    }
+    /// Is this [`Set`] a subset of another [`Set`]?
+    #[inline(always)]
+    pub fn is_subset(&self, other: &Set) -> bool {
+        self.iter_hashed()
+            .all(|(hash, value)| other.contains_hashed(hash, value))
+    }
+    /// Create a [`Set`] from the items of an [`Array`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorMismatchDataType`][EvalAltResult::ErrorMismatchDataType] if any item
+    /// is not hashable.
+    pub fn from_array(array: Array) -> Result<Self, Box<EvalAltResult>> {
+        let mut set = Self::new();
+
+        for item in array {
+            set.insert(item)?;
+        }
+
+        Ok(set)
+    }
+    /// Convert the [`Set`] into an [`Array`] of its members.
+    #[inline(always)]
+    pub fn into_array(self) -> Array {
+        self.into_iter().collect()
+    }
+}
+
+impl PartialEq for Set {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl Hash for Set {
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.iter_hashed().for_each(|(hash, _)| hash.hash(state));
+    }
+}
+
+impl IntoIterator for Set {
+    type Item = Dynamic;
+    type IntoIter = Flatten<btree_map::IntoValues<u64, Vec<Dynamic>>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_values().flatten()
+    }
+}
+
+impl fmt::Debug for Set {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("#")?;
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Distinct members with colliding hashes must be kept apart.
+    #[test]
+    fn check_hash_collisions() {
+        use super::Set;
+        use crate::{Dynamic, INT};
+
+        let mut set = Set::new();
+
+        assert!(set.insert_hashed(42, Dynamic::from(1 as INT)));
+        assert!(set.insert_hashed(42, Dynamic::from(2 as INT)));
+        assert!(!set.insert_hashed(42, Dynamic::from(1 as INT)));
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains_hashed(42, &Dynamic::from(2 as INT)));
+        assert!(!set.contains_hashed(42, &Dynamic::from(3 as INT)));
+
+        let mut other = Set::new();
+        other.insert_hashed(42, Dynamic::from(2 as INT));
+        assert!(other.is_subset(&set));
+        assert!(set != other);
+
+        other.insert_hashed(42, Dynamic::from(1 as INT));
+        assert!(set == other);
+
+        set.retain(|value| value.as_int() == Ok(2));
+        assert_eq!(set.into_array().len(), 1);
+    }
+}
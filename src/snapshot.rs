@@ -0,0 +1,227 @@
+//! Module defining snapshots of the state of a scripting session.
+
+use crate::Scope;
+
+#[cfg(not(feature = "no_module"))]
+use crate::engine::{ImportOrigin, Imports};
+
+#[cfg(not(feature = "no_module"))]
+use crate::stdlib::{boxed::Box, format, string::ToString, vec::Vec};
+
+#[cfg(not(feature = "no_module"))]
+use crate::{Engine, EvalAltResult, ImmutableString, Module, Position};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of the state of a long-lived scripting session: the variables and constants
+/// in a [`Scope`] and the paths of the [modules][crate::Module] imported via `import` statements.
+///
+/// Under the `serde` feature, a [`Snapshot`] can be serialized (e.g. to disk) and deserialized
+/// to restore the session later, even after a restart.
+///
+/// To record the imports, keep them in an [`Imports`][crate::Imports] via
+/// [`Engine::eval_ast_with_imports`][crate::Engine::eval_ast_with_imports], create the snapshot
+/// via [`Snapshot::with_imports`] and restore them via
+/// [`Engine::restore_imports`][crate::Engine::restore_imports].
+///
+/// * Values are serialized via [`Dynamic`][crate::Dynamic]'s serialization: custom types and
+///   function pointers are serialized as strings, and time-stamps are not restored.
+///
+/// * Variables sharing the same value still share the same value after being restored,
+///   but closures no longer share the values they captured.
+///
+/// * Imported modules are not serialized. Only their paths (and the sources of the importing
+///   scripts, for relative paths) are recorded, and they are resolved again via the
+///   [`Engine`][crate::Engine]'s [module resolver][crate::ModuleResolver] when the imports are
+///   restored.  For a selective import (e.g. `import { bar } from "foo";`), the
+///   selected functions are recorded as well and selected again; the selected variables are
+///   restored as constants in the [`Scope`].
+///
+/// * Modules declared inline via `module` statements are not recorded.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, Scope, Snapshot, INT};
+///
+/// let engine = Engine::new();
+///
+/// let mut scope = Scope::new();
+/// engine.consume_with_scope(&mut scope, "const LIMIT = 10; let x = 40;")?;
+///
+/// let snapshot = Snapshot::new(&scope);
+///
+/// // The snapshot is not affected by further changes to the scope
+/// engine.consume_with_scope(&mut scope, "x = 0;")?;
+///
+/// // ... some time later
+/// let mut scope = snapshot.scope();
+///
+/// assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x + 2")?, 42);
+/// assert!(engine.consume_with_scope(&mut scope, "LIMIT = 0;").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    /// Variables and constants.
+    scope: Scope<'static>,
+    /// Imported modules, in order of import.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    imports: Vec<SnapshotImport>,
+}
+
+/// An imported [module][crate::Module] recorded in a [`Snapshot`].
+#[cfg(not(feature = "no_module"))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct SnapshotImport {
+    /// Name of the imported module.  Empty for a selective import.
+    name: ImmutableString,
+    /// Path of the imported module.
+    path: ImmutableString,
+    /// Source of the script containing the `import` statement, which relative paths depend on.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    source: Option<ImmutableString>,
+    /// Names and aliases of the functions selected by a selective import.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    fns: Option<Vec<(ImmutableString, ImmutableString)>>,
+}
+
+impl Snapshot {
+    /// Create a new [`Snapshot`] of a [`Scope`].
+    #[inline(always)]
+    pub fn new(scope: &Scope) -> Self {
+        Self {
+            scope: scope.clone_detached(),
+            #[cfg(not(feature = "no_module"))]
+            imports: Default::default(),
+        }
+    }
+    /// Create a new [`Snapshot`] of a [`Scope`] and a stack of imported
+    /// [modules][crate::Module].
+    ///
+    /// [Modules][crate::Module] declared inline via `module` statements are skipped, because they
+    /// cannot be resolved again; run the declarations again after the imports are restored.
+    ///
+    /// Returns an error if a [module][crate::Module] was pushed directly onto the [`Imports`],
+    /// because there is no path to record for it.
+    #[cfg(not(feature = "no_module"))]
+    pub fn with_imports(scope: &Scope, mods: &Imports) -> Result<Self, Box<EvalAltResult>> {
+        let imports = mods
+            .scan_origins()
+            .filter_map(|(name, origin, fns)| match origin {
+                ImportOrigin::Path(path, source) => Some(Ok(SnapshotImport {
+                    name: name.clone(),
+                    path: path.clone(),
+                    source: source.clone(),
+                    fns: fns.map(|fns| fns.to_vec()),
+                })),
+                ImportOrigin::Inline => None,
+                ImportOrigin::Direct => Some(
+                    EvalAltResult::ErrorRuntime(
+                        format!("Imported module '{}' has no path to snapshot", name).into(),
+                        Position::NONE,
+                    )
+                    .into(),
+                ),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            scope: scope.clone_detached(),
+            imports,
+        })
+    }
+    /// Restore a new [`Scope`] from this [`Snapshot`].
+    ///
+    /// The [`Snapshot`] is not affected by changes to the restored [`Scope`],
+    /// and can be restored again.
+    #[inline(always)]
+    pub fn scope(&self) -> Scope<'static> {
+        self.scope.clone_detached()
+    }
+    /// Get an iterator over the names and paths of the imported [modules][crate::Module]
+    /// recorded in this [`Snapshot`], in order of import.
+    ///
+    /// The name of a selective import is empty.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn iter_imports(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.imports
+            .iter()
+            .map(|import| (import.name.as_str(), import.path.as_str()))
+    }
+}
+
+#[cfg(not(feature = "no_module"))]
+impl Engine {
+    /// Restore the stack of imported [modules][crate::Module] recorded in a [`Snapshot`],
+    /// resolving each path again via the [`Engine`]'s [module resolver][crate::ModuleResolver].
+    ///
+    /// Relative paths are resolved against the source of the script that originally imported them.
+    ///
+    /// Evaluate scripts with the restored imports via [`Engine::eval_ast_with_imports`].
+    pub fn restore_imports(&self, snapshot: &Snapshot) -> Result<Imports, Box<EvalAltResult>> {
+        let mut mods: Imports = Default::default();
+
+        for SnapshotImport {
+            name,
+            path,
+            source,
+            fns,
+        } in snapshot.imports.iter()
+        {
+            let module = self.module_resolver.resolve(
+                self,
+                source.as_ref().map(|s| s.as_str()),
+                path,
+                Position::NONE,
+            )?;
+
+            if let Some(fns) = fns {
+                let mut selected = Module::new();
+
+                for (name, alias) in fns.iter() {
+                    if !module.copy_fn_as(name, alias, &mut selected) {
+                        return EvalAltResult::ErrorNotExported(
+                            name.to_string(),
+                            path.to_string(),
+                            Position::NONE,
+                        )
+                        .into();
+                    }
+                }
+
+                selected.build_index();
+                mods.push_selected(
+                    selected,
+                    path.clone(),
+                    source.clone(),
+                    fns.iter().cloned().collect(),
+                );
+            } else if !module.is_indexed() {
+                let mut module = crate::fn_native::shared_take_or_clone(module);
+                module.build_index();
+                mods.push_with_path(name.clone(), module, path.clone(), source.clone());
+            } else {
+                mods.push_with_path(name.clone(), module, path.clone(), source.clone());
+            }
+        }
+
+        Ok(mods)
+    }
+}
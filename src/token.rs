@@ -21,6 +21,9 @@ use crate::ast::FloatWrapper;
 #[cfg(feature = "decimal")]
 use rust_decimal::Decimal;
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+
 #[cfg(not(feature = "no_function"))]
 use crate::engine::KEYWORD_IS_DEF_FN;
 
@@ -41,6 +44,7 @@ pub type TokenStream<'a> = Peekable<TokenIterator<'a>>;
 ///
 /// Advancing beyond the maximum line length or maximum number of lines is not an error but has no effect.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     /// Line number - 0 = none
     line: u16,
@@ -203,12 +207,22 @@ pub enum Token {
     /// Requires the `decimal` feature.
     #[cfg(feature = "decimal")]
     DecimalConstant(Decimal),
+    /// A [`BigInt`] constant, for integer literals too large for an `INT`.
+    ///
+    /// Requires the `bigint` feature.
+    #[cfg(feature = "bigint")]
+    BigIntConstant(Box<BigInt>),
     /// An identifier.
     Identifier(String),
     /// A character constant.
     CharConstant(char),
     /// A string constant.
     StringConstant(String),
+    /// A [blob][crate::Blob] constant, in the form of `b"..."`.
+    ///
+    /// Not available under `no_index`.
+    #[cfg(not(feature = "no_index"))]
+    BlobConstant(crate::Blob),
     /// `{`
     LeftBrace,
     /// `}`
@@ -257,6 +271,8 @@ pub enum Token {
     Period,
     /// `#{`
     MapStart,
+    /// `#[`
+    SetStart,
     /// `=`
     Equals,
     /// `true`
@@ -353,6 +369,23 @@ pub enum Token {
     /// Reserved under the `no_function` feature.
     #[cfg(not(feature = "no_function"))]
     Private,
+    /// `yield`
+    ///
+    /// Reserved under the `no_function` feature.
+    #[cfg(not(feature = "no_function"))]
+    Yield,
+    /// `type`, only when starting a type declaration (`type Name {`).
+    ///
+    /// Not available under the `no_function` and `no_object` features.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    Type,
+    /// `impl`, only when starting the methods of a type (`impl Name {`).
+    ///
+    /// Not available under the `no_function` and `no_object` features.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_object"))]
+    Impl,
     /// `import`
     ///
     /// Reserved under the `no_module` feature.
@@ -363,6 +396,11 @@ pub enum Token {
     /// Reserved under the `no_module` feature.
     #[cfg(not(feature = "no_module"))]
     Export,
+    /// `module`
+    ///
+    /// Reserved under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    Module,
     /// `as`
     ///
     /// Reserved under the `no_module` feature.
@@ -391,7 +429,11 @@ impl Token {
             FloatConstant(f) => f.to_string().into(),
             #[cfg(feature = "decimal")]
             DecimalConstant(d) => d.to_string().into(),
+            #[cfg(feature = "bigint")]
+            BigIntConstant(n) => n.to_string().into(),
             StringConstant(_) => "string".into(),
+            #[cfg(not(feature = "no_index"))]
+            BlobConstant(_) => "blob".into(),
             CharConstant(c) => c.to_string().into(),
             Identifier(s) => s.clone().into(),
             Reserved(s) => s.clone().into(),
@@ -420,6 +462,7 @@ impl Token {
                 Comma => ",",
                 Period => ".",
                 MapStart => "#{",
+                SetStart => "#[",
                 Equals => "=",
                 True => "true",
                 False => "false",
@@ -472,12 +515,22 @@ impl Token {
                 Fn => "fn",
                 #[cfg(not(feature = "no_function"))]
                 Private => "private",
+                #[cfg(not(feature = "no_function"))]
+                Yield => "yield",
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_object"))]
+                Type => "type",
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_object"))]
+                Impl => "impl",
 
                 #[cfg(not(feature = "no_module"))]
                 Import => "import",
                 #[cfg(not(feature = "no_module"))]
                 Export => "export",
                 #[cfg(not(feature = "no_module"))]
+                Module => "module",
+                #[cfg(not(feature = "no_module"))]
                 As => "as",
                 EOF => "{EOF}",
                 t => unreachable!("operator should be matched in outer scope: {:?}", t),
@@ -509,6 +562,7 @@ impl Token {
             "," => Comma,
             "." => Period,
             "#{" => MapStart,
+            "#[" => SetStart,
             "=" => Equals,
             "true" => True,
             "false" => False,
@@ -561,25 +615,29 @@ impl Token {
             "fn" => Fn,
             #[cfg(not(feature = "no_function"))]
             "private" => Private,
+            #[cfg(not(feature = "no_function"))]
+            "yield" => Yield,
 
             #[cfg(not(feature = "no_module"))]
             "import" => Import,
             #[cfg(not(feature = "no_module"))]
             "export" => Export,
             #[cfg(not(feature = "no_module"))]
+            "module" => Module,
+            #[cfg(not(feature = "no_module"))]
             "as" => As,
 
             #[cfg(feature = "no_function")]
-            "fn" | "private" => Reserved(syntax.into()),
+            "fn" | "private" | "yield" => Reserved(syntax.into()),
 
             #[cfg(feature = "no_module")]
-            "import" | "export" | "as" => Reserved(syntax.into()),
+            "import" | "export" | "as" | "module" => Reserved(syntax.into()),
 
             "===" | "!==" | "->" | "<-" | ":=" | "~" | "::<" | "(*" | "*)" | "#" | "public"
-            | "protected" | "super" | "new" | "use" | "module" | "package" | "var" | "static"
-            | "begin" | "end" | "shared" | "with" | "each" | "then" | "goto" | "unless"
-            | "exit" | "match" | "case" | "default" | "void" | "null" | "nil" | "spawn"
-            | "thread" | "go" | "sync" | "async" | "await" | "yield" => Reserved(syntax.into()),
+            | "protected" | "super" | "new" | "use" | "package" | "var" | "static" | "begin"
+            | "end" | "shared" | "with" | "each" | "then" | "goto" | "unless" | "exit"
+            | "match" | "case" | "default" | "void" | "null" | "nil" | "spawn" | "thread"
+            | "go" | "sync" | "async" | "await" => Reserved(syntax.into()),
 
             KEYWORD_PRINT | KEYWORD_DEBUG | KEYWORD_TYPE_OF | KEYWORD_EVAL | KEYWORD_FN_PTR
             | KEYWORD_FN_PTR_CALL | KEYWORD_FN_PTR_CURRY | KEYWORD_THIS | KEYWORD_IS_DEF_VAR => {
@@ -661,6 +719,9 @@ impl Token {
             Throw            |
             In               => true,
 
+            #[cfg(not(feature = "no_function"))]
+            Yield => true,
+
             _ => false,
         }
     }
@@ -726,11 +787,12 @@ impl Token {
         match self {
             LeftBrace | RightBrace | LeftParen | RightParen | LeftBracket | RightBracket | Plus
             | UnaryPlus | Minus | UnaryMinus | Multiply | Divide | Modulo | PowerOf | LeftShift
-            | RightShift | SemiColon | Colon | DoubleColon | Comma | Period | MapStart | Equals
-            | LessThan | GreaterThan | LessThanEqualsTo | GreaterThanEqualsTo | EqualsTo
-            | NotEqualsTo | Bang | Pipe | Or | XOr | Ampersand | And | PlusAssign | MinusAssign
-            | MultiplyAssign | DivideAssign | LeftShiftAssign | RightShiftAssign | AndAssign
-            | OrAssign | XOrAssign | ModuloAssign | PowerOfAssign => true,
+            | RightShift | SemiColon | Colon | DoubleColon | Comma | Period | MapStart
+            | SetStart | Equals | LessThan | GreaterThan | LessThanEqualsTo
+            | GreaterThanEqualsTo | EqualsTo | NotEqualsTo | Bang | Pipe | Or | XOr | Ampersand
+            | And | PlusAssign | MinusAssign | MultiplyAssign | DivideAssign | LeftShiftAssign
+            | RightShiftAssign | AndAssign | OrAssign | XOrAssign | ModuloAssign
+            | PowerOfAssign => true,
 
             _ => false,
         }
@@ -742,10 +804,14 @@ impl Token {
 
         match self {
             #[cfg(not(feature = "no_function"))]
-            Fn | Private => true,
+            Fn | Private | Yield => true,
+
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_object"))]
+            Type | Impl => true,
 
             #[cfg(not(feature = "no_module"))]
-            Import | Export | As => true,
+            Import | Export | Module | As => true,
 
             True | False | Let | Const | If | Else | Do | While | Until | Loop | For | In
             | Continue | Break | Return | Throw | Try | Catch => true,
@@ -834,11 +900,26 @@ pub trait InputStream {
 /// # Volatile API
 ///
 /// This function is volatile and may change.
+#[inline(always)]
 pub fn parse_string_literal(
     stream: &mut impl InputStream,
     state: &mut TokenizeState,
     pos: &mut Position,
     enclosing_char: char,
+) -> Result<String, (LexError, Position)> {
+    parse_literal(stream, state, pos, enclosing_char, false)
+}
+
+/// Parse a string literal wrapped by `enclosing_char`.
+///
+/// If `as_bytes` is `true`, each non-ASCII character in the literal (but not an escape sequence)
+/// is encoded as UTF-8, with one character in the result for each byte.
+fn parse_literal(
+    stream: &mut impl InputStream,
+    state: &mut TokenizeState,
+    pos: &mut Position,
+    enclosing_char: char,
+    as_bytes: bool,
 ) -> Result<String, (LexError, Position)> {
     let mut result: smallvec::SmallVec<[char; 16]> = Default::default();
     let mut escape: smallvec::SmallVec<[char; 12]> = Default::default();
@@ -948,6 +1029,12 @@ pub fn parse_string_literal(
                 return Err((LERR::UnterminatedString, start));
             }
 
+            // Non-ASCII characters as UTF-8 bytes
+            ch if as_bytes && !ch.is_ascii() => {
+                let mut buf = [0_u8; 4];
+                result.extend(ch.encode_utf8(&mut buf).bytes().map(char::from));
+            }
+
             // All other characters
             ch => {
                 escape.clear();
@@ -1212,19 +1299,34 @@ fn get_next_token_inner(
                 // Parse number
                 if let Some(radix) = radix_base {
                     let out: String = result.iter().skip(2).filter(|&&c| c != NUM_SEP).collect();
+                    let num = INT::from_str_radix(&out, radix).map(Token::IntegerConstant);
+
+                    // If the number is too large for an integer, try a big integer instead
+                    #[cfg(feature = "bigint")]
+                    let num = num.or_else(|err| {
+                        BigInt::parse_bytes(out.as_bytes(), radix)
+                            .map(|n| Token::BigIntConstant(n.into()))
+                            .ok_or(err)
+                    });
 
                     return Some((
-                        INT::from_str_radix(&out, radix)
-                            .map(Token::IntegerConstant)
-                            .unwrap_or_else(|_| {
-                                Token::LexError(LERR::MalformedNumber(result.into_iter().collect()))
-                            }),
+                        num.unwrap_or_else(|_| {
+                            Token::LexError(LERR::MalformedNumber(result.into_iter().collect()))
+                        }),
                         start_pos,
                     ));
                 } else {
                     let out: String = result.iter().filter(|&&c| c != NUM_SEP).collect();
                     let num = INT::from_str(&out).map(Token::IntegerConstant);
 
+                    // If the number is too large for an integer, try a big integer instead
+                    #[cfg(feature = "bigint")]
+                    let num = num.or_else(|err| {
+                        BigInt::from_str(&out)
+                            .map(|n| Token::BigIntConstant(n.into()))
+                            .map_err(|_| err)
+                    });
+
                     // If integer parsing is unnecessary, try float instead
                     #[cfg(not(feature = "no_float"))]
                     let num =
@@ -1248,6 +1350,26 @@ fn get_next_token_inner(
                 }
             }
 
+            // b" - blob literal
+            #[cfg(not(feature = "no_index"))]
+            ('b', '"') => {
+                eat_next(stream, pos);
+
+                return parse_literal(stream, state, pos, '"', true).map_or_else(
+                    |err| Some((Token::LexError(err.0), err.1)),
+                    |out| match out.chars().find(|&ch| ch > '\u{ff}') {
+                        Some(ch) => Some((
+                            Token::LexError(LERR::MalformedChar(ch.to_string())),
+                            start_pos,
+                        )),
+                        None => Some((
+                            Token::BlobConstant(out.chars().map(|ch| ch as u8).collect()),
+                            start_pos,
+                        )),
+                    },
+                );
+            }
+
             // letter or underscore ...
             #[cfg(not(feature = "unicode-xid-ident"))]
             ('a'..='z', _) | ('_', _) | ('A'..='Z', _) => {
@@ -1311,6 +1433,12 @@ fn get_next_token_inner(
                 eat_next(stream, pos);
                 return Some((Token::MapStart, start_pos));
             }
+            // Set literal
+            #[cfg(not(feature = "no_index"))]
+            ('#', '[') => {
+                eat_next(stream, pos);
+                return Some((Token::SetStart, start_pos));
+            }
             ('#', _) => return Some((Token::Reserved("#".into()), start_pos)),
 
             // Operators
@@ -1752,12 +1880,63 @@ pub struct TokenIterator<'a> {
     stream: MultiInputsStream<'a>,
     /// A processor function that maps a token to another.
     map: Option<fn(Token) -> Token>,
+    /// Tokens already read ahead.
+    lookahead: StaticVec<(Token, Position)>,
+    /// Is the next token at the start of a statement?
+    at_stmt_start: bool,
 }
 
 impl<'a> Iterator for TokenIterator<'a> {
     type Item = (Token, Position);
 
     fn next(&mut self) -> Option<Self::Item> {
+        let (token, pos) = if self.lookahead.is_empty() {
+            self.next_token()?
+        } else {
+            self.lookahead.remove(0)
+        };
+
+        // `type` and `impl` are only keywords when starting a declaration (`type Name {` or
+        // `impl Name {`), otherwise they are normal identifiers (e.g. property names)
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_object"))]
+        let token = match token {
+            Token::Identifier(ref s) if self.at_stmt_start && (s == "type" || s == "impl") => {
+                while self.lookahead.len() < 2 {
+                    match self.next_token() {
+                        Some(t) => self.lookahead.push(t),
+                        None => break,
+                    }
+                }
+
+                // A missing type name (`type {`) is also a declaration, to report the error
+                match self.lookahead.as_slice() {
+                    [(Token::Identifier(_), _), (Token::LeftBrace, _), ..]
+                    | [(Token::LeftBrace, _), ..]
+                        if s == "type" =>
+                    {
+                        Token::Type
+                    }
+                    [(Token::Identifier(_), _), (Token::LeftBrace, _), ..]
+                    | [(Token::LeftBrace, _), ..] => Token::Impl,
+                    _ => token,
+                }
+            }
+            _ => token,
+        };
+
+        self.at_stmt_start = matches!(
+            token,
+            Token::SemiColon | Token::LeftBrace | Token::RightBrace
+        );
+
+        Some((token, pos))
+    }
+}
+
+impl<'a> TokenIterator<'a> {
+    /// Get the next token from the input stream.
+    fn next_token(&mut self) -> Option<(Token, Position)> {
         let (token, pos) = match get_next_token(&mut self.stream, &mut self.state, &mut self.pos) {
             // {EOF}
             None => return None,
@@ -1882,6 +2061,8 @@ impl Engine {
                 index: 0,
             },
             map,
+            lookahead: Default::default(),
+            at_stmt_start: true,
         }
     }
 }
@@ -0,0 +1,271 @@
+use rhai::{Engine, EvalAltResult, ImmutableString, Scope, INT};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+/// A minimal executor that busy-polls a future to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn no_op(_: *const ()) {}
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, no_op, no_op, no_op);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            return value;
+        }
+    }
+}
+
+/// A future that is pending on the first poll.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            Poll::Pending
+        }
+    }
+}
+
+async fn double(x: INT) -> Result<INT, Box<EvalAltResult>> {
+    YieldOnce(false).await;
+    Ok(x * 2)
+}
+
+async fn fail(message: ImmutableString) -> Result<INT, Box<EvalAltResult>> {
+    YieldOnce(false).await;
+    Err(message.to_string().into())
+}
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_async_fn("double", double)
+        .register_async_fn("fail", fail)
+        .register_async_fn("greet", |name: String, n: INT| async move {
+            Ok::<_, Box<EvalAltResult>>(format!("hello {} x{}", name, n))
+        });
+
+    engine
+}
+
+#[test]
+fn test_async_fn() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let ast = engine.compile("let x = double(20); x + double(1)")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 42);
+
+    let ast = engine.compile(r#"greet("world", double(2))"#)?;
+    assert_eq!(
+        block_on(engine.eval_ast_async::<String>(&ast))?,
+        "hello world x4"
+    );
+
+    let ast = engine.compile(
+        r"
+            let total = 0;
+            for i in range(0, 3) {
+                total += double(i);
+            }
+            total
+        ",
+    )?;
+
+    let mut scope = Scope::new();
+    assert_eq!(
+        block_on(engine.eval_ast_with_scope_async::<INT>(&mut scope, &ast))?,
+        6
+    );
+    assert_eq!(scope.get_value::<INT>("total").unwrap(), 6);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile(
+            r"
+                fn quad(x) { double(double(x)) }
+                quad(1) + quad(2)
+            ",
+        )?;
+        assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 12);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_async_fn_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let ast = engine.compile(r#"let x = 1; try { x = fail("boom"); } catch { x = 2; } x"#)?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 2);
+
+    let ast = engine.compile(r#"try { fail("boom") } catch (err) { throw err + "!" }"#)?;
+    let err = block_on(engine.eval_ast_async::<()>(&ast)).expect_err("should error");
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(v, _) if v.to_string() == "boom!"));
+
+    let ast = engine.compile(r#"let x = 1; fail("boom")"#)?;
+    let err = block_on(engine.eval_ast_async::<INT>(&ast)).expect_err("should error");
+    assert!(matches!(*err, EvalAltResult::ErrorRuntime(_, pos) if pos.position() == Some(12)));
+
+    // Without async evaluation, the script is suspended instead
+    assert!(matches!(
+        *engine
+            .eval::<INT>("double(21)")
+            .expect_err("should suspend"),
+        EvalAltResult::ErrorSuspended(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_async_fn_str_param() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = make_engine();
+
+    engine.register_async_fn("shout", |s: ImmutableString| async move {
+        YieldOnce(false).await;
+        Ok::<_, Box<EvalAltResult>>(s.to_uppercase())
+    });
+
+    let ast = engine.compile(r#"let s = "hello"; shout(s + ", world") + "!""#)?;
+    assert_eq!(
+        block_on(engine.eval_ast_async::<String>(&ast))?,
+        "HELLO, WORLD!"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_async_fn_side_effects() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut engine = make_engine();
+    let count = Arc::new(AtomicUsize::new(0));
+    let log_count = count.clone();
+
+    engine.register_fn("log", move |x: INT| {
+        log_count.fetch_add(1, Ordering::SeqCst);
+        x
+    });
+
+    // Calls made before the `async` call in the same statement are not repeated
+    let ast = engine.compile("let x = log(1) + double(20); x")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 41);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    count.store(0, Ordering::SeqCst);
+    let ast = engine.compile("let x = log(1) + double(log(2)) + log(3) + double(log(4)); x")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 16);
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+
+    // ... nor are calls within statement blocks in the same statement
+    count.store(0, Ordering::SeqCst);
+    let ast = engine.compile(
+        "let x = { let y = 0; for i in range(0, 3) { y += log(i); } y } + double(20); x",
+    )?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 43);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        count.store(0, Ordering::SeqCst);
+        let ast = engine.compile(
+            "
+                fn f(x) { log(x) }
+                f(1) + double(20) + f(2)
+            ",
+        )?;
+        assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 43);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    {
+        let ast =
+            engine.compile("let a = [1, 2, 3]; let x = a.pop() + double(a.pop()); [x, a.len()]")?;
+        assert_eq!(
+            block_on(engine.eval_ast_async::<rhai::Array>(&ast))?
+                .into_iter()
+                .map(|v| v.as_int().unwrap())
+                .collect::<Vec<_>>(),
+            vec![7, 1]
+        );
+    }
+
+    // Assignments are only made once
+    let ast = engine.compile("let x = 1; x += 1 + double(20); x")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_async_fn_resume() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut engine = make_engine();
+    let count = Arc::new(AtomicUsize::new(0));
+    let log_count = count.clone();
+
+    engine
+        .register_async_fn("fetch", |x: INT| async move {
+            YieldOnce(false).await;
+            Ok::<_, Box<EvalAltResult>>(x)
+        })
+        .register_fn("log", move |x: INT| {
+            log_count.fetch_add(1, Ordering::SeqCst);
+            x
+        });
+
+    // The loop continues with the values still to be iterated, not the array it started with
+    #[cfg(not(feature = "no_index"))]
+    {
+        let ast = engine
+            .compile("let a = [1, 2, 3]; let t = 0; for x in a { a.push(x); t += fetch(x); } t")?;
+        assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 6);
+    }
+
+    let ast = engine.compile("let t = 0; for i in range(0, 2000) { t += fetch(i); } t")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 1999 * 1000);
+
+    // Side effects before the call are not repeated, whether they are function calls or not
+    let ast = engine.compile(
+        "
+            let n = 0;
+            let t = 0;
+            for i in range(0, 3) {
+                log(i);
+                n += 1;
+                t += { n += 10; fetch(n) } + fetch(n);
+            }
+            n * 1000 + t
+        ",
+    )?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 33_132);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    let ast = engine.compile("let i = 0; do { i += 1; } while fetch(i) < 3; i")?;
+    assert_eq!(block_on(engine.eval_ast_async::<INT>(&ast))?, 3);
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "bigint")]
+
+use num_bigint::BigInt;
+use rhai::{Engine, EvalAltResult, INT};
+use std::str::FromStr;
+
+#[test]
+fn test_bigint_literals() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("42")?, 42);
+    assert_eq!(
+        engine.eval::<BigInt>("123456789012345678901234567890")?,
+        BigInt::from_str("123456789012345678901234567890").unwrap()
+    );
+    assert_eq!(
+        engine.eval::<BigInt>("-123456789012345678901234567890")?,
+        BigInt::from_str("-123456789012345678901234567890").unwrap()
+    );
+    assert_eq!(
+        engine.eval::<BigInt>("0xffffffffffffffffffffffff")?,
+        BigInt::from_str("79228162514264337593543950335").unwrap()
+    );
+    assert_eq!(
+        engine.eval::<String>("type_of(100000000000000000000)")?,
+        "bigint"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_bigint_arithmetic() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<BigInt>("100000000000000000000 * 100000000000000000000 + 1")?,
+        BigInt::from_str("10000000000000000000000000000000000000001").unwrap()
+    );
+    assert_eq!(
+        engine.eval::<BigInt>("let x = 100000000000000000000; x -= 1; x / 3 % 1000")?,
+        BigInt::from(333)
+    );
+    assert_eq!(
+        engine.eval::<BigInt>("to_bigint(2) ** 100")?,
+        BigInt::from(2).pow(100)
+    );
+    assert_eq!(
+        engine.eval::<BigInt>("let x = to_bigint(-5); abs(x) + -x")?,
+        BigInt::from(10)
+    );
+    assert!(engine.eval::<bool>("100000000000000000000 > 42")?);
+    assert!(engine.eval::<bool>("to_bigint(42) == 42")?);
+    assert_eq!(engine.eval::<INT>("sign(-100000000000000000000)")?, -1);
+
+    assert!(matches!(
+        *engine
+            .eval::<BigInt>("100000000000000000000 / 0")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<BigInt>("to_bigint(2) ** -1")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bigint_conversions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("to_int(to_bigint(42))")?, 42);
+    assert!(matches!(
+        *engine
+            .eval::<INT>("to_int(100000000000000000000)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert_eq!(
+        engine.eval::<BigInt>(r#"parse_bigint("-100000000000000000000")"#)?,
+        BigInt::from_str("-100000000000000000000").unwrap()
+    );
+    assert!(engine.eval::<BigInt>(r#"parse_bigint("abc")"#).is_err());
+    assert_eq!(
+        engine.eval::<String>("to_string(100000000000000000000)")?,
+        "100000000000000000000"
+    );
+
+    #[cfg(feature = "decimal")]
+    {
+        use rust_decimal::Decimal;
+
+        assert_eq!(
+            engine.eval::<Decimal>("to_decimal(100000000000000000000)")?,
+            Decimal::from_str("100000000000000000000").unwrap()
+        );
+        assert_eq!(
+            engine.eval::<BigInt>(r#"to_bigint(parse_decimal("100000000000000000000"))"#)?,
+            BigInt::from_str("100000000000000000000").unwrap()
+        );
+        assert!(engine
+            .eval::<BigInt>(r#"to_bigint(parse_decimal("1.5"))"#)
+            .is_err());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_bigint_serde() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::{from_dynamic, to_dynamic};
+    use rhai::Dynamic;
+
+    let x = to_dynamic(u128::MAX)?;
+    assert!(x.is::<BigInt>());
+    assert_eq!(from_dynamic::<u128>(&x)?, u128::MAX);
+
+    let x: Dynamic = BigInt::from_str("-100000000000000000000").unwrap().into();
+    assert_eq!(from_dynamic::<i128>(&x)?, -100000000000000000000);
+
+    let x: Dynamic = BigInt::from(42).into();
+    assert_eq!(from_dynamic::<INT>(&x)?, 42);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_bigint_max_bits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_bigint_bits(256);
+
+    assert_eq!(
+        engine.eval::<BigInt>("to_bigint(2) ** 255")?,
+        BigInt::from(2).pow(255)
+    );
+    assert!(matches!(
+        *engine
+            .eval::<BigInt>("to_bigint(2) ** 256")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    #[cfg(not(feature = "only_i32"))]
+    assert!(matches!(
+        *engine
+            .eval::<BigInt>("to_bigint(2) ** 4000000000")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<BigInt>("let x = to_bigint(2) ** 200; x * x")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    assert_eq!(
+        engine.eval::<BigInt>("to_bigint(1) ** 1000000")?,
+        BigInt::from(1)
+    );
+
+    engine.set_max_bigint_bits(0);
+
+    assert_eq!(
+        engine.eval::<BigInt>("let x = to_bigint(2) ** 200; x * x")?,
+        BigInt::from(2).pow(400)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Blob, Engine, EvalAltResult, INT};
+
+#[test]
+fn test_blobs() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<Blob>(r#"b"hello""#)?, b"hello".to_vec());
+    assert_eq!(
+        engine.eval::<Blob>(r#"b"\x00\x7f\xff""#)?,
+        vec![0, 127, 255]
+    );
+    assert_eq!(engine.eval::<Blob>(r#"b"é❤""#)?, "é❤".as_bytes().to_vec());
+    assert_eq!(engine.eval::<Blob>(r#"b"é\xe9""#)?, vec![0xc3, 0xa9, 0xe9]);
+    assert!(engine.compile(r#"b"\u2764""#).is_err());
+
+    assert_eq!(engine.eval::<Blob>("blob(3, 0x1ff)")?, vec![255, 255, 255]);
+    assert_eq!(engine.eval::<INT>("let x = blob(10); len(x)")?, 10);
+    assert_eq!(engine.eval::<String>("type_of(blob())")?, "blob");
+
+    assert_eq!(engine.eval::<INT>(r#"let x = b"abc"; x[1]"#)?, 98);
+    assert_eq!(
+        engine.eval::<Blob>(r#"let x = b"abc"; x[1] = 0x142; x"#)?,
+        vec![97, 66, 99]
+    );
+    assert!(engine.eval::<INT>(r#"let x = b"abc"; x[3]"#).is_err());
+
+    assert_eq!(
+        engine.eval::<Blob>(
+            r#"
+                let x = blob();
+                x += 1;
+                push(x, 2);
+                x += b"\x03\x04";
+                insert(x, 0, 0);
+                x + b"\x05"
+            "#
+        )?,
+        vec![0, 1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = b"\x01\x02\x03";
+                let sum = 0;
+                for b in x { sum += b; }
+                sum + pop(x) + shift(x)
+            "#
+        )?,
+        10
+    );
+    assert_eq!(
+        engine.eval::<Blob>(r#"extract(b"hello world", 6, 3)"#)?,
+        b"wor".to_vec()
+    );
+    assert_eq!(
+        engine.eval::<Blob>(r#"let x = b"hello"; let y = split(x, 2); y + x"#)?,
+        b"llohe".to_vec()
+    );
+    assert!(engine.eval::<bool>(r#"b"abc" == to_blob("abc")"#)?);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = b"abc"; x.push(1); x.len"#)?,
+        4
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blobs_parse_write() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(r#"parse_le_int(b"\x00\x34\x12\xff", 1, 2)"#)?,
+        0x1234
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"parse_be_int(b"\x00\x12\x34\xff", 1, 2)"#)?,
+        0x1234
+    );
+    assert_eq!(
+        engine.eval::<Blob>("let x = blob(4); write_le_int(x, 1, 2, 0x1234); x")?,
+        vec![0, 0x34, 0x12, 0]
+    );
+    assert_eq!(
+        engine.eval::<Blob>("let x = blob(4); write_be_int(x, 2, 2, 0x1234); x")?,
+        vec![0, 0, 0x12, 0x34]
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = blob(8); write_be_int(x, 0, 8, -42); parse_be_int(x, 0, 8)")?,
+        -42
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<rhai::FLOAT>(
+            "let x = blob(10); write_le_float(x, 2, 1.5); parse_le_float(x, 2)"
+        )?,
+        1.5
+    );
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<rhai::FLOAT>(
+            "let x = blob(10); write_be_float(x, 1, -0.25); parse_be_float(x, 1)"
+        )?,
+        -0.25
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_blobs_strings() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<Blob>(r#"to_blob("héllo")"#)?,
+        "héllo".as_bytes()
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"as_string(to_blob("héllo"))"#)?,
+        "héllo"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"to_hex(b"\x01\xab\xff")"#)?,
+        "01abff"
+    );
+    assert_eq!(
+        engine.eval::<Blob>(r#"parse_hex("01ABff")"#)?,
+        vec![1, 0xab, 0xff]
+    );
+    assert!(engine.eval::<Blob>(r#"parse_hex("abc")"#).is_err());
+    assert!(engine.eval::<Blob>(r#"parse_hex("zz")"#).is_err());
+
+    for (data, encoded) in [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foobar", "Zm9vYmFy"),
+    ]
+    .iter()
+    {
+        let script = format!(r#"to_base64(to_blob("{}"))"#, data);
+        assert_eq!(engine.eval::<String>(&script)?, *encoded);
+        let script = format!(r#"as_string(parse_base64("{}"))"#, encoded);
+        assert_eq!(engine.eval::<String>(&script)?, *data);
+    }
+    assert!(engine.eval::<Blob>(r#"parse_base64("Z!==")"#).is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_blobs_limits() {
+    let mut engine = Engine::new();
+    engine.set_max_array_size(10);
+
+    assert!(engine.eval::<Blob>("blob(100)").is_err());
+    assert!(engine
+        .eval::<Blob>("let x = blob(8); x += blob(8); x")
+        .is_err());
+}
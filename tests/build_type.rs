@@ -0,0 +1,132 @@
+#![cfg(not(feature = "no_object"))]
+use rhai::{CustomType, Engine, EvalAltResult, TypeBuilder, INT};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, CustomType)]
+#[rhai(name = "Point", eq, display)]
+struct Point {
+    x: INT,
+    #[rhai(readonly)]
+    y: INT,
+    #[rhai(name = "label")]
+    tag: String,
+    #[rhai(skip)]
+    hidden: bool,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Point {
+    fn new(x: INT, y: INT) -> Self {
+        Self {
+            x,
+            y,
+            tag: "".into(),
+            hidden: false,
+        }
+    }
+}
+
+#[test]
+fn test_build_type_derive() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .build_type::<Point>()
+        .register_fn("new_point", Point::new);
+
+    assert_eq!(engine.eval::<String>("type_of(new_point(1, 2))")?, "Point");
+    assert_eq!(
+        engine.eval::<INT>("let p = new_point(1, 2); p.x = 40; p.x + p.y")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<Point>(r#"let p = new_point(1, 2); p.label = "hello"; p"#)?,
+        Point {
+            x: 1,
+            y: 2,
+            tag: "hello".into(),
+            hidden: false
+        }
+    );
+    assert!(engine
+        .eval::<INT>("let p = new_point(1, 2); p.y = 3; p.y")
+        .is_err());
+    assert!(engine.eval::<String>("new_point(1, 2).tag").is_err());
+    assert!(engine.eval::<bool>("new_point(1, 2).hidden").is_err());
+
+    assert!(engine.eval::<bool>("new_point(1, 2) == new_point(1, 2)")?);
+    assert!(engine.eval::<bool>("new_point(1, 2) != new_point(1, 3)")?);
+
+    assert_eq!(
+        engine.eval::<String>("to_string(new_point(1, 2))")?,
+        "(1, 2)"
+    );
+    assert_eq!(
+        engine.eval::<String>("to_debug(new_point(1, 2))")?,
+        r#"Point { x: 1, y: 2, tag: "", hidden: false }"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_build_type_derive_no_display() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone, CustomType)]
+    struct Bare {
+        value: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .build_type::<Bare>()
+        .register_fn("new_bare", |value: INT| Bare { value });
+
+    assert_eq!(
+        engine.eval::<String>("to_debug(new_bare(42))")?,
+        "Bare { value: 42 }"
+    );
+    // Debug output is not used for printing
+    assert!(!engine
+        .eval::<String>("to_string(new_bare(42))")?
+        .contains("value"));
+
+    Ok(())
+}
+
+#[test]
+fn test_build_type_manual() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Clone)]
+    struct Counter(INT);
+
+    impl CustomType for Counter {
+        fn build(mut builder: TypeBuilder<Self>) {
+            builder
+                .with_name("Counter")
+                .with_fn("counter", || Counter(0))
+                .with_fn("inc", |c: &mut Counter| c.0 += 1)
+                .with_get_set(
+                    "value",
+                    |c: &mut Counter| c.0,
+                    |c: &mut Counter, v: INT| c.0 = v,
+                );
+        }
+    }
+
+    let mut engine = Engine::new();
+
+    engine.build_type::<Counter>();
+
+    assert_eq!(
+        engine.eval::<INT>("let c = counter(); c.value = 40; c.inc(); c.inc(); c.value")?,
+        42
+    );
+    assert_eq!(engine.eval::<String>("type_of(counter())")?, "Counter");
+
+    Ok(())
+}
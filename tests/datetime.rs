@@ -0,0 +1,161 @@
+use rhai::{DateTime, Duration, Engine, EvalAltResult, INT};
+
+#[test]
+fn test_datetime() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<DateTime>("datetime(2021, 3, 4, 5, 6, 7)")?,
+        "2021-03-04T05:06:07Z".parse::<DateTime>().unwrap()
+    );
+    assert_eq!(
+        engine.eval::<String>("type_of(datetime(2021, 3, 4))")?,
+        "datetime"
+    );
+    assert!(engine.eval::<DateTime>("datetime(2021, 2, 29)").is_err());
+    assert!(engine.eval::<DateTime>("datetime(2021, 13, 1)").is_err());
+
+    assert!(engine
+        .eval::<DateTime>(r#"parse_datetime("2020-02-29T23:59:60Z")"#)
+        .is_err());
+    assert_eq!(
+        engine.eval::<String>(r#"to_string(parse_datetime("2020-02-29 10:36:07.25+05:30"))"#)?,
+        "2020-02-29T10:36:07.25+05:30"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"to_string(to_utc(parse_datetime("2020-02-29T03:00-05:00")))"#)?,
+        "2020-02-29T08:00:00Z"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"to_string(with_offset(datetime_from_unix(0), -3600))"#)?,
+        "1969-12-31T23:00:00-01:00"
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>(
+                r#"
+                    let x = parse_datetime("2024-12-31T18:30:00-08:00");
+                    x.year * 10000 + x.month * 100 + x.day + x.hour
+                "#
+            )?,
+            20241231 + 18
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"parse_datetime("2024-12-31").day_of_year"#)?,
+            366
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"parse_datetime("2021-03-04").weekday"#)?,
+            4
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"parse_datetime("2001-09-09T01:46:40Z").unix_timestamp"#)?,
+            1_000_000_000
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_datetime_arithmetic() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let x = datetime(2021, 2, 28, 23, 0, 0);
+                x += duration(3600);
+                to_string(x + parse_duration("PT30M"))
+            "#
+        )?,
+        "2021-03-01T00:30:00Z"
+    );
+    assert_eq!(
+        engine.eval::<Duration>("datetime(2021, 3, 1) - datetime(2020, 3, 1)")?,
+        Duration::from_secs(365 * 86400)
+    );
+    assert!(engine.eval::<bool>("datetime(2021, 3, 1) > datetime(2020, 3, 1)")?);
+    assert!(engine
+        .eval::<bool>(r#"parse_datetime("2021-03-01T05:30+05:30") == datetime(2021, 3, 1)"#)?);
+    assert!(engine
+        .eval::<DateTime>("datetime(9999, 12, 31) + duration(86400)")
+        .is_err());
+    assert!(engine
+        .eval::<DateTime>(
+            "let d = datetime(2000, 1, 1).with_offset(3600); d + duration(9223372036854775807 - d.unix_timestamp)"
+        )
+        .is_err());
+    assert!(engine
+        .eval::<DateTime>("datetime(0, 1, 1).with_offset(-3600) - duration(9223372036854775807)")
+        .is_err());
+    assert!(engine
+        .eval::<DateTime>(r#"parse_datetime("2021-01-01T00:00+1é1")"#)
+        .is_err());
+    assert!(engine
+        .eval::<DateTime>(r#"parse_datetime("2021-01-01T00:00+é")"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_duration() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<Duration>("duration(90)")?,
+        Duration::from_secs(90)
+    );
+    assert_eq!(engine.eval::<String>("type_of(duration(1))")?, "duration");
+    assert_eq!(
+        engine.eval::<String>(r#"to_string(parse_duration("P1W") + duration(1, 500000000))"#)?,
+        "P7DT1.5S"
+    );
+    assert_eq!(
+        engine.eval::<String>("to_string(-duration_ms(1500) * 3)")?,
+        "-PT4.5S"
+    );
+    assert_eq!(
+        engine.eval::<String>("to_string(abs(duration(-7200) / 3))")?,
+        "PT40M"
+    );
+    assert!(engine.eval::<bool>("duration(60) < duration(61)")?);
+    assert!(engine.eval::<Duration>(r#"parse_duration("P1Y")"#).is_err());
+    assert!(engine.eval::<Duration>(r#"parse_duration("P1é")"#).is_err());
+    assert!(engine
+        .eval::<Duration>(r#"parse_duration("PT1.5é")"#)
+        .is_err());
+    assert!(engine.eval::<Duration>(r#"parse_duration("PTé")"#).is_err());
+    assert!(engine.eval::<Duration>("duration(1) / 0").is_err());
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>(r#"parse_duration("-PT0.25S").milliseconds"#)?,
+        -250
+    );
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "only_i32"))]
+    assert!(engine
+        .eval::<INT>("duration(9223372036854775807).milliseconds")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn test_datetime_deterministic() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_deterministic(|| std::time::Duration::from_secs(86400));
+
+    assert_eq!(
+        engine.eval::<String>("to_string(now_utc())")?,
+        "1970-01-02T00:00:00Z"
+    );
+
+    Ok(())
+}
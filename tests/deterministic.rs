@@ -0,0 +1,248 @@
+use rhai::{Dynamic, Engine, EvalAltResult, Nondeterministic, ReplayLog, INT};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn make_engine(counter: Arc<AtomicI64>) -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_nondeterministic_fn(
+            "next_id",
+            move || counter.fetch_add(1, Ordering::SeqCst) as INT,
+            Nondeterministic::Reject,
+        )
+        .register_nondeterministic_fn(
+            "random",
+            || 42 as INT,
+            Nondeterministic::Stub(Dynamic::from(0 as INT)),
+        );
+
+    engine
+}
+
+#[test]
+fn test_deterministic_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = make_engine(Arc::new(AtomicI64::new(1)));
+
+    assert!(!engine.is_deterministic());
+    assert_eq!(engine.eval::<INT>("next_id() + next_id()")?, 3);
+    assert_eq!(engine.eval::<INT>("random()")?, 42);
+
+    engine.set_deterministic(|| Duration::from_secs(0));
+    assert!(engine.is_deterministic());
+
+    assert_eq!(engine.eval::<INT>("random()")?, 0);
+
+    let err = engine.eval::<INT>("next_id()").expect_err("should error");
+    assert!(
+        matches!(*err, EvalAltResult::ErrorRuntime(ref msg, _) if msg.to_string().contains("'next_id'"))
+    );
+
+    // Rejected calls can be caught
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; try { x = next_id(); } catch { x = -1; } x")?,
+        -1
+    );
+
+    engine.clear_deterministic();
+    assert_eq!(engine.eval::<INT>("next_id()")?, 3);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_deterministic_clock() -> Result<(), Box<EvalAltResult>> {
+    let ticks = Arc::new(AtomicU64::new(0));
+    let clock = ticks.clone();
+
+    let mut engine = Engine::new();
+    engine.set_deterministic(move || Duration::from_secs(clock.load(Ordering::SeqCst)));
+
+    let mut scope = rhai::Scope::new();
+    engine.consume_with_scope(&mut scope, "let start = timestamp();")?;
+
+    ticks.store(10, Ordering::SeqCst);
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval_with_scope::<rhai::FLOAT>(&mut scope, "elapsed(start)")?,
+        10.0
+    );
+    #[cfg(feature = "no_float")]
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "elapsed(start)")?,
+        10
+    );
+
+    assert!(engine.eval_with_scope::<bool>(&mut scope, "start + 5 < timestamp()")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_replay() -> Result<(), Box<EvalAltResult>> {
+    let counter = Arc::new(AtomicI64::new(1));
+    let mut engine = make_engine(counter.clone());
+
+    let log = ReplayLog::new();
+    engine.set_replay_log(log.clone());
+
+    let ast = engine.compile("let x = next_id(); x * 100 + random() + next_id()")?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 144);
+    assert_eq!(log.len(), 3);
+    assert!(!log.is_replaying());
+
+    // A new run gives different results
+    engine.set_replay_log(ReplayLog::new());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 346);
+
+    // Replaying reproduces the recorded run, even in deterministic mode
+    let replay = log.replay();
+    assert!(replay.is_replaying());
+    engine.set_replay_log(replay.clone());
+    engine.set_deterministic(|| Duration::from_secs(0));
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 144);
+    assert_eq!(counter.load(Ordering::SeqCst), 5);
+
+    // Calls beyond the recorded run diverge
+    assert!(engine.eval_ast::<INT>(&ast).is_err());
+
+    // Calls in a different order diverge
+    engine.set_replay_log(log.replay());
+    let err = engine.eval::<INT>("random()").expect_err("should error");
+    assert!(
+        matches!(*err, EvalAltResult::ErrorRuntime(ref msg, _) if msg.to_string().starts_with("Replay diverged"))
+    );
+
+    engine.clear_replay_log();
+    engine.clear_deterministic();
+    assert_eq!(engine.eval::<INT>("next_id()")?, 5);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_deterministic_replay_clock() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let log = ReplayLog::new();
+    engine.set_replay_log(log.clone());
+
+    let ast = engine.compile("let start = timestamp(); start")?;
+    let recorded: std::time::Instant = engine.eval_ast(&ast)?;
+    assert_eq!(log.len(), 1);
+
+    engine.set_replay_log(log.replay());
+    let replayed: std::time::Instant = engine.eval_ast(&ast)?;
+    assert_eq!(replayed, recorded);
+
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_overloads() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_nondeterministic_fn("roll", || 4 as INT, Nondeterministic::Reject)
+        .register_fn("roll", |x: INT| x);
+
+    let log = ReplayLog::new();
+    engine.set_replay_log(log.clone());
+
+    assert_eq!(engine.eval::<INT>("roll() + roll(10)")?, 14);
+    assert_eq!(log.len(), 1);
+
+    // Other overloads are not affected by deterministic mode
+    engine.set_replay_log(ReplayLog::new());
+    engine.set_deterministic(|| Duration::from_secs(0));
+    assert_eq!(engine.eval::<INT>("roll(10)")?, 10);
+    assert!(engine.eval::<INT>("roll()").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_deterministic_result_fn() -> Result<(), Box<EvalAltResult>> {
+    let counter = Arc::new(AtomicI64::new(0));
+    let count = counter.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_nondeterministic_result_fn(
+        "fetch",
+        move |x: INT| -> Result<INT, Box<EvalAltResult>> {
+            match count.fetch_add(1, Ordering::SeqCst) {
+                0 => Ok(x * 2),
+                _ => Err("connection lost".into()),
+            }
+        },
+        Nondeterministic::Reject,
+    );
+
+    let log = ReplayLog::new();
+    engine.set_replay_log(log.clone());
+
+    let ast = engine.compile("let x = fetch(21); try { x = fetch(1); } catch { } x")?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(log.len(), 2);
+
+    // Errors are replayed as well
+    engine.set_replay_log(log.replay());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    engine.clear_replay_log();
+    let err = engine.eval::<INT>("fetch(1)").expect_err("should error");
+    assert!(
+        matches!(*err, EvalAltResult::ErrorRuntime(ref msg, _) if msg.to_string() == "connection lost")
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_deterministic_replay_serde() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::{from_dynamic, to_dynamic};
+
+    let counter = Arc::new(AtomicI64::new(1));
+    let mut engine = make_engine(counter.clone());
+
+    let log = ReplayLog::new();
+    engine.set_replay_log(log.clone());
+
+    let ast = engine.compile("let x = next_id(); x * 100 + random() + next_id()")?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 144);
+
+    // Persist the log and replay it on a fresh engine
+    let saved = to_dynamic(&log)?;
+    let restored: ReplayLog = from_dynamic(&saved)?;
+    assert_eq!(restored.len(), 3);
+    assert!(!restored.is_replaying());
+
+    let mut engine = make_engine(Arc::new(AtomicI64::new(100)));
+    engine.set_replay_log(restored.replay());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 144);
+
+    #[cfg(not(feature = "no_std"))]
+    {
+        let log = ReplayLog::new();
+        engine.set_replay_log(log.clone());
+
+        let ast = engine.compile("let start = timestamp(); start.elapsed")?;
+        engine.eval_ast::<Dynamic>(&ast)?;
+
+        let restored: ReplayLog = from_dynamic(&to_dynamic(&log)?)?;
+        engine.set_replay_log(restored.replay());
+        assert!(engine.eval_ast::<Dynamic>(&ast).is_ok());
+    }
+
+    Ok(())
+}
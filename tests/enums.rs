@@ -0,0 +1,143 @@
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "no_index"))]
+#![cfg(not(feature = "no_module"))]
+use rhai::{CustomEnum, Engine, EvalAltResult, ImmutableString, INT};
+
+#[derive(Debug, Clone, PartialEq, CustomEnum)]
+enum Shape {
+    Empty,
+    Square(INT),
+    Rect { w: INT, h: INT },
+    Label(ImmutableString, INT),
+}
+
+#[test]
+fn test_enums_derive() {
+    assert_eq!(Shape::enum_name(), "Shape");
+    assert_eq!(Shape::all_field_names(), &["w", "h"]);
+
+    let shape = Shape::Label("hello".into(), 42);
+
+    assert_eq!(shape.variant_name(), "Label");
+    assert_eq!(shape.field_count(), 2);
+    assert!(shape.field_names().is_empty());
+    assert_eq!(shape.field(1).unwrap().as_int().unwrap(), 42);
+    assert!(shape.field(2).is_none());
+
+    let shape = Shape::Rect { w: 2, h: 3 };
+
+    assert_eq!(shape.field_names(), &["w", "h"]);
+    assert_eq!(shape.field_by_name("w").unwrap().as_int().unwrap(), 2);
+    assert!(shape.field_by_name("x").is_none());
+    assert_eq!(Shape::Empty.field_count(), 0);
+}
+
+#[derive(Debug, Clone, PartialEq, CustomEnum)]
+enum Marker {
+    Named {},
+    Unnamed(),
+}
+
+#[test]
+fn test_enums_derive_empty_fields() -> Result<(), Box<EvalAltResult>> {
+    assert!(Marker::all_field_names().is_empty());
+    assert_eq!(Marker::Named {}.field_count(), 0);
+    assert!(Marker::Named {}.field(0).is_none());
+    assert!(Marker::Unnamed().field(0).is_none());
+
+    let mut engine = Engine::new();
+
+    engine.register_enum::<Marker>();
+
+    assert_eq!(engine.eval::<Marker>("Marker::Named()")?, Marker::Named {});
+    assert_eq!(
+        engine.eval::<String>("let x = Marker::Unnamed(); x.variant")?,
+        "Unnamed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_enums() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_enum::<Shape>();
+
+    assert_eq!(engine.eval::<Shape>("Shape::Empty")?, Shape::Empty);
+    assert_eq!(engine.eval::<Shape>("Shape::Empty()")?, Shape::Empty);
+    assert_eq!(
+        engine.eval::<Shape>("Shape::Square(42)")?,
+        Shape::Square(42)
+    );
+    assert_eq!(
+        engine.eval::<Shape>("Shape::Rect(1, 2)")?,
+        Shape::Rect { w: 1, h: 2 }
+    );
+    assert_eq!(
+        engine.eval::<Shape>(r#"Shape::Label("x", 1)"#)?,
+        Shape::Label("x".into(), 1)
+    );
+    assert_eq!(engine.eval::<String>("type_of(Shape::Empty)")?, "Shape");
+
+    assert_eq!(
+        engine.eval::<String>("let x = Shape::Square(1); x.variant")?,
+        "Square"
+    );
+    assert_eq!(
+        engine.eval::<INT>("let x = Shape::Rect(6, 7); x.w * x.h")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = Shape::Rect(6, 7); x["h"]"#)?,
+        7
+    );
+    assert!(matches!(
+        *engine.eval::<()>("let x = Shape::Square(1); x.w").expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(ref msg, _)
+        if msg == "Variant 'Square' of 'Shape' has no field 'w'"
+    ));
+    assert!(matches!(
+        *engine.eval::<()>(r#"let x = Shape::Empty; x["h"]"#).expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(ref msg, _)
+        if msg == "Variant 'Empty' of 'Shape' has no field 'h'"
+    ));
+    assert_eq!(
+        engine.eval::<String>(r#"let x = Shape::Label("hi", 1); x[0]"#)?,
+        "hi"
+    );
+    assert!(engine
+        .eval::<INT>("let x = Shape::Square(1); x[1]")
+        .is_err());
+    assert!(engine
+        .eval::<INT>("let x = Shape::Square(1); x[-1]")
+        .is_err());
+
+    assert!(engine.eval::<bool>("Shape::Square(1) == Shape::Square(1)")?);
+    assert!(engine.eval::<bool>("Shape::Square(1) != Shape::Square(2)")?);
+    assert!(engine.eval::<bool>("Shape::Square(1) != Shape::Empty")?);
+
+    let script = r#"
+        fn area(shape) {
+            switch shape {
+                "Empty" => 0,
+                "Square" => shape[0] * shape[0],
+                "Rect" => shape.w * shape.h,
+                _ => 99
+            }
+        }
+        [area(Shape::Empty), area(Shape::Square(3)), area(Shape::Rect(2, 5)), area(Shape::Label("x", 1))]
+    "#;
+
+    let result = engine.eval::<rhai::Array>(script)?;
+
+    assert_eq!(
+        result
+            .into_iter()
+            .map(|v| v.as_int().unwrap())
+            .collect::<Vec<_>>(),
+        vec![0, 9, 10, 99]
+    );
+
+    Ok(())
+}
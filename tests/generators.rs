@@ -0,0 +1,239 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::packages::{BasicIteratorPackage, Package};
+use rhai::{Engine, EvalAltResult, Generator, ParseErrorType, Scope, INT};
+use std::sync::{Arc, RwLock};
+
+#[test]
+fn test_generators() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn count_to(n) {
+                    let x = 1;
+                    while x <= n {
+                        yield x;
+                        x += 1;
+                    }
+                }
+
+                let total = 0;
+                for x in count_to(5) { total = total * 10 + x; }
+                total
+            "
+        )?,
+        12345
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn pairs(list) {
+                    for x in list {
+                        if x % 2 == 0 {
+                            yield x;
+                        } else {
+                            for y in range(0, x) { yield y * 100; }
+                        }
+                    }
+                    return;
+                    yield 999;
+                }
+
+                let total = 0;
+                for x in pairs([1, 2, 3, 4]) { total += x; }
+                total
+            "
+        )?,
+        0 + 2 + 0 + 100 + 200 + 4
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn inner(n) { for x in range(0, n) { yield x; } }
+                fn outer() { for n in range(1, 4) { for x in inner(n) { yield n * 10 + x; } } }
+
+                let g = outer();
+                let total = 0;
+                for x in g { total += x; }
+                for x in g { total += x; }
+                total
+            "
+        )?,
+        2 * (10 + 20 + 21 + 30 + 31 + 32)
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn each_word() {
+                    yield this.len();
+                    yield;
+                }
+
+                let count = 0;
+                for x in [1, 2, 3].each_word() { count += 1; }
+                count
+            "
+        )?,
+        2
+    );
+
+    assert_eq!(
+        engine.eval::<String>(
+            r"
+                fn gen() { yield 1; }
+                type_of(gen())
+            "
+        )?,
+        "generator"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generators_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        *engine.compile("yield 42;").expect_err("should error").0,
+        ParseErrorType::WrongYield
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(
+                r#"
+                    fn gen() { yield 1; throw "boom"; }
+                    for x in gen() {}
+                "#
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(name, _, err, _)
+            if name == "gen" && matches!(*err, EvalAltResult::ErrorRuntime(_, _))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_generators_resume() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    let log = Arc::new(RwLock::new(Vec::<String>::new()));
+    let log2 = log.clone();
+
+    engine.on_print(move |s| log2.write().unwrap().push(s.to_string()));
+
+    // Side effects of a nested generator happen once
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn inner() { for x in range(0, 2) { print("inner " + x); yield x; } }
+                fn outer() { for x in inner() { yield x; yield x * 10; } }
+
+                let total = 0;
+                for x in outer() { total += x; }
+                total
+            "#
+        )?,
+        11
+    );
+    assert_eq!(*log.read().unwrap(), ["inner 0", "inner 1"]);
+
+    // The values still to be iterated are kept, not the mutated array
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn gen(a) { for x in a { a.push(x); yield x; } }
+
+                let total = 0;
+                for x in gen([1, 2, 3]) { total += x; }
+                total
+            "
+        )?,
+        6
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                fn gen(n) { for x in range(0, n) { yield x; } }
+
+                let total = 0;
+                for x in gen(10000) { total += x; }
+                total
+            "
+        )?,
+        9999 * 5000
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generators_iterator_package() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new_raw();
+
+    let script = r"
+        fn gen() { yield 1; yield 2; }
+        let total = 0;
+        for x in gen() { total = x; }
+        total
+    ";
+
+    assert!(matches!(
+        *engine.eval::<INT>(script).expect_err("should error"),
+        EvalAltResult::ErrorFor(_)
+    ));
+
+    engine.register_global_module(BasicIteratorPackage::new().as_shared_module());
+
+    assert_eq!(engine.eval::<INT>(script)?, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_generators_from_rust() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r"
+            fn squares(n) {
+                for x in range(0, n) {
+                    let y = x * x;
+                    yield y;
+                }
+            }
+            fn broken() {
+                yield 1;
+                yield 1 / 0;
+                yield 2;
+            }
+        ",
+    )?;
+
+    let generator: Generator = engine.call_fn(&mut Scope::new(), &ast, "squares", (4 as INT,))?;
+
+    assert_eq!(generator.fn_name(), "squares");
+    assert!(!generator.is_done());
+
+    let values = engine
+        .iter_generator(&ast, generator)
+        .map(|v| v.map(|v| v.as_int().unwrap()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(values, [0, 1, 4, 9]);
+
+    let generator: Generator = engine.call_fn(&mut Scope::new(), &ast, "broken", ())?;
+    let mut iter = engine.iter_generator(&ast, generator);
+
+    assert_eq!(iter.next().unwrap()?.as_int().unwrap(), 1);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+
+    Ok(())
+}
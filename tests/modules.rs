@@ -515,3 +515,823 @@ fn test_module_file() -> Result<(), Box<EvalAltResult>> {
     Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
     Ok(())
 }
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_module_file_relative() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::FileModuleResolver, ModuleResolver, Position};
+
+    // 'lib/answer' imports "../shared/util"
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path("scripts/modules"));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib/answer" as a; a::answer"#)?,
+        42
+    );
+
+    let resolver = FileModuleResolver::new_with_path("scripts/modules");
+
+    resolver.resolve(
+        &engine,
+        Some("scripts/modules/lib/answer.rhai"),
+        "../shared/util",
+        Position::NONE,
+    )?;
+    assert!(resolver.is_cached("shared/util"));
+    assert!(!resolver.is_cached("lib/answer"));
+
+    Ok(())
+}
+
+/// A temporary directory of script files for tests that modify them, removed when dropped
+/// (even when the test fails).
+#[cfg(not(feature = "no_std"))]
+struct TempScripts(std::path::PathBuf);
+
+#[cfg(not(feature = "no_std"))]
+impl TempScripts {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("rhai-test-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+    fn write(&self, path: &str, script: &str) {
+        std::fs::write(self.0.join(path), script).unwrap();
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl Drop for TempScripts {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_module_file_hot_reload() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+    use std::path::PathBuf;
+    use std::sync::{Arc, RwLock};
+
+    let dir = TempScripts::new("reload");
+    dir.write("util.rhai", "let x = 1; export x;");
+    dir.write(
+        "main.rhai",
+        r#"import "./util" as util; let x = util::x * 10; export x;"#,
+    );
+
+    let reloaded = Arc::new(RwLock::new(Vec::<PathBuf>::new()));
+    let log = reloaded.clone();
+
+    let mut resolver = FileModuleResolver::new_with_path(&dir.0);
+    resolver
+        .enable_hot_reload(true)
+        .on_reload(move |path| log.write().unwrap().push(path.to_path_buf()));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let script = r#"import "main" as m; m::x"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 10);
+    assert_eq!(engine.eval::<INT>(script)?, 10);
+    assert!(reloaded.read().unwrap().is_empty());
+
+    dir.write("util.rhai", "let x = 42; export x;");
+
+    assert_eq!(engine.eval::<INT>(script)?, 420);
+
+    let mut files: Vec<_> = reloaded
+        .read()
+        .unwrap()
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["main.rhai", "util.rhai"]);
+
+    // A script file changed while its module is being evaluated is reloaded the next time
+    let edit_path = dir.0.join("edit.rhai");
+    dir.write("edit.rhai", "let x = edit(); export x;");
+
+    engine.register_fn("edit", move || {
+        std::fs::write(&edit_path, "let x = 42; export x;").unwrap();
+        1 as INT
+    });
+
+    let script = r#"import "edit" as e; e::x"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 1);
+    assert_eq!(engine.eval::<INT>(script)?, 42);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_module_cyclic_import() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::FileModuleResolver, Position};
+
+    let dir = TempScripts::new("cycle");
+    dir.write("a.rhai", r#"import "b" as b;"#);
+    dir.write("b.rhai", r#"let x = 1; import "c" as c;"#);
+    dir.write("c.rhai", r#"import "a" as a;"#);
+    dir.write("d.rhai", r#"import "./d" as d;"#);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path(&dir.0));
+
+    let err = engine
+        .eval::<()>(r#"import "a" as a;"#)
+        .expect_err("should error");
+
+    match *err {
+        EvalAltResult::ErrorCyclicImport(ref cycle, pos) => {
+            let paths: Vec<_> = cycle.iter().map(|(path, _)| path.as_str()).collect();
+            assert_eq!(paths, ["a", "b", "c", "a"]);
+            assert_eq!(cycle[2].1, Position::new(1, 19));
+            assert_eq!(pos, Position::new(1, 8));
+        }
+        ref err => panic!("wrong error: {}", err),
+    }
+    assert_eq!(
+        err.to_string(),
+        "Cyclic module import: 'a' (line 1, position 8) -> 'b' (line 1, position 8) -> \
+         'c' (line 1, position 19) -> 'a' (line 1, position 8)"
+    );
+
+    assert!(matches!(
+        *engine.eval::<()>(r#"import "d" as d;"#).expect_err("should error"),
+        EvalAltResult::ErrorCyclicImport(cycle, _) if cycle.len() == 2
+    ));
+
+    // The engine can still import the modules afterwards
+    dir.write("c.rhai", "");
+    engine.set_module_resolver(FileModuleResolver::new_with_path(&dir.0));
+    engine.eval::<()>(r#"import "a" as a;"#)?;
+
+    dir.write("c.rhai", r#"import "a" as a;"#);
+    engine.set_module_resolver(FileModuleResolver::new_with_path(&dir.0));
+
+    match *engine
+        .compile_into_self_contained(&Default::default(), r#"import "b" as b;"#)
+        .expect_err("should error")
+    {
+        EvalAltResult::ErrorCyclicImport(cycle, _) => {
+            let paths: Vec<_> = cycle.iter().map(|(path, _)| path.as_str()).collect();
+            assert_eq!(paths, ["b", "c", "a", "b"]);
+        }
+        err => panic!("wrong error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_module_import_after_panic() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{ModuleResolver, Position, Shared};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    // Panics the first time "boom" is resolved, while its import is being resolved
+    struct PanickingResolver(AtomicBool);
+
+    impl ModuleResolver for PanickingResolver {
+        fn resolve(
+            &self,
+            engine: &Engine,
+            _: Option<&str>,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            match path {
+                "boom" if !self.0.swap(true, Ordering::SeqCst) => panic!("boom"),
+                "boom" => {
+                    let ast =
+                        engine.compile(r#"import "leaf" as leaf; export const x = leaf::x;"#)?;
+                    Module::eval_ast_as_new(Default::default(), &ast, engine).map(Into::into)
+                }
+                "leaf" => {
+                    let mut module = Module::new();
+                    module.set_var("x", 42 as INT);
+                    Ok(module.into())
+                }
+                _ => EvalAltResult::ErrorModuleNotFound(path.into(), pos).into(),
+            }
+        }
+    }
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(PanickingResolver(AtomicBool::new(false)));
+
+    assert!(catch_unwind(AssertUnwindSafe(
+        || engine.eval::<INT>(r#"import "boom" as b; b::x"#)
+    ))
+    .is_err());
+
+    // The interrupted import does not leave the module marked as being resolved
+    assert_eq!(engine.eval::<INT>(r#"import "boom" as b; b::x"#)?, 42);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_module_self_contained_relative() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    // 'lib/a' and 'sub/x' both import "./util", which are different files
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path("scripts/modules"));
+
+    let script = r#"import "lib/a" as a; a::answer"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 41);
+
+    let ast = engine.compile_into_self_contained(&Default::default(), script)?;
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 41);
+
+    Ok(())
+}
+
+#[test]
+fn test_module_import_selected() -> Result<(), Box<EvalAltResult>> {
+    let mut module = Module::new();
+    module.set_var("answer", 42 as INT);
+    module.set_native_fn("double", |x: INT| Ok(x * 2));
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import { answer, double } from "hello"; double(answer)"#)?,
+        84
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = 1;
+                import { answer as a, double as d } from "hello";
+                let y = 2;
+                d(a) + x + y
+            "#
+        )?,
+        87
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "hello" as h;
+                import { double } from "hello";
+                double(h::answer)
+            "#
+        )?,
+        84
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn quadruple(x) { double(double(x)) }
+                import { double } from "hello";
+                quadruple(2)
+            "#
+        )?,
+        8
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"{ import { double } from "hello"; } double(1)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import { answer, foo } from "hello";"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorNotExported(name, path, _) if name == "foo" && path == "hello"
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import { answer } from "hello"; answer = 0;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorAssignmentToConstant(_, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_import_selected_file() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path("scripts/modules"));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import { add, BASE as base } from "lib/ops"; add(base, 1)"#)?,
+        4
+    );
+
+    let script = r#"import { add, hidden } from "lib/ops";"#;
+
+    // Plain compilation does not resolve modules, so the selected names are checked at runtime
+    engine.compile(script)?;
+
+    assert!(matches!(
+        *engine.eval::<()>(script).expect_err("should error"),
+        EvalAltResult::ErrorNotExported(ref name, ref path, _)
+        if name == "hidden" && path == "lib/ops"
+    ));
+
+    assert!(matches!(
+        *engine.compile_into_self_contained(&Scope::new(), script).expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::NotExported(ref name, ref path), pos)
+        if name == "hidden" && path == "lib/ops" && pos.position() == Some(15)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_inline() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = r#"
+            module math {
+                const SCALE = 10;
+                export const ANSWER = 42;
+
+                fn triple(x) { x * 3 }
+                fn scaled(x) { secret(x) + inner::add(x, 1) }
+                private fn secret(x) { x * 100 }
+
+                module inner {
+                    fn add(x, y) { x + y }
+                }
+            }
+        "#;
+
+    assert_eq!(
+        engine.eval::<INT>(&format!("{} math::triple(math::ANSWER)", script))?,
+        126
+    );
+    assert_eq!(
+        engine.eval::<INT>(&format!("{} math::inner::add(1, 2)", script))?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>(&format!("{} math::scaled(2)", script))?,
+        203
+    );
+    assert!(matches!(
+        *engine
+            .eval::<INT>(&format!("{} math::secret(1)", script))
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(&format!("{} math::SCALE", script))
+            .expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(_, _)
+    ));
+
+    assert!(matches!(
+        *engine
+            .eval::<()>("module bad { let x = 1 / 0; }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorInModule(name, _, _) if name == "bad"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("fn foo() { module m { } }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ModuleWrongDefinition
+    ));
+    assert!(matches!(
+        *engine
+            .compile("{ module m { } }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::ModuleWrongDefinition
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_bundle() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::{Bundle, BundleModuleResolver};
+
+    let mut bundle = Bundle::from_dir("scripts/bundle", "rhai")?;
+    bundle.set_manifest_entry("version", "1.0");
+    bundle.check_syntax(&Engine::new())?;
+
+    assert_eq!(
+        bundle.paths().collect::<Vec<_>>(),
+        ["shapes/area", "shapes/perimeter", "units"]
+    );
+    assert!(Bundle::from_dir("scripts/bundle", "txt")?.is_empty());
+
+    let dir = TempScripts::new("bundle");
+    let file = dir.0.join("scripts.bundle");
+    bundle.save(&file)?;
+    let loaded = Bundle::load(&file)?;
+    assert_eq!(loaded, bundle);
+    assert_eq!(loaded.manifest()["version"], "1.0");
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(BundleModuleResolver::new(loaded));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "shapes/perimeter" as p; p::ratio(4)"#)?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "shapes/area" as a; a::square(3)"#)?,
+        18
+    );
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import "shapes/missing" as m;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(path, _) if path == "shapes/missing"
+    ));
+
+    let ast = engine.compile_into_self_contained(
+        &Default::default(),
+        r#"import "shapes/area" as a; a::square(1)"#,
+    )?;
+    assert_eq!(Engine::new().eval_ast::<INT>(&ast)?, 2);
+
+    // Scripts are compiled by the importing engine
+    let mut engine = Engine::new();
+    engine.disable_symbol("*");
+    engine.set_module_resolver(BundleModuleResolver::new(bundle.clone()));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "shapes/area" as a; a::square(3)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, _, _) if path == "shapes/area"
+    ));
+
+    bundle.insert("broken", "fn (");
+    assert!(matches!(
+        *bundle.check_syntax(&Engine::new()).expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, _, _) if path == "broken"
+    ));
+
+    // The file format
+    let loaded = Bundle::from_bytes(
+        b"RHAIBNDL\x01\x01\0\0\0\x01\0\0\0k\x01\0\0\0v\x01\0\0\0\x01\0\0\0x\x01\0\0\0\x31",
+    )?;
+    assert_eq!(loaded.manifest()["k"], "v");
+    assert_eq!(loaded.get("x"), Some("1"));
+
+    assert!(Bundle::from_bytes(b"RHAIBNDL").is_err());
+    assert!(Bundle::from_bytes(b"RHAIBNDL\x02\0\0\0\0\0\0\0\0").is_err());
+    assert!(Bundle::from_bytes(b"not a bundle").is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_insert_scripts() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut resolver = StaticModuleResolver::new();
+
+    assert!(matches!(
+        *resolver
+            .insert_scripts(&engine, &[("a", r#"import "b" as b;"#), ("b", r#"import "./a" as a;"#)])
+            .expect_err("should error"),
+        EvalAltResult::ErrorCyclicImport(chain, _)
+            if chain.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>() == ["b", "./a"]
+    ));
+    assert!(matches!(
+        *resolver
+            .insert_scripts(&engine, &[("a", "fn (")])
+            .expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, _, _) if path == "a"
+    ));
+    assert!(resolver.is_empty());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_module_custom_types() -> Result<(), Box<EvalAltResult>> {
+    mod net {
+        #[derive(Debug, Clone)]
+        pub struct Config {
+            pub port: rhai::INT,
+        }
+        #[derive(Debug, Clone)]
+        pub struct Cert;
+    }
+    mod db {
+        #[derive(Debug, Clone)]
+        pub struct Config {
+            pub name: rhai::ImmutableString,
+        }
+    }
+
+    let mut tls = Module::new();
+    tls.set_custom_type::<net::Cert>("Cert");
+    tls.set_native_fn("cert", || Ok(net::Cert));
+
+    let mut module = Module::new();
+    module.set_custom_type::<net::Config>("Config");
+    module.set_native_fn("config", || Ok(net::Config { port: 80 }));
+    module.set_getter_fn("port", |c: &mut net::Config| Ok(c.port));
+    module.set_setter_fn("port", |c: &mut net::Config, port: INT| {
+        c.port = port;
+        Ok(())
+    });
+    module.set_sub_module("tls", tls);
+    assert_eq!(
+        module.get_custom_type(std::any::type_name::<net::Config>()),
+        Some("Config")
+    );
+
+    let mut engine = Engine::new();
+    engine.register_static_module("net", module.into());
+
+    let mut module = Module::new();
+    module.set_custom_type::<db::Config>("Config");
+    module.set_native_fn("config", || {
+        Ok(db::Config {
+            name: "main".into(),
+        })
+    });
+    module.set_getter_fn("name", |c: &mut db::Config| Ok(c.name.clone()));
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("db", module);
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<String>("type_of(net::config())")?,
+        "net::Config"
+    );
+    assert_eq!(
+        engine.eval::<String>("type_of(net::tls::cert())")?,
+        "net::tls::Cert"
+    );
+    assert_eq!(
+        engine.eval::<INT>("let c = net::config(); c.port = 8080; c.port")?,
+        8080
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"import "db" as db; type_of(db::config())"#)?,
+        "db::Config"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"import "db" as db; db::config().name"#)?,
+        "main"
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("net::config().foo")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(msg, _) if msg.contains("'net::Config'")
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import "db" as db; db::config().foo("x")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(sig, _) if sig == "foo (db::Config, &str | ImmutableString | String)"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_module_lazy_import() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{FnNamespace, ModuleResolver, Position, Shared};
+    use std::any::TypeId;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[derive(Clone)]
+    struct Countdown(INT);
+
+    struct CountingResolver(Arc<AtomicUsize>);
+
+    impl ModuleResolver for CountingResolver {
+        fn resolve(
+            &self,
+            _: &Engine,
+            _: Option<&str>,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+
+            match path {
+                "heavy" => {
+                    let mut module = Module::new();
+                    module.set_var("x", 42 as INT);
+                    module.set_native_fn("double", |x: INT| Ok(x * 2));
+                    let hash = module.set_native_fn("triple", |x: INT| Ok(x * 3));
+                    module.update_fn_namespace(hash, FnNamespace::Global);
+                    module.set_iter(TypeId::of::<Countdown>(), |value| {
+                        Box::new((0..value.cast::<Countdown>().0).rev().map(Into::into))
+                    });
+                    Ok(module.into())
+                }
+                _ => Err(EvalAltResult::ErrorModuleNotFound(path.into(), pos).into()),
+            }
+        }
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut engine = Engine::new();
+    engine.set_module_resolver(CountingResolver(count.clone()));
+    engine.set_lazy_import(true);
+    engine.register_fn("countdown", |n: INT| Countdown(n));
+
+    assert_eq!(engine.eval::<INT>(r#"import "heavy" as h; 42"#)?, 42);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "heavy" as h; h::double(h::x) + h::x"#)?,
+        126
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "missing" as m; if false { m::x } 42"#)?,
+        42
+    );
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "missing" as m; let y = 0; m::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(path, pos) if path == "missing" && pos.position() == Some(8)
+    ));
+
+    // Global functions and type iterators resolve the module when not found elsewhere
+    count.store(0, Ordering::SeqCst);
+    assert_eq!(engine.eval::<INT>(r#"import "heavy" as h; triple(1)"#)?, 3);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        engine.eval::<INT>(r#"import "heavy" as h; let y = h::x; triple(y)"#)?,
+        126
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"import "heavy" as h; let t = 0; for i in countdown(4) { t = t * 10 + i; } t"#
+        )?,
+        3210
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    // Functions found elsewhere do not resolve the module
+    assert_eq!(engine.eval::<INT>(r#"import "heavy" as h; abs(-1)"#)?, 1);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    // Selected imports are always resolved immediately
+    count.store(0, Ordering::SeqCst);
+    assert_eq!(engine.eval::<INT>(r#"import { x } from "heavy"; x"#)?, 42);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    // Lazy imports in a module script become sub-modules
+    let ast = engine.compile(r#"import "heavy" as h;"#)?;
+    let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+    assert!(module.get_sub_module("h").unwrap().contains_var("x"));
+
+    #[cfg(not(feature = "unchecked"))]
+    {
+        engine.set_max_modules(1);
+
+        assert_eq!(
+            engine.eval::<INT>(r#"import "heavy" as a; import "heavy" as b; a::x"#)?,
+            42
+        );
+        assert!(matches!(
+            *engine
+                .eval::<INT>(r#"import "heavy" as a; import "heavy" as b; a::x + b::x"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorTooManyModules(_)
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_module_fn_resolver() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::FnModuleResolver, ModuleResolver, Position};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+    };
+
+    let scripts = Arc::new(RwLock::new(HashMap::new()));
+    let loads = Arc::new(AtomicUsize::new(0));
+
+    {
+        let mut scripts = scripts.write().unwrap();
+        scripts.insert("lib/math", "fn add(x, y) { x + y }");
+        scripts.insert(
+            "lib/calc",
+            r#"import "./math" as m; fn twice(x) { m::add(x, x) }"#,
+        );
+        scripts.insert("bad", "fn (");
+    }
+
+    let (db, counter) = (scripts.clone(), loads.clone());
+
+    let mut resolver = FnModuleResolver::new(move |_, path| {
+        counter.fetch_add(1, Ordering::SeqCst);
+
+        match path {
+            "denied" => Err("access denied".into()),
+            _ => match db.read().unwrap().get(path) {
+                Some(script) => Ok(script.to_string()),
+                None => Err(EvalAltResult::ErrorModuleNotFound(path.into(), Position::NONE).into()),
+            },
+        }
+    });
+
+    let engine = Engine::new();
+
+    let module = resolver.resolve(&engine, None, "lib/math", Position::NONE)?;
+    assert_eq!(module.id(), Some("lib/math"));
+    assert!(resolver.is_cached("lib/math"));
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    resolver.resolve(&engine, None, "lib/math", Position::NONE)?;
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    let ast = resolver
+        .resolve_ast(&engine, Some("lib/calc"), "./math", Position::NONE)
+        .unwrap()?;
+    assert_eq!(ast.source(), Some("lib/math"));
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    scripts
+        .write()
+        .unwrap()
+        .insert("lib/math", "fn add(x, y) { x + y + 1 }");
+    assert!(resolver.clear_cache_for_path("lib/math").is_some());
+    assert!(!resolver.is_cached("lib/math"));
+    resolver.clear_cache();
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib/calc" as c; c::twice(20)"#)?,
+        41
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import "missing" as m;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(path, pos) if path == "missing" && pos.position() == Some(8)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import "bad" as m;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, err, _)
+            if path == "bad" && matches!(*err, EvalAltResult::ErrorParsing(_, _))
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"import "denied" as m;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInModule(path, err, _)
+            if path == "denied" && matches!(*err, EvalAltResult::ErrorRuntime(_, _))
+    ));
+
+    Ok(())
+}
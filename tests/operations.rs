@@ -134,3 +134,42 @@ fn test_max_operations_progress() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_max_operations_cancel() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(500);
+
+    let token = engine.cancellation_token();
+
+    engine.eval::<()>("for x in range(0, 100) {}")?;
+
+    token.cancel();
+
+    assert!(matches!(
+        *engine
+            .eval::<()>("for x in range(0, 100) {}")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTerminated(x, _) if x.is::<()>()
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile("fn foo(x) { x + 1 }")?;
+
+        let result: Result<INT, _> =
+            engine.call_fn(&mut Default::default(), &ast, "foo", (41 as INT,));
+
+        assert!(matches!(
+            *result.expect_err("should error"),
+            EvalAltResult::ErrorTerminated(_, _)
+        ));
+
+        token.reset();
+
+        let result: INT = engine.call_fn(&mut Default::default(), &ast, "foo", (41 as INT,))?;
+        assert_eq!(result, 42);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Array, Engine, EvalAltResult, IntArray, INT};
+
+#[cfg(not(feature = "no_float"))]
+use rhai::{FloatArray, FLOAT};
+
+#[test]
+fn test_packed_int_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("len(int_array(5))")?, 5);
+    assert_eq!(engine.eval::<String>("type_of(int_array())")?, "int_array");
+    assert_eq!(
+        engine.eval::<IntArray>("let x = to_int_array([1, 2, 3]); x[1] = 42; x")?,
+        IntArray::from(vec![1, 42, 3])
+    );
+    assert_eq!(engine.eval::<INT>("let x = int_array(3, 7); x[2]")?, 7);
+    assert!(engine.eval::<INT>("let x = int_array(3); x[3]").is_err());
+    assert!(engine
+        .eval::<IntArray>(r#"to_int_array([1, "x"])"#)
+        .is_err());
+
+    assert_eq!(
+        engine.eval::<IntArray>("to_int_array([1, 2, 3]) + to_int_array([10, 20, 30])")?,
+        IntArray::from(vec![11, 22, 33])
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("to_int_array([1, 2, 3]) * 2")?,
+        IntArray::from(vec![2, 4, 6])
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("10 - to_int_array([1, 2, 3])")?,
+        IntArray::from(vec![9, 8, 7])
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("let x = to_int_array([1, 2, 3]); x += 1; let y = -x; y")?,
+        IntArray::from(vec![-2, -3, -4])
+    );
+    assert!(engine
+        .eval::<IntArray>("to_int_array([1, 2]) + to_int_array([1, 2, 3])")
+        .is_err());
+    assert!(engine.eval::<IntArray>("to_int_array([1, 2]) / 0").is_err());
+
+    assert_eq!(engine.eval::<INT>("sum(to_int_array([1, 2, 3, 4]))")?, 10);
+    assert_eq!(engine.eval::<INT>("min(to_int_array([3, 1, 2]))")?, 1);
+    assert_eq!(engine.eval::<INT>("max(to_int_array([3, 1, 2]))")?, 3);
+    assert_eq!(engine.eval::<()>("max(int_array())")?, ());
+    assert_eq!(
+        engine.eval::<INT>("dot(to_int_array([1, 2, 3]), to_int_array([4, 5, 6]))")?,
+        32
+    );
+
+    assert_eq!(
+        engine.eval::<IntArray>("slice(to_int_array([1, 2, 3, 4, 5]), 1, 3)")?,
+        IntArray::from(vec![2, 3, 4])
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("slice(to_int_array([1, 2, 3, 4, 5]), 3)")?,
+        IntArray::from(vec![4, 5])
+    );
+    assert_eq!(
+        engine
+            .eval::<Array>("to_array(to_int_array([1, 2]))")?
+            .len(),
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = int_array();
+                push(x, 1);
+                append(x, to_int_array([2, 3]));
+                let total = 0;
+                for n in x { total += n; }
+                total
+            "
+        )?,
+        6
+    );
+    assert_eq!(
+        engine.eval::<String>("to_string(to_int_array([1, 2]))")?,
+        "[1, 2]"
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_float"))]
+#[test]
+fn test_packed_float_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>("type_of(float_array())")?,
+        "float_array"
+    );
+    assert_eq!(
+        engine.eval::<FloatArray>("to_float_array([1, 2.5])")?,
+        FloatArray::from(vec![1.0, 2.5])
+    );
+    assert_eq!(
+        engine.eval::<FloatArray>("let x = float_array(2, 1.5); x[0] = 3.0; x * 2.0")?,
+        FloatArray::from(vec![6.0, 3.0])
+    );
+    assert_eq!(
+        engine.eval::<FloatArray>("1.0 / to_float_array([2.0, 4.0])")?,
+        FloatArray::from(vec![0.5, 0.25])
+    );
+    assert_eq!(
+        engine.eval::<FLOAT>("mean(to_float_array([1.0, 2.0, 3.0, 6.0]))")?,
+        3.0
+    );
+    assert_eq!(engine.eval::<FLOAT>("mean(to_int_array([1, 2]))")?, 1.5);
+    assert_eq!(
+        engine.eval::<FLOAT>("min(to_float_array([3.0, -1.5, 2.0]))")?,
+        -1.5
+    );
+    assert_eq!(
+        engine.eval::<FLOAT>("max(to_float_array([3.0, -1.5, 2.0]))")?,
+        3.0
+    );
+    assert_eq!(engine.eval::<()>("mean(float_array())")?, ());
+    assert_eq!(
+        engine.eval::<FLOAT>("let x = to_float_array([1.0, 2.0]); dot(x, x - 0.5) + sum(x)")?,
+        6.5
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("to_int_array(to_float_array([1.5, 2.9]))")?,
+        IntArray::from(vec![1, 2])
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_packed_arrays_max_size() {
+    let mut engine = Engine::new();
+    engine.set_max_array_size(10);
+
+    assert!(engine.eval::<IntArray>("int_array(11)").is_err());
+    assert!(engine
+        .eval::<IntArray>("let x = int_array(10); push(x, 1); x")
+        .is_err());
+}
@@ -0,0 +1,287 @@
+use rhai::{Capability, Engine, EvalAltResult, Permissions, AST, INT};
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone)]
+struct User {
+    id: INT,
+}
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("get_user", |id: INT| User { id })
+        .register_fn("delete_user", |_: INT| ())
+        .register_fn("log", |_: &str| ());
+
+    #[cfg(not(feature = "no_object"))]
+    engine.register_get("id", |user: &mut User| user.id);
+
+    engine
+}
+
+fn compile(engine: &Engine, script: &str, permissions: &Permissions) -> AST {
+    let mut ast = engine.compile(script).unwrap();
+    ast.set_permissions(permissions.clone());
+    ast
+}
+
+fn is_denied(err: &EvalAltResult, capability: Capability, name: &str) -> bool {
+    match err {
+        EvalAltResult::ErrorPermissionDenied(c, n, _) => *c == capability && n == name,
+        EvalAltResult::ErrorInFunctionCall(_, _, err, _) => is_denied(err, capability, name),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_permissions_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let mut permissions = Permissions::new();
+    permissions.allow_functions(["get_user", "log"]);
+
+    assert!(permissions.is_allowed(Capability::Function, "log"));
+    assert!(!permissions.is_allowed(Capability::Function, "delete_user"));
+    assert!(permissions.is_allowed(Capability::Import, "anything"));
+
+    // Standard library functions and operators are not restricted
+    let ast = compile(
+        &engine,
+        r#"log("hello"); let user = get_user(42); len("hello") + 37"#,
+        &permissions,
+    );
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let ast = compile(&engine, "get_user(42).id", &permissions);
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    }
+
+    let ast = compile(&engine, "delete_user(1)", &permissions);
+    let err = engine.eval_ast::<()>(&ast).expect_err("should error");
+    assert!(is_denied(&err, Capability::Function, "delete_user"));
+    assert_eq!(err.position().position(), Some(1));
+
+    // The same script without permissions is not restricted
+    engine.eval::<()>("delete_user(1)")?;
+
+    // Permission errors cannot be caught
+    let ast = compile(&engine, "try { delete_user(1) } catch { }", &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<()>(&ast).expect_err("should error"),
+        Capability::Function,
+        "delete_user"
+    ));
+
+    // Calls via function pointers and 'eval' are restricted too
+    let ast = compile(&engine, r#"call(Fn("delete_user"), 1)"#, &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<()>(&ast).expect_err("should error"),
+        Capability::Function,
+        "delete_user"
+    ));
+
+    let ast = compile(&engine, r#"eval("delete_user(1)")"#, &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<()>(&ast).expect_err("should error"),
+        Capability::Function,
+        "delete_user"
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    {
+        let ast = compile(&engine, r#"[1, 2].map(Fn("delete_user"))"#, &permissions);
+        assert!(is_denied(
+            &engine.eval_ast::<()>(&ast).expect_err("should error"),
+            Capability::Function,
+            "delete_user"
+        ));
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        use rhai::Scope;
+
+        let ast = compile(
+            &engine,
+            "fn cleanup(id) { delete_user(id) } fn fetch(id) { get_user(id) }",
+            &permissions,
+        );
+
+        let user: User = engine.call_fn(&mut Scope::new(), &ast, "fetch", (1 as INT,))?;
+        assert_eq!(user.id, 1);
+
+        let result: Result<(), _> = engine.call_fn(&mut Scope::new(), &ast, "cleanup", (1 as INT,));
+        assert!(is_denied(
+            &result.expect_err("should error"),
+            Capability::Function,
+            "delete_user"
+        ));
+    }
+
+    // An empty allowlist denies all registered functions
+    let mut permissions = Permissions::new();
+    permissions.allow_functions(Vec::<&str>::new());
+
+    let ast = compile(&engine, r#"log("hello")"#, &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<()>(&ast).expect_err("should error"),
+        Capability::Function,
+        "log"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_permissions_global_modules() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Module;
+
+    let mut engine = make_engine();
+
+    let mut module = Module::new();
+    module.set_native_fn("wipe", |_: INT| Ok(()));
+    engine.register_global_module(module.into());
+
+    let mut package = Module::new();
+    package.set_native_fn("square", |x: INT| Ok(x * x));
+    package.set_package(true);
+    engine.register_global_module(package.into());
+
+    let mut permissions = Permissions::new();
+    permissions.allow_functions(["get_user"]);
+
+    let ast = compile(&engine, "wipe(1)", &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<()>(&ast).expect_err("should error"),
+        Capability::Function,
+        "wipe"
+    ));
+
+    // Functions in packages are not restricted
+    let ast = compile(&engine, "square(6) + 6", &permissions);
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    permissions.allow_functions(["wipe"]);
+    let ast = compile(&engine, "wipe(1)", &permissions);
+    engine.eval_ast::<()>(&ast)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_permissions_modules() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::StaticModuleResolver, Module};
+
+    let mut engine = make_engine();
+
+    let mut module = Module::new();
+    module.set_var("ANSWER", 42 as INT);
+    module.set_native_fn("calc", |x: INT| Ok(x + 1));
+    let module = rhai::Shared::new(module);
+
+    engine.register_static_module("math", module.clone());
+    engine.register_static_module("secret", module.clone());
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module.as_ref().clone());
+    resolver.insert("private", module.as_ref().clone());
+    engine.set_module_resolver(resolver);
+
+    let mut permissions = Permissions::new();
+    permissions.allow_modules(["math"]).allow_imports(["hello"]);
+
+    let ast = compile(&engine, "math::calc(math::ANSWER)", &permissions);
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 43);
+
+    let ast = compile(&engine, "secret::calc(1)", &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        Capability::Module,
+        "secret"
+    ));
+
+    let ast = compile(&engine, "secret::ANSWER", &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        Capability::Module,
+        "secret"
+    ));
+
+    // Imported modules are checked by path, not by alias
+    let ast = compile(
+        &engine,
+        r#"import "hello" as secret; secret::calc(secret::ANSWER)"#,
+        &permissions,
+    );
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 43);
+
+    let ast = compile(&engine, r#"import "private" as math; 0"#, &permissions);
+    let err = engine.eval_ast::<INT>(&ast).expect_err("should error");
+    assert!(is_denied(&err, Capability::Import, "private"));
+    assert_eq!(err.position().position(), Some(8));
+
+    // Native functions in modules are restricted by the function allowlist
+    permissions.allow_functions(["get_user"]);
+
+    let ast = compile(&engine, "math::calc(1)", &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        Capability::Function,
+        "calc"
+    ));
+
+    let ast = compile(&engine, r#"import "hello" as h; h::calc(1)"#, &permissions);
+    assert!(is_denied(
+        &engine.eval_ast::<INT>(&ast).expect_err("should error"),
+        Capability::Function,
+        "calc"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_permissions_audit() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let denied = Arc::new(RwLock::new(Vec::new()));
+    let log = denied.clone();
+
+    let mut permissions = Permissions::new();
+    permissions
+        .allow_functions(["get_user"])
+        .on_denied(move |capability, name, pos| {
+            log.write()
+                .unwrap()
+                .push((capability, name.to_string(), pos.line().unwrap()))
+        });
+
+    let ast = compile(
+        &engine,
+        r#"
+            let user = get_user(1);
+            log("deleting");
+        "#,
+        &permissions,
+    );
+
+    assert!(engine.eval_ast::<()>(&ast).is_err());
+
+    let ast = compile(&engine, "delete_user(1)", &permissions);
+    assert!(engine.eval_ast::<()>(&ast).is_err());
+
+    assert_eq!(
+        *denied.read().unwrap(),
+        [
+            (Capability::Function, "log".to_string(), 3),
+            (Capability::Function, "delete_user".to_string(), 1),
+        ]
+    );
+
+    Ok(())
+}
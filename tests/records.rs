@@ -0,0 +1,141 @@
+#![cfg(not(feature = "no_object"))]
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, Record, INT};
+
+const POINT: &str = r#"
+    type Point { x, y }
+
+    impl Point {
+        fn len2() { this.x * this.x + this.y * this.y }
+        fn shift(dx, dy) { this.x += dx; this.y += dy; }
+        fn scaled(factor) { Point(this.x * factor, this.y * factor) }
+    }
+"#;
+
+#[test]
+fn test_records() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(POINT)?;
+
+    let eval = |script: &str| engine.compile(script).map(|s| ast.merge(&s));
+
+    assert_eq!(
+        engine.eval_ast::<INT>(&eval("let p = Point(3, 4); p.len2()")?)?,
+        25
+    );
+    assert_eq!(
+        engine.eval_ast::<INT>(&eval("let p = Point(1, 2); p.shift(2, 2); p.x + p.y")?)?,
+        7
+    );
+    assert_eq!(
+        engine.eval_ast::<INT>(&eval("let p = Point(1, 2); p.x = 40; p.y += 1; p.x + p.y")?)?,
+        43
+    );
+    assert_eq!(
+        engine.eval_ast::<INT>(&eval("Point(1, 2).scaled(3).y")?)?,
+        6
+    );
+    assert_eq!(
+        engine.eval_ast::<String>(&eval("type_of(Point(1, 2))")?)?,
+        "Point"
+    );
+    assert_eq!(
+        engine.eval_ast::<String>(&eval("to_string(Point(1, 2))")?)?,
+        "Point { x: 1, y: 2 }"
+    );
+
+    assert!(engine.eval_ast::<bool>(&eval("Point(1, 2) == Point(1, 2)")?)?);
+    assert!(engine.eval_ast::<bool>(&eval("Point(1, 2) != Point(2, 1)")?)?);
+    assert!(engine.eval_ast::<bool>(&eval("Point([1], #{a: 2}) == Point([1], #{a: 2})")?)?);
+    assert!(engine.eval_ast::<bool>(&eval("type Other { x, y } Point(1, 2) != Other(1, 2)")?)?);
+
+    let record = engine.eval_ast::<Record>(&eval("Point(1, 2)")?)?;
+    assert_eq!(record.type_name(), "Point");
+    assert_eq!(record.len(), 2);
+    assert_eq!(record.get("y").unwrap().as_int().unwrap(), 2);
+    assert!(record.get("z").is_none());
+
+    assert!(matches!(
+        *engine.eval_ast::<INT>(&eval("let p = Point(1, 2); p.z")?).expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(msg, _) if msg == "Unknown field 'z' of type Point"
+    ));
+    assert!(matches!(
+        *engine.eval_ast::<()>(&eval("let p = Point(1, 2); p.z = 42;")?).expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(msg, _) if msg == "Unknown field 'z' of type Point"
+    ));
+    assert!(matches!(
+        *engine
+            .eval_ast::<()>(&eval("let p = Point(1, 2); p.len3()")?)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    assert!(matches!(
+        *engine.eval_ast::<()>(&eval("Point(1, 2) + 1")?).expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, _) if f.starts_with("+ (Point, ")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_records_parse() {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        *engine.compile("type Point { x, x }").expect_err("should error").0,
+        ParseErrorType::TypeDuplicatedField(t, f) if t == "Point" && f == "x"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("fn foo() { type Point { x, y } }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::TypeWrongDefinition
+    ));
+    assert!(matches!(
+        *engine
+            .compile("type Point { x, y } fn Point(a, b) { a + b }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::FnDuplicatedDefinition(f, 2) if f == "Point"
+    ));
+    assert!(matches!(
+        *engine.compile("impl { }").expect_err("should error").0,
+        ParseErrorType::TypeMissingName
+    ));
+    assert!(matches!(
+        *engine.compile("impl Point { fn f() { 1 } }").expect_err("should error").0,
+        ParseErrorType::TypeUndefined(t) if t == "Point"
+    ));
+    assert!(matches!(
+        *engine
+            .compile("impl Point { fn f() { 1 } } type Point { x }")
+            .expect_err("should error")
+            .0,
+        ParseErrorType::TypeUndefined(t) if t == "Point"
+    ));
+}
+
+#[test]
+fn test_records_contextual_keywords() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("let type = 5; type")?, 5);
+    assert_eq!(engine.eval::<INT>("let impl = 5; impl * 2")?, 10);
+    assert_eq!(
+        engine.eval::<INT>("let m = #{type: 1, impl: 2}; m.type + m.impl")?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>("let m = #{}; m.type = 40; m.type + 2")?,
+        42
+    );
+    assert_eq!(engine.eval::<INT>("fn type(x) { x + 1 } type(41)")?, 42);
+    assert_eq!(
+        engine.eval::<INT>("let type = 1; { type } type Foo { x } Foo(41).x + type")?,
+        42
+    );
+
+    Ok(())
+}
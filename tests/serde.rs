@@ -16,7 +16,10 @@ use rust_decimal::Decimal;
 #[test]
 fn test_serde_ser_primary_types() -> Result<(), Box<EvalAltResult>> {
     assert!(to_dynamic(42_u64)?.is::<INT>());
+    #[cfg(not(feature = "bigint"))]
     assert!(to_dynamic(u64::MAX)?.is::<u64>());
+    #[cfg(feature = "bigint")]
+    assert!(to_dynamic(u64::MAX)?.is::<num_bigint::BigInt>());
     assert!(to_dynamic(42 as INT)?.is::<INT>());
     assert!(to_dynamic(true)?.is::<bool>());
     assert!(to_dynamic(())?.is::<()>());
@@ -379,6 +382,40 @@ fn test_serde_de_primary_types() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_serde_de_option() -> Result<(), Box<EvalAltResult>> {
+    assert_eq!(None, from_dynamic::<Option<INT>>(&().into())?);
+    assert_eq!(Some(42), from_dynamic::<Option<INT>>(&(42 as INT).into())?);
+    assert_eq!(
+        Some("hello".to_string()),
+        from_dynamic::<Option<String>>(&"hello".into())?
+    );
+    assert!(from_dynamic::<Option<INT>>(&true.into()).is_err());
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Hello {
+            a: Option<INT>,
+            b: Option<INT>,
+        }
+
+        let mut map = Map::new();
+        map.insert("a".into(), (42 as INT).into());
+        map.insert("b".into(), ().into());
+
+        assert_eq!(
+            Hello {
+                a: Some(42),
+                b: None
+            },
+            from_dynamic(&map.into())?
+        );
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_serde_de_integer_types() -> Result<(), Box<EvalAltResult>> {
     assert_eq!(42, from_dynamic::<i8>(&Dynamic::from(42 as INT))?);
@@ -401,6 +438,118 @@ fn test_serde_de_array() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_serde_blob() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Blob;
+
+    let blob = Dynamic::from_blob(vec![1, 2, 255]);
+
+    let d = to_dynamic(&blob)?;
+    assert!(d.is::<Blob>());
+    assert_eq!(d.cast::<Blob>(), vec![1, 2, 255]);
+
+    let d: Dynamic = from_dynamic(&blob)?;
+    assert_eq!(d.cast::<Blob>(), vec![1, 2, 255]);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_serde_set() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{Array, Set};
+
+    let mut set = Set::new();
+    set.insert(Dynamic::from(42 as INT))?;
+
+    let d = to_dynamic(Dynamic::from(set.clone()))?;
+    assert!(d.is::<Array>());
+    assert_eq!(d.cast::<Array>()[0].as_int().unwrap(), 42);
+
+    let d: Vec<INT> = from_dynamic(&Dynamic::from(set))?;
+    assert_eq!(d, vec![42]);
+
+    Ok(())
+}
+
+#[test]
+fn test_serde_datetime() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{DateTime, Duration};
+
+    let dt: DateTime = "2021-03-04T05:06:07.5+01:00".parse().unwrap();
+
+    let d = to_dynamic(dt)?;
+    assert_eq!(d.clone().cast::<String>(), "2021-03-04T05:06:07.5+01:00");
+    assert_eq!(from_dynamic::<DateTime>(&d)?, dt);
+    assert_eq!(
+        from_dynamic::<String>(&Dynamic::from(dt))?,
+        "2021-03-04T05:06:07.5+01:00"
+    );
+
+    let d = to_dynamic(Duration::from_millis(-1500))?;
+    assert_eq!(d.clone().cast::<String>(), "-PT1.5S");
+    assert_eq!(from_dynamic::<Duration>(&d)?, Duration::from_millis(-1500));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_module"))]
+fn test_serde_custom_enum() -> Result<(), Box<EvalAltResult>> {
+    use rhai::CustomEnum;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CustomEnum)]
+    enum Shape {
+        Empty,
+        Square(INT),
+        Rect { w: INT, h: INT },
+    }
+
+    let mut engine = Engine::new();
+    engine.register_enum::<Shape>();
+
+    for shape in vec![Shape::Empty, Shape::Square(42), Shape::Rect { w: 1, h: 2 }] {
+        let d = engine.to_dynamic(shape.clone())?;
+        assert!(d.is::<Shape>());
+        assert_eq!(engine.from_dynamic::<Shape>(&d)?, shape);
+
+        // The free functions are not affected by registration
+        let d = to_dynamic(shape.clone())?;
+        assert!(!d.is::<Shape>());
+        assert_eq!(from_dynamic::<Shape>(&d)?, shape);
+    }
+
+    // Neither are other engines
+    let d = Engine::new().to_dynamic(Shape::Square(42))?;
+    assert!(d.is::<Map>());
+
+    let shape = engine.eval::<Dynamic>("Shape::Rect(3, 4)")?;
+    assert_eq!(
+        engine.from_dynamic::<Shape>(&shape)?,
+        Shape::Rect { w: 3, h: 4 }
+    );
+    assert!(from_dynamic::<Shape>(&shape).is_err());
+
+    let shapes = engine.eval::<Dynamic>("[Shape::Empty, Shape::Square(2)]")?;
+    assert_eq!(
+        engine.from_dynamic::<Vec<Shape>>(&shapes)?,
+        vec![Shape::Empty, Shape::Square(2)]
+    );
+
+    let mut scope = rhai::Scope::new();
+    scope.push("shape", engine.to_dynamic(Shape::Square(2))?);
+    assert_eq!(
+        engine.eval_with_scope::<String>(&mut scope, "shape.variant")?,
+        "Square"
+    );
+    assert!(engine.eval_with_scope::<bool>(&mut scope, "shape == Shape::Square(2)")?);
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_index"))]
 #[cfg(not(feature = "no_object"))]
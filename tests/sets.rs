@@ -0,0 +1,106 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Array, Engine, EvalAltResult, Set, INT};
+
+#[test]
+fn test_sets() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("len(#[1, 2, 3, 2, 1])")?, 3);
+    assert_eq!(engine.eval::<INT>("len(#[])")?, 0);
+    assert_eq!(engine.eval::<String>("type_of(#[1])")?, "set");
+    assert!(engine.eval::<bool>(r#"let x = #[1, "hello", 'x', [1, 2]]; "hello" in x"#)?);
+    assert!(engine.eval::<bool>("let x = #[1, [1, 2]]; [1, 2] in x")?);
+    assert!(!engine.eval::<bool>(r#"let x = #[1, "hello"]; "world" in x"#)?);
+    assert!(!engine.eval::<bool>(r#"let x = #[1, "1"]; 2 in x"#)?);
+    assert_eq!(engine.eval::<INT>(r#"len(#[1, "1", '1', true, ()])"#)?, 5);
+    assert!(engine.eval::<Set>("#[Fn(\"foo\")]").is_err());
+    assert!(engine.eval::<Set>("let f = Fn(\"foo\"); #[1, f]").is_err());
+
+    assert!(engine.eval::<bool>(
+        r#"
+            let x = set();
+            insert(x, 42);
+            insert(x, "hello");
+            let added = insert(x, 42);
+            !added && len(x) == 2 && remove(x, 42) && !contains(x, 42)
+        "#
+    )?);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let x = #[1, 2, 3, 4];
+                let sum = 0;
+                for n in x { sum += n; }
+                sum
+            "#
+        )?,
+        10
+    );
+
+    let a = engine.eval::<Array>("to_array(#[3, 1, 2])")?;
+    assert_eq!(a.len(), 3);
+    assert_eq!(engine.eval::<INT>("len(to_set([1, 1, 2, 2, 3]))")?, 3);
+    assert!(engine.eval::<Set>("to_set([1, Fn(\"foo\")])").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_sets_algebra() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(engine.eval::<bool>("(#[1, 2] | #[2, 3]) == #[1, 2, 3]")?);
+    assert!(engine.eval::<bool>("(#[1, 2] & #[2, 3]) == #[2]")?);
+    assert!(engine.eval::<bool>("(#[1, 2] - #[2, 3]) == #[1]")?);
+    assert!(engine.eval::<bool>("(#[1, 2] ^ #[2, 3]) == #[3, 1]")?);
+    assert!(engine.eval::<bool>("#[1, 2] != #[1]")?);
+
+    assert!(engine.eval::<bool>(
+        r#"
+            let x = #[1, 2, 3];
+            x |= #[4];
+            x -= #[1];
+            x &= #[2, 3, 4, 5];
+            x ^= #[2, 6];
+            x == #[3, 4, 6]
+        "#
+    )?);
+
+    assert!(engine.eval::<bool>("is_subset(#[1], #[1, 2])")?);
+    assert!(engine.eval::<bool>("is_superset(#[1, 2], #[1])")?);
+    assert!(engine.eval::<bool>("is_disjoint(#[1, 2], #[3])")?);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_sets_max_size() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_max_set_size(3);
+
+    assert!(engine.compile("#[1, 2, 3, 4]").is_err());
+    assert_eq!(engine.eval::<INT>("len(#[1, 2, 2])")?, 2);
+    assert!(matches!(
+        *engine
+            .eval::<Set>("#[1, 2] | #[3, 4]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<Array>("[#[1, 2, 3], #[4, 5]]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<Set>("let x = #[1, 2, 3]; insert(x, 4); x")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, _)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use rhai::{Engine, EvalAltResult, Scope, Snapshot, INT};
+
+#[test]
+fn test_snapshot_scope() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    engine.consume_with_scope(
+        &mut scope,
+        "const LIMIT = 10; let x = 40; let s = \"hello\";",
+    )?;
+
+    let snapshot = Snapshot::new(&scope);
+
+    engine.consume_with_scope(&mut scope, "x = 0; let y = 1;")?;
+
+    let mut restored = snapshot.scope();
+    assert_eq!(restored.len(), 3);
+    assert_eq!(restored.get_value::<INT>("x").unwrap(), 40);
+    assert!(!restored.contains("y"));
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut restored, "x + len(s) + LIMIT - 13")?,
+        42
+    );
+    assert!(engine
+        .consume_with_scope(&mut restored, "LIMIT = 0;")
+        .is_err());
+
+    // The snapshot can be restored again
+    engine.consume_with_scope(&mut restored, "x = 1;")?;
+    assert_eq!(snapshot.scope().get_value::<INT>("x").unwrap(), 40);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_closure"))]
+#[test]
+fn test_snapshot_shared() -> Result<(), Box<EvalAltResult>> {
+    use rhai::Dynamic;
+
+    let engine = Engine::new();
+
+    let shared = Dynamic::from(1 as INT).into_shared();
+
+    let mut scope = Scope::new();
+    scope.push_dynamic("x", shared.clone());
+    scope.push_dynamic("y", shared);
+
+    let snapshot = Snapshot::new(&scope);
+
+    engine.consume_with_scope(&mut scope, "x = 2;")?;
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 2);
+
+    // Variables sharing a value still share it after being restored, but not with the original
+    let mut restored = snapshot.scope();
+    assert_eq!(restored.get_value::<INT>("y").unwrap(), 1);
+
+    engine.consume_with_scope(&mut restored, "y = 42;")?;
+    assert_eq!(restored.get_value::<INT>("x").unwrap(), 42);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 2);
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_snapshot_serde() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::{from_dynamic, to_dynamic};
+
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    engine.consume_with_scope(
+        &mut scope,
+        "const LIMIT = 10; let x = 40; let list = [1, 2, 3]; let map = #{a: 1, b: true};",
+    )?;
+
+    let saved = to_dynamic(Snapshot::new(&scope))?;
+    let snapshot: Snapshot = from_dynamic(&saved)?;
+
+    let mut scope = snapshot.scope();
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "x + list.len + map.a + LIMIT - 12")?,
+        42
+    );
+    assert!(engine.consume_with_scope(&mut scope, "LIMIT = 0;").is_err());
+
+    // Scopes are serializable on their own
+    let saved = to_dynamic(&scope)?;
+    let scope: Scope = from_dynamic(&saved)?;
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 40);
+    assert!(scope
+        .iter()
+        .any(|(name, is_constant, _)| name == "LIMIT" && is_constant));
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_closure"))]
+#[test]
+fn test_snapshot_serde_shared() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::{from_dynamic, to_dynamic};
+    use rhai::Dynamic;
+
+    let shared = Dynamic::from(1 as INT).into_shared();
+
+    let mut scope = Scope::new();
+    scope.push_dynamic("x", shared.clone());
+    scope.push_dynamic("y", shared);
+    scope.push("z", 42 as INT);
+
+    let saved = to_dynamic(&scope)?;
+    let mut scope: Scope = from_dynamic(&saved)?;
+
+    assert!(scope
+        .iter_raw()
+        .all(|(name, _, value)| value.is_shared() == (name != "z")));
+
+    *scope.get_mut("x").unwrap().write_lock::<INT>().unwrap() = 2;
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 2);
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_snapshot_imports() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::StaticModuleResolver;
+    use rhai::serde::{from_dynamic, to_dynamic};
+    use rhai::{Imports, Module};
+
+    let mut module = Module::new();
+    module.set_var("ANSWER", 41 as INT);
+    module.set_native_fn("inc", |x: INT| Ok(x + 1));
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let mut scope = Scope::new();
+    let mut mods = Imports::default();
+
+    let ast = engine.compile(r#"import "hello" as h; let x = h::ANSWER;"#)?;
+    engine.eval_ast_with_imports::<()>(&mut scope, &mut mods, &ast)?;
+
+    let saved = to_dynamic(Snapshot::with_imports(&scope, &mods)?)?;
+    let snapshot: Snapshot = from_dynamic(&saved)?;
+
+    assert_eq!(
+        snapshot.iter_imports().collect::<Vec<_>>(),
+        [("h", "hello")]
+    );
+
+    let mut scope = snapshot.scope();
+    let mut mods = engine.restore_imports(&snapshot)?;
+
+    let ast = engine.compile("h::inc(x)")?;
+    assert_eq!(
+        engine.eval_ast_with_imports::<INT>(&mut scope, &mut mods, &ast)?,
+        42
+    );
+
+    // Modules pushed directly have no path
+    let mut mods = Imports::default();
+    mods.push("m", Module::new());
+    assert!(Snapshot::with_imports(&scope, &mods).is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_snapshot_selective_imports() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::StaticModuleResolver;
+    use rhai::serde::{from_dynamic, to_dynamic};
+    use rhai::{Imports, Module};
+
+    let mut module = Module::new();
+    module.set_var("ANSWER", 41 as INT);
+    module.set_native_fn("inc", |x: INT| Ok(x + 1));
+    module.set_native_fn("dec", |x: INT| Ok(x - 1));
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let mut scope = Scope::new();
+    let mut mods = Imports::default();
+
+    let ast = engine.compile(r#"import { ANSWER, inc as next } from "hello";"#)?;
+    engine.eval_ast_with_imports::<()>(&mut scope, &mut mods, &ast)?;
+
+    let saved = to_dynamic(Snapshot::with_imports(&scope, &mods)?)?;
+    let snapshot: Snapshot = from_dynamic(&saved)?;
+
+    assert_eq!(snapshot.iter_imports().collect::<Vec<_>>(), [("", "hello")]);
+
+    let mut scope = snapshot.scope();
+    let mut mods = engine.restore_imports(&snapshot)?;
+
+    let ast = engine.compile("next(ANSWER)")?;
+    assert_eq!(
+        engine.eval_ast_with_imports::<INT>(&mut scope, &mut mods, &ast)?,
+        42
+    );
+
+    // Only the selected functions are restored
+    let ast = engine.compile("dec(ANSWER)")?;
+    assert!(engine
+        .eval_ast_with_imports::<INT>(&mut scope, &mut mods, &ast)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_snapshot_relative_imports() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+    use rhai::Imports;
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(FileModuleResolver::new_with_path("scripts/modules"));
+
+    let mut scope = Scope::new();
+    let mut mods = Imports::default();
+
+    // Relative paths are resolved against the directory of the importing script
+    let mut ast = engine.compile(r#"import "../shared/util" as u;"#)?;
+    ast.set_source("scripts/modules/lib/answer.rhai");
+    engine.eval_ast_with_imports::<()>(&mut scope, &mut mods, &ast)?;
+
+    let snapshot = Snapshot::with_imports(&scope, &mods)?;
+
+    assert_eq!(
+        snapshot.iter_imports().collect::<Vec<_>>(),
+        [("u", "../shared/util")]
+    );
+
+    let mut mods = engine.restore_imports(&snapshot)?;
+
+    let ast = engine.compile("u::x * 2")?;
+    assert_eq!(
+        engine.eval_ast_with_imports::<INT>(&mut scope, &mut mods, &ast)?,
+        42
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_snapshot_inline_modules() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::StaticModuleResolver;
+    use rhai::{Imports, Module};
+
+    let mut module = Module::new();
+    module.set_var("ANSWER", 41 as INT);
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let mut scope = Scope::new();
+    let mut mods = Imports::default();
+
+    let ast = engine.compile(
+        r#"
+            module inline { export const ONE = 1; }
+            import "hello" as h;
+        "#,
+    )?;
+    engine.eval_ast_with_imports::<()>(&mut scope, &mut mods, &ast)?;
+
+    // Modules declared inline are skipped
+    let snapshot = Snapshot::with_imports(&scope, &mods)?;
+
+    assert_eq!(
+        snapshot.iter_imports().collect::<Vec<_>>(),
+        [("h", "hello")]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use rhai::{Continuation, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.register_result_fn("wait", |x: INT| Continuation::suspend::<INT>(x.into()));
+
+    engine
+}
+
+fn run(
+    engine: &Engine,
+    ast: &AST,
+    mut reply: impl FnMut(INT) -> INT,
+) -> Result<(INT, INT), Box<EvalAltResult>> {
+    let mut scope = Scope::new();
+    let mut result = engine.eval_ast_with_scope::<INT>(&mut scope, ast);
+    let mut count = 0;
+
+    loop {
+        match result {
+            Ok(value) => return Ok((value, count)),
+            Err(err) => match *err {
+                EvalAltResult::ErrorSuspended(cont, _) => {
+                    count += 1;
+                    let value = reply(cont.value().as_int().unwrap());
+                    result = engine.resume_with_scope(&mut scope, ast, *cont, value.into());
+                }
+                err => return Err(err.into()),
+            },
+        }
+    }
+}
+
+#[test]
+fn test_suspend() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let ast = engine.compile("let x = 40; let y = wait(x); x + y")?;
+
+    let cont = match *engine.eval_ast::<INT>(&ast).expect_err("should suspend") {
+        EvalAltResult::ErrorSuspended(cont, _) => cont,
+        err => panic!("{}", err),
+    };
+
+    assert_eq!(cont.value().as_int().unwrap(), 40);
+    assert_eq!(cont.position().line(), Some(1));
+
+    let mut scope = Scope::new();
+
+    assert_eq!(
+        engine.resume_with_scope::<INT>(
+            &mut scope,
+            &ast,
+            (*cont).clone(),
+            Dynamic::from(2 as INT)
+        )?,
+        42
+    );
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 40);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 2);
+
+    // The same continuation can be resumed more than once
+    assert_eq!(
+        engine.resume_with_scope::<INT>(&mut scope, &ast, *cont, Dynamic::from(-40 as INT))?,
+        0
+    );
+
+    // Several suspended calls within the same statement
+    let ast = engine.compile("wait(wait(1) + 1) + wait(10)")?;
+    assert_eq!(run(&engine, &ast, |x| x * 2)?, (26, 3));
+
+    Ok(())
+}
+
+#[test]
+fn test_suspend_statements() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let ast = engine.compile(
+        r"
+            let total = 0;
+            let i = 0;
+
+            while i < 3 {
+                i += 1;
+                if i == 2 {
+                    total += wait(i);
+                } else {
+                    let x = i * 100;
+                    switch i {
+                        1 => total += wait(x),
+                        _ => { let y = 1; total += wait(x) + y; }
+                    }
+                }
+            }
+
+            do {
+                total += wait(1000);
+                i -= 1;
+            } while i > 0;
+
+            for n in range(0, 3) {
+                total += { let z = n; wait(z * 10000) };
+            }
+
+            try {
+                throw wait(42);
+            } catch {
+                total += wait(42);
+            }
+
+            total
+        ",
+    )?;
+
+    let (result, count) = run(&engine, &ast, |x| x)?;
+
+    assert_eq!(result, 100 + 2 + 301 + 3000 + 0 + 10000 + 20000 + 42);
+    assert_eq!(count, 3 + 3 + 3 + 2);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_suspend_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    let ast = engine.compile(
+        r"
+            fn ask(x) {
+                let y = wait(x);
+                x + y
+            }
+            fn outer(x) {
+                let a = ask(x);
+                let b = ask(x * 2);
+                a + b
+            }
+
+            let result = 0;
+
+            for x in [1, 2] {
+                result += outer(x);
+            }
+
+            result
+        ",
+    )?;
+
+    assert_eq!(run(&engine, &ast, |x| x * 10)?, (99, 4));
+
+    let ast = engine.compile("fn ask(x) { let y = wait(x); x + y }")?;
+
+    let result: Result<INT, _> = engine.call_fn(&mut Scope::new(), &ast, "ask", (21 as INT,));
+
+    match *result.expect_err("should suspend") {
+        EvalAltResult::ErrorSuspended(cont, _) => {
+            assert_eq!(
+                engine.resume_with_scope::<INT>(
+                    &mut Scope::new(),
+                    &ast,
+                    *cont,
+                    Dynamic::from(21 as INT)
+                )?,
+                42
+            );
+        }
+        err => panic!("{}", err),
+    }
+
+    // Errors after resuming within a function are caught by its callers
+    let ast = engine.compile(
+        r#"
+            fn check(x) {
+                let y = wait(x);
+                if y < 0 { throw "negative"; }
+                y
+            }
+            fn outer(x) {
+                try {
+                    return check(x);
+                } catch {
+                    return -1;
+                }
+            }
+
+            let result = 0;
+
+            try {
+                result = outer(1) + check(2);
+            } catch {
+                result = 1000;
+            }
+
+            result
+        "#,
+    )?;
+
+    assert_eq!(run(&engine, &ast, |x| x)?, (3, 2));
+    assert_eq!(run(&engine, &ast, |x| if x == 1 { -x } else { x })?, (1, 2));
+    assert_eq!(run(&engine, &ast, |x| -x)?, (1000, 2));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_suspend_functions_imports() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::StaticModuleResolver;
+
+    let mut engine = make_engine();
+
+    let mut resolver = StaticModuleResolver::new();
+    let ast = engine.compile("fn double(x) { x * 2 }")?;
+    resolver.insert(
+        "x",
+        rhai::Module::eval_ast_as_new(Scope::new(), &ast, &engine)?,
+    );
+    engine.set_module_resolver(resolver);
+
+    let ast = engine.compile(
+        r#"
+            import "x" as x;
+
+            fn f() {
+                let a = wait(1);
+                x::double(a)
+            }
+
+            f() + x::double(wait(10))
+        "#,
+    )?;
+
+    assert_eq!(run(&engine, &ast, |x| x * 2)?, (44, 2));
+
+    Ok(())
+}
+
+#[test]
+fn test_suspend_unsupported() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine();
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"eval("wait(1)")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(_, _, _, _)
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_function"))]
+    assert!(!matches!(
+        *engine
+            .eval::<INT>("[1, 2].map(|x| wait(x)).len()")
+            .expect_err("should error"),
+        EvalAltResult::ErrorSuspended(_, _)
+    ));
+
+    // Suspension cannot be caught
+    assert!(matches!(
+        *engine
+            .eval::<INT>("try { wait(1) } catch { 0 }")
+            .expect_err("should suspend"),
+        EvalAltResult::ErrorSuspended(_, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_suspend_serde() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::{from_dynamic, to_dynamic};
+
+    let engine = make_engine();
+
+    let ast = engine.compile(
+        r"
+            const LIMIT = 3;
+            let list = [];
+            for n in range(0, LIMIT) {
+                list.push(wait(n));
+            }
+            list.len() + list.reduce(|sum, v| sum + v, 0)
+        ",
+    )?;
+
+    let mut scope = Scope::new();
+    let mut result = engine.eval_ast_with_scope::<INT>(&mut scope, &ast);
+
+    while let Err(err) = result {
+        match *err {
+            EvalAltResult::ErrorSuspended(cont, _) => {
+                let saved = to_dynamic(*cont)?;
+                let cont: Continuation = from_dynamic(&saved)?;
+                let value = cont.value().as_int().unwrap() * 2;
+                result = engine.resume_with_scope(&mut scope, &ast, cont, value.into());
+            }
+            err => return Err(err.into()),
+        }
+    }
+
+    assert_eq!(result?, 9);
+    assert!(scope
+        .iter()
+        .any(|(name, is_constant, _)| name == "LIMIT" && is_constant));
+
+    // A `for` loop over a generator cannot be saved, but can still be resumed
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile(
+            r"
+                fn gen() { yield 1; yield 2; }
+                let total = 0;
+                for x in gen() { total += wait(x); }
+                total
+            ",
+        )?;
+
+        let mut scope = Scope::new();
+        let mut result = engine.eval_ast_with_scope::<INT>(&mut scope, &ast);
+
+        while let Err(err) = result {
+            match *err {
+                EvalAltResult::ErrorSuspended(cont, _) => {
+                    assert!(to_dynamic(&*cont).is_err());
+                    let value = cont.value().clone();
+                    result = engine.resume_with_scope(&mut scope, &ast, *cont, value);
+                }
+                err => return Err(err.into()),
+            }
+        }
+
+        assert_eq!(result?, 3);
+    }
+
+    Ok(())
+}